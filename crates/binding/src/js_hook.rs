@@ -11,13 +11,14 @@ pub struct JsHooks {
     pub name: Option<String>,
     pub enforce: Option<String>,
     #[napi(
-        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js' } | void> | void;"
+        ts_type = "(filePath: string) => Promise<{ content: string, type: 'css'|'js', resolveBase?: string } | void> | void;"
     )]
     pub load: Option<JsFunction>,
     #[napi(ts_type = "(filePath: string) => Promise<bool> | bool;")]
     pub load_include: Option<JsFunction>,
     #[napi(ts_type = r#"(data: {
     isFirstCompile: boolean;
+    buildId: number;
     time: number;
     stats: {
       hash: number;
@@ -35,6 +36,7 @@ pub struct JsHooks {
         string,
         { id: string; dependents: string[]; dependencies: string[] }
       >;
+      usedExports: Record<string, string[]>;
       chunks: {
         type: string;
         id: string;
@@ -49,6 +51,15 @@ pub struct JsHooks {
           loc: string;
           request: string;
         }[];
+        linkHints: { rel: 'prefetch' | 'preload'; chunkId: string }[];
+      }[];
+      packages: {
+        name: string;
+        version: string;
+        size: number;
+        modules: string[];
+        duplicated: boolean;
+        chain: string[];
       }[];
       entrypoints: Record<string, { name: string; chunks: string[] }>;
       rscClientComponents: { path; string; moduleId: string }[];
@@ -56,12 +67,39 @@ pub struct JsHooks {
       startTime: number;
       endTime: number;
     };
+    rebuild: {
+      addedModules: string[];
+      modifiedModules: string[];
+      removedModules: string[];
+      chunks: {
+        chunkId: string;
+        oldFilename: string | null;
+        newFilename: string | null;
+      }[];
+      assets: {
+        path: string;
+        oldFilename: string | null;
+        newFilename: string | null;
+      }[];
+      timing: { update: number; generateHotUpdate: number; emit: number };
+    } | null;
+    buildStats: {
+      resolve: { ms: number; count: number };
+      load: { ms: number; count: number };
+      transform: { ms: number; count: number };
+      treeShake: { ms: number; count: number };
+      generate: { ms: number; count: number };
+    } | null;
   }) => void"#)]
     pub generate_end: Option<JsFunction>,
+    #[napi(
+        ts_type = "(chunks: { fileName: string; code: string; map: string | null; type: 'js' | 'css' }[]) => Promise<{ fileName: string; code: string; map?: string | null; type: 'js' | 'css' }[] | void> | void;"
+    )]
+    pub generate_bundle: Option<JsFunction>,
     #[napi(ts_type = "() => Promise<void>;")]
     pub write_bundle: Option<JsFunction>,
     #[napi(
-        ts_type = "(id: string, change: { event: 'create' | 'delete' | 'update' }) => Promise<void> | void;"
+        ts_type = "(id: string, change: { event: 'create' | 'delete' | 'update' | 'rename', from: string | null, to: string | null, importers: string[] }) => Promise<void> | void;"
     )]
     pub watch_changes: Option<JsFunction>,
     #[napi(ts_type = "(path: string, content: Buffer) => Promise<void>;")]
@@ -71,17 +109,43 @@ pub struct JsHooks {
     #[napi(ts_type = "() => Promise<void>;")]
     pub build_end: Option<JsFunction>,
     #[napi(
-        ts_type = "(source: string, importer: string, { isEntry: bool }) => Promise<{ id: string }>;"
+        ts_type = "(source: string, importer: string, { isEntry: bool }) => Promise<{ id: string; meta?: any }>;"
     )]
     pub resolve_id: Option<JsFunction>,
+    #[napi(ts_type = "(source: string, importer: string) => Promise<bool | void> | bool | void;")]
+    pub filter_externals: Option<JsFunction>,
     #[napi(
-        ts_type = "(content: string, path: string) => Promise<{ content: string, type: 'css' | 'js' } | void> | void;"
+        ts_type = "(specifier: string, importer: string) => Promise<string | void> | string | void;"
+    )]
+    pub resolve_dynamic_import: Option<JsFunction>,
+    #[napi(
+        ts_type = "(pkgDir: string, subpath: string, conditions: string[]) => Promise<string | void> | string | void;"
+    )]
+    pub on_resolve_package_exports: Option<JsFunction>,
+    #[napi(
+        ts_type = "(source: string, importer: string) => Promise<string | void> | string | void;"
+    )]
+    pub resolve_file_url: Option<JsFunction>,
+    #[napi(
+        ts_type = "(content: string, path: string) => Promise<{ content: string, type: 'css' | 'js', emittedModules?: { id: string, content: string, type: 'css' | 'js' }[] } | void> | void;"
     )]
     pub transform: Option<JsFunction>,
     #[napi(ts_type = "(filePath: string) => Promise<bool> | bool;")]
     pub transform_include: Option<JsFunction>,
+    #[napi(
+        ts_type = "(error: string, path: string) => Promise<{ content: string, type: 'css' | 'js', resolveBase?: string } | void> | void;"
+    )]
+    pub transform_error: Option<JsFunction>,
     #[napi(ts_type = "(paths: string[]) => Promise<string[] | void> | void;")]
     pub before_rebuild: Option<JsFunction>,
+    #[napi(
+        ts_type = "(chunk: { name: string; fileName: string; type: 'js' | 'css' }) => Promise<string | void> | string | void;"
+    )]
+    pub banner: Option<JsFunction>,
+    #[napi(
+        ts_type = "(chunk: { name: string; fileName: string; type: 'js' | 'css' }) => Promise<string | void> | string | void;"
+    )]
+    pub footer: Option<JsFunction>,
 }
 
 type ResolveIdFuncParams = (PluginContext, String, String, ResolveIdParams);
@@ -92,16 +156,29 @@ pub struct TsFnHooks {
     pub build_end: Option<ThreadsafeFunction<PluginContext, ()>>,
     pub write_bundle: Option<ThreadsafeFunction<PluginContext, ()>>,
     pub generate_end: Option<ThreadsafeFunction<(PluginContext, Value), ()>>,
+    pub generate_bundle:
+        Option<ThreadsafeFunction<(PluginContext, Vec<BundleChunk>), Option<Vec<BundleChunk>>>>,
     pub load: Option<ThreadsafeFunction<(PluginContext, String), Option<LoadResult>>>,
     pub load_include: Option<ThreadsafeFunction<(PluginContext, String), Option<bool>>>,
     pub watch_changes: Option<ThreadsafeFunction<(PluginContext, String, WatchChangesParams), ()>>,
     pub resolve_id: Option<ThreadsafeFunction<ResolveIdFuncParams, Option<ResolveIdResult>>>,
+    pub filter_externals: Option<ThreadsafeFunction<(PluginContext, String, String), Option<bool>>>,
+    pub resolve_dynamic_import:
+        Option<ThreadsafeFunction<(PluginContext, String, String), Option<String>>>,
+    pub on_resolve_package_exports:
+        Option<ThreadsafeFunction<(PluginContext, String, String, Vec<String>), Option<String>>>,
+    pub resolve_file_url:
+        Option<ThreadsafeFunction<(PluginContext, String, String), Option<String>>>,
     pub _on_generate_file: Option<ThreadsafeFunction<(PluginContext, WriteFile), ()>>,
     pub transform:
         Option<ThreadsafeFunction<(PluginContext, String, String), Option<TransformResult>>>,
     pub transform_include: Option<ThreadsafeFunction<(PluginContext, String), Option<bool>>>,
+    pub transform_error:
+        Option<ThreadsafeFunction<(PluginContext, String, String), Option<LoadResult>>>,
     pub before_rebuild:
         Option<ThreadsafeFunction<((), BeforeRebuildPaths), Option<BeforeRebuildPaths>>>,
+    pub banner: Option<ThreadsafeFunction<(PluginContext, ChunkInfo), Option<String>>>,
+    pub footer: Option<ThreadsafeFunction<(PluginContext, ChunkInfo), Option<String>>>,
 }
 
 impl TsFnHooks {
@@ -119,6 +196,9 @@ impl TsFnHooks {
             generate_end: hooks.generate_end.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
+            generate_bundle: hooks.generate_bundle.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
             watch_changes: hooks.watch_changes.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
@@ -131,6 +211,20 @@ impl TsFnHooks {
             resolve_id: hooks.resolve_id.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
+            filter_externals: hooks.filter_externals.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            resolve_dynamic_import: hooks.resolve_dynamic_import.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            on_resolve_package_exports: hooks.on_resolve_package_exports.as_ref().map(
+                |hook| unsafe {
+                    ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+                },
+            ),
+            resolve_file_url: hooks.resolve_file_url.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
             _on_generate_file: hooks._on_generate_file.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
@@ -140,9 +234,18 @@ impl TsFnHooks {
             transform_include: hooks.transform_include.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
+            transform_error: hooks.transform_error.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
             before_rebuild: hooks.before_rebuild.as_ref().map(|hook| unsafe {
                 ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
             }),
+            banner: hooks.banner.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
+            footer: hooks.footer.as_ref().map(|hook| unsafe {
+                ThreadsafeFunction::from_napi_value(env.raw(), hook.raw()).unwrap()
+            }),
         }
     }
 }
@@ -150,8 +253,8 @@ impl TsFnHooks {
 #[napi(object)]
 pub struct WriteFile {
     pub path: String,
-    #[napi(ts_type = "Buffer")]
-    pub content: Vec<u8>,
+    // a Vec<u8> is passed as an array of numbers, Buffer keeps binary files like images as is
+    pub content: Buffer,
 }
 
 #[napi(object, use_nullable = true)]
@@ -159,17 +262,24 @@ pub struct LoadResult {
     pub content: String,
     #[napi(js_name = "type")]
     pub content_type: String,
+    // the directory which the relative urls and @imports of the css are resolved from,
+    // instead of the directory of the file
+    pub resolve_base: Option<String>,
 }
 
 #[napi(object, use_nullable = true)]
 pub struct WatchChangesParams {
     pub event: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub importers: Vec<String>,
 }
 
 #[napi(object, use_nullable = true)]
 pub struct ResolveIdResult {
     pub id: String,
     pub external: Option<bool>,
+    pub meta: Option<Value>,
 }
 
 #[napi(object)]
@@ -182,4 +292,47 @@ pub struct TransformResult {
     pub content: String,
     #[napi(js_name = "type")]
     pub content_type: String,
+    pub emitted_modules: Option<Vec<EmittedModule>>,
+}
+
+#[napi(object)]
+pub struct EmittedModule {
+    pub id: String,
+    pub content: String,
+    #[napi(js_name = "type")]
+    pub content_type: String,
+}
+
+#[napi(object)]
+pub struct ChunkInfo {
+    pub name: String,
+    pub file_name: String,
+    #[napi(js_name = "type")]
+    pub file_type: String,
+}
+
+// the output chunk of generate_bundle, the code and source map are utf-8
+#[napi(object, use_nullable = true)]
+pub struct BundleChunk {
+    pub file_name: String,
+    pub code: String,
+    pub map: Option<String>,
+    #[napi(js_name = "type", ts_type = "'js' | 'css'")]
+    pub chunk_type: String,
+}
+
+#[napi(object)]
+pub struct EmitFileMeta {
+    pub side_effects: Option<bool>,
+}
+
+#[napi(object)]
+pub struct ChunkPreloadHint {
+    #[napi(ts_type = "'prefetch' | 'preload'")]
+    pub rel: String,
+    pub chunk_id: String,
+    pub parent_chunk_id: String,
+    pub href: String,
+    #[napi(js_name = "as", ts_type = "'script' | 'style'")]
+    pub as_type: String,
 }
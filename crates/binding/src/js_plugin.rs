@@ -1,61 +1,154 @@
-use std::path::PathBuf;
-use std::sync::{Arc, Weak};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
 
 use anyhow::{anyhow, Result};
-use mako::ast::file::{Content, JsContent};
-use mako::compiler::Context;
-use mako::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam, PluginResolveIdParams};
+use mako::ast::file::{Content, CssContent, JsContent};
+use mako::compiler::{AssetMeta, Context, LinkHintRel};
+use mako::plugin::{
+    ChunkFile, ChunkFileType, OutputChunk, Plugin, PluginError, PluginGenerateEndParams,
+    PluginLoadParam, PluginResolveIdParams, PluginWatchChangesParams,
+};
 use mako::resolve::{ExternalResource, Resolution, ResolvedResource, ResolverResource};
 use napi_derive::napi;
 
 use crate::js_hook::{
-    LoadResult, ResolveIdParams, ResolveIdResult, TransformResult, TsFnHooks, WatchChangesParams,
-    WriteFile,
+    BundleChunk, ChunkInfo, ChunkPreloadHint, EmitFileMeta, LoadResult, ResolveIdParams,
+    ResolveIdResult, TransformResult, TsFnHooks, WatchChangesParams, WriteFile,
 };
 
-fn content_from_result(result: TransformResult) -> Result<Content> {
-    match result.content_type.as_str() {
+fn chunk_info(chunk_file: &ChunkFile) -> ChunkInfo {
+    ChunkInfo {
+        name: chunk_file.chunk_name.clone(),
+        file_name: chunk_file.disk_name(),
+        file_type: file_type_name(&chunk_file.file_type),
+    }
+}
+
+fn file_type_name(file_type: &ChunkFileType) -> String {
+    match file_type {
+        ChunkFileType::JS => "js".to_string(),
+        ChunkFileType::Css => "css".to_string(),
+    }
+}
+
+fn content_from_result(
+    content: String,
+    content_type: &str,
+    resolve_base: Option<PathBuf>,
+) -> Result<Content> {
+    match content_type {
         "js" | "ts" => Ok(Content::Js(JsContent {
-            content: result.content,
+            content,
             is_jsx: false,
         })),
         "jsx" | "tsx" => Ok(Content::Js(JsContent {
-            content: result.content,
+            content,
             is_jsx: true,
         })),
-        "css" => Ok(Content::Css(result.content)),
-        _ => Err(anyhow!("Unsupported content type: {}", result.content_type)),
+        "css" => Ok(Content::Css(CssContent {
+            content,
+            resolve_base,
+        })),
+        _ => Err(anyhow!("Unsupported content type: {}", content_type)),
     }
 }
 
 #[napi]
 pub struct PluginContext {
     context: Weak<Context>,
+    // reported by the hook wrapper before the rejection, since the rejection reason is lost
+    // across the napi boundary
+    thrown: Arc<Mutex<Option<ThrownError>>>,
+}
+
+struct ThrownError {
+    message: String,
+    stack: Option<String>,
+}
+
+impl PluginContext {
+    fn new(context: &Arc<Context>) -> Self {
+        Self {
+            context: Arc::downgrade(context),
+            thrown: Default::default(),
+        }
+    }
 }
 
 #[napi]
 impl PluginContext {
     #[napi]
     pub fn warn(&self, msg: String) {
-        println!("WARN: {}", msg)
+        let context = unsafe { self.context.as_ptr().as_ref_unchecked() };
+        context.warn(msg);
     }
     #[napi]
     pub fn error(&self, msg: String) {
         println!("ERROR: {}", msg)
     }
     #[napi]
-    pub fn emit_file(&self, origin_path: String, output_path: String) {
-        let mut assets_info = {
+    pub fn emit_file(&self, origin_path: String, output_path: String, meta: Option<EmitFileMeta>) {
+        let context = unsafe { self.context.as_ptr().as_ref_unchecked() };
+        match meta {
+            Some(meta) => context.emit_assets_with_meta(
+                origin_path,
+                output_path,
+                AssetMeta {
+                    side_effects: meta.side_effects,
+                },
+            ),
+            None => context.emit_assets(origin_path, output_path),
+        }
+    }
+    #[napi]
+    pub fn get_config(&self) -> napi::Result<serde_json::Value> {
+        let context = unsafe { self.context.as_ptr().as_ref_unchecked() };
+        context
+            .config
+            .to_plugin_value(&context.root)
+            .map_err(|e| napi::Error::from_reason(e.to_string()))
+    }
+    #[napi]
+    pub fn get_module_meta(&self, id: String) -> Option<serde_json::Value> {
+        unsafe { self.context.as_ptr().as_ref_unchecked() }.get_module_meta(&id)
+    }
+    #[napi]
+    pub fn log_timing(&self, label: String, ms: f64) {
+        unsafe { self.context.as_ptr().as_ref_unchecked() }.log_timing(label, ms)
+    }
+    #[napi]
+    pub fn register_js_extension(&self, ext: String) {
+        unsafe { self.context.as_ptr().as_ref_unchecked() }.register_js_extension(&ext)
+    }
+    #[napi]
+    pub fn chunk_preload_hints(&self) -> Vec<ChunkPreloadHint> {
+        let link_hints = {
             unsafe {
                 self.context
                     .as_ptr()
                     .as_ref_unchecked()
-                    .assets_info
+                    .link_hints
                     .lock()
                     .unwrap()
             }
         };
-        assets_info.insert(origin_path, output_path);
+        link_hints
+            .iter()
+            .map(|hint| ChunkPreloadHint {
+                rel: match hint.rel {
+                    LinkHintRel::Prefetch => "prefetch".to_string(),
+                    LinkHintRel::Preload => "preload".to_string(),
+                },
+                chunk_id: hint.chunk_id.clone(),
+                parent_chunk_id: hint.parent_chunk_id.clone(),
+                href: hint.href.clone(),
+                as_type: hint.as_type.clone(),
+            })
+            .collect()
+    }
+    #[napi]
+    pub fn report_error(&self, message: String, stack: Option<String>) {
+        *self.thrown.lock().unwrap() = Some(ThrownError { message, stack });
     }
 }
 
@@ -65,6 +158,42 @@ pub struct JsPlugin {
     pub enforce: Option<String>,
 }
 
+impl JsPlugin {
+    // times the round trip of a hook call for profilePlugins, `returned` tells whether the
+    // hook did something or passed through
+    fn call_hook<T>(
+        &self,
+        context: &Arc<Context>,
+        hook: &str,
+        returned: impl FnOnce(&T) -> bool,
+        call: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        context.time_plugin_hook(self.name(), hook, returned, call)
+    }
+
+    fn hook_error(
+        &self,
+        error: anyhow::Error,
+        thrown: &Mutex<Option<ThrownError>>,
+        hook: &str,
+        path: &str,
+        content: Option<&str>,
+    ) -> anyhow::Error {
+        let (message, stack) = match thrown.lock().unwrap().take() {
+            Some(thrown) => (thrown.message, thrown.stack),
+            None => (error.to_string(), None),
+        };
+        anyhow!(PluginError::new(
+            self.name(),
+            hook,
+            path,
+            message,
+            stack,
+            content
+        ))
+    }
+}
+
 impl Plugin for JsPlugin {
     fn name(&self) -> &str {
         self.name.as_deref().unwrap_or("js_plugin")
@@ -76,36 +205,46 @@ impl Plugin for JsPlugin {
 
     fn build_start(&self, context: &Arc<Context>) -> Result<()> {
         if let Some(hook) = &self.hooks.build_start {
-            hook.call(PluginContext {
-                context: Arc::downgrade(context),
-            })?
+            self.call_hook(
+                context,
+                "buildStart",
+                |_| true,
+                || hook.call(PluginContext::new(context)),
+            )?
         }
         Ok(())
     }
 
     fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if let Some(hook) = &self.hooks.load {
-            if self.hooks.load_include.is_some()
-                && self.hooks.load_include.as_ref().unwrap().call((
-                    PluginContext {
-                        context: Arc::downgrade(context),
-                    },
-                    param.file.path.to_string_lossy().to_string(),
-                ))? == Some(false)
-            {
-                return Ok(None);
+            if let Some(load_include) = &self.hooks.load_include {
+                if self.call_hook(context, "loadInclude", Option::is_some, || {
+                    load_include.call((
+                        PluginContext::new(context),
+                        param.file.path.to_string_lossy().to_string(),
+                    ))
+                })? == Some(false)
+                {
+                    return Ok(None);
+                }
             }
-            let x: Option<LoadResult> = hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                param.file.path.to_string_lossy().to_string(),
-            ))?;
-            if let Some(x) = x {
-                return content_from_result(TransformResult {
-                    content: x.content,
-                    content_type: x.content_type,
+            let path = param.file.path.to_string_lossy().to_string();
+            let plugin_context = PluginContext::new(context);
+            let thrown = plugin_context.thrown.clone();
+            let x: Option<LoadResult> = self
+                .call_hook(context, "load", Option::is_some, || {
+                    hook.call((plugin_context, path.clone()))
                 })
+                .map_err(|e| {
+                    let content = std::fs::read_to_string(&param.file.path).ok();
+                    self.hook_error(e, &thrown, "load", &path, content.as_deref())
+                })?;
+            if let Some(x) = x {
+                return content_from_result(
+                    x.content,
+                    &x.content_type,
+                    x.resolve_base.map(PathBuf::from),
+                )
                 .map(Some);
             }
         }
@@ -120,16 +259,20 @@ impl Plugin for JsPlugin {
         context: &Arc<Context>,
     ) -> Result<Option<ResolverResource>> {
         if let Some(hook) = &self.hooks.resolve_id {
-            let x: Option<ResolveIdResult> = hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                source.to_string(),
-                importer.to_string(),
-                ResolveIdParams {
-                    is_entry: params.is_entry,
-                },
-            ))?;
+            let plugin_context = PluginContext::new(context);
+            let thrown = plugin_context.thrown.clone();
+            let x: Option<ResolveIdResult> = self
+                .call_hook(context, "resolveId", Option::is_some, || {
+                    hook.call((
+                        plugin_context,
+                        source.to_string(),
+                        importer.to_string(),
+                        ResolveIdParams {
+                            is_entry: params.is_entry,
+                        },
+                    ))
+                })
+                .map_err(|e| self.hook_error(e, &thrown, "resolveId", importer, None))?;
             if let Some(x) = x {
                 if let Some(true) = x.external {
                     return Ok(Some(ResolverResource::External(ExternalResource {
@@ -138,6 +281,9 @@ impl Plugin for JsPlugin {
                         script: None,
                     })));
                 }
+                if let Some(meta) = x.meta {
+                    context.set_module_meta(&x.id, meta);
+                }
                 return Ok(Some(ResolverResource::Resolved(ResolvedResource(
                     Resolution {
                         path: PathBuf::from(x.id),
@@ -151,45 +297,219 @@ impl Plugin for JsPlugin {
         Ok(None)
     }
 
+    fn filter_externals(
+        &self,
+        source: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<bool>> {
+        if let Some(hook) = &self.hooks.filter_externals {
+            return self.call_hook(context, "filterExternals", Option::is_some, || {
+                hook.call((
+                    PluginContext::new(context),
+                    source.to_string(),
+                    importer.to_string(),
+                ))
+            });
+        }
+        Ok(None)
+    }
+
+    fn resolve_dynamic_import(
+        &self,
+        specifier: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        if let Some(hook) = &self.hooks.resolve_dynamic_import {
+            return self.call_hook(context, "resolveDynamicImport", Option::is_some, || {
+                hook.call((
+                    PluginContext::new(context),
+                    specifier.to_string(),
+                    importer.to_string(),
+                ))
+            });
+        }
+        Ok(None)
+    }
+
+    fn on_resolve_package_exports(
+        &self,
+        pkg_dir: &Path,
+        subpath: &str,
+        conditions: &[String],
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        if let Some(hook) = &self.hooks.on_resolve_package_exports {
+            return self.call_hook(context, "onResolvePackageExports", Option::is_some, || {
+                hook.call((
+                    PluginContext::new(context),
+                    pkg_dir.to_string_lossy().to_string(),
+                    subpath.to_string(),
+                    conditions.to_vec(),
+                ))
+            });
+        }
+        Ok(None)
+    }
+
+    fn has_on_resolve_package_exports(&self) -> bool {
+        self.hooks.on_resolve_package_exports.is_some()
+    }
+
+    fn resolve_file_url(
+        &self,
+        source: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Option<String> {
+        let hook = self.hooks.resolve_file_url.as_ref()?;
+        let plugin_context = PluginContext::new(context);
+        let thrown = plugin_context.thrown.clone();
+        self.call_hook(context, "resolveFileUrl", Option::is_some, || {
+            hook.call((plugin_context, source.to_string(), importer.to_string()))
+        })
+        .map_err(|e| self.hook_error(e, &thrown, "resolveFileUrl", importer, None))
+        .unwrap_or_else(|e| {
+            // the url is left to the builtin asset handling
+            context.warn(e.to_string());
+            None
+        })
+    }
+
     fn generate_end(&self, param: &PluginGenerateEndParams, context: &Arc<Context>) -> Result<()> {
         // keep generate_end for compatibility
         // since build_end does not have none error params in unplugin's api spec
         if let Some(hook) = &self.hooks.generate_end {
-            hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                serde_json::to_value(param)?,
-            ))?
+            self.call_hook(
+                context,
+                "generateEnd",
+                |_| true,
+                || hook.call((PluginContext::new(context), serde_json::to_value(param)?)),
+            )?
         }
         if let Some(hook) = &self.hooks.build_end {
-            hook.call(PluginContext {
-                context: Arc::downgrade(context),
-            })?
+            self.call_hook(
+                context,
+                "buildEnd",
+                |_| true,
+                || hook.call(PluginContext::new(context)),
+            )?
         }
         Ok(())
     }
 
-    fn watch_changes(&self, id: &str, event: &str, context: &Arc<Context>) -> Result<()> {
+    fn has_generate_bundle(&self) -> bool {
+        self.hooks.generate_bundle.is_some()
+    }
+
+    fn generate_bundle(&self, chunks: &mut Vec<OutputChunk>, context: &Arc<Context>) -> Result<()> {
+        let Some(hook) = &self.hooks.generate_bundle else {
+            return Ok(());
+        };
+        let params = chunks
+            .iter()
+            .map(|chunk| BundleChunk {
+                file_name: chunk.file_name.clone(),
+                code: String::from_utf8_lossy(&chunk.code).to_string(),
+                map: chunk
+                    .map
+                    .as_ref()
+                    .map(|map| String::from_utf8_lossy(map).to_string()),
+                chunk_type: file_type_name(&chunk.type_),
+            })
+            .collect();
+        let Some(result) = self.call_hook(context, "generateBundle", Option::is_some, || {
+            hook.call((PluginContext::new(context), params))
+        })?
+        else {
+            return Ok(());
+        };
+
+        // the chunks are matched by file names, so the returned chunks keep the chunk ids of
+        // the ones they replace, and a renamed chunk is a new one
+        chunks.retain(|chunk| {
+            result
+                .iter()
+                .any(|js_chunk| js_chunk.file_name == chunk.file_name)
+        });
+        for js_chunk in result {
+            let code = js_chunk.code.into_bytes();
+            let map = js_chunk.map.map(String::into_bytes);
+            match chunks
+                .iter_mut()
+                .find(|chunk| chunk.file_name == js_chunk.file_name)
+            {
+                Some(chunk) => {
+                    chunk.code = code;
+                    chunk.map = map;
+                }
+                None => {
+                    let file_type = match js_chunk.chunk_type.as_str() {
+                        "css" => ChunkFileType::Css,
+                        _ => ChunkFileType::JS,
+                    };
+                    chunks.push(OutputChunk::new(js_chunk.file_name, code, map, file_type));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn banner(&self, chunk_file: &ChunkFile, context: &Arc<Context>) -> Result<Option<String>> {
+        if let Some(hook) = &self.hooks.banner {
+            return self.call_hook(context, "banner", Option::is_some, || {
+                hook.call((PluginContext::new(context), chunk_info(chunk_file)))
+            });
+        }
+        Ok(None)
+    }
+
+    fn footer(&self, chunk_file: &ChunkFile, context: &Arc<Context>) -> Result<Option<String>> {
+        if let Some(hook) = &self.hooks.footer {
+            return self.call_hook(context, "footer", Option::is_some, || {
+                hook.call((PluginContext::new(context), chunk_info(chunk_file)))
+            });
+        }
+        Ok(None)
+    }
+
+    fn watch_changes(
+        &self,
+        id: &str,
+        params: &PluginWatchChangesParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
         if let Some(hook) = &self.hooks.watch_changes {
-            hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
+            self.call_hook(
+                context,
+                "watchChanges",
+                |_| true,
+                || {
+                    hook.call((
+                        PluginContext::new(context),
+                        id.to_string(),
+                        WatchChangesParams {
+                            event: params.event.clone(),
+                            from: params.from.clone(),
+                            to: params.to.clone(),
+                            importers: params.importers.clone(),
+                        },
+                    ))
                 },
-                id.to_string(),
-                WatchChangesParams {
-                    event: event.to_string(),
-                },
-            ))?
+            )?
         }
         Ok(())
     }
 
     fn write_bundle(&self, context: &Arc<Context>) -> Result<()> {
         if let Some(hook) = &self.hooks.write_bundle {
-            hook.call(PluginContext {
-                context: Arc::downgrade(context),
-            })?
+            self.call_hook(
+                context,
+                "writeBundle",
+                |_| true,
+                || hook.call(PluginContext::new(context)),
+            )?
         }
         Ok(())
     }
@@ -201,15 +521,20 @@ impl Plugin for JsPlugin {
         context: &Arc<Context>,
     ) -> Result<()> {
         if let Some(hook) = &self.hooks._on_generate_file {
-            hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                WriteFile {
-                    path: path.to_string_lossy().to_string(),
-                    content: content.to_vec(),
+            self.call_hook(
+                context,
+                "_onGenerateFile",
+                |_| true,
+                || {
+                    hook.call((
+                        PluginContext::new(context),
+                        WriteFile {
+                            path: path.to_string_lossy().to_string(),
+                            content: content.to_vec().into(),
+                        },
+                    ))
                 },
-            ))?;
+            )?;
         }
         Ok(())
     }
@@ -222,12 +547,9 @@ impl Plugin for JsPlugin {
         context: &Arc<Context>,
     ) -> Result<Option<Content>> {
         if let Some(hook) = &self.hooks.transform_include {
-            if hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                path.to_string(),
-            ))? == Some(false)
+            if self.call_hook(context, "transformInclude", Option::is_some, || {
+                hook.call((PluginContext::new(context), path.to_string()))
+            })? == Some(false)
             {
                 return Ok(None);
             }
@@ -236,25 +558,69 @@ impl Plugin for JsPlugin {
         if let Some(hook) = &self.hooks.transform {
             let content_str = match content {
                 Content::Js(js_content) => js_content.content.clone(),
-                Content::Css(css_content) => css_content.clone(),
+                Content::Css(css_content) => css_content.content.clone(),
                 _ => return Ok(None),
             };
 
-            let result: Option<TransformResult> = hook.call((
-                PluginContext {
-                    context: Arc::downgrade(context),
-                },
-                content_str,
-                path.to_string(),
-            ))?;
+            let plugin_context = PluginContext::new(context);
+            let thrown = plugin_context.thrown.clone();
+            let result: Option<TransformResult> = self
+                .call_hook(context, "transform", Option::is_some, || {
+                    hook.call((plugin_context, content_str.clone(), path.to_string()))
+                })
+                .map_err(|e| {
+                    self.hook_error(e, &thrown, "transform", path, Some(content_str.as_str()))
+                })?;
 
             if let Some(result) = result {
-                return content_from_result(result).map(Some);
+                // the transformed css still lives where it was loaded from
+                let resolve_base = match content {
+                    Content::Css(css_content) => css_content.resolve_base.clone(),
+                    _ => None,
+                };
+                let mut content =
+                    content_from_result(result.content, &result.content_type, resolve_base)?;
+                // the modules emitted before are replaced, even with none
+                let modules = result
+                    .emitted_modules
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|m| Ok((m.id, content_from_result(m.content, &m.content_type, None)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                context.emitted_modules.emit(path, modules, &mut content);
+                return Ok(Some(content));
             }
         }
         Ok(None)
     }
 
+    fn transform_error(
+        &self,
+        error: &anyhow::Error,
+        path: &str,
+        context: &Arc<Context>,
+    ) -> Option<Content> {
+        let hook = self.hooks.transform_error.as_ref()?;
+        let result: Option<LoadResult> = self
+            .call_hook(context, "transformError", Option::is_some, || {
+                hook.call((
+                    PluginContext::new(context),
+                    error.to_string(),
+                    path.to_string(),
+                ))
+            })
+            .ok()
+            .flatten();
+        result.and_then(|x| {
+            content_from_result(
+                x.content,
+                &x.content_type,
+                x.resolve_base.map(PathBuf::from),
+            )
+            .ok()
+        })
+    }
+
     fn before_rebuild(&self, paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
         // TODO: 临时方案，出于热更性能考虑只在less/sass文件变动时调用js-hook，后续优化。
         let (less_or_sass, others): (Vec<PathBuf>, Vec<PathBuf>) = paths
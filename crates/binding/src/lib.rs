@@ -8,9 +8,9 @@ use std::sync::{Arc, Once};
 use js_hook::{JsHooks, TsFnHooks};
 use js_plugin::JsPlugin;
 use mako::compiler::{Args, Compiler};
-use mako::config::Config;
+use mako::config::{validate_config, Config};
 use mako::dev::DevServer;
-use mako::plugin::Plugin;
+use mako::plugin::{create_builtin_plugins, Plugin};
 use mako::utils::logger::init_logger;
 use mako::utils::thread_pool;
 use napi::bindgen_prelude::*;
@@ -29,7 +29,9 @@ pub struct BuildParams {
 
     #[napi(ts_type = r#"
 {
-    entry?: Record<string, string>;
+    entry?:
+      | Record<string, string | { import: string; filename?: string; banner?: string; footer?: string }>
+      | { pattern: string; name?: string };
     output?: {
         path: string;
         mode: "bundle" | "bundless" ;
@@ -38,18 +40,41 @@ pub struct BuildParams {
         preserveModules?: boolean;
         preserveModulesRoot?: string;
         skipWrite?: boolean;
+        inlineDynamicImports?: boolean;
+        banner?: string;
+        footer?: string;
+        legacyBrowsers?: boolean;
     };
     resolve?: {
        alias?: Array<[string, string]>;
        extensions?: string[];
+       modules?: string[];
+       browserField?: boolean;
+       fallback?: Record<string, string | false | string[]>;
+       ignoreMissing?: string[];
+       symlinks?: boolean;
     };
     manifest?: false | {
         fileName: string;
         basePath: string;
     };
+    html?: boolean | {
+        entry: string;
+        template?: string;
+        filename?: string;
+        inject?: "head" | "body";
+        publicPath?: string;
+        title?: string;
+    }[];
     mode?: "development" | "production";
     define?: Record<string, string>;
+    envFile?: string[];
     devtool?: false | "source-map" | "inline-source-map";
+    sourceMap?: {
+        sourcesContent?: boolean;
+        sourceRoot?: string;
+        pathTransform?: "relative" | "module-id";
+    };
     externals?: Record<
         string,
         string | {
@@ -110,8 +135,11 @@ pub struct BuildParams {
         selectorDoubleList?: string[];
         mediaQuery?: boolean;
     };
+    analyze?: {} | boolean;
     stats?: false | {
         modules?: boolean;
+        duplicatePackageThreshold?: number;
+        shakeMetadata?: string;
     };
     hash?: boolean;
     autoCSSModules?: boolean;
@@ -124,7 +152,10 @@ pub struct BuildParams {
     clean?: boolean;
     nodePolyfill?: boolean;
     ignores?: string[];
-    moduleIdStrategy?: "hashed" | "named";
+    noSideEffectModules?: string[];
+    forceSideEffects?: string[];
+    moduleIdStrategy?: "hashed" | "named" | "numeric";
+    moduleWrapper?: "factory" | "commonjs" | "none";
     minify?: boolean;
     _minifish?: false | {
         mapping: Record<string, string>;
@@ -140,6 +171,12 @@ pub struct BuildParams {
     optimization?: false | {
         skipModules?: boolean;
         concatenateModules?: boolean;
+        mangleExports?: boolean;
+        treeShaking?: "modules" | "statements" | false;
+        ambiguousExportsThreshold?: number;
+        deferredExports?: boolean;
+        minifyWorkers?: number;
+        cssDedupe?: boolean;
     };
     react?: {
         runtime?: "automatic" | "classic";
@@ -148,6 +185,9 @@ pub struct BuildParams {
         pragmaFrag?: string;
     };
     emitAssets?: boolean;
+    profile?: boolean;
+    profilePlugins?: boolean;
+    svgr?: boolean;
     cssModulesExportOnlyLocales?: boolean;
     inlineCSS?: false | {};
     rscServer?: false | {
@@ -157,6 +197,13 @@ pub struct BuildParams {
     rscClient?: false | {
         "logServerComponent": "error" | "ignore";
     };
+    runtime?: {
+        chunkRetry?: false | {
+            times: number;
+            delayMs: number;
+            cacheBust?: boolean;
+        };
+    };
     moduleFederation?: {
         name: string;
         filename?: string;
@@ -185,19 +232,80 @@ pub struct BuildParams {
         rustPlugins?: Array<[string, any]>;
         centralEnsure?: bool,
         importsChecker?: bool,
+        lazyCompilation?: bool,
     };
     watch?: {
         ignoredPaths?: string[];
         _nodeModulesRegexes?: string[];
+        debounce?: number;
+    };
+    debug?: {
+        dumpModuleGraph?: boolean;
+        dumpStatementGraph?: string;
     };
     caseSensitiveCheck?: boolean;
+    lint?: {
+        circularRequire?: "warn" | "error" | "off";
+        circularDependency?: "warn" | "error" | "off";
+    };
+    performance?: {
+        maxChunkSize?: number;
+        maxEntrypointSize?: number;
+    };
 }"#)]
     pub config: serde_json::Value,
     pub plugins: Vec<JsHooks>,
+    // names of the compiled-in rust plugins to enable, e.g. ["svgr"]
+    pub builtin_plugins: Option<Vec<String>>,
     pub watch: bool,
 }
 
-#[napi(ts_return_type = r#"Promise<void>"#)]
+#[napi(object, use_nullable = true)]
+pub struct CompileResult {
+    pub output_files: Vec<OutputFile>,
+    pub warnings: Vec<Diagnostic>,
+    // the same as stats.json, only when `stats` is enabled
+    pub stats: Option<serde_json::Value>,
+}
+
+#[napi(object)]
+pub struct OutputFile {
+    // relative to the output dir
+    pub path: String,
+    pub size: i64,
+}
+
+#[napi(object)]
+pub struct Diagnostic {
+    pub message: String,
+}
+
+impl From<mako::compiler::CompileResult> for CompileResult {
+    fn from(result: mako::compiler::CompileResult) -> Self {
+        Self {
+            output_files: result
+                .output_files
+                .into_iter()
+                .map(|file| OutputFile {
+                    path: file.path,
+                    size: file.size as i64,
+                })
+                .collect(),
+            warnings: result
+                .warnings
+                .into_iter()
+                .map(|warning| Diagnostic {
+                    message: warning.message,
+                })
+                .collect(),
+            stats: result
+                .stats
+                .map(|stats| serde_json::to_value(stats).unwrap()),
+        }
+    }
+}
+
+#[napi(ts_return_type = r#"Promise<CompileResult>"#)]
 pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
     LOG_INIT.call_once(|| {
         init_logger();
@@ -213,6 +321,14 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
         };
         plugins.push(Arc::new(plugin));
     }
+    if let Some(names) = &build_params.builtin_plugins {
+        plugins.extend(create_builtin_plugins(names).map_err(|e| {
+            napi::Error::new(
+                Status::GenericFailure,
+                format!("Load builtin plugins failed: {}", e),
+            )
+        })?);
+    }
 
     // sort with enforce: pre / post
     plugins.sort_by_key(|plugin| match plugin.enforce() {
@@ -222,6 +338,9 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
     });
 
     let root = std::path::PathBuf::from(&build_params.root);
+    validate_config(&build_params.config).map_err(|e| {
+        napi::Error::new(Status::GenericFailure, format!("Load config failed: {}", e))
+    })?;
     let default_config = serde_json::to_string(&build_params.config).unwrap();
     let config = Config::new(&root, Some(&default_config), None).map_err(|e| {
         napi::Error::new(Status::GenericFailure, format!("Load config failed: {}", e))
@@ -240,15 +359,18 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
                 }
                 let compiler = compiler.unwrap();
 
-                if let Err(e) = compiler
+                let result = match compiler
                     .compile()
                     .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)))
                 {
-                    deferred.reject(e);
-                    return Ok(());
-                }
+                    Ok(result) => CompileResult::from(result),
+                    Err(e) => {
+                        deferred.reject(e);
+                        return Ok(());
+                    }
+                };
                 let d = DevServer::new(root.clone(), Arc::new(compiler));
-                deferred.resolve(move |env| env.get_undefined());
+                deferred.resolve(move |_env| Ok(result));
                 d.serve().await;
                 Ok(())
             },
@@ -271,11 +393,13 @@ pub fn build(env: Env, build_params: BuildParams) -> napi::Result<JsObject> {
             let ret = compiler
                 .compile()
                 .map_err(|e| napi::Error::new(Status::GenericFailure, format!("{}", e)));
-            if let Err(e) = ret {
-                deferred.reject(e);
-                return;
+            match ret {
+                Ok(result) => {
+                    let result = CompileResult::from(result);
+                    deferred.resolve(move |_env| Ok(result));
+                }
+                Err(e) => deferred.reject(e),
             }
-            deferred.resolve(move |env| env.get_undefined());
         });
         Ok(promise)
     }
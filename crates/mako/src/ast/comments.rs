@@ -15,6 +15,15 @@ impl Comments {
         self.0.add_leading(pos, comment);
     }
 
+    pub fn remove_leading_comments_at(&mut self, pos: BytePos, f: impl Fn(&Comment) -> bool) {
+        if let Some(comments) = self.0.take_leading(pos) {
+            let (_, kept): (Vec<_>, Vec<_>) = comments.into_iter().partition(|c| f(c));
+            if !kept.is_empty() {
+                self.0.add_leading_comments(pos, kept);
+            }
+        }
+    }
+
     /**
      * Check for `/*#__UNUSED__*/`
      */
@@ -42,11 +51,24 @@ impl Comments {
     /**
      * Check for `/*#__NO_SIDE_EFFECTS__*/`
      */
-    #[allow(dead_code)]
-    fn has_no_side_effects(&self, span: Span) -> bool {
+    pub fn has_no_side_effects(&self, span: Span) -> bool {
         self.has_flag(span, "NO_SIDE_EFFECTS")
     }
 
+    /**
+     * Check for `/*#__KEEP__*/`
+     */
+    pub fn has_keep(&self, span: Span) -> bool {
+        self.has_flag(span, "KEEP")
+    }
+
+    /**
+     * Check for `/*#__DROPPABLE__*/`
+     */
+    pub fn has_droppable(&self, span: Span) -> bool {
+        self.has_flag(span, "DROPPABLE")
+    }
+
     #[allow(dead_code)]
     fn has_flag(&self, span: Span, text: &'static str) -> bool {
         self.find_comment(span, |c| {
@@ -14,8 +14,8 @@ use swc_core::ecma::atoms;
 use swc_core::ecma::parser::StringInput;
 
 use crate::ast::error;
-use crate::ast::file::{Content, File};
-use crate::ast::sourcemap::build_source_map_to_buf;
+use crate::ast::file::{Content, CssContent, File};
+use crate::ast::sourcemap::{build_source_map_to_buf, finalize_source_map_buf};
 use crate::compiler::Context;
 use crate::config::{DevtoolConfig, Mode};
 use crate::module::Dependency;
@@ -86,7 +86,10 @@ impl CssAst {
         CssAst::new(
             &File::with_content(
                 path.to_string(),
-                Content::Css(content.to_string()),
+                Content::Css(CssContent {
+                    content: content.to_string(),
+                    resolve_base: None,
+                }),
                 context.clone(),
             ),
             context.clone(),
@@ -129,7 +132,10 @@ impl CssAst {
             })
         })?;
 
-        let buf = build_source_map_to_buf(&source_map, &context.meta.css.cm);
+        let buf = finalize_source_map_buf(
+            build_source_map_to_buf(&source_map, &context.meta.css.cm),
+            &context,
+        )?;
         let sourcemap = String::from_utf8(buf).unwrap();
         if matches!(context.config.devtool, Some(DevtoolConfig::SourceMap)) {
             let filename = &self.path;
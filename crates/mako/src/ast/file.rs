@@ -36,10 +36,19 @@ impl Default for JsContent {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct CssContent {
+    pub content: String,
+    // the directory which the relative urls and @imports are resolved from, for the css
+    // which conceptually lives somewhere else than the module, e.g. the style blocks of
+    // sfc files, relative paths are relative to the root
+    pub resolve_base: Option<PathBuf>,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Content {
     Js(JsContent),
-    Css(String),
+    Css(CssContent),
     // TODO: unify the assets handler
     // it's used in minifish plugin(bundless mode) only
     // and bundle mode will emit assets to context.assets_info
@@ -188,9 +197,8 @@ impl File {
 
     pub fn get_content_raw(&self) -> String {
         match &self.content {
-            Some(Content::Js(JsContent { content, .. })) | Some(Content::Css(content)) => {
-                content.clone()
-            }
+            Some(Content::Js(JsContent { content, .. }))
+            | Some(Content::Css(CssContent { content, .. })) => content.clone(),
             Some(Content::Assets(asset)) => asset.content.clone(),
             None => "".to_string(),
         }
@@ -201,7 +209,7 @@ impl File {
         if let Some(content) = &self.content {
             match content {
                 Content::Js(JsContent { content, .. })
-                | Content::Css(content)
+                | Content::Css(CssContent { content, .. })
                 | Content::Assets(Asset { content, .. }) => {
                     // hasher.write_u64(init);
                     hasher.write(content.as_bytes());
@@ -266,6 +274,10 @@ impl File {
         Ok(hash[0..8].to_string())
     }
 
+    pub fn is_ts(&self) -> bool {
+        matches!(self.extname.as_str(), "ts" | "tsx" | "mts" | "cts")
+    }
+
     pub fn is_content_jsx(&self) -> bool {
         match &self.content {
             Some(Content::Js(JsContent { is_jsx, .. })) => *is_jsx,
@@ -290,7 +302,7 @@ impl File {
         }
         let mut chain = vec![];
         match &self.content {
-            Some(Content::Css(content)) => {
+            Some(Content::Css(CssContent { content, .. })) => {
                 if let Some(captures) = css_source_map_regex().captures(content) {
                     let source_map_base64 = captures.get(1).unwrap().as_str().to_string();
                     chain.push(base64_decode(source_map_base64.as_bytes()));
@@ -312,7 +324,31 @@ impl File {
         }
     }
 
+    // the path in the resolve base of the css content, the dependencies are resolved from the
+    // directory of it
+    pub fn css_resolve_base_path(&self, context: &Arc<Context>) -> Option<String> {
+        let Some(Content::Css(CssContent {
+            resolve_base: Some(resolve_base),
+            ..
+        })) = &self.content
+        else {
+            return None;
+        };
+        let name = self.pathname.file_name().unwrap_or("index.css".as_ref());
+        Some(
+            context
+                .root
+                .join(resolve_base)
+                .join(name)
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+
     pub fn resolve_from(&self, context: &Arc<Context>) -> String {
+        if let Some(path) = self.css_resolve_base_path(context) {
+            return path;
+        }
         self.path().map_or_else(
             || {
                 context
@@ -382,6 +418,8 @@ pub fn parse_path(path: &str) -> Result<(PathName, Search, Params, Fragment)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::plugin::{Plugin, PluginLoadParam};
+    use crate::utils::test_helper::setup_compiler_with_plugins;
 
     #[test]
     fn test_abs_path() {
@@ -404,6 +442,32 @@ mod tests {
         assert_eq!(f.path(), Some("/root/d.js".to_string()));
     }
 
+    #[test]
+    fn test_css_resolve_base_path_of_virtual_file() {
+        let context = Arc::new(Context::default());
+        let mut f = File::new("virtual:/a/b/style.css".to_string(), context.clone());
+        f.set_content(Content::Css(CssContent::default()));
+        assert_eq!(f.css_resolve_base_path(&context), None);
+        f.set_content(Content::Css(CssContent {
+            content: "".to_string(),
+            resolve_base: Some("assets".into()),
+        }));
+        assert_eq!(
+            f.css_resolve_base_path(&context),
+            Some(
+                context
+                    .root
+                    .join("assets/style.css")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            f.css_resolve_base_path(&context),
+            Some(f.resolve_from(&context))
+        );
+    }
+
     #[test]
     fn test_parse_path_support_windows() {
         let path = "C:\\a\\b\\c?foo";
@@ -428,4 +492,41 @@ mod tests {
         assert!(!has_hash_without_dot("no_hash"));
         assert!(!has_hash_without_dot("#.dot_after_hash"));
     }
+
+    // loads src/style.css as css which refers to an image in assets/
+    struct CssResolveBasePlugin {}
+
+    impl Plugin for CssResolveBasePlugin {
+        fn name(&self) -> &str {
+            "css_resolve_base_test"
+        }
+
+        fn load(
+            &self,
+            param: &PluginLoadParam,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Content>> {
+            Ok(param.file.path.ends_with("style.css").then(|| {
+                Content::Css(CssContent {
+                    content: ".loaded { background: url(./img.png); }".to_string(),
+                    resolve_base: Some("assets".into()),
+                })
+            }))
+        }
+    }
+
+    #[test]
+    fn test_load_css_with_resolve_base() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/css-resolve-base",
+            vec![Arc::new(CssResolveBasePlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        // img.png is in assets/ instead of src/, and it's small enough to be inlined
+        let index = compiler.context.config.output.path.join("index.css");
+        let content = std::fs::read_to_string(index).unwrap();
+        assert!(content.contains("data:image/png;base64,"), "{}", content);
+    }
 }
@@ -16,7 +16,7 @@ use swc_core::ecma::visit;
 use swc_core::ecma::visit::{VisitMutWith, VisitWith};
 
 use crate::ast::file::{Content, File, JsContent};
-use crate::ast::sourcemap::build_source_map_to_buf;
+use crate::ast::sourcemap::{build_source_map_to_buf, finalize_source_map_buf};
 use crate::ast::{error, utils};
 use crate::compiler::Context;
 use crate::config::{DevtoolConfig, Mode, OutputMode};
@@ -47,7 +47,7 @@ impl JsAst {
         );
         let comments = context.meta.script.origin_comments.read().unwrap();
         let extname = &file.extname;
-        let syntax = if extname == "ts" || extname == "tsx" {
+        let syntax = if file.is_ts() {
             Syntax::Typescript(TsSyntax {
                 tsx: extname == "tsx",
                 decorators: true,
@@ -224,7 +224,10 @@ impl JsAst {
 
         let sourcemap = match context.config.devtool {
             Some(DevtoolConfig::SourceMap | DevtoolConfig::InlineSourceMap) => {
-                let src_buf = build_source_map_to_buf(&source_map_buf, &cm);
+                let src_buf = finalize_source_map_buf(
+                    build_source_map_to_buf(&source_map_buf, &cm),
+                    &context,
+                )?;
                 String::from_utf8(src_buf).unwrap()
             }
             None => "".to_string(),
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use anyhow::Result;
 use pathdiff::diff_paths;
 use swc_core::base::sourcemap as swc_sourcemap;
 use swc_core::common::source_map::SourceMapGenConfig;
 use swc_core::common::sync::Lrc;
 use swc_core::common::{BytePos, FileName, LineCol, SourceMap};
 
+use crate::ast::file::win_path;
+use crate::compiler::Context;
+use crate::config::SourceMapPathTransform;
+
 pub struct SwcSourceMapGenConfig;
 
 impl SourceMapGenConfig for SwcSourceMapGenConfig {
@@ -39,6 +44,99 @@ pub fn build_source_map(
     cm.build_source_map_with_config(mappings, None, config)
 }
 
+/**
+ * Apply the `sourceMap` config to a map which is about to be emitted, i.e. transform the
+ * paths of `sources`, drop `sourcesContent` and set `sourceRoot`.
+ */
+pub fn finalize_source_map(
+    mut sm: swc_sourcemap::SourceMap,
+    context: &Context,
+) -> swc_sourcemap::SourceMap {
+    let config = &context.config.source_map;
+
+    if let Some(path_transform) = config.path_transform {
+        let sources = sm.sources().map(|s| s.to_string()).collect::<Vec<_>>();
+        for (idx, source) in sources.iter().enumerate() {
+            let transformed = transform_source_path(source, &context.root, path_transform);
+            sm.set_source(idx as u32, &transformed);
+        }
+    }
+
+    if !config.sources_content {
+        for idx in 0..sm.get_source_count() {
+            sm.set_source_contents(idx, None::<&str>);
+        }
+    }
+
+    if let Some(source_root) = &config.source_root {
+        sm.set_source_root(Some(source_root.as_str()));
+    }
+
+    sm
+}
+
+pub fn finalize_source_map_buf(buf: Vec<u8>, context: &Context) -> Result<Vec<u8>> {
+    let config = &context.config.source_map;
+    if config.sources_content && config.source_root.is_none() && config.path_transform.is_none() {
+        return Ok(buf);
+    }
+
+    let sm = finalize_source_map(swc_sourcemap::SourceMap::from_slice(&buf)?, context);
+    let mut finalized = vec![];
+    sm.to_writer(&mut finalized)?;
+
+    Ok(finalized)
+}
+
+/**
+ * Shift the generated lines of a map by `lines`, for the code which is prepended to the
+ * file after the map is built, e.g. `output.banner`.
+ */
+pub fn offset_source_map_buf(buf: Vec<u8>, lines: u32) -> Result<Vec<u8>> {
+    if lines == 0 {
+        return Ok(buf);
+    }
+
+    let mut raw: RawSourceMap = swc_sourcemap::SourceMap::from_slice(&buf)?.into();
+    raw.tokens
+        .iter_mut()
+        .for_each(|token| token.dst_line += lines);
+    let sm: swc_sourcemap::SourceMap = raw.into();
+    let mut offset = vec![];
+    sm.to_writer(&mut offset)?;
+
+    Ok(offset)
+}
+
+fn transform_source_path(
+    source: &str,
+    root: &Path,
+    path_transform: SourceMapPathTransform,
+) -> String {
+    // sources like `<anon>` or the ones already transformed are kept
+    let relative = if Path::new(source).is_absolute() {
+        match diff_paths(source, root) {
+            Some(relative) => win_path(&relative.to_string_lossy()),
+            None => return source.to_string(),
+        }
+    } else {
+        source.to_string()
+    };
+
+    match path_transform {
+        SourceMapPathTransform::Relative => relative,
+        SourceMapPathTransform::ModuleId => {
+            if relative.starts_with("mako://") {
+                relative
+            } else if relative.starts_with("../") || relative.starts_with("./") {
+                format!("mako:///{}", relative)
+            } else {
+                format!("mako:///./{}", relative)
+            }
+        }
+    }
+}
+
 // Add this type because the sourcemap::SourceMap type can't be cached,
 // there is a RefCell type field in it
 #[derive(Clone, Default, Debug)]
@@ -158,7 +256,8 @@ mod test {
     use std::path::PathBuf;
     use std::str::FromStr;
 
-    use crate::ast::sourcemap::{merge_source_map, swc_sourcemap};
+    use crate::ast::sourcemap::{merge_source_map, swc_sourcemap, transform_source_path};
+    use crate::config::SourceMapPathTransform;
 
     #[test]
     fn test_merge_empty_chain() {
@@ -249,4 +348,34 @@ mod test {
 
         assert!(merged.eq(r#"{"version":3,"sources":["index.ts"],"sourcesContent":["function sayHello(name: string) {\n  console.log(`Hello, ${name}`);\n}\n"],"names":[],"mappings":"AAAA,SAAS,SAAS,CAAY,EAC5B,QAAQ,GAAG,CAAC,UAAA,MAAA,CAAU,GACxB"}"#));
     }
+
+    #[test]
+    fn test_transform_source_path() {
+        let root = PathBuf::from("/project");
+        let transform = |source: &str, path_transform| {
+            transform_source_path(source, &root, path_transform)
+        };
+
+        assert_eq!(
+            transform("/project/src/index.ts", SourceMapPathTransform::Relative),
+            "src/index.ts"
+        );
+        assert_eq!(
+            transform("/shared/utils.ts", SourceMapPathTransform::Relative),
+            "../shared/utils.ts"
+        );
+        assert_eq!(
+            transform("/project/src/index.ts", SourceMapPathTransform::ModuleId),
+            "mako:///./src/index.ts"
+        );
+        assert_eq!(
+            transform("/shared/utils.ts", SourceMapPathTransform::ModuleId),
+            "mako:///../shared/utils.ts"
+        );
+        assert_eq!(
+            transform("src/index.ts", SourceMapPathTransform::ModuleId),
+            "mako:///./src/index.ts"
+        );
+        assert_eq!(transform("<anon>", SourceMapPathTransform::Relative), "<anon>");
+    }
 }
@@ -6,7 +6,7 @@ use swc_core::ecma::transforms::base::resolver;
 use swc_core::ecma::visit::VisitMutWith;
 
 use super::css_ast::{CSSAstGenerated, CssAst};
-use super::file::{Content, File, JsContent};
+use super::file::{Content, CssContent, File, JsContent};
 use super::js_ast::{JSAstGenerated, JsAst};
 use crate::compiler::Context;
 use crate::config::Mode;
@@ -84,7 +84,10 @@ impl TestUtils {
             "".to_string()
         };
         if is_css {
-            file.set_content(Content::Css(content));
+            file.set_content(Content::Css(CssContent {
+                content,
+                resolve_base: None,
+            }));
         } else {
             file.set_content(Content::Js(JsContent { content, is_jsx }));
         }
@@ -1,10 +1,11 @@
 pub(crate) mod analyze_deps;
+pub(crate) mod emitted_modules;
 pub(crate) mod load;
 pub(crate) mod parse;
 pub(crate) mod targets;
 pub(crate) mod transform;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::channel;
 use std::sync::Arc;
 
@@ -14,10 +15,11 @@ use thiserror::Error;
 
 use crate::ast::file::{Content, File, JsContent};
 use crate::ast::utils::get_module_system;
-use crate::compiler::{Compiler, Context};
+use crate::compiler::{BuildPhase, Compiler, Context};
 use crate::generate::chunk_pot::util::hash_hashmap;
 use crate::module::{FedereationModuleType, Module, ModuleAst, ModuleId, ModuleInfo, ModuleSystem};
 use crate::plugin::NextBuildParam;
+use crate::plugins::const_enum::{self, ConstEnumMembers};
 use crate::resolve::{ConsumeSharedInfo, RemoteInfo, ResolverResource};
 use crate::utils::thread_pool;
 
@@ -297,28 +299,56 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         parent_resource: Option<ResolverResource>,
         context: Arc<Context>,
     ) -> Result<Module> {
-        // 1. load
         let mut file = file.clone();
-        let mut content = load::Load::load(&file, context.clone())?;
-        let content = context.plugin_driver.load_transform(
-            &mut content,
-            &file.path.to_string_lossy(),
-            file.is_entry,
-            &context,
-        )?;
-        file.set_content(content);
-
-        // 2. parse
-        let mut ast = parse::Parse::parse(&file, context.clone())?;
-
-        // 3. transform
-        transform::Transform::transform(&mut ast, &file, context.clone())?;
+        let path = file.path.to_string_lossy().to_string();
+        // the builds of output.legacyBrowsers share the contents after the load and transform
+        // hooks, so that the js plugins run once for each module
+        let shared_content = context
+            .shared_contents
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|contents| contents.get(&path).cloned());
+        let (ast, const_enums) = if let Some(content) = shared_content {
+            file.set_content(content);
+            context.record_phase(BuildPhase::Transform, || {
+                Self::parse_and_transform(&file, &context)
+            })?
+        } else {
+            // 1. load
+            let mut content = context.record_phase(BuildPhase::Load, || {
+                load::Load::load(&file, context.clone())
+            })?;
+
+            // 2. parse + 3. transform, plugins may recover from a failure with a substitute
+            // content
+            let ret =
+                context.record_phase(BuildPhase::Transform, || {
+                    match Self::transform_content(&mut file, &mut content, &context) {
+                        Ok(ret) => Ok(ret),
+                        Err(err) => {
+                            let Some(content) =
+                                context.plugin_driver.transform_error(&err, &path, &context)
+                            else {
+                                return Err(err);
+                            };
+                            file.set_content(content);
+                            Self::parse_and_transform(&file, &context)
+                        }
+                    }
+                })?;
+            if let Some(contents) = context.shared_contents.lock().unwrap().as_mut()
+                && let Some(content) = &file.content
+            {
+                contents.insert(path.clone(), content.clone());
+            }
+            ret
+        };
 
         // 4. analyze deps + resolve
         let deps = analyze_deps::AnalyzeDeps::analyze_deps(&ast, &file, context.clone())?;
 
         // 5. create module
-        let path = file.path.to_string_lossy().to_string();
         let module_id = ModuleId::new(path.clone());
         let raw = file.get_content_raw();
         let is_entry = file.is_entry;
@@ -327,7 +357,9 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
             ModuleAst::Script(ast) => ast.contains_top_level_await,
             _ => false,
         };
-        let is_async_module = file.extname == "wasm";
+        // wasm is instantiated when imported, except for `?init` and `?url`
+        let is_async_module =
+            file.extname == "wasm" && !file.has_param("init") && !file.has_param("url");
         let is_async = is_async_module || top_level_await;
 
         // raw_hash is only used in watch mode
@@ -349,12 +381,41 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
             is_async,
             raw_hash,
             raw,
+            const_enums,
             ..Default::default()
         };
         let module = Module::new(module_id, is_entry, Some(info));
         Ok(module)
     }
 
+    fn transform_content(
+        file: &mut File,
+        content: &mut Content,
+        context: &Arc<Context>,
+    ) -> Result<(ModuleAst, HashMap<String, ConstEnumMembers>)> {
+        let content = context.plugin_driver.load_transform(
+            content,
+            &file.path.to_string_lossy(),
+            file.is_entry,
+            context,
+        )?;
+        file.set_content(content);
+        Self::parse_and_transform(file, context)
+    }
+
+    fn parse_and_transform(
+        file: &File,
+        context: &Arc<Context>,
+    ) -> Result<(ModuleAst, HashMap<String, ConstEnumMembers>)> {
+        let mut ast = parse::Parse::parse(file, context.clone())?;
+
+        // const enums are erased by the ts transform, lower them to objects before it
+        let const_enums = const_enum::lower_const_enums(&mut ast, file);
+
+        transform::Transform::transform(&mut ast, file, context.clone())?;
+        Ok((ast, const_enums))
+    }
+
     pub(crate) fn create_remote_module(remote_info: RemoteInfo) -> Module {
         Module {
             is_entry: false,
@@ -383,3 +444,51 @@ __mako_require__.loadScript('{}', (e) => e.type === 'load' ? resolve() : reject(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use crate::ast::file::{Content, JsContent};
+    use crate::compiler::Context;
+    use crate::plugin::Plugin;
+    use crate::utils::test_helper::setup_compiler_with_plugins;
+
+    // replaces the modules which fail to be parsed with a module exporting the error
+    struct TransformErrorPlugin {}
+
+    impl Plugin for TransformErrorPlugin {
+        fn name(&self) -> &str {
+            "transform_error_test"
+        }
+
+        fn transform_error(
+            &self,
+            _error: &anyhow::Error,
+            path: &str,
+            _context: &Arc<Context>,
+        ) -> Option<Content> {
+            path.ends_with("broken.ts").then(|| {
+                Content::Js(JsContent {
+                    content: "export default 'transform failed';".to_string(),
+                    ..Default::default()
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn test_transform_error() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/transform-error",
+            vec![Arc::new(TransformErrorPlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let index = compiler.context.config.output.path.join("index.js");
+        let content = fs::read_to_string(index).unwrap();
+        assert!(content.contains("transform failed"));
+    }
+}
@@ -6,7 +6,7 @@ use thiserror::Error;
 
 use crate::ast::error;
 use crate::ast::file::File;
-use crate::compiler::Context;
+use crate::compiler::{BuildPhase, Context};
 use crate::module::{Dependency, ModuleAst};
 use crate::resolve::{resolve, ResolverResource};
 
@@ -51,21 +51,23 @@ impl AnalyzeDeps {
         let mut missing_deps = HashMap::new();
 
         for dep in deps {
-            let result = resolve(
-                &file.resolve_from(&context),
-                &dep,
-                &context.resolvers,
-                &context,
-            );
+            let result = context.record_phase(BuildPhase::Resolve, || {
+                resolve(
+                    &file.resolve_from(&context),
+                    &dep,
+                    &context.resolvers,
+                    &context,
+                )
+            });
             match result {
                 Ok(resolver_resource) => {
-                    let resolved_dep = ResolvedDep {
+                    let mut resolved_dep = ResolvedDep {
                         resolver_resource,
                         dependency: dep,
                     };
                     context
                         .plugin_driver
-                        .after_resolve(&resolved_dep, &context)?;
+                        .after_resolve(&mut resolved_dep, &context)?;
                     resolved_deps.push(resolved_dep);
                 }
                 Err(_err) => {
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use path_clean::PathClean;
+
+use crate::ast::file::{win_path, Content};
+
+/**
+ * Modules emitted by plugins when transforming another module, e.g. the css
+ * extracted from a `.vue` file. They are kept in memory, their ids are resolved
+ * relative to the emitting module, and the emitting module imports them so
+ * they are added to the module graph and chunked like other modules.
+ *
+ * They are only used when the imports can't be resolved to files, and are
+ * cleared at the start of each build.
+ */
+#[derive(Default)]
+pub struct EmittedModules {
+    modules: RwLock<HashMap<String, EmittedModule>>,
+}
+
+struct EmittedModule {
+    importer: String,
+    content: Content,
+}
+
+impl EmittedModules {
+    /// Register `modules` as (id, content) emitted from `importer`, which replace the ones it
+    /// emitted before, and add an import for each of them to the importer's js or css
+    /// `content`.
+    pub fn emit(&self, importer: &str, modules: Vec<(String, Content)>, content: &mut Content) {
+        let mut emitted = self.modules.write().unwrap();
+        emitted.retain(|_, module| module.importer != importer);
+        if modules.is_empty() {
+            return;
+        }
+        let mut paths = vec![];
+        for (id, module_content) in modules {
+            let path = resolve_path(&id, importer);
+            emitted.insert(
+                path.clone(),
+                EmittedModule {
+                    importer: importer.to_string(),
+                    content: module_content,
+                },
+            );
+            paths.push(serde_json::to_string(&path).unwrap());
+        }
+        match content {
+            // append instead of prepend, so that source maps of the importer stay valid
+            Content::Js(js_content) => {
+                let imports = paths
+                    .iter()
+                    .map(|path| format!("import {};", path))
+                    .collect::<Vec<_>>();
+                js_content.content = format!("{}\n{}\n", js_content.content, imports.join("\n"));
+            }
+            // `@import` must precede the other rules
+            Content::Css(css_content) => {
+                let imports = paths
+                    .iter()
+                    .map(|path| format!("@import {};", path))
+                    .collect::<Vec<_>>();
+                css_content.content = format!("{}\n{}", imports.join("\n"), css_content.content);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn resolve(&self, source: &str, importer: &str) -> Option<String> {
+        let modules = self.modules.read().unwrap();
+        if modules.is_empty() {
+            return None;
+        }
+        let path = resolve_path(source, importer);
+        modules.contains_key(&path).then_some(path)
+    }
+
+    pub fn load(&self, path: &str) -> Option<Content> {
+        self.modules
+            .read()
+            .unwrap()
+            .get(path)
+            .map(|module| module.content.clone())
+    }
+
+    pub fn clear(&self) {
+        self.modules.write().unwrap().clear();
+    }
+
+    /// Move the modules emitted in another build here, e.g. the legacy build of
+    /// `output.legacyBrowsers`, whose transformed contents are reused.
+    pub fn take_from(&self, other: &EmittedModules) {
+        let modules = std::mem::take(&mut *other.modules.write().unwrap());
+        self.modules.write().unwrap().extend(modules);
+    }
+}
+
+fn resolve_path(id: &str, importer: &str) -> String {
+    let id = PathBuf::from(id);
+    let path = if id.is_absolute() {
+        id
+    } else {
+        Path::new(importer)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(id)
+    };
+    win_path(&path.clean().to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EmittedModules;
+    use crate::ast::file::{Content, CssContent, JsContent};
+
+    #[test]
+    fn test_emit_and_resolve() {
+        let emitted = EmittedModules::default();
+        let mut content = Content::Js(JsContent {
+            content: "export default 1;".to_string(),
+            ..Default::default()
+        });
+        emitted.emit(
+            "/src/App.vue",
+            vec![(
+                "./App.vue.css".to_string(),
+                Content::Css(CssContent {
+                    content: ".a{}".to_string(),
+                    resolve_base: None,
+                }),
+            )],
+            &mut content,
+        );
+
+        let Content::Js(js_content) = &content else {
+            panic!("content should be js");
+        };
+        assert_eq!(
+            js_content.content,
+            "export default 1;\nimport \"/src/App.vue.css\";\n"
+        );
+        assert_eq!(
+            emitted.resolve("../src/App.vue.css", "/src/pages/index.ts"),
+            Some("/src/App.vue.css".to_string())
+        );
+        assert_eq!(emitted.resolve("./App.css", "/src/App.vue"), None);
+        assert!(matches!(
+            emitted.load("/src/App.vue.css"),
+            Some(Content::Css(css)) if css.content == ".a{}"
+        ));
+
+        // emitted again without the css
+        emitted.emit("/src/App.vue", vec![], &mut content);
+        assert!(emitted.load("/src/App.vue.css").is_none());
+    }
+
+    #[test]
+    fn test_emit_from_css() {
+        let emitted = EmittedModules::default();
+        let mut content = Content::Css(CssContent {
+            content: ".a{}".to_string(),
+            resolve_base: None,
+        });
+        emitted.emit(
+            "/src/theme.css",
+            vec![(
+                "./theme.vars.css".to_string(),
+                Content::Css(CssContent {
+                    content: ":root{}".to_string(),
+                    resolve_base: None,
+                }),
+            )],
+            &mut content,
+        );
+
+        let Content::Css(css_content) = &content else {
+            panic!("content should be css");
+        };
+        assert_eq!(
+            css_content.content,
+            "@import \"/src/theme.vars.css\";\n.a{}"
+        );
+        assert!(emitted.load("/src/theme.vars.css").is_some());
+
+        emitted.clear();
+        assert!(emitted.load("/src/theme.vars.css").is_none());
+    }
+}
@@ -11,10 +11,11 @@ use thiserror::Error;
 use toml::{from_str as from_toml_str, Value as TomlValue};
 use tracing::debug;
 
-use crate::ast::file::{Content, File, JsContent};
+use crate::ast::file::{Content, CssContent, File, JsContent};
 use crate::compiler::Context;
 use crate::config::Mode;
 use crate::plugin::PluginLoadParam;
+use crate::resolve::{missing_module_code, MISSING_MODULE_PREFIX};
 use crate::utils::create_cached_regex;
 
 #[derive(Debug, Error)]
@@ -25,23 +26,36 @@ enum LoadError {
     FileNotFound { path: String },
     #[error("Read file size error: {path:?}")]
     ReadFileSizeError { path: String },
-    #[error("To svgr error: {path:?}, reason: {reason:?}")]
-    ToSvgrError { path: String, reason: String },
     #[error("Compile md error: {path:?}, reason: {reason:?}")]
     CompileMdError { path: String, reason: String },
 }
 
-pub const JS_EXTENSIONS: [&str; 6] = ["js", "jsx", "ts", "tsx", "cjs", "mjs"];
+pub const JS_EXTENSIONS: [&str; 8] = ["js", "jsx", "ts", "tsx", "cjs", "mjs", "cts", "mts"];
 const CSS_EXTENSIONS: [&str; 1] = ["css"];
 const JSON_EXTENSIONS: [&str; 2] = ["json", "json5"];
 const YAML_EXTENSIONS: [&str; 2] = ["yaml", "yml"];
 const XML_EXTENSIONS: [&str; 1] = ["xml"];
 const TOML_EXTENSIONS: [&str; 1] = ["toml"];
-const SVG_EXTENSIONS: [&str; 1] = ["svg"];
 const MD_EXTENSIONS: [&str; 2] = ["md", "mdx"];
 const UNSUPPORTED_EXTENSIONS: [&str; 2] = ["sass", "stylus"];
 
-const SVGR_NAMED_EXPORT: &str = r#"ReactComponent"#;
+/// The builtin loader of the extension, to warn about the conflicts when an extension is
+/// registered as js by plugins.
+pub(crate) fn builtin_loader(ext: &str) -> Option<&'static str> {
+    [
+        (&JS_EXTENSIONS[..], "js"),
+        (&CSS_EXTENSIONS[..], "css"),
+        (&JSON_EXTENSIONS[..], "json"),
+        (&YAML_EXTENSIONS[..], "yaml"),
+        (&XML_EXTENSIONS[..], "xml"),
+        (&TOML_EXTENSIONS[..], "toml"),
+        (&MD_EXTENSIONS[..], "markdown"),
+        (&UNSUPPORTED_EXTENSIONS[..], "unsupported"),
+    ]
+    .into_iter()
+    .find(|(extensions, _)| extensions.contains(&ext))
+    .map(|(_, loader)| loader)
+}
 
 pub struct Load {}
 
@@ -75,6 +89,24 @@ export function moduleToDom(css) {
             }));
         }
 
+        // virtual:missing_*
+        if let Some(source) = file
+            .path
+            .to_str()
+            .unwrap()
+            .strip_prefix(MISSING_MODULE_PREFIX)
+        {
+            return Ok(Content::Js(JsContent {
+                content: missing_module_code(source),
+                ..Default::default()
+            }));
+        }
+
+        // modules emitted by plugin transforms
+        if let Some(content) = context.emitted_modules.load(&file.path.to_string_lossy()) {
+            return Ok(content);
+        }
+
         // file exists check must after virtual modules handling
         if !file.pathname.exists() || !file.pathname.is_file() {
             return Err(anyhow!(LoadError::FileNotFound {
@@ -83,7 +115,9 @@ export function moduleToDom(css) {
         }
 
         // unsupported
-        if UNSUPPORTED_EXTENSIONS.contains(&file.extname.as_str()) {
+        if UNSUPPORTED_EXTENSIONS.contains(&file.extname.as_str())
+            && !context.is_js_extension(&file.extname)
+        {
             return Err(anyhow!(LoadError::UnsupportedExtName {
                 ext_name: file.extname.clone(),
                 path: file.path.to_string_lossy().to_string(),
@@ -100,10 +134,12 @@ export function moduleToDom(css) {
             }));
         }
 
-        // js
-        if JS_EXTENSIONS.contains(&file.extname.as_str()) {
+        // js, and the extensions registered as js by plugins, which are parsed as jsx
+        if context.is_js_extension(&file.extname) {
             // entry with ?hmr
-            let is_jsx = file.extname.as_str() == "jsx" || file.extname.as_str() == "tsx";
+            let is_jsx = !JS_EXTENSIONS.contains(&file.extname.as_str())
+                || file.extname.as_str() == "jsx"
+                || file.extname.as_str() == "tsx";
             if file.is_entry && file.has_param("hmr") {
                 let content = format!(
                     "{}\nmodule.exports = require(\"{}\");\n",
@@ -119,7 +155,10 @@ export function moduleToDom(css) {
         // css
         if CSS_EXTENSIONS.contains(&file.extname.as_str()) {
             let content = FileSystem::read_file(&file.pathname)?;
-            return Ok(Content::Css(content));
+            return Ok(Content::Css(CssContent {
+                content,
+                resolve_base: None,
+            }));
         }
 
         // md & mdx
@@ -142,32 +181,6 @@ export function moduleToDom(css) {
             return Ok(Content::Js(JsContent { content, is_jsx }));
         }
 
-        // svg
-        // TODO: Not all svg files need to be converted to React Component, unnecessary performance consumption here
-        if SVG_EXTENSIONS.contains(&file.extname.as_str()) {
-            let content = FileSystem::read_file(&file.pathname)?;
-            let svgr_transformed = svgr_rs::transform(
-                content,
-                svgr_rs::Config {
-                    named_export: SVGR_NAMED_EXPORT.to_string(),
-                    export_type: Some(svgr_rs::ExportType::Named),
-                    ..Default::default()
-                },
-                svgr_rs::State {
-                    ..Default::default()
-                },
-            )
-            .map_err(|err| LoadError::ToSvgrError {
-                path: file.path.to_string_lossy().to_string(),
-                reason: err.to_string(),
-            })?;
-            let asset_path = Self::handle_asset(file, true, true, context.clone())?;
-            return Ok(Content::Js(JsContent {
-                content: format!("{}\nexport default {};", svgr_transformed, asset_path),
-                is_jsx: true,
-            }));
-        }
-
         // toml
         if TOML_EXTENSIONS.contains(&file.extname.as_str()) {
             let content = FileSystem::read_file(&file.pathname)?;
@@ -6,7 +6,7 @@ use thiserror::Error;
 use tracing::debug;
 
 use crate::ast::css_ast::CssAst;
-use crate::ast::file::{Content, File, JsContent};
+use crate::ast::file::{Content, CssContent, File, JsContent};
 use crate::ast::js_ast::JsAst;
 use crate::build::analyze_deps::AnalyzeDeps;
 use crate::build::transform::Transform;
@@ -86,7 +86,7 @@ impl Parse {
                 let ast = if is_browser {
                     CssAst::new(&file, context.clone(), css_modules)?
                 } else {
-                    file.set_content(Content::Css("".to_string()));
+                    file.set_content(Content::Css(CssContent::default()));
                     CssAst::new(&file, context.clone(), css_modules)?
                 };
                 // when inline_css is enabled
@@ -35,15 +35,19 @@ use crate::visitors::css_assets::CSSAssets;
 use crate::visitors::css_flexbugs::CSSFlexbugs;
 use crate::visitors::css_px2rem::Px2Rem;
 use crate::visitors::default_export_namer::DefaultExportNamer;
+use crate::visitors::dynamic_import_rewriter::DynamicImportRewriter;
 use crate::visitors::dynamic_import_to_require::DynamicImportToRequire;
 use crate::visitors::env_replacer::{build_env_map, EnvReplacer};
 use crate::visitors::fix_symbol_conflict::FixSymbolConflict;
+use crate::visitors::hot_api_replacer::HotApiReplacer;
 use crate::visitors::import_meta_env_replacer::ImportMetaEnvReplacer;
 use crate::visitors::import_template_to_string_literal::ImportTemplateToStringLiteral;
 use crate::visitors::new_url_assets::NewUrlAssets;
 use crate::visitors::provide::Provide;
-use crate::visitors::public_path_assignment::PublicPathAssignment;
+use crate::visitors::public_path_replacer::PublicPathReplacer;
 use crate::visitors::react::react;
+use crate::visitors::require_ensure::RequireEnsure;
+use crate::visitors::require_resolve::RequireResolve;
 use crate::visitors::try_resolve::TryResolve;
 use crate::visitors::ts_strip::ts_strip;
 use crate::visitors::tsx_strip::tsx_strip;
@@ -69,7 +73,7 @@ impl Transform {
                                     context.meta.script.cm.clone();
                                 let origin_comments =
                                     context.meta.script.origin_comments.read().unwrap();
-                                let is_ts = file.extname == "ts";
+                                let is_ts = file.is_ts() && file.extname != "tsx";
                                 let is_tsx = file.extname == "tsx";
                                 let is_jsx = file.is_content_jsx()
                                     || file.extname == "jsx"
@@ -107,6 +111,7 @@ impl Transform {
                                         unresolved_mark,
                                     }),
                                     Box::new(WorkerModule::new(unresolved_mark)),
+                                    Box::new(RequireEnsure::new(unresolved_mark)),
                                     Box::new(WebpackRuntimeReplacement::new(unresolved_mark)),
                                 ];
 
@@ -145,12 +150,22 @@ impl Transform {
                                         .push(Box::new(EnvReplacer::new(env_map, unresolved_mark)));
                                     visitors.push(Box::new(ImportMetaEnvReplacer::new(mode)));
                                 }
+                                // import.meta.hot and module.hot
+                                visitors.push(Box::new(HotApiReplacer::new(
+                                    context.args.watch && context.config.hmr.is_some(),
+                                    unresolved_mark,
+                                )));
                                 visitors.push(Box::new(TryResolve {
                                     path: file.path.to_string_lossy().to_string(),
                                     context: context.clone(),
                                     unresolved_mark,
                                 }));
-                                visitors.push(Box::new(PublicPathAssignment { unresolved_mark }));
+                                visitors.push(Box::new(RequireResolve {
+                                    path: file.path.to_string_lossy().to_string(),
+                                    context: context.clone(),
+                                    unresolved_mark,
+                                }));
+                                visitors.push(Box::new(PublicPathReplacer { unresolved_mark }));
                                 // TODO: refact provide
                                 visitors.push(Box::new(Provide::new(
                                     context.config.providers.clone(),
@@ -164,6 +179,12 @@ impl Transform {
                                 // TODO: move ContextModuleVisitor out of plugin
                                 visitors.push(Box::new(ContextModuleVisitor { unresolved_mark }));
                                 visitors.push(Box::new(ImportTemplateToStringLiteral {}));
+                                // after ImportTemplateToStringLiteral, so that `import(`./a`)` is
+                                // rewritten too
+                                visitors.push(Box::new(DynamicImportRewriter {
+                                    path: file.path.to_string_lossy().to_string(),
+                                    context: context.clone(),
+                                }));
                                 // DynamicImportToRequire must be after ContextModuleVisitor
                                 // since ContextModuleVisitor will add extra dynamic imports
                                 if context.config.dynamic_import_to_require {
@@ -269,11 +290,11 @@ impl Transform {
                 visitors.push(Box::new(Compiler::new(compiler::Config {
                     process: swc_css_compat::feature::Features::NESTING,
                 })));
-                let path = file.path.to_string_lossy().to_string();
-                visitors.push(Box::new(CSSAssets {
-                    path,
-                    context: context.clone(),
-                }));
+                // the resolve base of the css content if any
+                let path = file
+                    .css_resolve_base_path(&context)
+                    .unwrap_or_else(|| file.path.to_string_lossy().to_string());
+                visitors.push(Box::new(CSSAssets::new(path, context.clone())));
                 // same ability as postcss-flexbugs-fixes
                 if context.config.flex_bugs {
                     visitors.push(Box::new(CSSFlexbugs {}));
@@ -307,7 +328,7 @@ impl Transform {
 
 impl Context {
     pub fn assumptions_for(&self, file: &File) -> Assumptions {
-        let is_ts = file.extname == "ts" || file.extname == "tsx";
+        let is_ts = file.is_ts();
 
         let mut assumptions = Assumptions::default();
         assumptions.set_public_class_fields |= !self.config.use_define_for_class_fields;
@@ -22,4 +22,7 @@ pub struct Cli {
                 })
     )]
     pub mode: Mode,
+    /// Write a treemap report of the bundle composition after the build
+    #[arg(long)]
+    pub analyze: bool,
 }
@@ -1,32 +1,45 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Error, Result};
 use colored::Colorize;
 use libloading::Library;
+use rayon::ThreadPool;
 use regex::Regex;
-use serde_json::Value;
+use serde::Serialize;
+use serde_json::{json, Value};
 use swc_core::common::sync::Lrc;
 use swc_core::common::{Globals, SourceMap, DUMMY_SP};
-use swc_core::ecma::ast::Ident;
+use swc_core::ecma::ast::{EsVersion, Ident};
 use swc_node_comments::SwcComments;
 use tracing::debug;
 
 use crate::ast::comments::Comments;
-use crate::ast::file::win_path;
+use crate::ast::file::{win_path, Content, File};
+use crate::build::emitted_modules::EmittedModules;
+use crate::build::load::{builtin_loader, JS_EXTENSIONS};
+use crate::config::entry::{Entry, EntryItem};
 use crate::config::{Config, Mode, ModuleIdStrategy, OutputMode};
 use crate::generate::chunk_graph::ChunkGraph;
+pub use crate::generate::link_hints::{LinkHint, LinkHintRel};
+use crate::generate::minify_cache::MinifyCache;
 use crate::generate::optimize_chunk::OptimizeChunksInfo;
+use crate::generate::OutputSnapshot;
 use crate::module_graph::ModuleGraph;
-use crate::plugin::{Plugin, PluginDriver, PluginGenerateEndParams};
+use crate::optimizer::{build_minify_pool, Optimizer};
+use crate::plugin::{create_builtin_plugins, Plugin, PluginDriver, PluginGenerateEndParams};
 use crate::plugins;
+use crate::plugins::lazy_compilation::LazyCompilation;
+use crate::plugins::legacy_browsers::LegacyBrowsersPlugin;
 use crate::plugins::module_federation::ModuleFederationPlugin;
+use crate::plugins::tree_shaking::cache::TreeShakingCache;
 use crate::resolve::{get_resolvers, Resolvers};
 use crate::share::helpers::SWC_HELPERS;
-use crate::stats::StatsInfo;
+use crate::stats::{StatsInfo, StatsJsonMap};
 use crate::utils::id_helper::{assign_numeric_ids, compare_modules_by_incoming_edges};
 use crate::utils::{thread_pool, ParseRegex};
 
@@ -34,8 +47,13 @@ pub struct Context {
     pub module_graph: RwLock<ModuleGraph>,
     pub chunk_graph: RwLock<ChunkGraph>,
     pub assets_info: Mutex<HashMap<String, String>>,
+    // metadata of the emitted assets by output path, written to the stats
+    pub assets_meta: Mutex<HashMap<String, AssetMeta>>,
     pub modules_with_missing_deps: RwLock<Vec<String>>,
     pub config: Config,
+    // the entries of the build, `config.entry` with the ones discovered by `entry.pattern`
+    // added and removed on rebuilds in watch mode
+    pub(crate) entries: RwLock<Entry>,
     pub numeric_ids_map: RwLock<HashMap<String, usize>>,
     pub args: Args,
     pub root: PathBuf,
@@ -45,6 +63,134 @@ pub struct Context {
     pub resolvers: Resolvers,
     pub static_cache: RwLock<MemoryChunkFileCache>,
     pub optimize_infos: Mutex<Option<Vec<OptimizeChunksInfo>>>,
+    pub link_hints: Mutex<Vec<LinkHint>>,
+    pub tree_shaking_cache: Mutex<TreeShakingCache>,
+    pub emitted_modules: EmittedModules,
+    // starts from 1 for the first build and increases on each rebuild in watch mode
+    pub build_id: AtomicU64,
+    // output paths of the assets written in the current build
+    pub(crate) written_assets: Mutex<HashSet<String>>,
+    pub lazy_compilation: LazyCompilation,
+    // meta set by plugins when resolving a module, by module id
+    pub(crate) module_meta: RwLock<HashMap<String, serde_json::Value>>,
+    // js files of the entries built for output.legacyBrowsers, by entry name
+    pub(crate) legacy_entry_files: Mutex<HashMap<String, Vec<String>>>,
+    // contents of the modules after the load and transform hooks by path, shared by the
+    // legacy and the main build of output.legacyBrowsers, see Compiler::compile_legacy
+    pub(crate) shared_contents: Mutex<Option<HashMap<String, Content>>>,
+    // the output of the last build in watch mode, see Compiler::diff_output
+    pub(crate) output_snapshot: Mutex<OutputSnapshot>,
+    // time spent by the plugins of the current compile, see Context::log_timing and
+    // Context::time_plugin_hook
+    pub(crate) plugin_timings: Mutex<PluginTimings>,
+    // warnings of the current compile, returned in the compile result
+    pub(crate) warnings: Mutex<Vec<Diagnostic>>,
+    // extensions registered as js by plugins, without the leading dot
+    pub(crate) js_extensions: RwLock<HashSet<String>>,
+    // optimizers registered with register_optimizer, run before the builtin ones
+    pub(crate) optimizers: RwLock<Vec<Arc<dyn Optimizer>>>,
+    pub(crate) minify_cache: MinifyCache,
+    // the threads of optimization.minifyWorkers, see optimize_js
+    pub(crate) minify_pool: Option<ThreadPool>,
+    // time spent in the build phases of the current compile, see Context::record_phase
+    pub(crate) build_stats: Mutex<BuildStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimingEntry {
+    pub label: String,
+    pub ms: f64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PluginTimings {
+    // reported by the plugins with log_timing, written to profile.json
+    pub(crate) logged: Vec<TimingEntry>,
+    // calls of the js plugin hooks by plugin and hook name, only with profilePlugins
+    pub(crate) hooks: BTreeMap<(String, String), PluginHookStats>,
+}
+
+/// The calls of a hook of a js plugin, `returned` is how many of them returned a result, the
+/// others passed through to the next plugin. `ms` includes the napi round trip.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PluginHookStats {
+    pub plugin: String,
+    pub hook: String,
+    pub calls: usize,
+    pub returned: usize,
+    pub ms: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BuildPhase {
+    Resolve,
+    Load,
+    Transform,
+    TreeShake,
+    Generate,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhaseStats {
+    pub ms: f64,
+    pub count: usize,
+}
+
+/// The time spent in each phase of a compile and how many times it ran, e.g. `load` and
+/// `transform` run once per module and `resolve` once per dependency. The time of the
+/// modules built in parallel is summed up, so it may exceed the time of the compile.
+/// `transform` includes the parsing and `generate` includes the tree shaking.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildStats {
+    pub resolve: PhaseStats,
+    pub load: PhaseStats,
+    pub transform: PhaseStats,
+    pub tree_shake: PhaseStats,
+    pub generate: PhaseStats,
+}
+
+impl BuildStats {
+    fn phase_mut(&mut self, phase: BuildPhase) -> &mut PhaseStats {
+        match phase {
+            BuildPhase::Resolve => &mut self.resolve,
+            BuildPhase::Load => &mut self.load,
+            BuildPhase::Transform => &mut self.transform,
+            BuildPhase::TreeShake => &mut self.tree_shake,
+            BuildPhase::Generate => &mut self.generate,
+        }
+    }
+}
+
+/// What a compile produces, for the programmatic users to inspect the build without reading
+/// the output dir.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompileResult {
+    pub output_files: Vec<OutputFile>,
+    pub warnings: Vec<Diagnostic>,
+    /// The same as stats.json, only when `stats` is enabled
+    pub stats: Option<StatsJsonMap>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputFile {
+    /// Relative to the output dir
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+}
+
+/// Metadata attached to an emitted asset for downstream consumers of the build output.
+#[derive(Debug, Clone, Default)]
+pub struct AssetMeta {
+    /// `Some(false)` marks the asset as free of side effects, so that builds consuming it
+    /// can tree-shake it
+    pub side_effects: Option<bool>,
 }
 
 #[derive(Default)]
@@ -123,18 +269,37 @@ impl Default for Context {
         let config: Config = Default::default();
         let resolvers = get_resolvers(&config);
         Self {
+            entries: RwLock::new(config.entry.clone()),
             config,
             args: Args { watch: false },
             root: PathBuf::from(""),
             module_graph: RwLock::new(ModuleGraph::new()),
             chunk_graph: RwLock::new(ChunkGraph::new()),
             assets_info: Mutex::new(HashMap::new()),
+            assets_meta: Mutex::new(HashMap::new()),
             modules_with_missing_deps: RwLock::new(Vec::new()),
             meta: Meta::new(),
             plugin_driver: Default::default(),
             stats_info: StatsInfo::new(),
             resolvers,
             optimize_infos: Mutex::new(None),
+            link_hints: Mutex::new(vec![]),
+            tree_shaking_cache: Mutex::new(Default::default()),
+            emitted_modules: Default::default(),
+            build_id: AtomicU64::new(0),
+            written_assets: Mutex::new(HashSet::new()),
+            lazy_compilation: Default::default(),
+            module_meta: Default::default(),
+            legacy_entry_files: Default::default(),
+            shared_contents: Default::default(),
+            output_snapshot: Default::default(),
+            plugin_timings: Default::default(),
+            js_extensions: Default::default(),
+            optimizers: Default::default(),
+            minify_cache: Default::default(),
+            minify_pool: None,
+            build_stats: Default::default(),
+            warnings: Default::default(),
             static_cache: Default::default(),
             numeric_ids_map: RwLock::new(numeric_ids_map),
         }
@@ -212,14 +377,208 @@ impl CssMeta {
 }
 
 impl Context {
+    pub fn build_id(&self) -> u64 {
+        self.build_id.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn next_build_id(&self) -> u64 {
+        self.build_id.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Attach the meta returned by `resolve_id` to a module, it can be read in the
+    /// later hooks of the module, e.g. `load` and `transform`.
+    ///
+    /// Meta lives for a build, it's cleared when a full build starts and kept for the
+    /// rebuilds in watch mode, resolving the module again overwrites it.
+    pub fn set_module_meta(&self, id: &str, meta: serde_json::Value) {
+        self.module_meta
+            .write()
+            .unwrap()
+            .insert(id.to_string(), meta);
+    }
+
+    pub fn get_module_meta(&self, id: &str) -> Option<serde_json::Value> {
+        self.module_meta.read().unwrap().get(id).cloned()
+    }
+
+    pub fn clear_tree_shaking_cache(&self) {
+        self.tree_shaking_cache.lock().unwrap().clear();
+    }
+
+    /// In watch mode without hmr, e.g. `build --watch`, the modules are shaken and each
+    /// rebuild is a full compile, which reuses the tree shaking analysis of the unchanged
+    /// modules. With hmr, the hot updates can't follow the removed exports, so tree shaking is
+    /// skipped, ref: https://github.com/umijs/mako/issues/396
+    pub(crate) fn rebuilds_fully(&self) -> bool {
+        self.args.watch && self.config.hmr.is_none()
+    }
+
+    /// The chunk renders are cached by the raw hashes of their modules, which don't cover the
+    /// shaken output, so they are only cached when the modules aren't shaken.
+    pub(crate) fn caches_renders(&self) -> bool {
+        self.args.watch && !self.rebuilds_fully()
+    }
+
     pub fn emit_assets(&self, origin_path: String, output_path: String) {
         let mut assets_info = self.assets_info.lock().unwrap();
         assets_info.insert(origin_path, output_path);
     }
+
+    pub fn emit_assets_with_meta(&self, origin_path: String, output_path: String, meta: AssetMeta) {
+        self.assets_meta
+            .lock()
+            .unwrap()
+            .insert(output_path.clone(), meta);
+        self.emit_assets(origin_path, output_path);
+    }
+
+    /// Record the time spent by a plugin on its own work, e.g. compiling sass, it's
+    /// written to profile.json with the build stages when `profile` is enabled.
+    pub fn log_timing(&self, label: String, ms: f64) {
+        self.plugin_timings
+            .lock()
+            .unwrap()
+            .logged
+            .push(TimingEntry { label, ms });
+    }
+
+    /// Run `f` and add its time to the phase, only when `profile` is enabled so that the
+    /// builds without it don't pay for the bookkeeping.
+    pub(crate) fn record_phase<T>(&self, phase: BuildPhase, f: impl FnOnce() -> T) -> T {
+        if !self.config.profile {
+            return f();
+        }
+        let start = Instant::now();
+        let ret = f();
+        self.add_phase_time(phase, start.elapsed());
+        ret
+    }
+
+    pub(crate) fn add_phase_time(&self, phase: BuildPhase, duration: Duration) {
+        if !self.config.profile {
+            return;
+        }
+        let mut build_stats = self.build_stats.lock().unwrap();
+        let stats = build_stats.phase_mut(phase);
+        stats.ms += duration.as_secs_f64() * 1000.0;
+        stats.count += 1;
+    }
+
+    /// Run a hook of a plugin and add the call to the report of `profilePlugins`, `returned`
+    /// tells whether the hook returned a result instead of passing through. The hook is not
+    /// timed when `profilePlugins` is disabled.
+    pub fn time_plugin_hook<T>(
+        &self,
+        plugin: &str,
+        hook: &str,
+        returned: impl FnOnce(&T) -> bool,
+        call: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        if !self.config.profile_plugins {
+            return call();
+        }
+        let start = Instant::now();
+        let result = call();
+        let duration = start.elapsed();
+        let returned = result.as_ref().is_ok_and(returned);
+        let mut plugin_timings = self.plugin_timings.lock().unwrap();
+        let stats = plugin_timings
+            .hooks
+            .entry((plugin.to_string(), hook.to_string()))
+            .or_insert_with(|| PluginHookStats {
+                plugin: plugin.to_string(),
+                hook: hook.to_string(),
+                ..Default::default()
+            });
+        stats.calls += 1;
+        if returned {
+            stats.returned += 1;
+        }
+        stats.ms += duration.as_secs_f64() * 1000.0;
+        result
+    }
+
+    /// The calls of the js plugin hooks of the last compile, the slowest first, empty unless
+    /// `profilePlugins` is enabled.
+    pub fn plugin_hook_stats(&self) -> Vec<PluginHookStats> {
+        let mut stats = self
+            .plugin_timings
+            .lock()
+            .unwrap()
+            .hooks
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| b.ms.total_cmp(&a.ms));
+        stats
+    }
+
+    /// The timing of the build phases of the last compile, `None` unless `profile` is
+    /// enabled.
+    pub fn stats(&self) -> Option<BuildStats> {
+        self.config
+            .profile
+            .then(|| self.build_stats.lock().unwrap().clone())
+    }
+
+    /// Print a warning, which is also returned in the compile result.
+    pub fn warn(&self, message: String) {
+        println!("{} {}", "Warning".yellow(), message);
+        self.warnings.lock().unwrap().push(Diagnostic { message });
+    }
+
+    /// Load and parse the files with the extension as js without a `load` hook, it's
+    /// supposed to be called in `build_start` before any module is loaded.
+    ///
+    /// Registering an extension which has a builtin loader, e.g. `json`, overrides the
+    /// loader with a warning.
+    pub fn register_js_extension(&self, ext: &str) {
+        let ext = ext.trim_start_matches('.');
+        match builtin_loader(ext) {
+            Some("js") => return,
+            Some(loader) => self.warn(format!(
+                "`.{}` is registered as a js extension, which overrides its builtin {} loader",
+                ext, loader
+            )),
+            None => {}
+        }
+        self.js_extensions.write().unwrap().insert(ext.to_string());
+    }
+
+    pub fn is_js_extension(&self, ext: &str) -> bool {
+        JS_EXTENSIONS.contains(&ext) || self.js_extensions.read().unwrap().contains(ext)
+    }
+
+    /// Add a pass over the js of each chunk after tree shaking, see [`Optimizer`]. It's
+    /// supposed to be registered once, e.g. right after the compiler is created.
+    pub fn register_optimizer(&self, optimizer: Arc<dyn Optimizer>) {
+        self.optimizers.write().unwrap().push(optimizer);
+    }
+
+    /// Copy emitted assets to the output dir, assets already written in the current
+    /// build are skipped, so that assets emitted in late hooks are written as well
+    pub(crate) fn write_assets(&self) -> Result<()> {
+        let assets_info = self.assets_info.lock().unwrap();
+        let mut written_assets = self.written_assets.lock().unwrap();
+        for (k, v) in assets_info.iter() {
+            if written_assets.contains(v) {
+                continue;
+            }
+            let asset_path = self.root.join(k);
+            if !asset_path.exists() {
+                return Err(anyhow!("asset not found: {}", asset_path.display()));
+            }
+            fs::copy(&asset_path, self.config.output.path.join(v))?;
+            written_assets.insert(v.clone());
+        }
+        Ok(())
+    }
 }
 
 pub struct Compiler {
     pub context: Arc<Context>,
+    // the plugins passed in, reused by the extra compilations, e.g. compile_legacy
+    extra_plugins: Vec<Arc<dyn Plugin>>,
 }
 
 impl Compiler {
@@ -238,10 +597,8 @@ impl Compiler {
         // why add plugins before builtin plugins?
         // because plugins like less-loader need to be added before assets plugin
         // TODO: support plugin orders
-        let mut plugins: Vec<Arc<dyn Plugin>> = vec![];
-        if let Some(extra_plugins) = extra_plugins {
-            plugins.extend(extra_plugins);
-        }
+        let extra_plugins = extra_plugins.unwrap_or_default();
+        let mut plugins: Vec<Arc<dyn Plugin>> = extra_plugins.clone();
 
         let mut external_plugins: Vec<Arc<dyn Plugin>> = vec![];
         unsafe {
@@ -255,6 +612,15 @@ impl Compiler {
             }
         }
 
+        // compiled-in plugins enabled by config
+        let mut builtin_plugin_names = vec![];
+        if cfg!(feature = "svgr") && config.svgr {
+            builtin_plugin_names.push("svgr".to_string());
+        }
+        // the ones enabled by name already, e.g. with `builtinPlugins` of the node binding
+        builtin_plugin_names.retain(|name| !plugins.iter().any(|plugin| plugin.name() == name));
+        plugins.extend(create_builtin_plugins(&builtin_plugin_names)?);
+
         let builtin_plugins: Vec<Arc<dyn Plugin>> = vec![
             // features
             Arc::new(plugins::manifest::ManifestPlugin {}),
@@ -268,8 +634,10 @@ impl Compiler {
             Arc::new(plugins::wasm_runtime::WasmRuntimePlugin {}),
             Arc::new(plugins::async_runtime::AsyncRuntimePlugin {}),
             Arc::new(plugins::emotion::EmotionPlugin {}),
+            Arc::new(plugins::const_enum::ConstEnumPlugin {}),
             Arc::new(plugins::tree_shaking::FarmTreeShake {}),
             Arc::new(plugins::detect_circular_dependence::LoopDetector {}),
+            Arc::new(plugins::circular_require::CircularLint {}),
         ];
         plugins.extend(external_plugins);
         plugins.extend(builtin_plugins);
@@ -280,6 +648,15 @@ impl Compiler {
             plugins.push(Arc::new(plugins::imports_checker::ImportsChecker {}));
         }
 
+        if let Some(html) = &config.html {
+            plugins.push(Arc::new(plugins::html::HtmlPlugin::new(
+                html.iter()
+                    .map(plugins::html::HtmlPluginConfig::from_html_config)
+                    .collect(),
+                config.entry_glob.as_ref().map_or(false, |glob| glob.html),
+            )));
+        }
+
         if let Some(progress) = &config.progress {
             plugins.push(Arc::new(plugins::progress::ProgressPlugin::new(
                 plugins::progress::ProgressPluginOptions {
@@ -305,18 +682,28 @@ impl Compiler {
             ));
         }
 
+        if !config.resolve.ignore_missing.is_empty() {
+            plugins.push(Arc::new(plugins::ignore_missing::IgnoreMissingPlugin {}));
+        }
+
         if config.experimental.require_context {
             plugins.push(Arc::new(plugins::require_context::RequireContextPlugin {}))
         }
 
-        if config.output.mode == OutputMode::Bundless {
+        if config.output.mode == OutputMode::Bundless || config.output.is_preserve_modules() {
             plugins.insert(
                 0,
                 Arc::new(plugins::bundless_compiler::BundlessCompilerPlugin {}),
             );
         }
         if std::env::var("DEBUG_GRAPH").is_ok_and(|v| v == "true") {
-            plugins.push(Arc::new(plugins::graphviz::Graphviz {}));
+            plugins.push(Arc::new(plugins::graphviz::Graphviz::default()));
+        }
+
+        if config.debug.dump_module_graph {
+            plugins.push(Arc::new(plugins::graphviz::Graphviz {
+                module_graph_only: true,
+            }));
         }
 
         if args.watch && std::env::var("SSU").is_ok_and(|v| v == "true") {
@@ -327,6 +714,12 @@ impl Compiler {
             plugins.push(Arc::new(plugins::central_ensure::CentralChunkEnsure {}));
         }
 
+        if plugins::lazy_compilation::is_enabled(&config, args.watch) {
+            plugins.push(Arc::new(
+                plugins::lazy_compilation::LazyCompilationPlugin {},
+            ));
+        }
+
         if let Some(mf_cfg) = config.module_federation.as_ref() {
             plugins.push(Arc::new(ModuleFederationPlugin::new(mf_cfg.clone())));
         }
@@ -380,6 +773,8 @@ impl Compiler {
         SWC_HELPERS.iter().enumerate().for_each(|(i, item)| {
             numeric_ids_map.insert(item.to_string(), i);
         });
+        let minify_cache = MinifyCache::new(&root, &config);
+        let minify_pool = build_minify_pool(&config)?;
         Ok(Self {
             context: Arc::new(Context {
                 static_cache: if config.write_to_disk {
@@ -387,12 +782,14 @@ impl Compiler {
                 } else {
                     Default::default()
                 },
+                entries: RwLock::new(config.entry.clone()),
                 config,
                 args,
                 root,
                 module_graph: RwLock::new(ModuleGraph::new()),
                 chunk_graph: RwLock::new(ChunkGraph::new()),
                 assets_info: Mutex::new(HashMap::new()),
+                assets_meta: Mutex::new(HashMap::new()),
                 modules_with_missing_deps: RwLock::new(Vec::new()),
                 meta: Meta::new(),
                 plugin_driver,
@@ -400,11 +797,39 @@ impl Compiler {
                 stats_info: StatsInfo::new(),
                 resolvers,
                 optimize_infos: Mutex::new(None),
+                link_hints: Mutex::new(vec![]),
+                tree_shaking_cache: Mutex::new(Default::default()),
+                emitted_modules: Default::default(),
+                build_id: AtomicU64::new(0),
+                written_assets: Mutex::new(HashSet::new()),
+                lazy_compilation: Default::default(),
+                module_meta: Default::default(),
+                legacy_entry_files: Default::default(),
+                shared_contents: Default::default(),
+                output_snapshot: Default::default(),
+                plugin_timings: Default::default(),
+                js_extensions: Default::default(),
+                optimizers: Default::default(),
+                minify_cache,
+                minify_pool,
+                build_stats: Default::default(),
+                warnings: Default::default(),
             }),
+            extra_plugins,
         })
     }
 
-    pub fn compile(&self) -> Result<()> {
+    /// The write_bundle hook is called once per build, after all the chunk files and assets
+    /// are written to disk, including the assets emitted in hooks after generate like
+    /// generate_end
+    pub(crate) fn write_bundle(&self) -> Result<()> {
+        if self.context.config.emit_assets {
+            self.context.write_assets()?;
+        }
+        self.context.plugin_driver.write_bundle(&self.context)
+    }
+
+    pub fn compile(&self) -> Result<CompileResult> {
         // 先清空 dist 目录
         if self.context.config.clean {
             self.clean_dist()?;
@@ -419,27 +844,35 @@ impl Compiler {
         )
         .green();
         println!("{}", building_with_message);
+        self.context.module_meta.write().unwrap().clear();
+        self.context.assets_meta.lock().unwrap().clear();
+        self.context.emitted_modules.clear();
+        *self.context.plugin_timings.lock().unwrap() = Default::default();
+        *self.context.build_stats.lock().unwrap() = Default::default();
+        self.context.warnings.lock().unwrap().clear();
+        // compiling again is a full rebuild, e.g. on changes with `Context::rebuilds_fully`
+        if self.context.build_id() > 0 {
+            *self.context.module_graph.write().unwrap() = ModuleGraph::new();
+            *self.context.chunk_graph.write().unwrap() = ChunkGraph::new();
+            self.context.stats_info.clear_assets();
+        }
+        // after the state of the last build is cleared, the legacy build hands over its
+        // transformed contents
+        if self.context.config.output.legacy_browsers && !self.context.args.watch {
+            self.compile_legacy()?;
+        }
+        let t_build = Instant::now();
         {
             crate::mako_profile_scope!("Build Stage");
             let files = self
                 .context
-                .config
-                .entry
+                .entries
+                .read()
+                .unwrap()
                 .values()
-                .map(|entry| {
-                    let mut entry = entry.import.to_string_lossy().to_string();
-                    let is_browser = matches!(
-                        self.context.config.platform,
-                        crate::config::Platform::Browser
-                    );
-                    let watch = self.context.args.watch;
-                    let hmr = self.context.config.hmr.is_some();
-                    if is_browser && watch && hmr {
-                        entry = format!("{}?hmr", entry);
-                    }
-                    crate::ast::file::File::new_entry(entry, self.context.clone())
-                })
+                .map(|entry| self.entry_file(entry))
                 .collect();
+            self.context.next_build_id();
             self.context.plugin_driver.build_start(&self.context)?;
 
             self.build(files)?;
@@ -450,6 +883,7 @@ impl Compiler {
                 .plugin_driver
                 .after_build(&self.context, self)?;
         }
+        let t_build = t_build.elapsed();
 
         self.context.plugin_driver.before_generate(&self.context)?;
 
@@ -466,17 +900,22 @@ impl Compiler {
             )
         }
 
+        let t_generate = Instant::now();
         let result = {
             crate::mako_profile_scope!("Generate Stage");
             // need to put all rayon parallel iterators run in the existed scope, or else rayon
             // will create a new thread pool for those parallel iterators
             thread_pool::scope(|_| self.generate())
         };
+        let t_generate = t_generate.elapsed();
+        self.context
+            .add_phase_time(BuildPhase::Generate, t_generate);
         let t_compiler_duration = t_compiler.elapsed();
         match result {
             Ok(mut stats) => {
                 stats.start_time = start_time;
                 stats.end_time = chrono::Local::now().timestamp_millis();
+                stats.plugin_hooks = self.context.plugin_hook_stats();
                 println!(
                     "{}",
                     format!(
@@ -489,27 +928,126 @@ impl Compiler {
                     println!("{}", "Complete!".bold());
                 }
                 let params = PluginGenerateEndParams {
-                    is_first_compile: true,
+                    is_first_compile: self.context.build_id() == 1,
+                    build_id: self.context.build_id(),
                     time: t_compiler.elapsed().as_millis() as i64,
                     stats,
+                    rebuild: None,
+                    build_stats: self.context.stats(),
                 };
                 self.context
                     .plugin_driver
                     .generate_end(&params, &self.context)?;
 
-                self.context.plugin_driver.write_bundle(&self.context)?;
-                Ok(())
+                self.write_bundle()?;
+
+                if self.context.config.profile {
+                    self.write_profile(t_build, t_generate)?;
+                }
+                // the same calls as in the stats, without generateEnd and the hooks after it
+                if self.context.config.profile_plugins {
+                    self.print_plugin_hooks(&params.stats.plugin_hooks);
+                }
+                Ok(self.compile_result(params.stats))
             }
             Err(e) => Err(e),
         }
     }
 
+    fn compile_result(&self, stats: StatsJsonMap) -> CompileResult {
+        let mut output_files = self
+            .context
+            .stats_info
+            .get_assets()
+            .into_iter()
+            .map(|asset| OutputFile {
+                path: asset.hashname,
+                size: asset.size,
+            })
+            .collect::<Vec<_>>();
+        output_files.sort_by(|a, b| a.path.cmp(&b.path));
+        CompileResult {
+            output_files,
+            warnings: self.context.warnings.lock().unwrap().clone(),
+            stats: self.context.config.stats.is_some().then_some(stats),
+        }
+    }
+
+    fn write_profile(&self, t_build: Duration, t_generate: Duration) -> Result<()> {
+        let profile = json!({
+            "build": t_build.as_secs_f64() * 1000.0,
+            "generate": t_generate.as_secs_f64() * 1000.0,
+            "pluginTimings": self.context.plugin_timings.lock().unwrap().logged,
+            "phases": *self.context.build_stats.lock().unwrap(),
+        });
+        fs::write(
+            self.context.config.output.path.join("profile.json"),
+            serde_json::to_string_pretty(&profile)?,
+        )?;
+        Ok(())
+    }
+
+    // the hmr runtime is injected into the entries with `?hmr`
+    pub(crate) fn entry_file(&self, entry: &EntryItem) -> File {
+        let mut entry = entry.import.to_string_lossy().to_string();
+        let is_browser = matches!(
+            self.context.config.platform,
+            crate::config::Platform::Browser
+        );
+        let watch = self.context.args.watch;
+        let hmr = self.context.config.hmr.is_some();
+        if is_browser && watch && hmr {
+            entry = format!("{}?hmr", entry);
+        }
+        File::new_entry(entry, self.context.clone())
+    }
+
     pub fn full_hash(&self) -> u64 {
         crate::mako_profile_function!();
         let cg = self.context.chunk_graph.read().unwrap();
         let mg = self.context.module_graph.read().unwrap();
         cg.full_hash(&mg)
     }
+    // build the entries again for browsers without esm support, into [name].legacy.js,
+    // the html plugin of this build injects them with <script nomodule>
+    fn compile_legacy(&self) -> Result<()> {
+        let mut config = self.context.config.reload(&self.context.root)?;
+        config.targets = HashMap::from([("ie".to_string(), 11.0)]);
+        config.output.es_version = EsVersion::Es5;
+        config.output.legacy_browsers = false;
+        config.output.chunk_loading_global =
+            format!("{}_legacy", config.output.chunk_loading_global);
+        // html, manifest, stats, profile and copied files are emitted by the main build only
+        config.clean = false;
+        config.html = None;
+        config.manifest = None;
+        config.stats = None;
+        config.analyze = None;
+        config.profile = false;
+        config.copy = vec![];
+
+        let mut plugins: Vec<Arc<dyn Plugin>> = vec![Arc::new(LegacyBrowsersPlugin {
+            main_context: self.context.clone(),
+        })];
+        plugins.extend(self.extra_plugins.clone());
+        let compiler = Compiler::new(
+            config,
+            self.context.root.clone(),
+            Args { watch: false },
+            Some(plugins),
+        )?;
+        // the main build reuses the contents of the legacy one, so that the js plugins run
+        // once, with the modules emitted when transforming them
+        *compiler.context.shared_contents.lock().unwrap() = Some(HashMap::new());
+        compiler.compile()?;
+        *self.context.shared_contents.lock().unwrap() =
+            compiler.context.shared_contents.lock().unwrap().take();
+        self.context
+            .emitted_modules
+            .take_from(&compiler.context.emitted_modules);
+        Ok(())
+    }
+
     fn clean_dist(&self) -> Result<()> {
         // compiler 前清除 dist，如果后续 dev 环境不在 output_path 里，需要再补上 dev 的逻辑
         let output_path = &self.context.config.output.path;
@@ -519,3 +1057,262 @@ impl Compiler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use anyhow::Result;
+
+    use super::{BuildStats, Context};
+    use crate::ast::file::{Content, JsContent};
+    use crate::plugin::{Plugin, PluginGenerateEndParams, PluginLoadParam};
+    use crate::utils::test_helper::setup_compiler_with_plugins;
+
+    // emits an asset in generate_end, and checks the files on disk in write_bundle
+    #[derive(Default)]
+    struct WriteBundlePlugin {
+        files_present: Mutex<Vec<bool>>,
+    }
+
+    impl Plugin for WriteBundlePlugin {
+        fn name(&self) -> &str {
+            "write_bundle_test"
+        }
+
+        fn generate_end(
+            &self,
+            _params: &PluginGenerateEndParams,
+            context: &Arc<Context>,
+        ) -> Result<()> {
+            context.emit_assets("extra.txt".to_string(), "extra.txt".to_string());
+            Ok(())
+        }
+
+        fn write_bundle(&self, context: &Arc<Context>) -> Result<()> {
+            let output = &context.config.output.path;
+            self.files_present
+                .lock()
+                .unwrap()
+                .push(output.join("index.js").exists() && output.join("extra.txt").exists());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_bundle_after_files_written() {
+        let plugin = Arc::new(WriteBundlePlugin::default());
+        let extra_plugin: Arc<dyn Plugin> = plugin.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/write-bundle", vec![extra_plugin], |_| {});
+        compiler.compile().unwrap();
+
+        assert_eq!(*plugin.files_present.lock().unwrap(), vec![true]);
+    }
+
+    // reports the time of its own work
+    struct TimingPlugin {}
+
+    impl Plugin for TimingPlugin {
+        fn name(&self) -> &str {
+            "timing_test"
+        }
+
+        fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+            context.log_timing("timing_test:compile".to_string(), 12.5);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plugin_timings_in_profile() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/write-bundle",
+            vec![Arc::new(TimingPlugin {})],
+            |config| config.profile = true,
+        );
+        compiler.compile().unwrap();
+
+        let profile = compiler.context.config.output.path.join("profile.json");
+        let profile: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(profile).unwrap()).unwrap();
+        assert_eq!(
+            profile["pluginTimings"],
+            serde_json::json!([{ "label": "timing_test:compile", "ms": 12.5 }])
+        );
+        assert!(profile["build"].is_f64());
+        assert!(profile["generate"].is_f64());
+    }
+
+    // loads `.myjs` as js, and overrides the json loader
+    struct JsExtensionPlugin {}
+
+    impl Plugin for JsExtensionPlugin {
+        fn name(&self) -> &str {
+            "js_extension_test"
+        }
+
+        fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+            context.register_js_extension(".myjs");
+            context.register_js_extension("json");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_register_js_extension() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/js-extension",
+            vec![Arc::new(JsExtensionPlugin {})],
+            |_| {},
+        );
+        let result = compiler.compile().unwrap();
+
+        // answer.myjs is bundled as a js module instead of an asset
+        let index = compiler.context.config.output.path.join("index.js");
+        let content = fs::read_to_string(index).unwrap();
+        assert!(content.contains("answer = 42"));
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(
+            result.warnings[0].message,
+            "`.json` is registered as a js extension, which overrides its builtin json loader"
+        );
+    }
+
+    // keeps the build stats passed to generate_end
+    #[derive(Default)]
+    struct BuildStatsPlugin {
+        build_stats: Mutex<Option<BuildStats>>,
+    }
+
+    impl Plugin for BuildStatsPlugin {
+        fn name(&self) -> &str {
+            "build_stats_test"
+        }
+
+        fn generate_end(
+            &self,
+            params: &PluginGenerateEndParams,
+            _context: &Arc<Context>,
+        ) -> Result<()> {
+            *self.build_stats.lock().unwrap() = params.build_stats.clone();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_build_stats() {
+        let plugin = Arc::new(BuildStatsPlugin::default());
+        let extra_plugin: Arc<dyn Plugin> = plugin.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/write-bundle", vec![extra_plugin], |config| {
+                config.profile = true
+            });
+        compiler.compile().unwrap();
+
+        let stats = compiler.context.stats().unwrap();
+        assert!(stats.transform.ms > 0.0, "{:?}", stats);
+        assert_eq!(stats.transform.count, stats.load.count);
+        assert!(stats.generate.ms > 0.0, "{:?}", stats);
+        let params_stats = plugin.build_stats.lock().unwrap().clone().unwrap();
+        assert_eq!(params_stats.transform.count, stats.transform.count);
+
+        // nothing is recorded without profile
+        let compiler = setup_compiler_with_plugins("test/build/write-bundle", vec![], |_| {});
+        compiler.compile().unwrap();
+        assert!(compiler.context.stats().is_none());
+        assert_eq!(
+            compiler.context.build_stats.lock().unwrap().transform.count,
+            0
+        );
+    }
+
+    // times its load calls the same as the js plugins, `active` loads the `.ts` files and
+    // keeps the stats passed to generate_end
+    struct ProfiledPlugin {
+        name: &'static str,
+        active: bool,
+        stats: Mutex<Option<serde_json::Value>>,
+    }
+
+    impl Plugin for ProfiledPlugin {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+            context.time_plugin_hook(self.name, "load", Option::is_some, || {
+                std::thread::sleep(Duration::from_millis(1));
+                Ok((self.active && param.file.extname == "ts").then(|| {
+                    Content::Js(JsContent {
+                        content: "export default 1;".to_string(),
+                        is_jsx: false,
+                    })
+                }))
+            })
+        }
+
+        fn generate_end(
+            &self,
+            params: &PluginGenerateEndParams,
+            _context: &Arc<Context>,
+        ) -> Result<()> {
+            *self.stats.lock().unwrap() = Some(serde_json::to_value(&params.stats)?);
+            Ok(())
+        }
+    }
+
+    fn profiled_plugin(name: &'static str, active: bool) -> Arc<ProfiledPlugin> {
+        Arc::new(ProfiledPlugin {
+            name,
+            active,
+            stats: Mutex::new(None),
+        })
+    }
+
+    #[test]
+    fn test_plugin_hook_stats() {
+        let active = profiled_plugin("active", true);
+        let passive = profiled_plugin("passive", false);
+        // the passive plugin goes first, so that both of them see every module
+        let extra_plugins: Vec<Arc<dyn Plugin>> = vec![passive.clone(), active.clone()];
+        let compiler =
+            setup_compiler_with_plugins("test/build/write-bundle", extra_plugins, |config| {
+                config.profile_plugins = true
+            });
+        compiler.compile().unwrap();
+
+        let stats = compiler.context.plugin_hook_stats();
+        assert_eq!(stats.len(), 2, "{:?}", stats);
+        let active_load = stats.iter().find(|s| s.plugin == "active").unwrap();
+        let passive_load = stats.iter().find(|s| s.plugin == "passive").unwrap();
+        assert_eq!(active_load.hook, "load");
+        assert_eq!(active_load.calls, passive_load.calls);
+        // only the entry is a ts file
+        assert_eq!(active_load.returned, 1);
+        assert_eq!(passive_load.returned, 0);
+        assert!(
+            active_load.ms >= active_load.calls as f64,
+            "{:?}",
+            active_load
+        );
+        // the stats passed to generate_end are the same as the ones of the table
+        let stats_json = active.stats.lock().unwrap().clone().unwrap();
+        assert_eq!(
+            stats_json["pluginHooks"],
+            serde_json::to_value(compiler.context.plugin_hook_stats()).unwrap()
+        );
+
+        // nothing is recorded without profilePlugins
+        let passive = profiled_plugin("passive", false);
+        let extra_plugin: Arc<dyn Plugin> = passive.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/write-bundle", vec![extra_plugin], |_| {});
+        compiler.compile().unwrap();
+        assert!(compiler.context.plugin_hook_stats().is_empty());
+        let stats_json = passive.stats.lock().unwrap().clone().unwrap();
+        assert!(stats_json.get("pluginHooks").is_none());
+    }
+}
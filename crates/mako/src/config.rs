@@ -1,5 +1,6 @@
 mod analyze;
 mod code_splitting;
+mod debug;
 mod dev_server;
 mod devtool;
 mod duplicate_package_checker;
@@ -8,15 +9,19 @@ mod experimental;
 mod external;
 mod generic_usize;
 mod hmr;
+mod html;
 mod inline_css;
+mod lint;
 mod macros;
 mod manifest;
 mod minifish;
 mod mode;
 pub mod module_federation;
 mod module_id_strategy;
+mod module_wrapper;
 mod optimization;
 mod output;
+mod performance;
 mod progress;
 mod provider;
 mod px2rem;
@@ -24,26 +29,30 @@ mod react;
 mod resolve;
 mod rsc_client;
 mod rsc_server;
+mod runtime;
 mod stats;
 mod transform_import;
 mod tree_shaking;
 mod umd;
+mod validate;
 mod watch;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub use analyze::AnalyzeConfig;
+pub use analyze::{deserialize_analyze, AnalyzeConfig};
 use anyhow::{anyhow, Result};
 pub use code_splitting::*;
 use colored::Colorize;
 use config;
+pub use debug::{debug_dir, DebugConfig};
 pub use dev_server::{deserialize_dev_server, DevServerConfig};
-pub use devtool::{deserialize_devtool, DevtoolConfig};
+pub use devtool::{deserialize_devtool, DevtoolConfig, SourceMapConfig, SourceMapPathTransform};
 pub use duplicate_package_checker::{
     deserialize_check_duplicate_package, DuplicatePackageCheckerConfig,
 };
+pub use entry::EntryGlob;
 use entry::{Entry, EntryItem};
 use experimental::ExperimentalConfig;
 pub use external::{
@@ -52,23 +61,29 @@ pub use external::{
 };
 pub use generic_usize::GenericUsizeDefault;
 pub use hmr::{deserialize_hmr, HmrConfig};
+pub use html::{deserialize_html, HtmlConfig, HtmlInject};
 pub use inline_css::{deserialize_inline_css, InlineCssConfig};
+pub use lint::{LintConfig, LintLevel};
 pub use manifest::{deserialize_manifest, ManifestConfig};
 use miette::{miette, ByteOffset, Diagnostic, NamedSource, SourceOffset, SourceSpan};
 pub use minifish::{deserialize_minifish, MinifishConfig};
 pub use mode::Mode;
 use module_federation::ModuleFederationConfig;
 pub use module_id_strategy::ModuleIdStrategy;
-pub use optimization::{deserialize_optimization, OptimizationConfig};
+pub use module_wrapper::ModuleWrapper;
+pub use optimization::{deserialize_optimization, OptimizationConfig, TreeShakingGranularity};
 use output::get_default_chunk_loading_global;
 pub use output::{CrossOriginLoading, OutputConfig, OutputMode};
+use path_clean::PathClean;
+pub use performance::PerformanceConfig;
 pub use progress::{deserialize_progress, ProgressConfig};
 pub use provider::Providers;
 pub use px2rem::{deserialize_px2rem, Px2RemConfig};
 pub use react::{ReactConfig, ReactRuntimeConfig};
-pub use resolve::ResolveConfig;
+pub use resolve::{ResolveConfig, ResolveFallback};
 pub use rsc_client::{deserialize_rsc_client, LogServerComponent, RscClientConfig};
 pub use rsc_server::{deserialize_rsc_server, RscServerConfig};
+pub use runtime::{ChunkRetryConfig, RuntimeConfig};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 pub use stats::{deserialize_stats, StatsConfig};
@@ -76,10 +91,12 @@ use thiserror::Error;
 pub use transform_import::{TransformImportConfig, TransformImportStyle};
 pub use tree_shaking::{deserialize_tree_shaking, TreeShakingStrategy};
 pub use umd::{deserialize_umd, Umd};
+pub use validate::validate_config;
 pub use watch::WatchConfig;
 
 use crate::build::load::JS_EXTENSIONS;
 use crate::config::experimental::RustPlugin;
+use crate::features::env_file::EnvFile;
 use crate::features::node::Node;
 
 #[derive(Debug, Diagnostic)]
@@ -140,14 +157,20 @@ pub enum CopyConfig {
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub entry: Entry,
+    // the glob form of `entry`, which is expanded into `entry`
+    #[serde(skip)]
+    pub entry_glob: Option<EntryGlob>,
     pub output: OutputConfig,
     pub resolve: ResolveConfig,
     #[serde(deserialize_with = "deserialize_manifest", default)]
     pub manifest: Option<ManifestConfig>,
+    #[serde(deserialize_with = "deserialize_html", default)]
+    pub html: Option<Vec<HtmlConfig>>,
     pub mode: Mode,
     pub minify: bool,
     #[serde(deserialize_with = "deserialize_devtool")]
     pub devtool: Option<DevtoolConfig>,
+    pub source_map: SourceMapConfig,
     pub externals: HashMap<String, ExternalConfig>,
     pub providers: Providers,
     pub copy: Vec<CopyConfig>,
@@ -157,7 +180,10 @@ pub struct Config {
     pub targets: HashMap<String, f32>,
     pub platform: Platform,
     pub module_id_strategy: ModuleIdStrategy,
+    pub module_wrapper: ModuleWrapper,
     pub define: HashMap<String, Value>,
+    pub env_file: Option<Vec<PathBuf>>,
+    #[serde(deserialize_with = "deserialize_analyze", default)]
     pub analyze: Option<AnalyzeConfig>,
     pub stats: Option<StatsConfig>,
     pub mdx: bool,
@@ -188,6 +214,12 @@ pub struct Config {
     pub clean: bool,
     pub node_polyfill: bool,
     pub ignores: Vec<String>,
+    // globs of the root relative paths of modules which are treated as `sideEffects: false`,
+    // before the sideEffects of their package.json
+    pub no_side_effect_modules: Vec<String>,
+    // the inverse of `no_side_effect_modules`, for packages which are `sideEffects: false` but not
+    // actually safe to drop
+    pub force_side_effects: Vec<String>,
     #[serde(
         rename = "_minifish",
         deserialize_with = "deserialize_minifish",
@@ -197,11 +229,14 @@ pub struct Config {
     #[serde(rename = "optimizePackageImports")]
     pub optimize_package_imports: bool,
     pub emotion: bool,
+    pub svgr: bool,
     pub flex_bugs: bool,
     #[serde(deserialize_with = "deserialize_optimization")]
     pub optimization: Option<OptimizationConfig>,
     pub react: ReactConfig,
     pub emit_assets: bool,
+    pub profile: bool,
+    pub profile_plugins: bool,
     #[serde(rename = "cssModulesExportOnlyLocales")]
     pub css_modules_export_only_locales: bool,
     #[serde(
@@ -222,8 +257,12 @@ pub struct Config {
         default
     )]
     pub rsc_client: Option<RscClientConfig>,
+    pub runtime: RuntimeConfig,
     pub experimental: ExperimentalConfig,
     pub watch: WatchConfig,
+    pub debug: DebugConfig,
+    pub lint: LintConfig,
+    pub performance: PerformanceConfig,
     pub use_define_for_class_fields: bool,
     pub emit_decorator_metadata: bool,
     #[serde(
@@ -236,6 +275,9 @@ pub struct Config {
     // 是否开启 case sensitive 检查,只有mac平台才需要开启
     #[serde(rename = "caseSensitiveCheck")]
     pub case_sensitive_check: bool,
+    // the json passed to Config::new, to create the config again, see Config::reload
+    #[serde(skip)]
+    sources: (Option<String>, Option<String>),
 }
 
 const CONFIG_FILE: &str = "mako.config.json";
@@ -290,6 +332,10 @@ impl Config {
         };
 
         let c = c.build()?;
+        let entry_glob = c
+            .get::<Value>("entry")
+            .ok()
+            .and_then(|entry| EntryGlob::from_value(&entry));
         let mut ret = c.try_deserialize::<Config>();
         // normalize & check
         if let Ok(config) = &mut ret {
@@ -333,6 +379,42 @@ impl Config {
                 return Err(anyhow!("hmr can only be used with devServer",));
             }
 
+            if config.output.is_preserve_modules() && (config.cjs || config.umd.is_some()) {
+                return Err(anyhow!(
+                    "output.preserveModules only supports esm output, it cannot be used with cjs or umd",
+                ));
+            }
+
+            if config.module_wrapper == ModuleWrapper::None {
+                if config.cjs || config.umd.is_some() {
+                    return Err(anyhow!(
+                        "moduleWrapper \"none\" cannot be used with cjs or umd, the entry exports are dropped",
+                    ));
+                }
+                // the node builtins are externals, which are required through the runtime
+                if config.platform == Platform::Node {
+                    return Err(anyhow!(
+                        "moduleWrapper \"none\" cannot be used with the node platform",
+                    ));
+                }
+                // without scope hoisting the modules can't be inlined without name collisions
+                if !config
+                    .optimization
+                    .as_ref()
+                    .map_or(false, |o| o.concatenate_modules.unwrap_or(false))
+                {
+                    return Err(anyhow!(
+                        "moduleWrapper \"none\" requires optimization.concatenateModules",
+                    ));
+                }
+            }
+
+            if config.output.legacy_browsers && config.platform != Platform::Browser {
+                return Err(anyhow!(
+                    "output.legacyBrowsers can only be used with the browser platform",
+                ));
+            }
+
             if config.inline_css.is_some() && config.umd.is_none() {
                 return Err(anyhow!("inlineCSS can only be used with umd",));
             }
@@ -367,6 +449,19 @@ impl Config {
                 }
             }
 
+            // expand the glob form of entry
+            if let Some(mut entry_glob) = entry_glob {
+                config.entry = entry_glob.expand(root)?;
+                if config.entry.is_empty() {
+                    return Err(anyhow!(
+                        "entry pattern {} matches no files",
+                        entry_glob.pattern
+                    ));
+                }
+                entry_glob.html = config.html.as_ref().map_or(false, |html| html.is_empty());
+                config.entry_glob = Some(entry_glob);
+            }
+
             // support default entries
             if config.entry.is_empty() {
                 let file_paths = ["src/index", "index"];
@@ -377,8 +472,8 @@ impl Config {
                             config.entry.insert(
                                 "index".to_string(),
                                 EntryItem {
-                                    filename: None,
                                     import: file_path,
+                                    ..Default::default()
                                 },
                             );
                             break 'outer;
@@ -424,6 +519,37 @@ impl Config {
                 Ok(())
             })?;
 
+            // normalize html
+            if let Some(html) = &mut config.html {
+                if html.is_empty() {
+                    *html = config
+                        .entry
+                        .keys()
+                        .map(|entry| HtmlConfig::for_entry(entry))
+                        .collect();
+                }
+                let mut filenames = HashSet::new();
+                for html_config in html.iter_mut() {
+                    if !config.entry.contains_key(&html_config.entry) {
+                        return Err(anyhow!(
+                            "html entry {} is not found in entry",
+                            html_config.entry
+                        ));
+                    }
+                    if !filenames.insert(html_config.output_filename()) {
+                        return Err(anyhow!(
+                            "html filename {} is duplicated",
+                            html_config.output_filename()
+                        ));
+                    }
+                    if let Some(template) = &html_config.template
+                        && template.is_relative()
+                    {
+                        html_config.template = Some(root.join(template));
+                    }
+                }
+            }
+
             // support relative alias
             config.resolve.alias.iter_mut().for_each(|(_, v)| {
                 #[allow(clippy::needless_borrows_for_generic_args)]
@@ -432,18 +558,81 @@ impl Config {
                 }
             });
 
+            // `./shared` is relative to root, while `shared` is searched in ancestors
+            config.resolve.modules.iter_mut().for_each(|m| {
+                if m.starts_with(".") {
+                    *m = root.join(&m).clean();
+                }
+            });
+
+            // like rollup's inlineDynamicImports, import() is evaluated with require()
+            // and no chunk is split out of the entries
+            if config.output.inline_dynamic_imports {
+                config.dynamic_import_to_require = true;
+                config.code_splitting = None;
+                config.experimental.lazy_compilation = false;
+            }
+
             // dev 环境下不产生 hash, prod 环境下根据用户配置
             if config.mode == Mode::Development {
                 config.hash = false;
             }
 
+            // load env files into define
+            EnvFile::modify_config(config, root)?;
+
             // configure node platform
             Node::modify_config(config);
+
+            config.sources = (
+                default_config.map(|c| c.to_string()),
+                cli_config.map(|c| c.to_string()),
+            );
         }
         ret.map_err(|e| anyhow!("{}: {}", "config error".red(), e.to_string().red()))
     }
 }
 
+// internal options which are not part of the public config
+const INTERNAL_PATHS: &[&[&str]] = &[
+    &["_treeShaking"],
+    &["_minifish"],
+    &["watch", "_nodeModulesRegexes"],
+];
+
+impl Config {
+    /// The config exposed to js plugins, with the `root` of the project. Internal
+    /// options are left out, so are `process.env.*` and `import.meta.env.*` defines
+    /// since they may come from env files and carry secrets.
+    pub fn to_plugin_value(&self, root: &Path) -> Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        value["root"] = Value::String(root.to_string_lossy().to_string());
+        for path in INTERNAL_PATHS {
+            let (key, parents) = path.split_last().unwrap();
+            let parent = parents
+                .iter()
+                .try_fold(&mut value, |value, parent| value.get_mut(*parent));
+            if let Some(Value::Object(parent)) = parent {
+                parent.remove(*key);
+            }
+        }
+        if let Some(Value::Object(define)) = value.get_mut("define") {
+            define.retain(|key, _| {
+                !key.starts_with("process.env.") && !key.starts_with("import.meta.env.")
+            });
+        }
+        Ok(value)
+    }
+
+    /// Create the config again from the same sources, for the extra compilations of
+    /// the project, e.g. the legacy build of `output.legacyBrowsers`.
+    pub fn reload(&self, root: &Path) -> Result<Self> {
+        let mut config = Config::new(root, self.sources.0.as_deref(), self.sources.1.as_deref())?;
+        config.mode = self.mode.clone();
+        Ok(config)
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let c = config::Config::builder();
@@ -460,6 +649,8 @@ impl Default for Config {
 pub enum ConfigError {
     #[error("define value '{0}' is not an Expression")]
     InvalidateDefineConfig(String),
+    #[error("invalid config:\n{}", .0.join("\n"))]
+    InvalidConfig(Vec<String>),
 }
 
 #[cfg(test)]
@@ -515,6 +706,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_entry_pattern() {
+        let current_dir = std::env::current_dir().unwrap();
+        let root = current_dir.join("test/config/entry-pattern");
+        let config = Config::new(&root, None, None).unwrap();
+        assert_eq!(config.entry.keys().collect::<Vec<_>>(), vec!["bar", "foo"]);
+        assert_eq!(
+            config.entry["foo"].import,
+            root.join("src/pages/foo/index.ts").canonicalize().unwrap()
+        );
+        let html = config.html.unwrap();
+        assert_eq!(
+            html.iter().map(|h| h.entry.as_str()).collect::<Vec<_>>(),
+            vec!["bar", "foo"]
+        );
+        assert!(config.entry_glob.unwrap().html);
+
+        let err = Config::new(
+            &root,
+            None,
+            Some(r#"{"entry":{"pattern":"src/pages/*/index.ts","name":"[name]"}}"#),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("entry name index"), "{}", err);
+        assert!(
+            err.contains("bar/index.ts") && err.contains("foo/index.ts"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_inline_dynamic_imports() {
+        let current_dir = std::env::current_dir().unwrap();
+        let config = Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"output":{"inlineDynamicImports":true},"codeSplitting":{"strategy":"auto"}}"#),
+        )
+        .unwrap();
+        assert!(config.dynamic_import_to_require);
+        assert!(config.code_splitting.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "moduleWrapper \"none\" cannot be used with the node platform")]
+    fn test_module_wrapper_none_with_node_platform() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"moduleWrapper":"none","platform":"node"}"#),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "moduleWrapper \"none\" requires optimization.concatenateModules")]
+    fn test_module_wrapper_none_without_concatenate_modules() {
+        let current_dir = std::env::current_dir().unwrap();
+        Config::new(
+            &current_dir.join("test/config/normal"),
+            None,
+            Some(r#"{"moduleWrapper":"none","optimization":{"concatenateModules":false}}"#),
+        )
+        .unwrap();
+    }
+
     #[test]
     #[should_panic(expected = "public_path must end with '/' or be 'runtime'")]
     fn test_config_invalid_public_path() {
@@ -527,6 +787,32 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_config_to_plugin_value() {
+        let current_dir = std::env::current_dir().unwrap();
+        let root = current_dir.join("test/build/env-file");
+        let config = Config::new(&root, None, None).unwrap();
+        let value = config.to_plugin_value(&root).unwrap();
+        assert_eq!(value["mode"], "production");
+        assert_eq!(value["root"], root.to_string_lossy().to_string());
+        assert_eq!(
+            value["output"]["path"],
+            root.join("dist").to_string_lossy().to_string()
+        );
+        assert!(value.get("_treeShaking").is_none());
+        assert!(value.get("_minifish").is_none());
+        assert!(value["watch"].get("_nodeModulesRegexes").is_none());
+        let define = value["define"].as_object().unwrap();
+        assert!(define.keys().all(|k| !k.starts_with("process.env.")));
+        assert!(define.keys().all(|k| !k.starts_with("import.meta.env.")));
+
+        let config = Config::new(&root, None, Some(r#"{"mode":"development"}"#)).unwrap();
+        assert_eq!(
+            config.to_plugin_value(&root).unwrap()["mode"],
+            "development"
+        );
+    }
+
     #[test]
     fn test_node_platform() {
         let current_dir = std::env::current_dir().unwrap();
@@ -538,8 +824,8 @@ mod tests {
             "use node targets by default if platform is node",
         );
         assert!(
-            config.ignores.iter().any(|i| i.contains("|fs|")),
-            "ignore Node.js standard library by default if platform is node",
+            !config.ignores.iter().any(|i| i.contains("|fs|")),
+            "externalize Node.js standard library instead of ignoring if platform is node",
         );
     }
 }
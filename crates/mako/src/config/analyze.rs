@@ -2,3 +2,24 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct AnalyzeConfig {}
+
+// same as `create_deserialize_fn!`, but `true` is allowed too since there's no option yet
+pub fn deserialize_analyze<'de, D>(deserializer: D) -> Result<Option<AnalyzeConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
+
+    match value {
+        serde_json::Value::Bool(false) | serde_json::Value::Null => Ok(None),
+        serde_json::Value::Bool(true) => Ok(Some(AnalyzeConfig {})),
+        serde_json::Value::Object(obj) => Ok(Some(
+            serde_json::from_value::<AnalyzeConfig>(serde_json::Value::Object(obj))
+                .map_err(serde::de::Error::custom)?,
+        )),
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid `analyze` value: {}",
+            value
+        ))),
+    }
+}
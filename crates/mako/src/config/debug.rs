@@ -0,0 +1,20 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugConfig {
+    /// Write the modules and their static imports to `module-graph.dot` of the debug dir
+    /// after the build
+    pub dump_module_graph: bool,
+    /// Write the statement graphs of the modules whose root relative paths match the glob
+    /// to `statement-graph/` of the debug dir before tree shaking
+    pub dump_statement_graph: Option<String>,
+}
+
+/// The dumps of the debug options are written to `node_modules/.mako`, rather than the output
+/// directory which is deployed
+pub fn debug_dir(root: &Path) -> PathBuf {
+    root.join("node_modules/.mako")
+}
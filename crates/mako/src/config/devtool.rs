@@ -13,3 +13,21 @@ pub enum DevtoolConfig {
 }
 
 create_deserialize_fn!(deserialize_devtool, DevtoolConfig);
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMapConfig {
+    pub sources_content: bool,
+    pub source_root: Option<String>,
+    pub path_transform: Option<SourceMapPathTransform>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceMapPathTransform {
+    /// Paths relative to the project root, e.g. `src/index.ts`
+    #[serde(rename = "relative")]
+    Relative,
+    /// Module ids like webpack's, e.g. `mako:///./src/index.ts`
+    #[serde(rename = "module-id")]
+    ModuleId,
+}
@@ -1,14 +1,33 @@
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use anyhow::{anyhow, Result};
+use glob::{glob, Pattern};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default, Clone)]
 pub struct EntryItem {
     #[serde(default)]
     pub filename: Option<String>,
     pub import: PathBuf,
+    // override output.banner and output.footer for the files of this entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<String>,
+}
+
+// the object form, a derived impl on EntryItem itself would be shadowed by the one below
+#[derive(Deserialize)]
+struct EntryItemObject {
+    #[serde(default)]
+    filename: Option<String>,
+    import: PathBuf,
+    #[serde(default)]
+    banner: Option<String>,
+    #[serde(default)]
+    footer: Option<String>,
 }
 
 pub type Entry = BTreeMap<String, EntryItem>;
@@ -21,11 +40,18 @@ impl<'de> Deserialize<'de> for EntryItem {
         let value: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
         match &value {
             Value::String(s) => Ok(EntryItem {
-                filename: None,
                 import: s.into(),
+                ..Default::default()
             }),
             Value::Object(_) => {
-                Ok(serde_json::from_value::<EntryItem>(value).map_err(serde::de::Error::custom)?)
+                let item = serde_json::from_value::<EntryItemObject>(value)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(EntryItem {
+                    filename: item.filename,
+                    import: item.import,
+                    banner: item.banner,
+                    footer: item.footer,
+                })
             }
             _ => Err(serde::de::Error::custom(format!(
                 "invalid `{}` value: {}",
@@ -35,3 +61,91 @@ impl<'de> Deserialize<'de> for EntryItem {
         }
     }
 }
+
+/// The glob form of `entry`, e.g. `{ pattern: "src/pages/*/index.tsx", name: "[dir]" }`, for
+/// multi-page apps. It's expanded into `entry` against the filesystem in `Config::new`, and
+/// again on each rebuild in watch mode so that the added pages get their entries.
+///
+/// The entry name is derived from the matched file with the tokens
+///
+/// - `[dir]`, the name of the directory of the file, e.g. `foo` for `src/pages/foo/index.tsx`
+/// - `[name]`, the file name without the extension, e.g. `index`
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct EntryGlob {
+    pub pattern: String,
+    #[serde(default = "default_entry_glob_name")]
+    pub name: String,
+    // `html: true`, every discovered entry gets a html, see HtmlPlugin
+    #[serde(skip)]
+    pub html: bool,
+}
+
+fn default_entry_glob_name() -> String {
+    "[dir]".to_string()
+}
+
+impl EntryGlob {
+    // an entry object with only `pattern` and `name` of strings is the glob form
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let Value::Object(entry) = value else {
+            return None;
+        };
+        let is_glob = entry.get("pattern").map_or(false, |v| v.is_string())
+            && entry
+                .iter()
+                .all(|(k, v)| (k == "pattern" || k == "name") && v.is_string());
+        if is_glob {
+            serde_json::from_value(value.clone()).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn expand(&self, root: &Path) -> Result<Entry> {
+        let pattern = root.join(&self.pattern);
+        let paths = glob(&pattern.to_string_lossy())
+            .map_err(|e| anyhow!("invalid entry pattern {}: {}", self.pattern, e))?;
+        let mut entry = Entry::new();
+        for path in paths.flatten().filter(|path| path.is_file()) {
+            let path = path.canonicalize()?;
+            let name = self.entry_name(&path);
+            if let Some(existing) = entry.get(&name) {
+                return Err(anyhow!(
+                    "entry name {} derived from pattern {} conflicts, both {} and {} match",
+                    name,
+                    self.pattern,
+                    existing.import.to_string_lossy(),
+                    path.to_string_lossy()
+                ));
+            }
+            entry.insert(
+                name,
+                EntryItem {
+                    import: path,
+                    ..Default::default()
+                },
+            );
+        }
+        Ok(entry)
+    }
+
+    // the entry paths are canonicalized, so is the root
+    pub fn matches(&self, root: &Path, path: &Path) -> bool {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        Pattern::new(&root.join(&self.pattern).to_string_lossy())
+            .map_or(false, |pattern| pattern.matches_path(path))
+    }
+
+    fn entry_name(&self, path: &Path) -> String {
+        let dir = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.name.replace("[dir]", &dir).replace("[name]", &name)
+    }
+}
@@ -22,6 +22,8 @@ pub struct ExperimentalConfig {
     pub rust_plugins: Vec<RustPlugin>,
     pub central_ensure: bool,
     pub imports_checker: bool,
+    // dev server only, dynamic imports are compiled when they are requested
+    pub lazy_compilation: bool,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HtmlInject {
+    #[serde(rename = "head")]
+    Head,
+    #[serde(rename = "body")]
+    #[default]
+    Body,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HtmlConfig {
+    pub entry: String,
+    pub template: Option<PathBuf>,
+    pub filename: Option<String>,
+    #[serde(default)]
+    pub inject: HtmlInject,
+    pub public_path: Option<String>,
+    pub title: Option<String>,
+}
+
+impl HtmlConfig {
+    pub fn for_entry(entry: &str) -> Self {
+        Self {
+            entry: entry.to_string(),
+            template: None,
+            filename: None,
+            inject: HtmlInject::default(),
+            public_path: None,
+            title: None,
+        }
+    }
+
+    pub fn output_filename(&self) -> String {
+        self.filename
+            .clone()
+            .unwrap_or_else(|| format!("{}.html", self.entry))
+    }
+}
+
+// `true` means every entry gets a html, it's normalized to one config per entry
+// in `Config::new` when entries are known
+pub fn deserialize_html<'de, D>(deserializer: D) -> Result<Option<Vec<HtmlConfig>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
+    match &value {
+        serde_json::Value::Bool(false) | serde_json::Value::Null => Ok(None),
+        serde_json::Value::Bool(true) => Ok(Some(vec![])),
+        serde_json::Value::Array(_) => Ok(Some(
+            serde_json::from_value::<Vec<HtmlConfig>>(value).map_err(serde::de::Error::custom)?,
+        )),
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid `{}` value: {}",
+            stringify!(deserialize_html).replace("deserialize_", ""),
+            value
+        ))),
+    }
+}
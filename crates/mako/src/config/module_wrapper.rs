@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * - `factory`, every module is wrapped in `function(module, exports, __mako_require__) {}`
+ *   and registered to the runtime
+ * - `commonjs`, same as `factory`, the factory already has the commonjs wrapper signature
+ * - `none`, the scope hoisted entry module is inlined into the entry chunk without the
+ *   factory and the runtime
+ */
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleWrapper {
+    Factory,
+    Commonjs,
+    None,
+}
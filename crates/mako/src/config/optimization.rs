@@ -2,11 +2,60 @@ use serde::{Deserialize, Serialize};
 
 use crate::create_deserialize_fn;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct OptimizationConfig {
     pub skip_modules: Option<bool>,
     pub concatenate_modules: Option<bool>,
+    pub mangle_exports: Option<bool>,
+    #[serde(deserialize_with = "deserialize_tree_shaking_granularity", default)]
+    pub tree_shaking: Option<TreeShakingGranularity>,
+    // bytes, modules kept for used exports which can only come from star exports of
+    // modules with unknown exports (e.g. commonjs) are warned when they are larger
+    pub ambiguous_exports_threshold: Option<u64>,
+    // initialize the pure exports only read by their getters when they're first accessed
+    pub deferred_exports: Option<bool>,
+    // the number of threads which optimize the js of the chunks, i.e. the chunks being
+    // minified at the same time, defaults to the shared thread pool
+    pub minify_workers: Option<usize>,
+    // move the css modules in more than one entry to a shared chunk, see Compiler::dedupe_css
+    pub css_dedupe: Option<bool>,
 }
 
 create_deserialize_fn!(deserialize_optimization, OptimizationConfig);
+
+/**
+ * - `modules`, only the modules which are not used at all are removed
+ * - `statements`, the unused statements of the used modules are removed too
+ * - `disabled` (`false` in config), nothing is removed
+ */
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TreeShakingGranularity {
+    Modules,
+    Statements,
+    Disabled,
+}
+
+pub fn deserialize_tree_shaking_granularity<'de, D>(
+    deserializer: D,
+) -> Result<Option<TreeShakingGranularity>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value: serde_json::Value = serde_json::Value::deserialize(deserializer)?;
+    match value {
+        serde_json::Value::Bool(false) => Ok(Some(TreeShakingGranularity::Disabled)),
+        serde_json::Value::Bool(true) => Ok(Some(TreeShakingGranularity::Statements)),
+        serde_json::Value::String(ref s) if s == "modules" => {
+            Ok(Some(TreeShakingGranularity::Modules))
+        }
+        serde_json::Value::String(ref s) if s == "statements" => {
+            Ok(Some(TreeShakingGranularity::Statements))
+        }
+        _ => Err(serde::de::Error::custom(format!(
+            "invalid `optimization.treeShaking` value: {}",
+            value
+        ))),
+    }
+}
@@ -24,6 +24,21 @@ pub struct OutputConfig {
     #[serde(deserialize_with = "deserialize_cross_origin_loading")]
     pub cross_origin_loading: Option<CrossOriginLoading>,
     pub global_module_registry: bool,
+    // bundle the dynamically imported modules into the entry chunks, see Config::new
+    pub inline_dynamic_imports: bool,
+    // prepended and appended to every emitted js and css file, see apply_banner_footer
+    pub banner: Option<String>,
+    pub footer: Option<String>,
+    // also build the entries for browsers without esm support into [name].legacy.js,
+    // see Compiler::compile_legacy
+    pub legacy_browsers: bool,
+}
+
+impl OutputConfig {
+    // emit one esm file per module instead of chunks, for library builds
+    pub fn is_preserve_modules(&self) -> bool {
+        self.mode == OutputMode::Bundle && self.preserve_modules
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq, ValueEnum, Clone)]
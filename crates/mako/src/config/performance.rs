@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceConfig {
+    // in bytes, the source map files are not counted
+    pub max_chunk_size: Option<usize>,
+    pub max_entrypoint_size: Option<usize>,
+}
@@ -1,7 +1,38 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ResolveConfig {
     pub alias: Vec<(String, String)>,
     pub extensions: Vec<String>,
+    // directories to search bare specifiers in, in order, relative names are
+    // looked up in every ancestor directory like node_modules
+    pub modules: Vec<PathBuf>,
+    // false to ignore the `browser` field of package.json, e.g. for ssr builds
+    #[serde(rename = "browserField")]
+    pub browser_field: bool,
+    // consulted when a specifier can't be resolved
+    #[serde(default)]
+    pub fallback: HashMap<String, ResolveFallback>,
+    // globs of specifiers which are replaced with modules throwing at runtime when
+    // they can't be resolved, e.g. optional dependencies
+    #[serde(rename = "ignoreMissing", default)]
+    pub ignore_missing: Vec<String>,
+    // resolve symlinks to their real paths, so that a file reachable through different
+    // links is one module, e.g. packages linked in workspaces
+    pub symlinks: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ResolveFallback {
+    /// Resolve this path or package from the root instead
+    Target(String),
+    /// `false` for an empty module
+    Empty(bool),
+    /// The key is a prefix of the specifiers, which is replaced with these paths or packages
+    /// in order until one resolves from the root, e.g. `{ "@/": ["./src/", "./shared/"] }`
+    Candidates(Vec<String>),
 }
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use crate::create_deserialize_fn;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeConfig {
+    #[serde(deserialize_with = "deserialize_chunk_retry", default)]
+    pub chunk_retry: Option<ChunkRetryConfig>,
+}
+
+/**
+ * Retry failed loads of async js and css chunks, e.g. when the chunk files of an
+ * old page have been replaced by a deploy. The n-th retry is delayed by
+ * `delay_ms * 2^(n-1)`, and with `cache_bust` a unique query is appended to the url.
+ */
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkRetryConfig {
+    pub times: usize,
+    pub delay_ms: usize,
+    #[serde(default)]
+    pub cache_bust: bool,
+}
+
+create_deserialize_fn!(deserialize_chunk_retry, ChunkRetryConfig);
@@ -5,6 +5,13 @@ use crate::create_deserialize_fn;
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct StatsConfig {
     pub modules: bool,
+    // bytes, packages bundled with multiple versions are warned when they are larger
+    #[serde(rename = "duplicatePackageThreshold", default)]
+    pub duplicate_package_threshold: u64,
+    // root relative path, the exports and statements kept or removed by tree shaking are
+    // written to it, see ShakeMetadata
+    #[serde(rename = "shakeMetadata", default)]
+    pub shake_metadata: Option<String>,
 }
 
 create_deserialize_fn!(deserialize_stats, StatsConfig);
@@ -0,0 +1,237 @@
+use anyhow::{anyhow, Result};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+use super::{
+    AnalyzeConfig, Config, ConfigError, DebugConfig, DevServerConfig, HmrConfig, LintConfig,
+    ManifestConfig, OptimizationConfig, OutputConfig, PerformanceConfig, ProgressConfig,
+    Px2RemConfig, ReactConfig, ResolveConfig, RuntimeConfig, StatsConfig, WatchConfig,
+    DEFAULT_CONFIG,
+};
+
+// keys handled by the js side or owned by plugins, anything goes under them
+const OPEN_KEYS: [&str; 6] = [
+    "experimental",
+    "less",
+    "sass",
+    "postcss",
+    "forkTSChecker",
+    "plugins",
+];
+
+/**
+ * Validate the config received from js before it's merged into the default config.
+ *
+ * All the problems are collected instead of failing on the first one, each with the
+ * json path, e.g.
+ *
+ * - unknown keys, with a suggestion of the most similar known key at that level
+ * - values which can't be deserialized, with the received value and the expected type
+ *
+ * Every value is checked on its own on top of the default config, so a problem doesn't
+ * hide the others.
+ */
+pub fn validate_config(config: &Value) -> Result<()> {
+    let Value::Object(config) = config else {
+        return Err(anyhow!(ConfigError::InvalidConfig(vec![format!(
+            "config must be an object, received {}",
+            config
+        )])));
+    };
+    let defaults: Value = serde_json::from_str(DEFAULT_CONFIG)?;
+    let known_keys = struct_fields::<Config>();
+    let mut problems = vec![];
+
+    for (key, value) in config {
+        if OPEN_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        if !known_keys.contains(&key.as_str()) {
+            problems.push(unknown_key(key, key, known_keys));
+            continue;
+        }
+
+        match (section_fields(key), value) {
+            (Some(section_keys), Value::Object(section)) => {
+                for (sub_key, sub_value) in section {
+                    let path = format!("{}.{}", key, sub_key);
+                    if !section_keys.contains(&sub_key.as_str()) {
+                        problems.push(unknown_key(&path, sub_key, section_keys));
+                        continue;
+                    }
+                    let mut merged = defaults.clone();
+                    let section = merged
+                        .as_object_mut()
+                        .unwrap()
+                        .entry(key.clone())
+                        .or_insert_with(|| Value::Object(Map::new()));
+                    if !section.is_object() {
+                        *section = Value::Object(Map::new());
+                    }
+                    section
+                        .as_object_mut()
+                        .unwrap()
+                        .insert(sub_key.clone(), sub_value.clone());
+                    if let Some(problem) = check_value(&path, sub_value, merged) {
+                        problems.push(problem);
+                    }
+                }
+            }
+            _ => {
+                let mut merged = defaults.clone();
+                merged
+                    .as_object_mut()
+                    .unwrap()
+                    .insert(key.clone(), value.clone());
+                if let Some(problem) = check_value(key, value, merged) {
+                    problems.push(problem);
+                }
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(ConfigError::InvalidConfig(problems)))
+    }
+}
+
+fn check_value(path: &str, value: &Value, merged: Value) -> Option<String> {
+    serde_json::from_value::<Config>(merged)
+        .err()
+        .map(|e| format!("`{}`: received {}, {}", path, value, e))
+}
+
+fn unknown_key(path: &str, key: &str, known_keys: &[&str]) -> String {
+    let suggestion = known_keys
+        .iter()
+        .map(|known| (edit_distance(key, known), known))
+        .filter(|(distance, _)| *distance <= (key.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance);
+    match suggestion {
+        Some((_, known)) => format!("`{}`: unknown key, did you mean `{}`?", path, known),
+        None => format!("`{}`: unknown key", path),
+    }
+}
+
+// sections whose keys are checked too
+fn section_fields(key: &str) -> Option<&'static [&'static str]> {
+    let fields = match key {
+        "output" => struct_fields::<OutputConfig>(),
+        "resolve" => struct_fields::<ResolveConfig>(),
+        "optimization" => struct_fields::<OptimizationConfig>(),
+        "react" => struct_fields::<ReactConfig>(),
+        "watch" => struct_fields::<WatchConfig>(),
+        "debug" => struct_fields::<DebugConfig>(),
+        "runtime" => struct_fields::<RuntimeConfig>(),
+        "devServer" => struct_fields::<DevServerConfig>(),
+        "hmr" => struct_fields::<HmrConfig>(),
+        "px2rem" => struct_fields::<Px2RemConfig>(),
+        "progress" => struct_fields::<ProgressConfig>(),
+        "stats" => struct_fields::<StatsConfig>(),
+        "manifest" => struct_fields::<ManifestConfig>(),
+        "analyze" => struct_fields::<AnalyzeConfig>(),
+        "lint" => struct_fields::<LintConfig>(),
+        "performance" => struct_fields::<PerformanceConfig>(),
+        _ => return None,
+    };
+    Some(fields)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+// the serialized field names of a struct, which serde passes to `deserialize_struct`
+fn struct_fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields = None;
+    let _ = T::deserialize(FieldsProbe {
+        fields: &mut fields,
+    });
+    fields.unwrap_or_default()
+}
+
+struct FieldsProbe<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de> Deserializer<'de> for FieldsProbe<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(de::Error::custom("fields probed"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::validate_config;
+
+    #[test]
+    fn test_valid_config() {
+        let config = json!({
+            "mode": "production",
+            "inlineLimit": 100,
+            "optimization": { "treeShaking": "modules" },
+            "less": { "theme": {} },
+            "experimental": { "somethingNew": true }
+        });
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_report_all_problems() {
+        let config = json!({
+            "outptu": { "path": "dist" },
+            "optimization": { "treeShake": "modules" },
+            "inlineLimit": "10"
+        });
+        let message = validate_config(&config).unwrap_err().to_string();
+
+        assert!(
+            message.contains("`outptu`: unknown key, did you mean `output`?"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains("`optimization.treeShake`: unknown key, did you mean `treeShaking`?"),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains(r#"`inlineLimit`: received "10", invalid type: string "10""#),
+            "{}",
+            message
+        );
+    }
+}
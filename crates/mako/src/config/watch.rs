@@ -6,4 +6,6 @@ pub struct WatchConfig {
     pub ignore_paths: Option<Vec<String>>,
     #[serde(rename = "_nodeModulesRegexes")]
     pub node_modules_regexes: Option<Vec<String>>,
+    /// The window in ms to coalesce the rapid changes of a file into one rebuild
+    pub debounce: u64,
 }
@@ -13,14 +13,17 @@ use get_if_addrs::get_if_addrs;
 use hyper::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CACHE_CONTROL, CONTENT_TYPE};
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Server};
-use notify_debouncer_full::new_debouncer;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, DebouncedEvent};
 use tokio::sync::broadcast;
 use tracing::debug;
 use tungstenite::Message;
 use {hyper, hyper_staticfile, hyper_tungstenite, open};
 
-use crate::compiler::{Compiler, Context};
-use crate::plugin::PluginGenerateEndParams;
+use crate::compiler::{BuildPhase, Compiler, Context};
+use crate::plugin::{PluginError, PluginGenerateEndParams};
+use crate::plugins::lazy_compilation;
 use crate::utils::{process_req_url, tokio_runtime};
 
 pub struct DevServer {
@@ -35,19 +38,26 @@ impl DevServer {
 
     pub async fn serve(&self) {
         let (txws, _) = broadcast::channel::<WsMessage>(256);
+        // file changes, the lazy compilation endpoint sends changes of proxy modules too
+        let (txwatch, rxwatch) = mpsc::channel::<DebounceEventResult>();
 
         // watch
         let root = self.root.clone();
         let compiler = self.compiler.clone();
         let txws_watch = txws.clone();
+        let txwatch_watcher = txwatch.clone();
 
         if self.compiler.context.config.dev_server.is_some() {
             std::thread::spawn(move || {
-                if let Err(e) = Self::watch_for_changes(root, compiler, txws_watch) {
+                if let Err(e) =
+                    Self::watch_for_changes(root, compiler, txws_watch, txwatch_watcher, rxwatch)
+                {
                     eprintln!("Error watching files: {:?}", e);
                 }
             });
-        } else if let Err(e) = Self::watch_for_changes(root, compiler, txws_watch) {
+        } else if let Err(e) =
+            Self::watch_for_changes(root, compiler, txws_watch, txwatch_watcher, rxwatch)
+        {
             eprintln!("Error watching files: {:?}", e);
         }
 
@@ -68,17 +78,21 @@ impl DevServer {
             let make_svc = make_service_fn(move |_conn| {
                 let context = context.clone();
                 let txws = txws.clone();
+                let txwatch = txwatch.clone();
                 async move {
                     Ok::<_, hyper::Error>(service_fn(move |req| {
                         let context = context.clone();
                         let txws = txws.clone();
+                        let txwatch = txwatch.clone();
                         let staticfile = {
                             let mut sf =
                                 hyper_staticfile::Static::new(context.config.output.path.clone());
                             sf.cache_headers(Some(0));
                             sf
                         };
-                        async move { Self::handle_requests(req, context, staticfile, txws).await }
+                        async move {
+                            Self::handle_requests(req, context, staticfile, txws, txwatch).await
+                        }
                     }))
                 }
             });
@@ -123,6 +137,7 @@ impl DevServer {
         context: Arc<Context>,
         staticfile: hyper_staticfile::Static,
         txws: broadcast::Sender<WsMessage>,
+        txwatch: mpsc::Sender<DebounceEventResult>,
     ) -> Result<hyper::Response<Body>> {
         debug!("> {} {}", req.method().to_string(), req.uri().path());
 
@@ -161,6 +176,8 @@ impl DevServer {
                     Ok(not_found_response())
                 }
             }
+            "/__/lazy-compile" => Ok(Self::handle_lazy_compile(&req, &context, &txwatch)
+                .unwrap_or_else(not_found_response)),
             _ => {
                 // for bundle outputs
 
@@ -225,6 +242,45 @@ impl DevServer {
         }
     }
 
+    // compile the requested dynamic import target by rebuilding its proxy module,
+    // the browser receives it with the next hot update
+    fn handle_lazy_compile(
+        req: &Request<Body>,
+        context: &Arc<Context>,
+        txwatch: &mpsc::Sender<DebounceEventResult>,
+    ) -> Option<hyper::Response<Body>> {
+        if !lazy_compilation::is_enabled(&context.config, context.args.watch) {
+            return None;
+        }
+        let target = url::form_urlencoded::parse(req.uri().query()?.as_bytes())
+            .find(|(k, _)| k == "id")
+            .map(|(_, v)| v.to_string())?;
+        let proxy_id = lazy_compilation::proxy_id(&target);
+        if !context
+            .module_graph
+            .read()
+            .unwrap()
+            .has_module(&proxy_id.clone().into())
+        {
+            return None;
+        }
+        if context.lazy_compilation.activate(&target) {
+            debug!("lazy compile: {}", target);
+            let event = Event::new(EventKind::Modify(ModifyKind::Any)).add_path(proxy_id.into());
+            txwatch
+                .send(Ok(vec![DebouncedEvent::new(event, Instant::now())]))
+                .ok()?;
+        }
+        Some(
+            hyper::Response::builder()
+                .status(hyper::StatusCode::OK)
+                .header(CACHE_CONTROL, "no-cache")
+                .header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                .body(hyper::Body::empty())
+                .unwrap(),
+        )
+    }
+
     fn get_ips() -> Vec<String> {
         let mut ips = vec![];
         match get_if_addrs() {
@@ -251,7 +307,6 @@ impl DevServer {
         }
     }
 
-    // TODO: refact socket message data structure
     async fn handle_websocket(
         websocket: hyper_tungstenite::HyperWebsocket,
         mut receiver: broadcast::Receiver<WsMessage>,
@@ -261,11 +316,7 @@ impl DevServer {
         let task = tokio_runtime::spawn(async move {
             loop {
                 if let Ok(msg) = receiver.recv().await {
-                    if sender
-                        .send(Message::text(format!(r#"{{"hash":"{}"}}"#, msg.hash)))
-                        .await
-                        .is_err()
-                    {
+                    if sender.send(Message::text(msg.to_json())).await.is_err() {
                         break;
                     }
                 }
@@ -285,29 +336,38 @@ impl DevServer {
         root: PathBuf,
         compiler: Arc<Compiler>,
         txws: broadcast::Sender<WsMessage>,
+        tx: mpsc::Sender<DebounceEventResult>,
+        rx: mpsc::Receiver<DebounceEventResult>,
     ) -> Result<()> {
-        let (tx, rx) = mpsc::channel();
         // let mut watcher = RecommendedWatcher::new(tx, notify::Config::default())?;
-        let mut debouncer = new_debouncer(Duration::from_millis(10), None, tx).unwrap();
+        let window = Duration::from_millis(compiler.context.config.watch.debounce);
+        let mut debouncer = new_debouncer(window, None, tx).unwrap();
         let mut watcher = watch::Watcher::new(&root, debouncer.watcher(), &compiler);
         watcher.watch()?;
 
         let initial_hash = compiler.full_hash();
         let mut snapshot_hash = Box::new(initial_hash);
         let mut hmr_hash = Box::new(initial_hash);
+        let mut shown_error = false;
 
-        for result in rx {
+        for result in &rx {
             if result.is_err() {
                 eprintln!("Error watching files: {:?}", result.err().unwrap());
                 continue;
             }
-            let paths = watch::Watcher::normalize_events(result.unwrap());
-            if !paths.is_empty() {
+            let events = watch::Watcher::coalesce_events(result.unwrap(), &rx, window);
+            let changes = watch::Watcher::normalize_events(events);
+            if !changes.is_empty() {
                 let compiler = compiler.clone();
                 let txws = txws.clone();
-                if let Err(e) =
-                    Self::rebuild(paths, compiler, txws, &mut snapshot_hash, &mut hmr_hash)
-                {
+                if let Err(e) = Self::rebuild(
+                    changes,
+                    compiler,
+                    txws,
+                    &mut snapshot_hash,
+                    &mut hmr_hash,
+                    &mut shown_error,
+                ) {
                     eprintln!("Error rebuilding: {:?}", e);
                 }
             }
@@ -316,20 +376,42 @@ impl DevServer {
     }
 
     fn rebuild(
-        paths: Vec<PathBuf>,
+        changes: Vec<watch::WatchChange>,
         compiler: Arc<Compiler>,
         txws: broadcast::Sender<WsMessage>,
         last_snapshot_hash: &mut Box<u64>,
         hmr_hash: &mut Box<u64>,
+        // whether the clients show the error of a plugin, until the next successful rebuild
+        shown_error: &mut bool,
     ) -> Result<()> {
-        debug!("watch paths detected: {:?}", paths);
+        debug!("watch changes detected: {:?}", changes);
         debug!("checking update status...");
         println!("Checking...");
 
+        compiler.watch_changes(&changes)?;
+        let mut paths = changes
+            .iter()
+            .flat_map(|change| change.paths())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
         let paths = compiler.context.plugin_driver.before_rebuild(paths)?;
         debug!("update paths: {:?}", paths);
 
+        // the modules are shaken, so they are all built again instead of the hot updates
+        if compiler.context.rebuilds_fully() {
+            if let Err(e) = compiler.compile() {
+                // do not return error, the next change triggers a rebuild again
+                eprintln!("{}", e);
+            }
+            return Ok(());
+        }
+
+        *compiler.context.build_stats.lock().unwrap() = Default::default();
+        let t_update = Instant::now();
         let update_result = compiler.update(paths);
+        let t_update = t_update.elapsed();
         let has_missing_deps = {
             compiler
                 .context
@@ -345,6 +427,13 @@ impl DevServer {
         if let Err(e) = update_result {
             debug!("checking update status... failed");
             eprintln!("{}", e);
+            // the modules keep their previous versions, so the clients only show the error
+            if let Some(error) = PluginError::find(&e)
+                && txws.receiver_count() > 0
+            {
+                txws.send(WsMessage::Error(error.clone())).unwrap();
+                *shown_error = true;
+            }
             // do not return error, since it's already printed
             return Ok(());
         }
@@ -357,9 +446,13 @@ impl DevServer {
             return Ok(());
         }
 
+        let mut rebuild_info = res.rebuild_info();
+        rebuild_info.timing.update = t_update.as_millis() as u64;
+
         let t_compiler = Instant::now();
         let start_time = chrono::Local::now().timestamp_millis();
         let next_hash = compiler.generate_hot_update_chunks(res, **last_snapshot_hash, **hmr_hash);
+        rebuild_info.timing.generate_hot_update = t_compiler.elapsed().as_millis() as u64;
         debug!(
             "hot update chunks generated, next_full_hash: {:?}",
             next_hash
@@ -377,6 +470,26 @@ impl DevServer {
         );
         if next_snapshot_hash == **last_snapshot_hash {
             debug!("hash equals, will not do full rebuild");
+            // e.g. the error is fixed by reverting the module, clear the error of the clients
+            if *shown_error && txws.receiver_count() > 0 {
+                txws.send(WsMessage::Hash(**hmr_hash)).unwrap();
+            }
+            *shown_error = false;
+            // the output is unchanged, still let the plugins know about the rebuild
+            if !has_missing_deps {
+                let params = PluginGenerateEndParams {
+                    is_first_compile: false,
+                    build_id: compiler.context.build_id(),
+                    time: t_compiler.elapsed().as_millis() as i64,
+                    stats: compiler.create_stats_info(),
+                    rebuild: Some(rebuild_info),
+                    build_stats: compiler.context.stats(),
+                };
+                compiler
+                    .context
+                    .plugin_driver
+                    .generate_end(&params, &compiler.context)?;
+            }
             return Ok(());
         } else {
             **last_snapshot_hash = next_snapshot_hash;
@@ -387,7 +500,8 @@ impl DevServer {
 
         compiler.context.stats_info.clear_assets();
 
-        let mut stats = compiler
+        let t_emit = Instant::now();
+        let (mut stats, output_changes) = compiler
             .emit_dev_chunks(next_hmr_hash, current_hmr_hash)
             .map_err(|e| {
                 debug!("  > build failed: {:?}", e);
@@ -396,6 +510,11 @@ impl DevServer {
 
         stats.start_time = start_time;
         stats.end_time = chrono::Local::now().timestamp_millis();
+        rebuild_info.output = output_changes;
+        rebuild_info.timing.emit = t_emit.elapsed().as_millis() as u64;
+        compiler
+            .context
+            .add_phase_time(BuildPhase::Generate, t_emit.elapsed());
 
         debug!("full rebuild...done");
         if !has_missing_deps {
@@ -405,8 +524,11 @@ impl DevServer {
             );
             let params = PluginGenerateEndParams {
                 is_first_compile: false,
+                build_id: compiler.context.build_id(),
                 time: t_compiler.elapsed().as_millis() as i64,
                 stats,
+                rebuild: Some(rebuild_info),
+                build_stats: compiler.context.stats(),
             };
             compiler
                 .context
@@ -416,28 +538,40 @@ impl DevServer {
                     debug!("generate end failed: {:?}", e);
                     e
                 })?;
-            compiler
-                .context
-                .plugin_driver
-                .write_bundle(&compiler.context)
-                .map_err(|e| {
-                    debug!("write bundle failed: {:?}", e);
-                    e
-                })?;
+            compiler.write_bundle().map_err(|e| {
+                debug!("write bundle failed: {:?}", e);
+                e
+            })?;
         }
 
         let receiver_count = txws.receiver_count();
         debug!("receiver count: {}", receiver_count);
         if receiver_count > 0 {
-            txws.send(WsMessage { hash: **hmr_hash }).unwrap();
+            txws.send(WsMessage::Hash(**hmr_hash)).unwrap();
             debug!("send message to clients");
         }
+        *shown_error = false;
 
         Ok(())
     }
 }
 
 #[derive(Clone, Debug)]
-struct WsMessage {
-    hash: u64,
+enum WsMessage {
+    Hash(u64),
+    // the error of a plugin which fails the rebuild, cleared by the next hash
+    Error(PluginError),
+}
+
+impl WsMessage {
+    fn to_json(&self) -> String {
+        match self {
+            WsMessage::Hash(hash) => format!(r#"{{"hash":"{}"}}"#, hash),
+            WsMessage::Error(error) => serde_json::json!({
+                "type": "error",
+                "error": error,
+            })
+            .to_string(),
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::fmt::Debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Ok, Result};
 use rayon::prelude::*;
@@ -10,10 +10,13 @@ use tracing::debug;
 use crate::ast::file::File;
 use crate::build::BuildError;
 use crate::compiler::Compiler;
+use crate::dev::watch::WatchChange;
 use crate::generate::transform::transform_modules;
 use crate::module::{Dependency, Module, ModuleId, ResolveType};
 use crate::module_graph::ModuleGraph;
-use crate::plugin::NextBuildParam;
+use crate::plugin::{NextBuildParam, PluginRebuildInfo, PluginWatchChangesParams};
+use crate::plugins::const_enum;
+use crate::plugins::lazy_compilation::proxy_target;
 use crate::resolve::{self, clear_resolver_cache};
 
 #[derive(Debug, Clone)]
@@ -33,6 +36,8 @@ pub struct UpdateResult {
     pub modified: HashSet<ModuleId>,
     // 依赖变更，典型的如 async import 变成 import
     pub dep_changed: HashSet<ModuleId>,
+    // entries discovered by entry.pattern are added or removed
+    pub entries_changed: bool,
 }
 
 impl UpdateResult {
@@ -41,6 +46,22 @@ impl UpdateResult {
             || !self.added.is_empty()
             || !self.removed.is_empty()
             || !self.dep_changed.is_empty()
+            || self.entries_changed
+    }
+
+    // the module part of the rebuild info passed to generate_end
+    pub fn rebuild_info(&self) -> PluginRebuildInfo {
+        let sorted_ids = |ids: &HashSet<ModuleId>| {
+            let mut ids = ids.iter().map(|id| id.id.clone()).collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+        PluginRebuildInfo {
+            added_modules: sorted_ids(&self.added),
+            modified_modules: sorted_ids(&self.modified),
+            removed_modules: sorted_ids(&self.removed),
+            ..Default::default()
+        }
     }
 }
 
@@ -80,17 +101,83 @@ dep_changed:{:?}
 }
 
 impl Compiler {
+    pub(crate) fn watch_changes(&self, changes: &[WatchChange]) -> Result<()> {
+        // every batch of changes triggers a rebuild
+        self.context.next_build_id();
+        for change in changes {
+            let params = self.watch_changes_params(change);
+            self.context.plugin_driver.watch_changes(
+                &change.path().to_string_lossy(),
+                &params,
+                &self.context,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn watch_changes_params(&self, change: &WatchChange) -> PluginWatchChangesParams {
+        let module_graph = self.context.module_graph.read().unwrap();
+        let (from, to) = match change {
+            WatchChange::Rename { from, to } => (
+                Some(from.to_string_lossy().to_string()),
+                Some(to.to_string_lossy().to_string()),
+            ),
+            _ => (None, None),
+        };
+        // a renamed file is still imported with the old path before rebuilding
+        let path = match change {
+            WatchChange::Rename { from, .. } => from,
+            _ => change.path(),
+        };
+        let path = path.to_string_lossy().to_string();
+        let mut importers = ["", "?modules", "?watch=parent"]
+            .iter()
+            .map(|search| ModuleId::from(format!("{}{}", path, search)))
+            .filter(|id| module_graph.has_module(id))
+            .flat_map(|id| {
+                module_graph
+                    .get_dependents(&id)
+                    .into_iter()
+                    .map(|(importer, _)| importer.id.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        importers.sort();
+        importers.dedup();
+
+        PluginWatchChangesParams {
+            event: change.event().to_string(),
+            from,
+            to,
+            importers,
+        }
+    }
+
     pub fn update(&self, paths: Vec<PathBuf>) -> Result<UpdateResult> {
         let module_graph = self.context.module_graph.read().unwrap();
+        let is_module = |path: &Path| {
+            let path = path.to_string_lossy().to_string();
+            module_graph.has_module(&path.clone().into())
+                || module_graph.has_module(&format!("{}?modules", path).into())
+                || module_graph.has_module(&format!("{}?watch=parent", path).into())
+        };
         let paths = paths
             .into_iter()
             .map(|path| {
-                let update_type = if path.exists() {
-                    let path = path.to_string_lossy().to_string();
-                    if module_graph.has_module(&path.clone().into())
-                        || module_graph.has_module(&format!("{}?modules", path).into())
-                        || module_graph.has_module(&format!("{}?watch=parent", path).into())
-                    {
+                // modules are identified by the real path, while the changes may be
+                // watched through a link, and the entries keep the paths of the config
+                let path = if self.context.config.resolve.symlinks && !is_module(&path) {
+                    path.canonicalize().unwrap_or(path)
+                } else {
+                    path
+                };
+                // lazy compilation proxies exist as long as their target exists
+                let exists = match proxy_target(&path.to_string_lossy()) {
+                    Some(target) => Path::new(target).exists(),
+                    None => path.exists(),
+                };
+                let update_type = if exists {
+                    if is_module(&path) {
                         UpdateType::Modify
                     } else {
                         UpdateType::Add
@@ -163,6 +250,15 @@ impl Compiler {
                 if module_graph.has_module(&p.clone().into()) {
                     debug!("  > {} is filtered", p.to_string_lossy());
                     new_paths.push((p.clone(), update_type.clone()));
+                    // the importers inlined the const enums of the module
+                    if matches!(update_type, UpdateType::Modify) {
+                        let dependents =
+                            const_enum::const_enum_dependents(&module_graph, &p.clone().into());
+                        for dependent in dependents {
+                            debug!("  > {} is filtered", dependent.id);
+                            new_paths.push((dependent.to_path(), UpdateType::Modify));
+                        }
+                    }
                 }
                 let path = p.to_string_lossy().to_string();
                 let watch_parent_searches = vec!["?modules", "?watch=parent"];
@@ -180,37 +276,23 @@ impl Compiler {
                     }
                 }
             });
+            let mut seen = HashSet::new();
+            new_paths.retain(|(p, _)| seen.insert(p.clone()));
             new_paths
         };
         debug!("filtering paths...done");
 
+        // the pattern entries are only changed by the added or removed files
+        let files_added_or_removed = paths
+            .iter()
+            .any(|(_, update_type)| !matches!(update_type, UpdateType::Modify));
+
         // 先分组
         for (path, update_type) in paths {
             match update_type {
-                UpdateType::Add => {
-                    self.context.plugin_driver.watch_changes(
-                        &path.to_string_lossy(),
-                        "create",
-                        &self.context,
-                    )?;
-                    added.push(path);
-                }
-                UpdateType::Remove => {
-                    self.context.plugin_driver.watch_changes(
-                        &path.to_string_lossy(),
-                        "delete",
-                        &self.context,
-                    )?;
-                    removed.push(path);
-                }
-                UpdateType::Modify => {
-                    self.context.plugin_driver.watch_changes(
-                        &path.to_string_lossy(),
-                        "update",
-                        &self.context,
-                    )?;
-                    modified.push(path);
-                }
+                UpdateType::Add => added.push(path),
+                UpdateType::Remove => removed.push(path),
+                UpdateType::Modify => modified.push(path),
             }
         }
 
@@ -254,6 +336,15 @@ impl Compiler {
         );
         update_result.added.extend(added_module_ids);
 
+        // the added pages are not in the module graph, so they are built as entries here,
+        // while the removed ones are gone with their modules
+        if files_added_or_removed {
+            debug!("updating pattern entries...");
+            let (entries_changed, added_entry_module_ids) = self.update_pattern_entries()?;
+            update_result.entries_changed = entries_changed;
+            update_result.added.extend(added_entry_module_ids);
+        }
+
         debug!("update_result: {:?}", &update_result);
 
         self.context.plugin_driver.after_update(self)?;
@@ -283,7 +374,8 @@ impl Compiler {
                 debug!("build by modify: {:?} start", entry);
                 // first build
                 let is_entry = {
-                    let mut entries = self.context.config.entry.values().map(|e| &e.import);
+                    let entries = self.context.entries.read().unwrap();
+                    let mut entries = entries.values().map(|e| &e.import);
                     entries.any(|e| e.eq(entry))
                 };
 
@@ -440,6 +532,48 @@ impl Compiler {
         self.build(files)
     }
 
+    // expand entry.pattern again, returns whether the entries are changed and the modules
+    // built for the added entries
+    fn update_pattern_entries(&self) -> Result<(bool, HashSet<ModuleId>)> {
+        let Some(entry_glob) = &self.context.config.entry_glob else {
+            return Ok((false, HashSet::new()));
+        };
+        let root = &self.context.root;
+        let expanded = entry_glob.expand(root)?;
+        let added = {
+            let mut entries = self.context.entries.write().unwrap();
+            let removed = entries
+                .iter()
+                .filter(|(name, item)| {
+                    entry_glob.matches(root, &item.import)
+                        && expanded.get(*name).map(|e| &e.import) != Some(&item.import)
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            for name in &removed {
+                debug!("  > entry {} is removed", name);
+                entries.remove(name);
+            }
+            let added = expanded
+                .into_iter()
+                .filter(|(name, item)| entries.get(name).map(|e| &e.import) != Some(&item.import))
+                .collect::<Vec<_>>();
+            if removed.is_empty() && added.is_empty() {
+                return Ok((false, HashSet::new()));
+            }
+            entries.extend(added.clone());
+            added
+        };
+        let files = added
+            .iter()
+            .map(|(name, item)| {
+                debug!("  > entry {} is added", name);
+                self.entry_file(item)
+            })
+            .collect();
+        Ok((true, self.build(files)?))
+    }
+
     fn build_by_remove(&self, removed: Vec<PathBuf>) -> (HashSet<ModuleId>, HashSet<ModuleId>) {
         let mut module_graph = self.context.module_graph.write().unwrap();
         let mut removed_module_ids = HashSet::new();
@@ -525,3 +659,501 @@ fn diff(origin: &[(ModuleId, Dependency)], new_deps: &[(ModuleId, Dependency)])
         modified,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use std::{fs, thread};
+
+    use notify::event::{DataChange, ModifyKind};
+    use notify::{Event, EventKind};
+    use notify_debouncer_full::DebouncedEvent;
+    use tokio::sync::broadcast;
+
+    use crate::ast::file::Content;
+    use crate::compiler::{Args, Compiler, Context};
+    use crate::config::Config;
+    use crate::dev::watch::{WatchChange, Watcher};
+    use crate::dev::DevServer;
+    use crate::generate::chunk::ChunkType;
+    use crate::plugin::{
+        ChangedAsset, OutputChanges, Plugin, PluginError, PluginWatchChangesParams,
+    };
+    use crate::utils::test_helper::{setup_compiler, setup_logger};
+
+    struct WatchChangesPlugin {
+        calls: AtomicUsize,
+        ids: Mutex<Vec<String>>,
+    }
+
+    impl Plugin for WatchChangesPlugin {
+        fn name(&self) -> &str {
+            "watch_changes"
+        }
+
+        fn watch_changes(
+            &self,
+            id: &str,
+            _params: &PluginWatchChangesParams,
+            _context: &Arc<Context>,
+        ) -> anyhow::Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.ids.lock().unwrap().push(id.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_watch_changes_params() {
+        let compiler = setup_compiler("test/build/watch-changes", false);
+        compiler.compile().unwrap();
+        let root = compiler.context.root.clone();
+        let id = |name: &str| root.join(name).to_string_lossy().to_string();
+
+        let params = |change: WatchChange| compiler.watch_changes_params(&change);
+
+        assert_eq!(
+            params(WatchChange::Update(root.join("a.ts"))),
+            PluginWatchChangesParams {
+                event: "update".to_string(),
+                from: None,
+                to: None,
+                importers: vec![id("b.ts"), id("index.ts")],
+            }
+        );
+        assert_eq!(
+            params(WatchChange::Delete(root.join("b.ts"))).importers,
+            vec![id("index.ts")]
+        );
+        assert_eq!(
+            params(WatchChange::Rename {
+                from: root.join("a.ts"),
+                to: root.join("c.ts"),
+            }),
+            PluginWatchChangesParams {
+                event: "rename".to_string(),
+                from: Some(id("a.ts")),
+                to: Some(id("c.ts")),
+                importers: vec![id("b.ts"), id("index.ts")],
+            }
+        );
+        // not in the module graph
+        assert_eq!(
+            params(WatchChange::Create(root.join("d.ts"))),
+            PluginWatchChangesParams {
+                event: "create".to_string(),
+                from: None,
+                to: None,
+                importers: vec![],
+            }
+        );
+        assert!(params(WatchChange::Update(root.join("README.md")))
+            .importers
+            .is_empty());
+    }
+
+    #[test]
+    fn test_coalesce_rapid_edits() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/watch-changes");
+        let plugin = Arc::new(WatchChangesPlugin {
+            calls: AtomicUsize::new(0),
+            ids: Mutex::new(vec![]),
+        });
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(
+            config,
+            root.clone(),
+            Args { watch: true },
+            Some(vec![plugin.clone()]),
+        )
+        .unwrap();
+        compiler.compile().unwrap();
+
+        // three saves of a.ts, each comes in its own batch of the debouncer
+        let a = root.join("a.ts");
+        let (tx, rx) = mpsc::channel();
+        let edited = a.clone();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let event = Event::new(EventKind::Modify(ModifyKind::Data(DataChange::Content)))
+                    .add_path(edited.clone());
+                tx.send(Ok(vec![DebouncedEvent::new(event, Instant::now())]))
+                    .unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let first = rx.recv().unwrap().unwrap();
+        let events = Watcher::coalesce_events(first, &rx, Duration::from_millis(100));
+        assert_eq!(events.len(), 3);
+        let changes = Watcher::normalize_events(events);
+        assert_eq!(changes, vec![WatchChange::Update(a.clone())]);
+
+        compiler.watch_changes(&changes).unwrap();
+        assert_eq!(plugin.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *plugin.ids.lock().unwrap(),
+            vec![a.to_string_lossy().to_string()]
+        );
+    }
+
+    #[test]
+    fn test_build_id_increases_on_rebuild() {
+        let compiler = setup_compiler("test/build/watch-changes", false);
+        compiler.compile().unwrap();
+        let first_build_id = compiler.context.build_id();
+        assert_eq!(first_build_id, 1);
+
+        let a = compiler.context.root.join("a.ts");
+        let mut build_ids = vec![first_build_id];
+        for _ in 0..2 {
+            compiler
+                .watch_changes(&[WatchChange::Update(a.clone())])
+                .unwrap();
+            compiler.update(vec![a.clone()]).unwrap();
+            build_ids.push(compiler.context.build_id());
+        }
+        assert_eq!(build_ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rebuild_output_changes() {
+        setup_logger();
+        let root = std::env::temp_dir().join("mako-test-rebuild-output-changes");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("index.ts"),
+            "import { a } from './a';\nimport('./lazy');\nconsole.log(a);\n",
+        )
+        .unwrap();
+        fs::write(root.join("a.ts"), "export const a = 1;\n").unwrap();
+        fs::write(root.join("lazy.ts"), "export const lazy = 1;\n").unwrap();
+
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root.clone(), Args { watch: true }, None).unwrap();
+        compiler.compile().unwrap();
+        let snapshot_hash = compiler.full_hash();
+        let hmr_hash = snapshot_hash;
+
+        let a = root.join("a.ts");
+        fs::write(&a, "export const a = 2;\n").unwrap();
+        let res = compiler.update(vec![a.clone()]).unwrap();
+        let info = res.rebuild_info();
+        assert_eq!(info.modified_modules, vec![a.to_string_lossy().to_string()]);
+        assert!(info.added_modules.is_empty());
+        assert!(info.removed_modules.is_empty());
+
+        let (_, next_hmr_hash, current_hmr_hash) = compiler
+            .generate_hot_update_chunks(res, snapshot_hash, hmr_hash)
+            .unwrap();
+        let (_, changes) = compiler
+            .emit_dev_chunks(next_hmr_hash, current_hmr_hash)
+            .unwrap();
+        // only the entry chunk which contains a.ts, the lazy chunk is untouched
+        assert_eq!(changes.chunks.len(), 1, "{:?}", changes);
+        let chunk = &changes.chunks[0];
+        assert_eq!(chunk.old_filename, Some("index.js".to_string()));
+        assert_eq!(chunk.new_filename, Some("index.js".to_string()));
+        assert!(changes.assets.is_empty());
+
+        // nothing changed since the last emit
+        let (_, changes) = compiler
+            .emit_dev_chunks(next_hmr_hash, current_hmr_hash)
+            .unwrap();
+        assert_eq!(changes, OutputChanges::default());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rebuild_changed_and_removed_assets() {
+        setup_logger();
+        let root = std::env::temp_dir().join("mako-test-rebuild-changed-and-removed-assets");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        let index = root.join("index.ts");
+        let logo = root.join("logo.png");
+        fs::write(
+            &index,
+            "import logo from './logo.png';\nconsole.log(logo);\n",
+        )
+        .unwrap();
+        // larger than the inline limit
+        fs::write(&logo, vec![0u8; 10_001]).unwrap();
+
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root.clone(), Args { watch: true }, None).unwrap();
+        compiler.compile().unwrap();
+        let logo_path = logo.to_string_lossy().to_string();
+        let old_filename = compiler.context.assets_info.lock().unwrap()[&logo_path].clone();
+        let mut snapshot_hash = compiler.full_hash();
+        let mut hmr_hash = snapshot_hash;
+
+        let mut rebuild = |path: &PathBuf| {
+            let res = compiler.update(vec![path.clone()]).unwrap();
+            let (next_snapshot_hash, next_hmr_hash, current_hmr_hash) = compiler
+                .generate_hot_update_chunks(res, snapshot_hash, hmr_hash)
+                .unwrap();
+            snapshot_hash = next_snapshot_hash;
+            hmr_hash = next_hmr_hash;
+            let (_, changes) = compiler
+                .emit_dev_chunks(next_hmr_hash, current_hmr_hash)
+                .unwrap();
+            changes
+        };
+
+        fs::write(&logo, vec![1u8; 10_001]).unwrap();
+        let changes = rebuild(&logo);
+        let new_filename = compiler.context.assets_info.lock().unwrap()[&logo_path].clone();
+        assert_ne!(old_filename, new_filename);
+        assert_eq!(
+            changes.assets,
+            vec![ChangedAsset {
+                path: logo_path.clone(),
+                old_filename: Some(old_filename),
+                new_filename: Some(new_filename.clone()),
+            }]
+        );
+
+        fs::write(&index, "console.log(1);\n").unwrap();
+        let changes = rebuild(&index);
+        assert_eq!(
+            changes.assets,
+            vec![ChangedAsset {
+                path: logo_path,
+                old_filename: Some(new_filename),
+                new_filename: None,
+            }]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_const_enum_importers_rebuild() {
+        setup_logger();
+        let root = std::env::temp_dir().join("mako-test-const-enum-importers-rebuild");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("index.ts"),
+            "import { Kind } from './enums';\nconsole.log(Kind.A);\n",
+        )
+        .unwrap();
+        fs::write(root.join("enums.ts"), "export * from './kind.mts';\n").unwrap();
+        fs::write(root.join("kind.mts"), "export const enum Kind { A = 1 }\n").unwrap();
+
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root.clone(), Args { watch: true }, None).unwrap();
+        compiler.compile().unwrap();
+
+        let kind = root.join("kind.mts");
+        fs::write(&kind, "export const enum Kind { A = 2 }\n").unwrap();
+        let res = compiler.update(vec![kind.clone()]).unwrap();
+        let mut expected = vec![
+            root.join("enums.ts").to_string_lossy().to_string(),
+            root.join("index.ts").to_string_lossy().to_string(),
+            kind.to_string_lossy().to_string(),
+        ];
+        expected.sort();
+        assert_eq!(res.rebuild_info().modified_modules, expected);
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let index = module_graph
+            .get_module(&root.join("index.ts").into())
+            .unwrap();
+        let ast = index.info.as_ref().unwrap().ast.as_script().unwrap();
+        let code = ast.generate(compiler.context.clone()).unwrap().code;
+        assert!(code.contains("console.log(2)"), "{}", code);
+        drop(module_graph);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_pattern_entries_rebuild() {
+        setup_logger();
+        let root = std::env::temp_dir().join("mako-test-pattern-entries-rebuild");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        let add_page = |name: &str| {
+            let dir = root.join("src/pages").join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("index.ts"), format!("console.log('{}');\n", name)).unwrap();
+            dir.join("index.ts")
+        };
+        for name in ["a", "b", "c"] {
+            add_page(name);
+        }
+        fs::write(
+            root.join("mako.config.json"),
+            r#"{ "entry": { "pattern": "src/pages/*/index.ts", "name": "[dir]" }, "hmr": false }"#,
+        )
+        .unwrap();
+        let entry_chunks = |compiler: &Compiler| {
+            let chunk_graph = compiler.context.chunk_graph.read().unwrap();
+            let mut names = chunk_graph
+                .get_chunks()
+                .into_iter()
+                .filter_map(|chunk| match &chunk.chunk_type {
+                    ChunkType::Entry(_, name, _) => Some(name.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root.clone(), Args { watch: true }, None).unwrap();
+        compiler.compile().unwrap();
+        assert_eq!(entry_chunks(&compiler), vec!["a", "b", "c"]);
+
+        let d = add_page("d");
+        let snapshot_hash = compiler.full_hash();
+        let res = compiler.update(vec![d.clone()]).unwrap();
+        assert!(res.entries_changed);
+        assert!(res
+            .added
+            .iter()
+            .any(|id| id.id == d.canonicalize().unwrap().to_string_lossy()));
+        compiler
+            .generate_hot_update_chunks(res, snapshot_hash, snapshot_hash)
+            .unwrap();
+        assert_eq!(entry_chunks(&compiler), vec!["a", "b", "c", "d"]);
+
+        let a = root.join("src/pages/a/index.ts").canonicalize().unwrap();
+        fs::remove_dir_all(root.join("src/pages/a")).unwrap();
+        let snapshot_hash = compiler.full_hash();
+        let res = compiler.update(vec![a]).unwrap();
+        assert!(res.entries_changed);
+        compiler
+            .generate_hot_update_chunks(res, snapshot_hash, snapshot_hash)
+            .unwrap();
+        assert_eq!(entry_chunks(&compiler), vec!["b", "c", "d"]);
+
+        // the pattern is not expanded again for the modified files
+        let b = add_page("b");
+        let res = compiler.update(vec![b]).unwrap();
+        assert!(!res.entries_changed);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    // fails the transform of the modules with a `throw`, like a js plugin which throws
+    struct ThrowingTransformPlugin;
+
+    impl Plugin for ThrowingTransformPlugin {
+        fn name(&self) -> &str {
+            "throwing_transform"
+        }
+
+        fn load_transform(
+            &self,
+            content: &mut Content,
+            path: &str,
+            _is_entry: bool,
+            _context: &Arc<Context>,
+        ) -> anyhow::Result<Option<Content>> {
+            if let Content::Js(js) = content
+                && let Some(line) = js.content.lines().position(|l| l.contains("throw"))
+            {
+                return Err(anyhow::anyhow!(PluginError::new(
+                    self.name(),
+                    "transform",
+                    path,
+                    format!("Unexpected throw ({}:0)", line + 1),
+                    Some("Error: Unexpected throw\n    at transform (plugin.js:1:1)".to_string()),
+                    Some(js.content.as_str()),
+                )));
+            }
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_plugin_error_overlay() {
+        setup_logger();
+        let root = std::env::temp_dir().join("mako-test-plugin-error-overlay");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("index.ts"),
+            "import { a } from './a';\nconsole.log(a);\n",
+        )
+        .unwrap();
+        let a = root.join("a.ts");
+        fs::write(&a, "export const a = 1;\n").unwrap();
+
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(
+            config,
+            root.clone(),
+            Args { watch: true },
+            Some(vec![Arc::new(ThrowingTransformPlugin)]),
+        )
+        .unwrap();
+        compiler.compile().unwrap();
+        let compiler = Arc::new(compiler);
+
+        let (txws, mut rxws) = broadcast::channel(16);
+        let mut snapshot_hash = Box::new(compiler.full_hash());
+        let mut hmr_hash = Box::new(*snapshot_hash);
+        let mut shown_error = false;
+        let mut rebuild = |content: &str| {
+            fs::write(&a, content).unwrap();
+            DevServer::rebuild(
+                vec![WatchChange::Update(a.clone())],
+                compiler.clone(),
+                txws.clone(),
+                &mut snapshot_hash,
+                &mut hmr_hash,
+                &mut shown_error,
+            )
+            .unwrap();
+            serde_json::from_str::<serde_json::Value>(&rxws.try_recv().unwrap().to_json()).unwrap()
+        };
+
+        let msg = rebuild("export const a = 2;\nthrow a;\n");
+        assert_eq!(msg["type"], "error");
+        let error = &msg["error"];
+        assert_eq!(error["plugin"], "throwing_transform");
+        assert_eq!(error["hook"], "transform");
+        assert_eq!(error["path"], a.to_string_lossy().as_ref());
+        assert_eq!(error["message"], "Unexpected throw (2:0)");
+        assert!(error["stack"].as_str().unwrap().contains("plugin.js:1:1"));
+        assert_eq!(
+            error["snippet"],
+            "  1 | export const a = 2;\n> 2 | throw a;\n    | ^"
+        );
+        // the previous version is kept
+        assert!(compiler
+            .context
+            .module_graph
+            .read()
+            .unwrap()
+            .has_module(&a.clone().into()));
+
+        // fixed, the hash clears the error
+        let msg = rebuild("export const a = 3;\n");
+        assert!(msg["type"].is_null());
+        assert!(msg["hash"].is_string());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
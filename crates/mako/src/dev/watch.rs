@@ -1,18 +1,24 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{self, Ok};
 use colored::Colorize;
+use notify::event::{ModifyKind, RenameMode};
 use notify::{self, EventKind, Watcher as NotifyWatcher};
-use notify_debouncer_full::DebouncedEvent;
+use notify_debouncer_full::{DebounceEventResult, DebouncedEvent};
 use regex::Regex;
 use tracing::debug;
 
 use crate::compiler::Compiler;
 use crate::resolve::ResolverResource;
 
+// the max number of debounce windows the event batches are coalesced for
+const COALESCE_WINDOWS: u32 = 5;
+
 pub struct Watcher<'a> {
     pub watcher: &'a mut dyn NotifyWatcher,
     pub root: &'a PathBuf,
@@ -194,25 +200,370 @@ impl<'a> Watcher<'a> {
         ignore_list.iter().any(|ignored| path.ends_with(ignored))
     }
 
-    pub fn normalize_events(events: Vec<DebouncedEvent>) -> Vec<PathBuf> {
-        let mut paths = vec![];
-        let mut create_paths = HashMap::new();
+    /**
+     * Collect the event batches which arrive within the debounce window after each
+     * other, so that a file saved several times in a row, e.g. by an editor with
+     * format on save, gets the hooks called and is rebuilt once. The batches are
+     * collected for at most COALESCE_WINDOWS windows, so that files written
+     * continuously, e.g. logs under the root, don't hold the rebuild forever.
+     */
+    pub fn coalesce_events(
+        mut events: Vec<DebouncedEvent>,
+        rx: &Receiver<DebounceEventResult>,
+        window: Duration,
+    ) -> Vec<DebouncedEvent> {
+        let deadline = Instant::now() + window * COALESCE_WINDOWS;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match rx.recv_timeout(window.min(remaining)) {
+                Result::Ok(Result::Ok(more)) => events.extend(more),
+                Result::Ok(Err(e)) => eprintln!("Error watching files: {:?}", e),
+                Err(_) => break,
+            }
+        }
+        events
+    }
+
+    /**
+     * Coalesce the events of one debounce window into one change per file.
+     *
+     * - create + update is a create, update + delete is a delete
+     * - delete + create is an update, e.g. editors which save by replacing the file
+     * - create + delete is dropped, e.g. temporary files
+     * - renames are stitched by the debouncer with the file id (inode) or by
+     *   the event tracker, otherwise a delete and a create with the same file
+     *   name in the same window are treated as a rename
+     */
+    pub fn normalize_events(events: Vec<DebouncedEvent>) -> Vec<WatchChange> {
+        let mut states: BTreeMap<PathBuf, ChangeKind> = BTreeMap::new();
+        let mut renames: Vec<(PathBuf, PathBuf)> = vec![];
+        let mut rename_froms: HashMap<usize, PathBuf> = HashMap::new();
+
+        let record = |states: &mut BTreeMap<PathBuf, ChangeKind>, path: &Path, kind| {
+            match ChangeKind::merge(states.get(path).copied(), kind) {
+                Some(kind) => states.insert(path.to_path_buf(), kind),
+                None => states.remove(path),
+            };
+        };
+
         events.iter().for_each(|debounced_event| {
-            let kind = &debounced_event.event.kind;
-            debounced_event.event.paths.iter().for_each(|path| {
-                if Self::should_ignore_event(path, kind) {
-                    return;
+            let event = &debounced_event.event;
+            let paths = event
+                .paths
+                .iter()
+                .filter(|path| !Self::should_ignore_event(path, &event.kind))
+                .collect::<Vec<_>>();
+            match &event.kind {
+                EventKind::Create(_) => paths
+                    .iter()
+                    .for_each(|path| record(&mut states, path, ChangeKind::Create)),
+                EventKind::Remove(_) => paths
+                    .iter()
+                    .for_each(|path| record(&mut states, path, ChangeKind::Delete)),
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                    renames.push((paths[0].clone(), paths[1].clone()));
+                }
+                EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                    paths.iter().for_each(|path| match event.tracker() {
+                        Some(tracker) => {
+                            rename_froms.insert(tracker, path.to_path_buf());
+                        }
+                        None => record(&mut states, path, ChangeKind::Delete),
+                    })
                 }
-                paths.push(path.clone());
-                if matches!(debounced_event.event.kind, EventKind::Create(_)) {
-                    create_paths.insert(path.clone(), true);
-                } else {
-                    create_paths.remove(path);
+                EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                    paths.iter().for_each(|path| {
+                        match event.tracker().and_then(|t| rename_froms.remove(&t)) {
+                            Some(from) => renames.push((from, path.to_path_buf())),
+                            None => record(&mut states, path, ChangeKind::Create),
+                        }
+                    })
                 }
-            });
+                // the platform does not tell which side of the rename it is
+                EventKind::Modify(ModifyKind::Name(_)) => paths.iter().for_each(|path| {
+                    let kind = if path.exists() {
+                        ChangeKind::Create
+                    } else {
+                        ChangeKind::Delete
+                    };
+                    record(&mut states, path, kind)
+                }),
+                _ => paths
+                    .iter()
+                    .for_each(|path| record(&mut states, path, ChangeKind::Update)),
+            }
         });
-        paths.sort();
-        paths.dedup();
-        paths
+        // the other side of the rename is out of the watched dirs
+        rename_froms
+            .into_values()
+            .for_each(|path| record(&mut states, &path, ChangeKind::Delete));
+
+        let mut changes = vec![];
+        for (from, to) in renames {
+            if matches!(states.get(&from), Some(ChangeKind::Create)) {
+                // renamed from a file created in the same window, e.g. atomic writes
+                states.remove(&from);
+                record(&mut states, &to, ChangeKind::Update);
+            } else {
+                states.remove(&from);
+                states.remove(&to);
+                changes.push(WatchChange::Rename { from, to });
+            }
+        }
+
+        // same file name heuristic, only when the pair is unambiguous
+        let file_name_of = |kind: ChangeKind| {
+            let mut map: HashMap<OsString, Vec<PathBuf>> = HashMap::new();
+            states
+                .iter()
+                .filter(|(_, k)| **k == kind)
+                .for_each(|(path, _)| {
+                    if let Some(name) = path.file_name() {
+                        map.entry(name.to_os_string())
+                            .or_default()
+                            .push(path.clone());
+                    }
+                });
+            map
+        };
+        let deleted = file_name_of(ChangeKind::Delete);
+        let created = file_name_of(ChangeKind::Create);
+        for (name, froms) in deleted {
+            match created.get(&name) {
+                Some(tos) if froms.len() == 1 && tos.len() == 1 => {
+                    states.remove(&froms[0]);
+                    states.remove(&tos[0]);
+                    changes.push(WatchChange::Rename {
+                        from: froms[0].clone(),
+                        to: tos[0].clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        changes.extend(states.into_iter().map(|(path, kind)| match kind {
+            ChangeKind::Create => WatchChange::Create(path),
+            ChangeKind::Update => WatchChange::Update(path),
+            ChangeKind::Delete => WatchChange::Delete(path),
+        }));
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeKind {
+    Create,
+    Update,
+    Delete,
+}
+
+impl ChangeKind {
+    fn merge(prev: Option<ChangeKind>, next: ChangeKind) -> Option<ChangeKind> {
+        match (prev, next) {
+            (Some(ChangeKind::Create), ChangeKind::Update) => Some(ChangeKind::Create),
+            (Some(ChangeKind::Create), ChangeKind::Delete) => None,
+            (Some(ChangeKind::Delete), ChangeKind::Create) => Some(ChangeKind::Update),
+            (Some(ChangeKind::Delete), ChangeKind::Update) => Some(ChangeKind::Update),
+            (_, next) => Some(next),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchChange {
+    Create(PathBuf),
+    Update(PathBuf),
+    Delete(PathBuf),
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+impl WatchChange {
+    pub fn event(&self) -> &'static str {
+        match self {
+            WatchChange::Create(_) => "create",
+            WatchChange::Update(_) => "update",
+            WatchChange::Delete(_) => "delete",
+            WatchChange::Rename { .. } => "rename",
+        }
+    }
+
+    // the new path for renames
+    pub fn path(&self) -> &PathBuf {
+        match self {
+            WatchChange::Create(path) | WatchChange::Update(path) | WatchChange::Delete(path) => {
+                path
+            }
+            WatchChange::Rename { to, .. } => to,
+        }
+    }
+
+    // the paths to rebuild, a rename is a delete of `from` and a create of `to`
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            WatchChange::Rename { from, to } => vec![from.clone(), to.clone()],
+            _ => vec![self.path().clone()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use notify::event::{CreateKind, DataChange, ModifyKind, RemoveKind, RenameMode};
+    use notify::{Event, EventKind};
+    use notify_debouncer_full::DebouncedEvent;
+
+    use super::{WatchChange, Watcher};
+
+    fn event(kind: EventKind, paths: &[&str]) -> DebouncedEvent {
+        let event = paths
+            .iter()
+            .fold(Event::new(kind), |event, path| event.add_path(path.into()));
+        DebouncedEvent::new(event, Instant::now())
+    }
+
+    fn create(path: &str) -> DebouncedEvent {
+        event(EventKind::Create(CreateKind::File), &[path])
+    }
+
+    fn update(path: &str) -> DebouncedEvent {
+        event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &[path],
+        )
+    }
+
+    fn delete(path: &str) -> DebouncedEvent {
+        event(EventKind::Remove(RemoveKind::File), &[path])
+    }
+
+    fn path(p: &str) -> PathBuf {
+        PathBuf::from(p)
+    }
+
+    #[test]
+    fn test_coalesce_events_is_capped() {
+        // a file written every 5ms for one second
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for _ in 0..200 {
+                if tx.send(Ok(vec![update("/src/a.log")])).is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+        });
+
+        let start = Instant::now();
+        let events = Watcher::coalesce_events(vec![], &rx, Duration::from_millis(20));
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert!(!events.is_empty() && events.len() < 200, "{}", events.len());
+    }
+
+    #[test]
+    fn test_normalize_create_update_delete() {
+        let changes = Watcher::normalize_events(vec![
+            create("/root/src/a.ts"),
+            update("/root/src/a.ts"),
+            update("/root/src/b.ts"),
+            update("/root/src/b.ts"),
+            update("/root/src/c.ts"),
+            delete("/root/src/c.ts"),
+        ]);
+        assert_eq!(
+            changes,
+            vec![
+                WatchChange::Create(path("/root/src/a.ts")),
+                WatchChange::Update(path("/root/src/b.ts")),
+                WatchChange::Delete(path("/root/src/c.ts")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_transient_and_replaced_files() {
+        let changes = Watcher::normalize_events(vec![
+            // temporary file
+            create("/root/src/a.ts.tmp"),
+            delete("/root/src/a.ts.tmp"),
+            // replaced by the editor on save
+            delete("/root/src/b.ts"),
+            create("/root/src/b.ts"),
+            update("/root/src/.index.ts.swp"),
+        ]);
+        assert_eq!(changes, vec![WatchChange::Update(path("/root/src/b.ts"))]);
+    }
+
+    #[test]
+    fn test_normalize_rename() {
+        let both = Watcher::normalize_events(vec![event(
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+            &["/root/src/a.ts", "/root/src/b.ts"],
+        )]);
+        let tracked = Watcher::normalize_events(vec![
+            DebouncedEvent::new(
+                Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::From)))
+                    .add_path(path("/root/src/a.ts"))
+                    .set_tracker(1),
+                Instant::now(),
+            ),
+            DebouncedEvent::new(
+                Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::To)))
+                    .add_path(path("/root/src/b.ts"))
+                    .set_tracker(1),
+                Instant::now(),
+            ),
+        ]);
+        let expected = vec![WatchChange::Rename {
+            from: path("/root/src/a.ts"),
+            to: path("/root/src/b.ts"),
+        }];
+        assert_eq!(both, expected);
+        assert_eq!(tracked, expected);
+    }
+
+    #[test]
+    fn test_normalize_rename_by_file_name() {
+        let changes = Watcher::normalize_events(vec![
+            delete("/root/src/a.ts"),
+            create("/root/src/utils/a.ts"),
+            // ambiguous, kept as is
+            delete("/root/src/b.ts"),
+            create("/root/src/x/b.ts"),
+            create("/root/src/y/b.ts"),
+        ]);
+        assert_eq!(
+            changes,
+            vec![
+                WatchChange::Delete(path("/root/src/b.ts")),
+                WatchChange::Rename {
+                    from: path("/root/src/a.ts"),
+                    to: path("/root/src/utils/a.ts"),
+                },
+                WatchChange::Create(path("/root/src/x/b.ts")),
+                WatchChange::Create(path("/root/src/y/b.ts")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_rename_of_created_file() {
+        let changes = Watcher::normalize_events(vec![
+            create("/root/src/a.ts.tmp"),
+            event(
+                EventKind::Modify(ModifyKind::Name(RenameMode::Both)),
+                &["/root/src/a.ts.tmp", "/root/src/a.ts"],
+            ),
+        ]);
+        assert_eq!(changes, vec![WatchChange::Update(path("/root/src/a.ts"))]);
     }
 }
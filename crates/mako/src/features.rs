@@ -1,2 +1,3 @@
+pub mod env_file;
 pub mod node;
 pub mod rsc;
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::config::Config;
+
+pub struct EnvFile {}
+
+impl EnvFile {
+    // load variables of `envFile` into `define` as `process.env.*` and `import.meta.env.*`,
+    // later files override earlier ones, and keys already in `define` are kept
+    pub fn modify_config(config: &mut Config, root: &Path) -> Result<()> {
+        let Some(env_files) = &config.env_file else {
+            return Ok(());
+        };
+
+        let mut envs = BTreeMap::new();
+        for env_file in env_files {
+            let path = root.join(env_file);
+            // e.g. .env.local is optional
+            if !path.exists() {
+                continue;
+            }
+            let iter = dotenvy::from_path_iter(&path)
+                .map_err(|e| anyhow!("failed to read env file {}: {}", path.display(), e))?;
+            for item in iter {
+                let (key, value) = item
+                    .map_err(|e| anyhow!("failed to parse env file {}: {}", path.display(), e))?;
+                envs.insert(key, value);
+            }
+        }
+
+        for (key, value) in envs {
+            // define values are expressions, so strings need to be quoted
+            let value = Value::String(serde_json::to_string(&value)?);
+            for prefix in ["process.env", "import.meta.env"] {
+                config
+                    .define
+                    .entry(format!("{}.{}", prefix, key))
+                    .or_insert_with(|| value.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_env_file() {
+        let compiler = setup_compiler("test/build/env-file", false);
+        compiler.compile().unwrap();
+        let define = &compiler.context.config.define;
+        assert_eq!(
+            define.get("process.env.REACT_APP_API_URL").unwrap(),
+            r#""https://api.example.com""#
+        );
+        // explicit define wins
+        assert_eq!(
+            define.get("process.env.REACT_APP_NAME").unwrap(),
+            r#""from-define""#
+        );
+
+        let content =
+            fs::read_to_string(compiler.context.config.output.path.join("index.js")).unwrap();
+        assert!(content.contains(r#"console.log("https://api.example.com");"#));
+        assert!(content.contains(r#"console.log("https://api.example.com/v2");"#));
+        assert!(content.contains(r#"console.log("from-define");"#));
+        assert!(!content.contains("http://localhost"));
+        assert!(!content.contains("REACT_APP_"));
+    }
+}
@@ -19,15 +19,7 @@ impl Node {
             // set default node target
             let target = config.targets.get("node").unwrap_or(&14.0);
             config.targets = HashMap::from([("node".into(), *target)]);
-            // ignore all built-in node modules
-            config.ignores.push(format!(
-                "^({})(/.+|$)",
-                Self::get_all_builtin_modules_besides_node_prefixed_only().join("|")
-            ));
-            config.ignores.push(format!(
-                "^node:({})(/.+|$)",
-                Self::get_all_builtin_modules().join("|")
-            ));
+            // built-in node modules are externalized when resolving, see is_builtin_module
             // polifyll __dirname & __filename is supported with MockFilenameAndDirname Visitor
         } else {
             // polyfill __dirname & __filename for browser
@@ -55,6 +47,10 @@ impl Node {
                     ExternalConfig::Basic("".to_string()),
                 );
             }
+            // the web streams are not the streams of stream-browserify
+            config
+                .externals
+                .insert("stream/web".into(), ExternalConfig::Basic("".to_string()));
             // polyfill identifiers
             config
                 .providers
@@ -69,6 +65,66 @@ impl Node {
         }
     }
 
+    /// Whether the source is a built-in node module or its subpath, e.g. `fs`,
+    /// `fs/promises`, `node:fs` and `node:sqlite`.
+    pub fn is_builtin_module(source: &str) -> bool {
+        Self::split_builtin_module(source).is_some()
+    }
+
+    /// Split a built-in node module into the module name and the subpath, e.g. both
+    /// `stream/web` and `node:stream/web` are `("stream", Some("web"))`.
+    pub fn split_builtin_module(source: &str) -> Option<(&str, Option<&str>)> {
+        let (name, node_prefixed) = match source.strip_prefix("node:") {
+            Some(name) => (name, true),
+            None => (source, false),
+        };
+        let (name, subpath) = match name.split_once('/') {
+            Some((name, subpath)) => (name, Some(subpath)),
+            None => (name, None),
+        };
+        let modules = if node_prefixed {
+            Self::get_all_builtin_modules()
+        } else {
+            Self::get_all_builtin_modules_besides_node_prefixed_only()
+        };
+        modules.iter().any(|m| m == name).then_some((name, subpath))
+    }
+
+    /// The source to resolve in browser builds, the `node:` prefixed imports and the known
+    /// subpaths of a polyfilled built-in node module share the polyfill of the module, e.g.
+    /// `node:util`, `util/types` and `node:util/types` are all resolved as `util`. Other
+    /// subpaths are kept since they may be files of the npm package, e.g. `process/browser`.
+    pub fn polyfill_source<'a>(source: &'a str, config: &Config) -> &'a str {
+        let Some((name, subpath)) = Self::split_builtin_module(source) else {
+            return source;
+        };
+        // the polyfill may be replaced or removed by the user config
+        let polyfilled = config.resolve.alias.iter().any(|(key, _)| key == name)
+            || config.externals.contains_key(name);
+        if !polyfilled {
+            return source;
+        }
+        match subpath {
+            None => name,
+            Some(subpath) if Self::get_builtin_subpaths().contains(&(name, subpath)) => name,
+            Some(_) => source.strip_prefix("node:").unwrap_or(source),
+        }
+    }
+
+    // the subpaths which are covered by the polyfill of the module
+    fn get_builtin_subpaths() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("assert", "strict"),
+            ("dns", "promises"),
+            ("fs", "promises"),
+            ("inspector", "promises"),
+            ("path", "posix"),
+            ("path", "win32"),
+            ("readline", "promises"),
+            ("util", "types"),
+        ]
+    }
+
     fn get_polyfill_modules() -> Vec<String> {
         vec![
             "assert",
@@ -176,3 +232,67 @@ impl VisitMut for MockFilenameAndDirname {
         expr.visit_mut_children_with(self);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Node;
+    use crate::config::Config;
+
+    #[test]
+    fn test_is_builtin_module() {
+        assert!(Node::is_builtin_module("fs"));
+        assert!(Node::is_builtin_module("fs/promises"));
+        assert!(Node::is_builtin_module("node:fs"));
+        assert!(Node::is_builtin_module("node:sqlite"));
+        assert!(!Node::is_builtin_module("sqlite"));
+        assert!(!Node::is_builtin_module("fs-extra"));
+        assert!(!Node::is_builtin_module("./fs"));
+    }
+
+    #[test]
+    fn test_split_builtin_module() {
+        assert_eq!(
+            Node::split_builtin_module("stream/web"),
+            Some(("stream", Some("web")))
+        );
+        assert_eq!(
+            Node::split_builtin_module("node:stream/web"),
+            Some(("stream", Some("web")))
+        );
+        assert_eq!(
+            Node::split_builtin_module("node:util"),
+            Some(("util", None))
+        );
+        assert_eq!(Node::split_builtin_module("sqlite/foo"), None);
+        assert_eq!(Node::split_builtin_module("utils/types"), None);
+    }
+
+    #[test]
+    fn test_polyfill_source() {
+        // polyfilled for the default browser platform
+        let mut config = Config::new(Path::new("."), None, None).unwrap();
+        let polyfill_source = |source| Node::polyfill_source(source, &config);
+        for source in ["util", "node:util", "util/types", "node:util/types"] {
+            assert_eq!(polyfill_source(source), "util", "{}", source);
+        }
+        assert_eq!(polyfill_source("node:fs/promises"), "fs");
+        assert_eq!(polyfill_source("path/posix"), "path");
+        // files of the npm packages
+        assert_eq!(
+            polyfill_source("util/support/isBuffer"),
+            "util/support/isBuffer"
+        );
+        assert_eq!(polyfill_source("process/browser"), "process/browser");
+        // external as an empty module
+        assert_eq!(polyfill_source("node:stream/web"), "stream/web");
+        assert!(config.externals.contains_key("stream/web"));
+        assert_eq!(polyfill_source("node:sqlite"), "node:sqlite");
+        assert_eq!(polyfill_source("lodash/get"), "lodash/get");
+
+        // not polyfilled when the alias is removed
+        config.resolve.alias.retain(|(key, _)| key != "util");
+        assert_eq!(Node::polyfill_source("node:util", &config), "node:util");
+    }
+}
@@ -2,16 +2,20 @@ pub(crate) mod analyze;
 pub(crate) mod chunk;
 pub(crate) mod chunk_graph;
 pub(crate) mod chunk_pot;
+pub(crate) mod css_dedupe;
 pub(crate) mod generate_chunks;
 pub(crate) mod group_chunk;
 pub(crate) mod hmr;
+pub(crate) mod link_hints;
 pub(crate) mod minify;
+pub(crate) mod minify_cache;
 pub(crate) mod optimize_chunk;
 pub(crate) mod runtime;
 pub(crate) mod swc_helpers;
 pub(crate) mod transform;
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::hash::Hasher;
 use std::ops::DerefMut;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -23,12 +27,15 @@ use indexmap::IndexSet;
 use rayon::prelude::*;
 use serde::Serialize;
 use tracing::debug;
+use twox_hash::XxHash64;
 
-use crate::compiler::{Compiler, Context};
-use crate::config::{DevtoolConfig, OutputMode, TreeShakingStrategy};
+use crate::compiler::{BuildPhase, Compiler, Context};
+use crate::config::{DevtoolConfig, OutputMode, TreeShakingGranularity, TreeShakingStrategy};
 use crate::dev::update::UpdateResult;
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::generate::link_hints::resolve_link_hints;
 use crate::module::{Dependency, ModuleId};
+use crate::plugin::{ChangedAsset, ChangedChunkFile, OutputChanges, OutputChunk};
 use crate::plugins::bundless_compiler::BundlessCompiler;
 use crate::stats::StatsJsonMap;
 use crate::utils::base64_encode;
@@ -40,6 +47,14 @@ struct ChunksUrlMap {
     css: HashMap<String, String>,
 }
 
+#[derive(Default)]
+pub struct OutputSnapshot {
+    // (filename, content hash) by chunk id and file type
+    chunk_files: HashMap<(String, &'static str), (String, u64)>,
+    // output path by source file
+    assets: HashMap<String, String>,
+}
+
 impl Compiler {
     fn generate_bundless(&self) -> Result<StatsJsonMap> {
         let bundless_compiler = BundlessCompiler::new(self.context.clone());
@@ -84,17 +99,25 @@ impl Compiler {
 
         let async_dep_map = self.mark_async();
 
-        // Disable tree shaking in watch mode temporarily
+        let tree_shaking_disabled = self
+            .context
+            .config
+            .optimization
+            .as_ref()
+            .is_some_and(|o| o.tree_shaking == Some(TreeShakingGranularity::Disabled));
+        // Disable tree shaking in watch mode with hmr temporarily
         // ref: https://github.com/umijs/mako/issues/396
-        if !self.context.args.watch {
+        if (!self.context.args.watch || self.context.rebuilds_fully()) && !tree_shaking_disabled {
             match self.context.config._tree_shaking {
                 Some(TreeShakingStrategy::Basic) => {
                     let mut module_graph = self.context.module_graph.write().unwrap();
 
                     crate::mako_profile_scope!("tree shake");
-                    self.context
-                        .plugin_driver
-                        .optimize_module_graph(module_graph.deref_mut(), &self.context)?;
+                    self.context.record_phase(BuildPhase::TreeShake, || {
+                        self.context
+                            .plugin_driver
+                            .optimize_module_graph(module_graph.deref_mut(), &self.context)
+                    })?;
                     let t_tree_shaking = t_tree_shaking.elapsed();
                     debug!("basic optimize in {}ms.", t_tree_shaking.as_millis());
                 }
@@ -106,7 +129,9 @@ impl Compiler {
         }
         let t_tree_shaking = t_tree_shaking.elapsed();
 
-        if self.context.config.output.mode == OutputMode::Bundless {
+        if self.context.config.output.mode == OutputMode::Bundless
+            || self.context.config.output.is_preserve_modules()
+        {
             let stats = self.generate_bundless()?;
             return Ok(stats);
         }
@@ -122,6 +147,16 @@ impl Compiler {
             .before_optimize_chunk(&self.context)?;
 
         self.optimize_chunk();
+        // like tree shaking, it's not applied in watch mode to keep the hot updates simple
+        let css_dedupe = self
+            .context
+            .config
+            .optimization
+            .as_ref()
+            .is_some_and(|o| o.css_dedupe == Some(true));
+        if css_dedupe && !self.context.args.watch {
+            self.dedupe_css();
+        }
         let t_optimize_chunks = t_optimize_chunks.elapsed();
 
         {
@@ -155,18 +190,8 @@ impl Compiler {
         if config.emit_assets {
             let t_write_assets = Instant::now();
             debug!("write assets");
-            {
-                let assets_info = &(*self.context.assets_info.lock().unwrap());
-                for (k, v) in assets_info {
-                    let asset_path = &self.context.root.join(k);
-                    let asset_output_path = &config.output.path.join(v);
-                    if asset_path.exists() {
-                        fs::copy(asset_path, asset_output_path)?;
-                    } else {
-                        return Err(anyhow!("asset not found: {}", asset_path.display()));
-                    }
-                }
-            }
+            self.context.written_assets.lock().unwrap().clear();
+            self.context.write_assets()?;
             let t_write_assets = t_write_assets.elapsed();
             debug!("  - write assets: {}ms", t_write_assets.as_millis());
         }
@@ -182,6 +207,11 @@ impl Compiler {
         // print stats
         if !self.context.args.watch {
             self.print_stats();
+            self.print_oversized_chunks(&stats);
+            if let Some(stats_config) = &self.context.config.stats {
+                self.print_duplicate_modules();
+                self.print_duplicate_packages(&stats, stats_config.duplicate_package_threshold);
+            }
         }
 
         if self.context.config.analyze.is_some() {
@@ -209,7 +239,8 @@ impl Compiler {
         // generate chunks
         let t_generate_chunks = Instant::now();
         debug!("generate chunks");
-        let chunk_files = self.generate_chunk_files(full_hash)?;
+        let chunk_files = self.generate_bundle(self.generate_chunk_files(full_hash)?)?;
+        resolve_link_hints(&self.context, &chunk_files);
         self.context
             .plugin_driver
             .after_generate_chunk_files(&chunk_files, &self.context)?;
@@ -217,6 +248,7 @@ impl Compiler {
         let t_generate_chunks = t_generate_chunks.elapsed();
 
         let t_ast_to_code_and_write = if self.context.args.watch {
+            self.diff_output(&chunk_files);
             self.generate_chunk_mem_file(&chunk_files)?
         } else {
             self.generate_chunk_disk_file(&chunk_files)?
@@ -225,6 +257,77 @@ impl Compiler {
         Ok((t_generate_chunks, t_ast_to_code_and_write))
     }
 
+    // the chunk files are replaced by the output chunks which the generate_bundle hooks leave,
+    // the chunks from a chunk file keep its chunk id, and they can't be renamed since the
+    // runtime and the html refer to them by the names
+    fn generate_bundle(&self, mut chunk_files: Vec<ChunkFile>) -> Result<Vec<ChunkFile>> {
+        if !self.context.plugin_driver.has_generate_bundle() {
+            return Ok(chunk_files);
+        }
+
+        // the content is moved to the output chunks, the hash tells whether it's modified
+        let content_hashes = chunk_files
+            .iter()
+            .map(|file| content_raw_hash(&file.content))
+            .collect::<Vec<_>>();
+        let mut chunks = chunk_files
+            .iter_mut()
+            .enumerate()
+            .map(|(index, file)| OutputChunk {
+                origin: Some(index),
+                ..OutputChunk::new(
+                    file.disk_name(),
+                    std::mem::take(&mut file.content),
+                    file.source_map.take(),
+                    file.file_type.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.context
+            .plugin_driver
+            .generate_bundle(&mut chunks, &self.context)?;
+
+        let mut chunk_files = chunk_files.into_iter().map(Some).collect::<Vec<_>>();
+        chunks
+            .into_iter()
+            .map(|chunk| {
+                let origin = chunk.origin.and_then(|index| {
+                    chunk_files
+                        .get_mut(index)
+                        .and_then(|file| file.take())
+                        .map(|file| (file, content_hashes[index]))
+                });
+                let Some((mut file, content_hash)) = origin else {
+                    return Ok(ChunkFile {
+                        raw_hash: content_raw_hash(&chunk.code),
+                        content: chunk.code,
+                        source_map: chunk.map,
+                        hash: None,
+                        chunk_name: chunk.file_name.clone(),
+                        chunk_id: chunk.file_name.clone(),
+                        file_name: chunk.file_name,
+                        file_type: chunk.type_,
+                        file_name_template: None,
+                    });
+                };
+                if file.disk_name() != chunk.file_name {
+                    return Err(anyhow!(
+                        "Chunk {} can't be renamed to {} in generate_bundle, since the runtime and the html refer to it by its name",
+                        file.disk_name(),
+                        chunk.file_name
+                    ));
+                }
+                if content_raw_hash(&chunk.code) != content_hash {
+                    file.raw_hash = content_raw_hash(&chunk.code);
+                }
+                file.content = chunk.code;
+                file.source_map = chunk.map;
+                file.file_type = chunk.type_;
+                Ok(file)
+            })
+            .collect()
+    }
+
     fn generate_chunk_disk_file(&self, chunk_files: &Vec<ChunkFile>) -> Result<Duration> {
         let t_ast_to_code_and_write = Instant::now();
         debug!("ast to code and write");
@@ -259,7 +362,7 @@ impl Compiler {
         &self,
         current_hmr_hash: u64,
         last_hmr_hash: u64,
-    ) -> Result<StatsJsonMap> {
+    ) -> Result<(StatsJsonMap, OutputChanges)> {
         crate::mako_profile_function!("emit_dev_chunks");
 
         debug!("generate(hmr-fullbuild)");
@@ -283,7 +386,7 @@ impl Compiler {
 
         // generate chunks
         let t_generate_chunks = Instant::now();
-        let chunk_files = self.generate_chunk_files(current_hmr_hash)?;
+        let chunk_files = self.generate_bundle(self.generate_chunk_files(current_hmr_hash)?)?;
 
         if config.hmr.is_some() {
             let mut chunk_id_url_map = ChunksUrlMap {
@@ -315,6 +418,7 @@ impl Compiler {
             .after_generate_chunk_files(&chunk_files, &self.context)?;
 
         let t_generate_chunks = t_generate_chunks.elapsed();
+        let output_changes = self.diff_output(&chunk_files);
 
         // ast to code and sourcemap, then write
         debug!("ast to code and write");
@@ -323,18 +427,8 @@ impl Compiler {
         // write assets
         let t_write_assets = Instant::now();
         debug!("write assets");
-        {
-            let assets_info = &(*self.context.assets_info.lock().unwrap());
-            for (k, v) in assets_info {
-                let asset_path = &self.context.root.join(k);
-                let asset_output_path = &config.output.path.join(v);
-                if asset_path.exists() {
-                    fs::copy(asset_path, asset_output_path)?;
-                } else {
-                    panic!("asset not found: {}", asset_path.display());
-                }
-            }
-        }
+        self.context.written_assets.lock().unwrap().clear();
+        self.context.write_assets()?;
         let t_write_assets = t_write_assets.elapsed();
 
         let stats = self.create_stats_info();
@@ -352,7 +446,112 @@ impl Compiler {
         );
         debug!("  - write assets: {}ms", t_write_assets.as_millis());
 
-        Ok(stats)
+        Ok((stats, output_changes))
+    }
+
+    // the chunk files and the assets added, removed or changed since the last build in watch
+    // mode
+    fn diff_output(&self, chunk_files: &[ChunkFile]) -> OutputChanges {
+        let chunk_file_key = |file: &ChunkFile| {
+            let file_type = match file.file_type {
+                ChunkFileType::JS => "js",
+                ChunkFileType::Css => "css",
+            };
+            (file.chunk_id.clone(), file_type)
+        };
+        let chunk_files = chunk_files
+            .iter()
+            .map(|file| (chunk_file_key(file), (file.disk_name(), file.raw_hash)))
+            .collect::<HashMap<_, _>>();
+        // the assets of the modules which are no longer in any chunk are removed, the module
+        // graph and `assets_info` are not shrunk in watch mode
+        let module_path = |module_id: &ModuleId| {
+            module_id
+                .id
+                .split_once('?')
+                .map_or(module_id.id.clone(), |(path, _)| path.to_string())
+        };
+        let module_paths = self
+            .context
+            .module_graph
+            .read()
+            .unwrap()
+            .modules()
+            .iter()
+            .map(|module| module_path(&module.id))
+            .collect::<HashSet<_>>();
+        let chunk_module_paths = self
+            .context
+            .chunk_graph
+            .read()
+            .unwrap()
+            .get_chunks()
+            .iter()
+            .flat_map(|chunk| chunk.modules.iter().map(module_path))
+            .collect::<HashSet<_>>();
+        let assets = self
+            .context
+            .assets_info
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| !module_paths.contains(*path) || chunk_module_paths.contains(*path))
+            .map(|(path, output_path)| (path.clone(), output_path.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let mut snapshot = self.context.output_snapshot.lock().unwrap();
+        let mut changes = OutputChanges::default();
+        for ((chunk_id, file_type), (filename, hash)) in &chunk_files {
+            match snapshot.chunk_files.get(&(chunk_id.clone(), file_type)) {
+                Some((old_filename, old_hash)) if old_filename == filename && old_hash == hash => {}
+                old => changes.chunks.push(ChangedChunkFile {
+                    chunk_id: chunk_id.clone(),
+                    old_filename: old.map(|(old_filename, _)| old_filename.clone()),
+                    new_filename: Some(filename.clone()),
+                }),
+            }
+        }
+        for ((chunk_id, file_type), (old_filename, _)) in &snapshot.chunk_files {
+            if !chunk_files.contains_key(&(chunk_id.clone(), file_type)) {
+                changes.chunks.push(ChangedChunkFile {
+                    chunk_id: chunk_id.clone(),
+                    old_filename: Some(old_filename.clone()),
+                    new_filename: None,
+                });
+            }
+        }
+        changes.chunks.sort_by(|a, b| {
+            (&a.chunk_id, &a.new_filename, &a.old_filename).cmp(&(
+                &b.chunk_id,
+                &b.new_filename,
+                &b.old_filename,
+            ))
+        });
+        // the filename of an asset is changed with its content hash
+        for (path, filename) in &assets {
+            let old_filename = snapshot.assets.get(path);
+            if old_filename != Some(filename) {
+                changes.assets.push(ChangedAsset {
+                    path: path.clone(),
+                    old_filename: old_filename.cloned(),
+                    new_filename: Some(filename.clone()),
+                });
+            }
+        }
+        for (path, old_filename) in &snapshot.assets {
+            if !assets.contains_key(path) {
+                changes.assets.push(ChangedAsset {
+                    path: path.clone(),
+                    old_filename: Some(old_filename.clone()),
+                    new_filename: None,
+                });
+            }
+        }
+        changes.assets.sort_by(|a, b| a.path.cmp(&b.path));
+
+        snapshot.chunk_files = chunk_files;
+        snapshot.assets = assets;
+        changes
     }
 
     // TODO: integrate into generate fn
@@ -499,6 +698,12 @@ impl Compiler {
     }
 }
 
+fn content_raw_hash(content: &[u8]) -> u64 {
+    let mut hasher: XxHash64 = Default::default();
+    hasher.write(content);
+    hasher.finish()
+}
+
 fn write_dev_chunk_file(context: &Arc<Context>, chunk: &ChunkFile) -> Result<()> {
     crate::mako_profile_function!();
 
@@ -524,11 +729,7 @@ fn write_dev_chunk_file(context: &Arc<Context>, chunk: &ChunkFile) -> Result<()>
             }
             Some(DevtoolConfig::InlineSourceMap) => {
                 code.extend_from_slice(
-                    format!(
-                        "\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
-                        base64_encode(source_map)
-                    )
-                    .as_bytes(),
+                    inline_source_map_url_line(&chunk.file_type, source_map).as_bytes(),
                 );
             }
             None => {}
@@ -620,11 +821,7 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
 
             if let Some(source_map) = &chunk_file.source_map {
                 code.extend_from_slice(
-                    format!(
-                        "\n//# sourceMappingURL=data:application/json;charset=utf-8;base64,{}",
-                        base64_encode(source_map)
-                    )
-                    .as_bytes(),
+                    inline_source_map_url_line(&chunk_file.file_type, source_map).as_bytes(),
                 );
             }
 
@@ -651,6 +848,18 @@ fn emit_chunk_file(context: &Arc<Context>, chunk_file: &ChunkFile) {
     }
 }
 
+// css has no line comments
+fn inline_source_map_url_line(file_type: &ChunkFileType, source_map: &[u8]) -> String {
+    let url = format!(
+        "data:application/json;charset=utf-8;base64,{}",
+        base64_encode(source_map)
+    );
+    match file_type {
+        ChunkFileType::JS => format!("\n//# sourceMappingURL={}", url),
+        ChunkFileType::Css => format!("\n/*# sourceMappingURL={}*/", url),
+    }
+}
+
 fn write_to_file(path: &str, content: &Vec<u8>) -> std::io::Result<()> {
     if let Some(parent) = std::path::Path::new(path).parent() {
         if !parent.exists() {
@@ -682,3 +891,102 @@ struct HotUpdateManifest {
     // #[serde(rename(serialize = "c"))]
     // removed_modules: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use crate::compiler::Context;
+    use crate::generate::generate_chunks::ChunkFileType;
+    use crate::plugin::{OutputChunk, Plugin};
+    use crate::utils::test_helper::setup_compiler_with_plugins;
+
+    // adds a chunk and appends a comment to the others in generate_bundle
+    struct GenerateBundlePlugin {}
+
+    impl Plugin for GenerateBundlePlugin {
+        fn name(&self) -> &str {
+            "generate_bundle_test"
+        }
+
+        fn generate_bundle(
+            &self,
+            chunks: &mut Vec<OutputChunk>,
+            context: &Arc<Context>,
+        ) -> Result<()> {
+            // nothing is written yet
+            assert!(!context.config.output.path.join("index.js").exists());
+            for chunk in chunks.iter_mut() {
+                chunk.code.extend_from_slice(b"\n// generate bundle");
+            }
+            chunks.push(OutputChunk::new(
+                "extra.js".to_string(),
+                b"console.log(\"extra\");".to_vec(),
+                None,
+                ChunkFileType::JS,
+            ));
+            Ok(())
+        }
+
+        fn has_generate_bundle(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_generate_bundle() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/generate-bundle",
+            vec![Arc::new(GenerateBundlePlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let index = fs::read_to_string(output.join("index.js")).unwrap();
+        assert!(index.contains("// generate bundle"), "{}", index);
+        let extra = fs::read_to_string(output.join("extra.js")).unwrap();
+        assert_eq!(extra, "console.log(\"extra\");");
+    }
+
+    struct RenameChunkPlugin {}
+
+    impl Plugin for RenameChunkPlugin {
+        fn name(&self) -> &str {
+            "rename_chunk_test"
+        }
+
+        fn generate_bundle(
+            &self,
+            chunks: &mut Vec<OutputChunk>,
+            _context: &Arc<Context>,
+        ) -> Result<()> {
+            for chunk in chunks.iter_mut() {
+                chunk.file_name = format!("renamed-{}", chunk.file_name);
+            }
+            Ok(())
+        }
+
+        fn has_generate_bundle(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_generate_bundle_rejects_renames() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/generate-bundle",
+            vec![Arc::new(RenameChunkPlugin {})],
+            |_| {},
+        );
+        let err = compiler.compile().unwrap_err().to_string();
+        assert!(
+            err.contains("can't be renamed to renamed-index.js in generate_bundle"),
+            "{}",
+            err
+        );
+    }
+}
@@ -5,11 +5,19 @@ use anyhow::Result;
 
 use crate::stats::StatsJsonMap;
 
+const REPORT_FILE: &str = "bundle-analyzer-report.html";
+const DATA_ISLAND_ID: &str = "mako-analyze-data";
+
+/**
+ * A self-contained html report with a treemap of the module sizes in each chunk, the
+ * stats json is embedded as a data island which is read by the pre-bundled treemap.
+ */
 pub struct Analyze {}
 
 impl Analyze {
     pub fn write_analyze(stats: &StatsJsonMap, path: &Path) -> Result<()> {
-        let stats_json = serde_json::to_string_pretty(&stats).unwrap();
+        // `<\/` is still valid json, and the data island won't be closed by `</script>`
+        let stats_json = serde_json::to_string(&stats)?.replace("</", "<\\/");
         let html_str = format!(
             r#"<!DOCTYPE html>
 <html>
@@ -20,18 +28,21 @@ impl Analyze {
   </head>
   <body>
     <div id="root"></div>
+    <script id="{}" type="application/json">{}</script>
     <script>
-      window.chartData = {};
+      window.chartData = JSON.parse(document.getElementById('{}').textContent);
     </script>
     <script>{}</script>
   </body>
 </html>"#,
             include_str!("../../../../client/dist/index.css"),
+            DATA_ISLAND_ID,
             stats_json,
+            DATA_ISLAND_ID,
             include_str!("../../../../client/dist/index.js").replace("</script>", "<\\/script>")
         );
-        let report_path = path.join("analyze-report.html");
-        fs::write(&report_path, html_str).unwrap();
+        let report_path = path.join(REPORT_FILE);
+        fs::write(&report_path, html_str)?;
         println!(
             "Analyze report generated at: {}",
             report_path.to_string_lossy()
@@ -39,3 +50,36 @@ impl Analyze {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_write_analyze() {
+        let compiler = setup_compiler("test/build/analyze", false);
+        compiler.compile().unwrap();
+
+        let report_path = compiler.context.config.output.path.join(REPORT_FILE);
+        let html = fs::read_to_string(report_path).unwrap();
+        let data_start = format!(
+            r#"<script id="{}" type="application/json">"#,
+            DATA_ISLAND_ID
+        );
+        let data = html
+            .split_once(&data_start)
+            .and_then(|(_, rest)| rest.split_once("</script>"))
+            .map(|(data, _)| data)
+            .unwrap();
+        let stats: serde_json::Value = serde_json::from_str(data).unwrap();
+
+        let module_ids = stats["chunkModules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["id"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(module_ids.iter().any(|id| id.ends_with("foo.ts")));
+    }
+}
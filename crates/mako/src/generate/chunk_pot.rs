@@ -11,10 +11,11 @@ use hashlink::LinkedHashSet;
 use swc_core::css::ast::Stylesheet;
 
 use crate::compiler::Context;
-use crate::config::Mode;
+use crate::config::{Mode, ModuleWrapper};
 use crate::generate::chunk::Chunk;
 pub use crate::generate::chunk_pot::util::CHUNK_FILE_NAME_HASH_LENGTH;
 use crate::generate::chunk_pot::util::{hash_hashmap, hash_vec};
+use crate::generate::css_dedupe::is_shared_css_chunk;
 use crate::generate::generate_chunks::ChunkFile;
 use crate::module::{Module, ModuleAst, ModuleId};
 use crate::module_graph::ModuleGraph;
@@ -38,7 +39,7 @@ impl<'cp> ChunkPot<'cp> {
         let (js_modules, stylesheet) = ChunkPot::split_modules(chunk.get_modules(), mg, context);
 
         ChunkPot {
-            js_name: chunk.filename(),
+            js_name: get_js_chunk_filename(chunk, context),
             chunk_id: chunk.id.id.clone(),
             module_map: js_modules.module_map,
             js_hash: js_modules.raw_hash,
@@ -61,29 +62,33 @@ impl<'cp> ChunkPot<'cp> {
             return Ok(files);
         }
 
-        let js_chunk_file = ternary!(
-            self.use_chunk_parallel(context),
-            ternary!(
-                context.args.watch,
-                str_impl::render_normal_js_chunk,
-                str_impl::render_normal_js_chunk_no_cache
-            ),
-            ternary!(
-                context.args.watch,
-                ast_impl::render_normal_js_chunk,
-                ast_impl::render_normal_js_chunk_no_cache
-            )
-        )(self, context)?;
+        // the shared chunk of optimization.cssDedupe only has css, no js is emitted for it
+        let css_only = self.stylesheet.is_some() && is_shared_css_chunk(chunk);
+        if !css_only {
+            let js_chunk_file = ternary!(
+                self.use_chunk_parallel(context),
+                ternary!(
+                    context.caches_renders(),
+                    str_impl::render_normal_js_chunk,
+                    str_impl::render_normal_js_chunk_no_cache
+                ),
+                ternary!(
+                    context.caches_renders(),
+                    ast_impl::render_normal_js_chunk,
+                    ast_impl::render_normal_js_chunk_no_cache
+                )
+            )(self, context)?;
+
+            if js_chunk_file.content.is_empty() {
+                panic!("Normal chunk {} output is empty.", chunk.id.id);
+            }
 
-        if js_chunk_file.content.is_empty() {
-            panic!("Normal chunk {} output is empty.", chunk.id.id);
+            files.push(js_chunk_file);
         }
 
-        files.push(js_chunk_file);
-
         if self.stylesheet.is_some() {
             let css_chunk_file = ternary!(
-                context.args.watch,
+                context.caches_renders(),
                 ast_impl::render_css_chunk,
                 ast_impl::render_css_chunk_no_cache
             )(self, chunk, context)?;
@@ -112,7 +117,9 @@ impl<'cp> ChunkPot<'cp> {
             css_map.insert(css_chunk_file.chunk_id.clone(), css_chunk_file.disk_name());
             files.push(css_chunk_file);
 
-            if self.use_chunk_parallel(context) {
+            if self.is_unwrapped(context) {
+                ast_impl::render_unwrapped_entry_js_chunk(self, chunk, context)?
+            } else if self.use_chunk_parallel(context) {
                 str_impl::render_entry_js_chunk(self, js_map, &css_map, chunk, context, hmr_hash)?
             } else {
                 ast_impl::render_entry_js_chunk(self, js_map, &css_map, chunk, context, hmr_hash)?
@@ -120,7 +127,9 @@ impl<'cp> ChunkPot<'cp> {
         } else {
             crate::mako_profile_scope!("EntryDevJsChunk", &self.chunk_id);
 
-            if self.use_chunk_parallel(context) {
+            if self.is_unwrapped(context) {
+                ast_impl::render_unwrapped_entry_js_chunk(self, chunk, context)?
+            } else if self.use_chunk_parallel(context) {
                 str_impl::render_entry_js_chunk(self, js_map, css_map, chunk, context, hmr_hash)?
             } else {
                 ast_impl::render_entry_js_chunk(self, js_map, css_map, chunk, context, hmr_hash)?
@@ -143,6 +152,11 @@ impl<'cp> ChunkPot<'cp> {
             && matches!(context.config.mode, Mode::Development)
     }
 
+    fn is_unwrapped(&self, context: &Arc<Context>) -> bool {
+        // modules are not concatenated in watch mode, and hmr needs the runtime
+        context.config.module_wrapper == ModuleWrapper::None && !context.args.watch
+    }
+
     fn split_modules<'a>(
         module_ids: &LinkedHashSet<ModuleId>,
         module_graph: &'a ModuleGraph,
@@ -230,3 +244,19 @@ pub fn get_css_chunk_filename(js_chunk_filename: &str) -> String {
         js_chunk_filename.strip_suffix(".js").unwrap_or("")
     )
 }
+
+pub fn get_js_chunk_filename(chunk: &Chunk, context: &Arc<Context>) -> String {
+    match context.plugin_driver.resolve_file_name(chunk, context) {
+        Some(file_name) => {
+            let file_name = file_name
+                .replace("[name]", &chunk.name())
+                .replace("[id]", &chunk.id.id);
+            if file_name.ends_with(".js") {
+                file_name
+            } else {
+                format!("{}.js", file_name)
+            }
+        }
+        None => chunk.filename(),
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use cached::proc_macro::cached;
 use cached::SizedCache;
 use pathdiff::diff_paths;
@@ -11,10 +11,12 @@ use swc_core::css::ast::Stylesheet;
 use swc_core::css::codegen::writer::basic::{BasicCssWriter, BasicCssWriterConfig};
 use swc_core::css::codegen::{CodeGenerator, CodegenConfig, Emit};
 use swc_core::ecma::ast::{
-    BlockStmt, FnExpr, Function, KeyValueProp, Lit, Module as SwcModule, Number, ObjectLit, Prop,
-    PropOrSpread, Stmt, UnaryExpr, UnaryOp, VarDeclKind,
+    BlockStmt, Callee, Expr, FnExpr, Function, Ident, KeyValueProp, Lit, MemberProp,
+    Module as SwcModule, ModuleItem, Number, ObjectLit, Prop, PropOrSpread, Stmt, UnaryExpr,
+    UnaryOp, VarDeclKind,
 };
 use swc_core::ecma::utils::{quote_ident, quote_str, ExprFactory};
+use swc_core::ecma::visit::{Visit, VisitWith};
 
 use crate::ast::js_ast::JsAst;
 use crate::ast::sourcemap::{build_source_map, merge_source_map};
@@ -26,8 +28,11 @@ use crate::generate::chunk_pot::util::{
 };
 use crate::generate::chunk_pot::{get_css_chunk_filename, util, ChunkPot};
 use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
-use crate::generate::minify::{minify_css, minify_js};
+use crate::generate::minify::minify_css;
+use crate::generate::minify_cache::optimize_and_render_js;
 use crate::generate::transform::transform_css_generate;
+use crate::module::relative_to_root;
+use crate::optimizer::optimize_js;
 use crate::{mako_profile_scope, ternary};
 
 #[cached(
@@ -165,11 +170,7 @@ pub(crate) fn render_normal_js_chunk(
         path: "".to_string(),
     });
 
-    if context.config.minify && matches!(context.config.mode, Mode::Production) {
-        minify_js(&mut ast, context)?;
-    }
-
-    let (buf, source_map) = util::render_module_js(&ast.ast, context)?;
+    let (buf, source_map) = optimize_and_render_js(&mut ast, chunk_pot, context)?;
 
     let hash = if context.config.hash {
         Some(file_content_hash(&buf))
@@ -205,7 +206,7 @@ pub(crate) fn render_entry_js_chunk(
         source_map,
         hash,
     } = ternary!(
-        context.args.watch,
+        context.caches_renders(),
         render_entry_chunk_js_without_full_hash,
         render_entry_chunk_js_without_full_hash_no_cache
     )(pot, js_map, css_map, chunk, context)?;
@@ -335,9 +336,7 @@ fn render_entry_chunk_js_without_full_hash(
         ast.ast = wrap_in_iife(ast.ast);
     }
 
-    if context.config.minify && matches!(context.config.mode, Mode::Production) {
-        minify_js(&mut ast, context)?;
-    }
+    optimize_js(&mut ast, &pot.chunk_id, context)?;
 
     let (buf, source_map_buf) = util::render_module_js(&ast.ast, context)?;
 
@@ -355,6 +354,130 @@ fn render_entry_chunk_js_without_full_hash(
     })
 }
 
+// moduleWrapper: "none", the scope hoisted entry module is emitted as is, without
+// the module factory and the runtime
+pub(crate) fn render_unwrapped_entry_js_chunk(
+    pot: &ChunkPot,
+    chunk: &Chunk,
+    context: &Arc<Context>,
+) -> Result<ChunkFile> {
+    crate::mako_profile_function!(&pot.chunk_id);
+
+    // the externals are required through the runtime
+    if let Some((external, _)) = pot
+        .module_map
+        .values()
+        .find(|(module, _)| module.is_external())
+    {
+        return Err(anyhow!(
+            "moduleWrapper \"none\" can't inline chunk {}, it requires the external {}",
+            pot.chunk_id,
+            external.id.id
+        ));
+    }
+
+    let module = match pot.module_map.values().next() {
+        Some((module, _)) if pot.module_map.len() == 1 => *module,
+        _ => {
+            return Err(anyhow!(
+                "moduleWrapper \"none\" requires the modules of chunk {} to be concatenated into one, but {} modules are left, check that they are esm and not dynamically imported",
+                pot.chunk_id,
+                pot.module_map.len()
+            ));
+        }
+    };
+
+    let mut ast = module
+        .as_script()
+        .ok_or_else(|| anyhow!("{} is not a script module", module.id.id))?
+        .clone();
+
+    // the exports of the entry are not reachable without the runtime
+    ast.ast.body.retain(|item| !is_entry_exports_stmt(item));
+
+    let mut runtime_refs = RuntimeRefs {
+        unresolved_mark: ast.unresolved_mark,
+        found: None,
+    };
+    ast.ast.visit_with(&mut runtime_refs);
+    if let Some(name) = runtime_refs.found {
+        return Err(anyhow!(
+            "moduleWrapper \"none\" can't inline {}, it still references `{}` of the module runtime",
+            relative_to_root(&module.id.id, &context.root),
+            name
+        ));
+    }
+
+    optimize_js(&mut ast, &pot.chunk_id, context)?;
+
+    let (buf, source_map) = util::render_module_js(&ast.ast, context)?;
+
+    let hash = if context.config.hash || context.config.output.filename.is_some() {
+        Some(file_content_hash(&buf))
+    } else {
+        None
+    };
+
+    let entry_info = if let ChunkType::Entry(_, name, _) = &chunk.chunk_type {
+        context.config.entry.get(name)
+    } else {
+        None
+    };
+
+    Ok(ChunkFile {
+        raw_hash: pot.js_hash,
+        content: buf,
+        hash,
+        source_map,
+        file_name: pot.js_name.clone(),
+        chunk_id: pot.chunk_id.clone(),
+        file_type: ChunkFileType::JS,
+        chunk_name: pot.chunk_name.clone(),
+        file_name_template: entry_info.and_then(|e| {
+            e.filename
+                .as_ref()
+                .xor(context.config.output.filename.as_ref())
+                .cloned()
+        }),
+    })
+}
+
+// __mako_require__.d(exports, ...) and __mako_require__.e(exports, ...) added by concatenation
+fn is_entry_exports_stmt(item: &ModuleItem) -> bool {
+    let Some(call) = item
+        .as_stmt()
+        .and_then(|stmt| stmt.as_expr())
+        .and_then(|expr_stmt| expr_stmt.expr.as_call())
+    else {
+        return false;
+    };
+    let is_runtime_export = match &call.callee {
+        Callee::Expr(box Expr::Member(member)) => {
+            matches!(&*member.obj, Expr::Ident(obj) if obj.sym == "__mako_require__")
+                && matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == "d" || prop.sym == "e")
+        }
+        _ => false,
+    };
+    is_runtime_export
+        && matches!(call.args.first(), Some(arg) if matches!(&*arg.expr, Expr::Ident(ident) if ident.sym == "exports"))
+}
+
+struct RuntimeRefs {
+    unresolved_mark: Mark,
+    found: Option<String>,
+}
+
+impl Visit for RuntimeRefs {
+    fn visit_ident(&mut self, ident: &Ident) {
+        if self.found.is_none()
+            && ident.ctxt.outer() == self.unresolved_mark
+            && ["__mako_require__", "module", "exports", "require"].contains(&ident.sym.as_ref())
+        {
+            self.found = Some(ident.sym.to_string());
+        }
+    }
+}
+
 #[derive(Clone)]
 struct RenderedChunk {
     content: Vec<u8>,
@@ -436,3 +559,52 @@ fn wrap_in_iife(module: SwcModule) -> SwcModule {
         span: DUMMY_SP,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::compiler::{Args, Compiler};
+    use crate::config::Config;
+    use crate::utils::test_helper::setup_logger;
+
+    #[test]
+    fn test_module_wrapper_none() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/module-wrapper-none");
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root, Args { watch: false }, None).unwrap();
+        compiler.compile().unwrap();
+        let code =
+            fs::read_to_string(compiler.context.config.output.path.join("index.js")).unwrap();
+
+        assert!(code.contains("console.log("));
+        assert!(code.contains("'mako'") || code.contains("\"mako\""));
+        // neither the module factories nor the runtime
+        assert!(!code.contains("function"));
+        assert!(!code.contains("__mako_require__"));
+    }
+
+    #[test]
+    fn test_module_wrapper_none_with_externals() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/module-wrapper-none");
+        let config = Config::new(
+            &root,
+            None,
+            Some(r#"{"entry":{"external":"external.js"},"externals":{"react":"React"}}"#),
+        )
+        .unwrap();
+        let compiler = Compiler::new(config, root, Args { watch: false }, None).unwrap();
+        let err = compiler.compile().unwrap_err();
+        assert!(
+            err.to_string().contains("it requires the external react"),
+            "{}",
+            err
+        );
+    }
+}
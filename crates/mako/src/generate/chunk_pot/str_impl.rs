@@ -40,7 +40,7 @@ pub(super) fn render_entry_js_chunk(
     if pot.stylesheet.is_some() {
         crate::mako_profile_scope!("CssChunk");
         let css_chunk_file = ternary!(
-            context.args.watch,
+            context.caches_renders(),
             render_css_chunk,
             render_css_chunk_no_cache
         )(pot, chunk, context)?;
@@ -253,7 +253,11 @@ fn pot_to_chunk_module_object_string(
     let emitted_modules_with_mapping = sorted_kv
         .par_iter()
         .map(|(module_id, module_and_hash)| {
-            emit_module_with_mapping(module_id, module_and_hash.0, module_and_hash.1, context)
+            ternary!(
+                context.caches_renders(),
+                emit_module_with_mapping,
+                emit_module_with_mapping_no_cache
+            )(module_id, module_and_hash.0, module_and_hash.1, context)
         })
         .collect::<Result<Vec<(String, Option<RawSourceMap>)>>>()?;
 
@@ -319,7 +323,9 @@ mod tests {
     use anyhow::Result;
     use swc_core::base::sourcemap;
     use swc_core::common::comments::Comments;
+    use swc_core::common::util::take::Take;
     use swc_core::common::GLOBALS;
+    use swc_core::ecma::ast::Program;
     use swc_core::ecma::codegen::text_writer::JsWriter;
     use swc_core::ecma::codegen::{Config as JsCodegenConfig, Emitter};
     use swc_core::ecma::transforms::base::hygiene::hygiene_with_config;
@@ -427,7 +433,9 @@ mod tests {
                 ..Default::default()
             }));
 
-        minify_js(&mut ast, context).unwrap();
+        let mut program = Program::Module(ast.ast.take());
+        minify_js(&mut program, ast.unresolved_mark, top, context).unwrap();
+        ast.ast = program.expect_module();
 
         let mut buf = vec![];
         let mut source_map_buf = Vec::new();
@@ -23,6 +23,7 @@ use crate::ast::sourcemap::build_source_map_to_buf;
 use crate::compiler::Context;
 use crate::config::Mode;
 use crate::generate::chunk_pot::ChunkPot;
+use crate::generate::link_hints::group_link_hint_targets;
 use crate::generate::runtime::AppRuntimeTemplate;
 use crate::module::{relative_to_root, Module, ModuleAst};
 use crate::utils::get_app_info;
@@ -102,8 +103,10 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
             .map(|e| format!("[{}]", serde_json::to_string(e).unwrap()))
             .collect()
     });
-    let chunk_graph = context.chunk_graph.read().unwrap();
-    let has_dynamic_chunks = chunk_graph.get_all_chunks().len() > 1;
+    let has_dynamic_chunks = {
+        let chunk_graph = context.chunk_graph.read().unwrap();
+        chunk_graph.get_all_chunks().len() > 1
+    };
     let has_hmr = context.args.watch;
     let chunk_matcher = context.config.module_federation.as_ref().and_then(|mf| {
         mf.remotes.as_ref().and_then(|remotes| {
@@ -114,6 +117,12 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
             }
         })
     });
+    // hints from entry chunks are emitted on startup, the others when their chunks are loaded
+    let (entry_link_hints, async_link_hints) = if has_dynamic_chunks {
+        group_link_hint_targets(context)
+    } else {
+        Default::default()
+    };
     let app_runtime = AppRuntimeTemplate {
         has_dynamic_chunks,
         has_hmr,
@@ -137,6 +146,9 @@ pub(crate) fn runtime_code(context: &Arc<Context>) -> Result<String> {
             .map_or(false, |o| o.concatenate_modules.unwrap_or(false)),
         global_module_registry: context.config.output.global_module_registry,
         chunk_matcher,
+        entry_link_hints,
+        async_link_hints,
+        chunk_retry: context.config.runtime.chunk_retry.clone(),
     };
     let app_runtime = app_runtime.render_once()?;
     let app_runtime = app_runtime.replace(
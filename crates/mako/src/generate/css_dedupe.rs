@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use tracing::debug;
+
+use crate::compiler::Compiler;
+use crate::generate::chunk::{Chunk, ChunkId, ChunkType};
+use crate::generate::chunk_graph::ChunkGraph;
+use crate::module::{relative_to_root, ModuleAst, ModuleId};
+use crate::stats::CssDedupeInfo;
+
+impl Compiler {
+    /// `optimization.cssDedupe`, the css modules in more than one entry chunk are moved to
+    /// a shared chunk which all of those entries load, so their rules are emitted once. It's
+    /// the shared chunk of code splitting if the entries have one in common, otherwise a
+    /// `shared-css~<entries>` chunk is created for them.
+    ///
+    /// The shared chunk is loaded before the entries, so only the modules which are imported
+    /// before the other css of all the entries are moved, the rules keep their order. The
+    /// others stay in the entries with a warning.
+    pub fn dedupe_css(&self) {
+        crate::mako_profile_function!();
+        let module_graph = self.context.module_graph.read().unwrap();
+        let mut chunk_graph = self.context.chunk_graph.write().unwrap();
+
+        let mut entry_chunks = chunk_graph
+            .get_chunks()
+            .into_iter()
+            .filter(|chunk| matches!(chunk.chunk_type, ChunkType::Entry(_, _, false)))
+            .collect::<Vec<_>>();
+        entry_chunks.sort_by_key(|chunk| chunk.name());
+
+        // the entry chunks of each css module, in the order of their first appearance
+        let mut css_entries: IndexMap<ModuleId, Vec<ChunkId>> = IndexMap::new();
+        // the css modules of each entry chunk, in order
+        let mut entry_css: HashMap<ChunkId, Vec<ModuleId>> = HashMap::new();
+        for chunk in entry_chunks {
+            let ChunkType::Entry(entry_id, _, _) = &chunk.chunk_type else {
+                unreachable!()
+            };
+            for module_id in chunk.get_modules() {
+                // the css of a css entry stays with its entry
+                if module_id == entry_id {
+                    continue;
+                }
+                let is_css = module_graph
+                    .get_module(module_id)
+                    .and_then(|module| module.info.as_ref())
+                    .is_some_and(|info| matches!(info.ast, ModuleAst::Css(_)));
+                if is_css {
+                    css_entries
+                        .entry(module_id.clone())
+                        .or_default()
+                        .push(chunk.id.clone());
+                    entry_css
+                        .entry(chunk.id.clone())
+                        .or_default()
+                        .push(module_id.clone());
+                }
+            }
+        }
+
+        let mut groups: IndexMap<Vec<ChunkId>, Vec<ModuleId>> = IndexMap::new();
+        for (module_id, entries) in css_entries {
+            if entries.len() > 1 {
+                groups.entry(entries).or_default().push(module_id);
+            }
+        }
+
+        let mut infos = vec![];
+        for (entries, modules) in groups {
+            let names = entries
+                .iter()
+                .map(|entry| chunk_graph.chunk(entry).unwrap().name())
+                .collect::<Vec<_>>();
+
+            // the modules at the start of the css of every entry, the modules of the groups
+            // before are already moved out
+            let hoisted = modules
+                .iter()
+                .enumerate()
+                .take_while(|(index, module_id)| {
+                    entries
+                        .iter()
+                        .all(|entry| entry_css[entry].get(*index) == Some(*module_id))
+                })
+                .count();
+            for module_id in &modules[hoisted..] {
+                self.context.warn(format!(
+                    "css module {} of entries {} is not deduped, since other css is imported before it, moving it to a shared chunk would change the order of the rules",
+                    relative_to_root(&module_id.id, &self.context.root),
+                    names.join(", ")
+                ));
+            }
+            if hoisted == 0 {
+                continue;
+            }
+            let modules = &modules[..hoisted];
+            for entry in &entries {
+                entry_css.get_mut(entry).unwrap().drain(..hoisted);
+            }
+
+            let shared_chunk_id = shared_chunk_of(&chunk_graph, &entries)
+                .unwrap_or_else(|| add_shared_css_chunk(&mut chunk_graph, &entries, &names));
+            debug!(
+                "hoist css modules {:?} of {:?} to {}",
+                modules, entries, shared_chunk_id.id
+            );
+
+            for module_id in modules {
+                for entry in &entries {
+                    chunk_graph
+                        .mut_chunk(entry)
+                        .unwrap()
+                        .remove_module(module_id);
+                }
+                chunk_graph
+                    .mut_chunk(&shared_chunk_id)
+                    .unwrap()
+                    .add_module(module_id.clone());
+                infos.push(CssDedupeInfo {
+                    module: module_id.generate(&self.context),
+                    chunk: shared_chunk_id.id.clone(),
+                    entries: names.clone(),
+                });
+            }
+        }
+        self.context.stats_info.set_css_dedupe(infos);
+    }
+}
+
+/// Whether the chunk is a `shared-css~<entries>` chunk of `optimization.cssDedupe`, which only
+/// has css.
+pub(crate) fn is_shared_css_chunk(chunk: &Chunk) -> bool {
+    match &chunk.chunk_type {
+        ChunkType::Entry(_, name, true) => name.starts_with(SHARED_CSS_CHUNK_PREFIX),
+        _ => false,
+    }
+}
+
+const SHARED_CSS_CHUNK_PREFIX: &str = "shared-css~";
+
+// a shared chunk of code splitting which all the entries load
+fn shared_chunk_of(chunk_graph: &ChunkGraph, entries: &[ChunkId]) -> Option<ChunkId> {
+    chunk_graph
+        .entry_dependencies_chunk(&entries[0])
+        .into_iter()
+        .filter(|dep| {
+            matches!(
+                chunk_graph.chunk(dep).unwrap().chunk_type,
+                ChunkType::Entry(_, _, true)
+            )
+        })
+        .find(|dep| {
+            entries[1..]
+                .iter()
+                .all(|entry| chunk_graph.entry_dependencies_chunk(entry).contains(dep))
+        })
+}
+
+fn add_shared_css_chunk(
+    chunk_graph: &mut ChunkGraph,
+    entries: &[ChunkId],
+    names: &[String],
+) -> ChunkId {
+    let name = format!("{}{}", SHARED_CSS_CHUNK_PREFIX, names.join("~"));
+    let chunk_id = ChunkId::new(name.clone());
+    chunk_graph.add_chunk(Chunk::new(
+        chunk_id.clone(),
+        ChunkType::Entry(chunk_id.clone(), name, true),
+    ));
+    for entry in entries {
+        chunk_graph.add_edge(entry, &chunk_id);
+    }
+    chunk_id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::compiler::Compiler;
+    use crate::config::Config;
+    use crate::utils::test_helper::setup_logger;
+
+    #[test]
+    fn test_css_dedupe() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/css-dedupe");
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+        let output = &compiler.context.config.output.path;
+        let read = |name: &str| fs::read_to_string(output.join(name)).unwrap();
+
+        let shared_css = read("shared-css~a~b.css");
+        assert_eq!(shared_css.matches(".shared").count(), 1, "{}", shared_css);
+        assert!(!output.join("shared-css~a~b.js").exists());
+        for entry in ["a", "b"] {
+            let css = read(&format!("{}.css", entry));
+            assert!(!css.contains(".shared"), "{}", css);
+            // imported after the css of the entry, it's not moved to keep the order
+            assert!(css.contains(".late"), "{}", css);
+            // the rules left in the entry keep their order
+            let first = css.find(&format!(".first-{}", entry)).unwrap();
+            let late = css.find(".late").unwrap();
+            let last = css.find(&format!(".last-{}", entry)).unwrap();
+            assert!(first < late && late < last, "{}", css);
+            let html = read(&format!("{}.html", entry));
+            assert!(html.contains("shared-css~a~b.css"), "{}", html);
+        }
+
+        let stats = serde_json::to_value(compiler.create_stats_info()).unwrap();
+        for entry in ["a", "b"] {
+            assert!(
+                stats["entrypoints"][entry]["chunks"]
+                    .as_array()
+                    .unwrap()
+                    .contains(&"shared-css~a~b".into()),
+                "{}",
+                stats["entrypoints"]
+            );
+        }
+        assert_eq!(stats["cssDedupe"][0]["chunk"], "shared-css~a~b");
+        assert_eq!(
+            stats["cssDedupe"][0]["entries"],
+            serde_json::json!(["a", "b"])
+        );
+        assert_eq!(stats["cssDedupe"].as_array().unwrap().len(), 1);
+        let warnings = compiler.context.warnings.lock().unwrap();
+        assert!(
+            warnings.iter().any(|warning| warning
+                .message
+                .contains("css module ./late.css of entries a, b is not deduped")),
+            "{:?}",
+            warnings
+        );
+    }
+}
@@ -13,15 +13,17 @@ use swc_core::ecma::ast::{Expr, KeyValueProp, Prop, PropName, PropOrSpread, Str}
 use tracing::warn;
 use twox_hash::XxHash64;
 
+use crate::ast::sourcemap::{finalize_source_map_buf, offset_source_map_buf};
 use crate::compiler::{Compiler, Context};
+use crate::config::entry::EntryItem;
 use crate::generate::chunk::{Chunk, ChunkType};
 use crate::generate::chunk_pot::util::file_content_hash;
 use crate::generate::chunk_pot::{get_css_chunk_filename, ChunkPot, CHUNK_FILE_NAME_HASH_LENGTH};
 use crate::generate::transform::transform_css_generate;
 use crate::module::{ModuleAst, ModuleId};
-use crate::utils::thread_pool;
+use crate::utils::{get_app_info, thread_pool};
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum ChunkFileType {
     JS,
     Css,
@@ -111,6 +113,9 @@ impl Compiler {
         );
 
         let normal_chunk_files = normal_chunk_files?;
+        if let Err(e) = self.context.minify_cache.evict() {
+            warn!("Failed to evict the minified chunks from the cache: {}", e);
+        }
 
         let mut entry_chunk_files_with_placeholder = entry_chunk_files_with_placeholder?;
 
@@ -154,12 +159,32 @@ impl Compiler {
         )?;
         }
 
-        let entry_chunk_files = entry_chunk_files_with_placeholder
+        let entry_chunk_files: Vec<ChunkFile> = entry_chunk_files_with_placeholder
             .into_iter()
             .flat_map(|e| e.0)
             .collect();
+        let entry_chunk_files_len = entry_chunk_files.len();
+
+        let version = get_app_info(&self.context.root).1.unwrap_or_default();
+        let mut chunk_files = [entry_chunk_files, normal_chunk_files].concat();
+        chunk_files.par_iter_mut().enumerate().try_for_each(
+            |(index, chunk_file)| -> Result<()> {
+                let entry = if index < entry_chunk_files_len {
+                    self.context.config.entry.get(&chunk_file.chunk_name)
+                } else {
+                    None
+                };
+                apply_banner_footer(chunk_file, entry, &version, &self.context)?;
+
+                if let Some(source_map) = chunk_file.source_map.take() {
+                    chunk_file.source_map =
+                        Some(finalize_source_map_buf(source_map, &self.context)?);
+                }
+                Ok(())
+            },
+        )?;
 
-        Ok([entry_chunk_files, normal_chunk_files].concat())
+        Ok(chunk_files)
     }
 
     fn generate_entry_chunk_files(
@@ -311,6 +336,72 @@ impl Compiler {
     }
 }
 
+// the files are minified already so the banner is kept, the map is offset by its lines,
+// the banners of plugins come first so that a shebang stays on the first line
+fn apply_banner_footer(
+    chunk_file: &mut ChunkFile,
+    entry: Option<&EntryItem>,
+    version: &str,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let output = &context.config.output;
+    let banner = entry
+        .and_then(|e| e.banner.as_ref())
+        .or(output.banner.as_ref());
+    let footer = entry
+        .and_then(|e| e.footer.as_ref())
+        .or(output.footer.as_ref());
+    let plugin_banners = context.plugin_driver.banner(chunk_file, context)?;
+    let plugin_footers = context.plugin_driver.footer(chunk_file, context)?;
+    if banner.is_none()
+        && footer.is_none()
+        && plugin_banners.is_empty()
+        && plugin_footers.is_empty()
+    {
+        return Ok(());
+    }
+
+    let name = chunk_file.chunk_name.clone();
+    let hash = chunk_file
+        .hash
+        .clone()
+        .unwrap_or_else(|| file_content_hash(&chunk_file.content));
+    let render = |tpl: &str| {
+        tpl.replace("[name]", &name)
+            .replace("[version]", version)
+            .replace("[hash]", &hash)
+    };
+
+    let banner = plugin_banners
+        .into_iter()
+        .chain(banner.map(|banner| render(banner)))
+        .map(|mut banner| {
+            if !banner.ends_with('\n') {
+                banner.push('\n');
+            }
+            banner
+        })
+        .collect::<String>();
+    if !banner.is_empty() {
+        let lines = banner.matches('\n').count() as u32;
+        chunk_file.content.splice(0..0, banner.into_bytes());
+        if let Some(source_map) = chunk_file.source_map.take() {
+            chunk_file.source_map = Some(offset_source_map_buf(source_map, lines)?);
+        }
+    }
+
+    for footer in footer
+        .map(|footer| render(footer))
+        .into_iter()
+        .chain(plugin_footers)
+    {
+        chunk_file.content.push(b'\n');
+        chunk_file.content.extend(footer.into_bytes());
+    }
+
+    Ok(())
+}
+
 fn replace_chunks_placeholder(
     chunk_files: &mut [ChunkFile],
     chunks_hash_placeholder: &ChunksHashPlaceholder,
@@ -425,8 +516,11 @@ pub fn modules_to_js_stmts(
 
 fn hash_file_name(file_name: &String, hash: &String) -> String {
     let path = Path::new(&file_name);
-    let file_stem = path.file_stem().unwrap().to_str().unwrap();
     let file_extension = path.extension().unwrap().to_str().unwrap();
+    // keep the dirs, e.g. file names from plugins
+    let file_stem = file_name
+        .strip_suffix(&format!(".{}", file_extension))
+        .unwrap();
 
     format!("{}.{}.{}", file_stem, hash, file_extension)
 }
@@ -457,6 +551,8 @@ fn hash_too_long_file_name(file_name: &String) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
+    use crate::plugin::Plugin;
 
     #[test]
     fn test_simple_template_render() {
@@ -474,4 +570,174 @@ mod tests {
 
         assert_eq!(chunk_file.disk_name(), "chunk.hash999.c_id.js");
     }
+
+    #[test]
+    fn test_banner_footer() {
+        let root = std::env::current_dir().unwrap().join("test/build/banner");
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+
+        let index = read("index.js");
+        assert!(index.starts_with("/*! index v1.2.3 "));
+        assert!(index.contains(" * license MIT */\n"));
+        assert_eq!(index.matches("/*! index v1.2.3").count(), 1);
+        assert_eq!(index.matches("/* end of index */").count(), 1);
+        assert!(!index.contains("[hash]"));
+
+        let css = read("index.css");
+        assert!(css.starts_with("/*! index v1.2.3 "));
+        assert_eq!(css.matches("/* end of index */").count(), 1);
+
+        // overridden by the entry
+        let other = read("other.js");
+        assert!(other.starts_with("/*! other entry */\n"));
+        assert!(!other.contains("v1.2.3"));
+        assert_eq!(other.matches("/* end of other */").count(), 1);
+
+        // the mappings are shifted by the two lines of the banner
+        let map = swc_core::base::sourcemap::SourceMap::from_slice(read("index.js.map").as_bytes())
+            .unwrap();
+        let (line, code) = index
+            .lines()
+            .enumerate()
+            .find(|(_, code)| code.contains("banner-marker"))
+            .unwrap();
+        assert_eq!(line, 2);
+        let col = code.find("banner-marker").unwrap();
+        let token = map.lookup_token(line as u32, col as u32).unwrap();
+        assert_eq!(token.get_src_line(), 2);
+    }
+
+    struct CliPlugin {}
+
+    impl Plugin for CliPlugin {
+        fn name(&self) -> &str {
+            "cli"
+        }
+
+        fn banner(
+            &self,
+            chunk_file: &ChunkFile,
+            _context: &Arc<Context>,
+        ) -> Result<Option<String>> {
+            Ok((chunk_file.chunk_name == "index"
+                && matches!(chunk_file.file_type, ChunkFileType::JS))
+            .then(|| "#!/usr/bin/env node".to_string()))
+        }
+
+        fn footer(
+            &self,
+            chunk_file: &ChunkFile,
+            _context: &Arc<Context>,
+        ) -> Result<Option<String>> {
+            Ok(Some(format!("/* footer of {} */", chunk_file.disk_name())))
+        }
+    }
+
+    #[test]
+    fn test_banner_footer_hooks() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/banner-hook");
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(
+            config,
+            root,
+            Default::default(),
+            Some(vec![Arc::new(CliPlugin {})]),
+        )
+        .unwrap();
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let read = |name: &str| std::fs::read_to_string(output.join(name)).unwrap();
+
+        // before the banner of the config
+        let index = read("index.js");
+        assert!(index.starts_with("#!/usr/bin/env node\n/*! index */\n"));
+        let footer_pos = index.find("/* footer of index.js */").unwrap();
+        let map_comment_pos = index.find("//# sourceMappingURL=index.js.map").unwrap();
+        assert!(footer_pos < map_comment_pos);
+
+        // filtered by the plugin
+        let css = read("index.css");
+        assert!(css.starts_with("/*! index */\n"));
+        assert!(css.contains("/* footer of index.css */"));
+        let other = read("other.js");
+        assert!(other.starts_with("/*! other */\n"));
+        assert!(other.contains("/* footer of other.js */"));
+
+        // the mappings are shifted by the shebang and the banner of the config
+        let map = swc_core::base::sourcemap::SourceMap::from_slice(read("index.js.map").as_bytes())
+            .unwrap();
+        let (line, code) = index
+            .lines()
+            .enumerate()
+            .find(|(_, code)| code.contains("banner-hook-marker"))
+            .unwrap();
+        let col = code.find("banner-hook-marker").unwrap();
+        let token = map.lookup_token(line as u32, col as u32).unwrap();
+        assert_eq!(token.get_src_line(), 2);
+    }
+
+    struct LocalePlugin {}
+
+    impl Plugin for LocalePlugin {
+        fn name(&self) -> &str {
+            "locale"
+        }
+
+        fn resolve_file_name(&self, chunk: &Chunk, _context: &Arc<Context>) -> Option<String> {
+            matches!(chunk.chunk_type, ChunkType::Async)
+                .then(|| "[locale]/[name].js".replace("[locale]", "zh-CN"))
+        }
+    }
+
+    #[test]
+    fn test_resolve_file_name_hook() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/resolve-file-name");
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(
+            config,
+            root,
+            Default::default(),
+            Some(vec![Arc::new(LocalePlugin {})]),
+        )
+        .unwrap();
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let locale_files = std::fs::read_dir(output.join("zh-CN"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(locale_files.len(), 1);
+        let async_file = format!("zh-CN/{}", locale_files[0]);
+        // hashed by mako
+        assert!(async_file.ends_with(".js"));
+        assert!(!async_file.ends_with("-async.js"));
+
+        let index_file = compiler
+            .context
+            .stats_info
+            .get_assets()
+            .into_iter()
+            .find(|a| a.hashname.starts_with("index."))
+            .unwrap()
+            .hashname;
+        let index = std::fs::read_to_string(output.join(index_file)).unwrap();
+        assert!(index.contains(&format!("\"{}\"", async_file)));
+        assert!(compiler
+            .context
+            .stats_info
+            .get_assets()
+            .iter()
+            .any(|a| a.hashname == async_file));
+    }
 }
@@ -26,10 +26,11 @@ impl Compiler {
 
         let entries = module_graph.get_entry_modules();
         debug!("entries: {:?}", entries);
+        let entry_items = self.context.entries.read().unwrap();
         for entry in entries {
             let mut entry_chunk_name = "index";
 
-            for (key, value) in &self.context.config.entry {
+            for (key, value) in entry_items.iter() {
                 // hmr entry id has query '?hmr'
                 if parse_path(&value.import.to_string_lossy()).unwrap().0
                     == parse_path(&entry.id).unwrap().0
@@ -231,7 +232,11 @@ impl Compiler {
         // 1. for logic simplicity, full re-group if modified files are more than 1
         //    ex. git checkout another branch;
         // 2. if dependencies have been changed, should full re-group too.
-        if modified_files.len() > 1 || !update_result.dep_changed.is_empty() {
+        // 3. if entries have been added or removed, should full re-group too.
+        if modified_files.len() > 1
+            || !update_result.dep_changed.is_empty()
+            || update_result.entries_changed
+        {
             self.group_chunk();
             // empty vec means full re-group
             return Some((vec![], vec![]));
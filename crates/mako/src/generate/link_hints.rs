@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::compiler::Context;
+use crate::generate::chunk::ChunkType;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::module::{generate_module_id, ResolveType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkHintRel {
+    Prefetch,
+    Preload,
+}
+
+/**
+ * A resource hint for an async chunk, collected from `import()` calls
+ * annotated with `webpackPrefetch` / `webpackPreload` magic comments.
+ * One hint is emitted per output file (js or css) of the target chunk.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkHint {
+    pub rel: LinkHintRel,
+    pub chunk_id: String,
+    // the chunk which contains the `import()` call
+    pub parent_chunk_id: String,
+    pub href: String,
+    // the output file, the href is it prefixed with the publicPath
+    #[serde(skip)]
+    pub file_name: String,
+    // "script" or "style"
+    #[serde(rename = "as")]
+    pub as_type: String,
+}
+
+/**
+ * Ids of the chunks hinted by a parent chunk, which are injected by the runtime when
+ * the parent chunk is loaded, preloads immediately and prefetches at idle after the
+ * page is loaded.
+ */
+#[derive(Debug, Default, Serialize)]
+pub struct ChunkLinkHints {
+    pub prefetch: Vec<String>,
+    pub preload: Vec<String>,
+}
+
+impl ChunkLinkHints {
+    fn add(&mut self, rel: LinkHintRel, chunk_id: String) {
+        let chunk_ids = match rel {
+            LinkHintRel::Prefetch => &mut self.prefetch,
+            LinkHintRel::Preload => &mut self.preload,
+        };
+        if !chunk_ids.contains(&chunk_id) {
+            chunk_ids.push(chunk_id);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefetch.is_empty() && self.preload.is_empty()
+    }
+}
+
+pub(crate) struct LinkHintTarget {
+    pub rel: LinkHintRel,
+    pub chunk_id: String,
+    pub parent_chunk_id: String,
+    pub parent_is_entry: bool,
+}
+
+pub(crate) fn collect_link_hint_targets(context: &Arc<Context>) -> Vec<LinkHintTarget> {
+    let module_graph = context.module_graph.read().unwrap();
+    let chunk_graph = context.chunk_graph.read().unwrap();
+
+    let mut visited = HashSet::new();
+    let mut targets = vec![];
+    let mut module_ids = module_graph.get_module_ids();
+    // make the output stable
+    module_ids.sort_by(|a, b| a.id.cmp(&b.id));
+
+    for module_id in module_ids {
+        let parent_chunk = match chunk_graph.get_chunk_for_module(&module_id) {
+            Some(chunk) => chunk,
+            None => continue,
+        };
+        for (dep_id, dep) in module_graph.get_dependencies(&module_id) {
+            let ResolveType::DynamicImport(import_options) = &dep.resolve_type else {
+                continue;
+            };
+            let rel = if import_options.preload {
+                LinkHintRel::Preload
+            } else if import_options.prefetch {
+                LinkHintRel::Prefetch
+            } else {
+                continue;
+            };
+            let chunk_id = match import_options.get_chunk_name() {
+                Some(chunk_name) => generate_module_id(chunk_name, context),
+                None => dep_id.generate(context),
+            };
+            if !visited.insert((rel, chunk_id.clone(), parent_chunk.id.id.clone())) {
+                continue;
+            }
+            targets.push(LinkHintTarget {
+                rel,
+                chunk_id,
+                parent_chunk_id: parent_chunk.id.id.clone(),
+                parent_is_entry: matches!(parent_chunk.chunk_type, ChunkType::Entry(..)),
+            });
+        }
+    }
+
+    targets
+}
+
+// the hints of all the entry chunks, and the hints of the async chunks by their ids
+pub(crate) fn group_link_hint_targets(
+    context: &Arc<Context>,
+) -> (ChunkLinkHints, BTreeMap<String, ChunkLinkHints>) {
+    let mut entry_hints = ChunkLinkHints::default();
+    let mut async_hints: BTreeMap<String, ChunkLinkHints> = BTreeMap::new();
+    for target in collect_link_hint_targets(context) {
+        if target.parent_is_entry {
+            entry_hints.add(target.rel, target.chunk_id);
+        } else {
+            async_hints
+                .entry(target.parent_chunk_id)
+                .or_default()
+                .add(target.rel, target.chunk_id);
+        }
+    }
+    (entry_hints, async_hints)
+}
+
+pub(crate) fn resolve_link_hints(context: &Arc<Context>, chunk_files: &[ChunkFile]) {
+    let public_path = match context.config.public_path.as_str() {
+        // resolved by the runtime, leave hrefs relative
+        "runtime" | "auto" => "",
+        p => p,
+    };
+
+    let hints = collect_link_hint_targets(context)
+        .into_iter()
+        .flat_map(|target| {
+            chunk_files
+                .iter()
+                .filter(|f| f.chunk_id == target.chunk_id)
+                .map(|f| LinkHint {
+                    rel: target.rel,
+                    chunk_id: target.chunk_id.clone(),
+                    parent_chunk_id: target.parent_chunk_id.clone(),
+                    href: format!("{}{}", public_path, f.disk_name()),
+                    file_name: f.disk_name(),
+                    as_type: match f.file_type {
+                        ChunkFileType::JS => "script".to_string(),
+                        ChunkFileType::Css => "style".to_string(),
+                    },
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    *context.link_hints.lock().unwrap() = hints;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_link_hints() {
+        let compiler = setup_compiler("test/build/link-hints", false);
+        compiler.compile().unwrap();
+        let hints = compiler.context.link_hints.lock().unwrap();
+        let rels = hints
+            .iter()
+            .filter(|h| h.as_type == "script")
+            .map(|h| h.rel)
+            .collect::<Vec<_>>();
+        assert_eq!(rels.len(), 2);
+        assert!(rels.contains(&LinkHintRel::Prefetch));
+        assert!(rels.contains(&LinkHintRel::Preload));
+        assert!(hints.iter().all(|h| h.href.starts_with('/')));
+        let chunk_id_of = |rel: LinkHintRel| {
+            hints
+                .iter()
+                .find(|h| h.rel == rel)
+                .map(|h| h.chunk_id.clone())
+                .unwrap()
+        };
+        let prefetch_chunk_id = chunk_id_of(LinkHintRel::Prefetch);
+        let preload_chunk_id = chunk_id_of(LinkHintRel::Preload);
+        drop(hints);
+
+        let index_js =
+            std::fs::read_to_string(compiler.context.config.output.path.join("index.js")).unwrap();
+        // the runtime code is printed again, so it's not always valid json
+        let entry_link_hints = index_js
+            .split_once("var entryLinkHints = ")
+            .and_then(|(_, rest)| rest.split_once("var asyncLinkHints"))
+            .map(|(hints, _)| hints)
+            .unwrap();
+        // only the hinted chunk is prefetched
+        assert!(entry_link_hints.contains(&prefetch_chunk_id));
+        assert!(entry_link_hints.contains(&preload_chunk_id));
+        assert!(!entry_link_hints.contains("plain"));
+        // the magic comments are removed
+        assert!(!index_js.contains("makoPrefetch"));
+        assert!(!index_js.contains("webpackPreload"));
+
+        let stats = compiler.create_stats_info();
+        let entry_chunk = stats.chunks.iter().find(|c| c.entry).unwrap();
+        assert_eq!(entry_chunk.link_hints.len(), 2);
+    }
+}
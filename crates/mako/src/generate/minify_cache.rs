@@ -0,0 +1,323 @@
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use swc_core::common::comments::{Comments, SwcComments};
+use swc_core::common::{Span, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::Module as SwcModule;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+use tracing::warn;
+use twox_hash::XxHash64;
+
+use crate::ast::js_ast::JsAst;
+use crate::compiler::Context;
+use crate::config::{Config, Mode};
+use crate::generate::chunk_pot::{util, ChunkPot};
+use crate::optimizer::optimize_js;
+
+type RenderedJs = (Vec<u8>, Option<Vec<u8>>);
+
+// the least recently used entries beyond it are evicted after a build
+const MAX_ENTRIES: usize = 1000;
+
+/**
+ * Caches the optimized and rendered js of the normal chunks on disk, in
+ * `node_modules/.cache_mako/minify`, so that the chunks which are unchanged across builds
+ * skip the minification.
+ *
+ * Entries are keyed by the ast of the chunk before it's optimized, the sources of its
+ * modules when the source map is generated, the config, the registered optimizers and the
+ * version of mako. Each entry is stored as `<key>.js` and its source map as `<key>.js.map`,
+ * and only the `MAX_ENTRIES` most recently used ones are kept.
+ */
+pub struct MinifyCache {
+    // the cache is disabled without a root, e.g. with `Context::default`
+    dir: Option<PathBuf>,
+    config_hash: u64,
+    max_entries: usize,
+    pub(crate) hits: AtomicUsize,
+    pub(crate) misses: AtomicUsize,
+}
+
+impl Default for MinifyCache {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            config_hash: 0,
+            max_entries: MAX_ENTRIES,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl MinifyCache {
+    pub fn new(root: &Path, config: &Config) -> Self {
+        // every option of the config may change the code of the chunks or their minification,
+        // it's serialized as a value so that the keys of the maps are sorted
+        let Ok(config) = serde_json::to_value(config) else {
+            return Default::default();
+        };
+        let mut hasher: XxHash64 = Default::default();
+        hasher.write(config.to_string().as_bytes());
+        hasher.write(env!("CARGO_PKG_VERSION").as_bytes());
+        Self {
+            dir: Some(root.join("node_modules/.cache_mako/minify")),
+            config_hash: hasher.finish(),
+            ..Default::default()
+        }
+    }
+
+    fn key(&self, module: &SwcModule, chunk_pot: &ChunkPot, context: &Arc<Context>) -> String {
+        let mut hasher: XxHash64 = Default::default();
+        hasher.write_u64(self.config_hash);
+        for optimizer in context.optimizers.read().unwrap().iter() {
+            hasher.write(optimizer.name().as_bytes());
+        }
+
+        // the spans and the syntax contexts differ across builds, e.g. the files are loaded
+        // in parallel, so the ast is hashed without them, with the comments at the spans
+        let comments = context.meta.script.origin_comments.read().unwrap();
+        let mut module = module.clone();
+        module.visit_mut_with(&mut SpanEraser {
+            comments: comments.get_swc_comments(),
+            hasher: &mut hasher,
+        });
+        module.hash(&mut hasher);
+
+        // the source map is built from the sources of the modules
+        if context.config.devtool.is_some() {
+            let mut modules = chunk_pot
+                .module_map
+                .iter()
+                .map(|(id, (module, _))| (id, module.info.as_ref().unwrap().file.get_raw_hash()))
+                .collect::<Vec<_>>();
+            modules.sort();
+            modules.hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn get(&self, dir: &Path, key: &str) -> Option<RenderedJs> {
+        let path = dir.join(format!("{}.js", key));
+        let content = fs::read(&path).ok()?;
+        let source_map = fs::read(dir.join(format!("{}.js.map", key))).ok();
+        // the entries are evicted by the time they were last used
+        let _ = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some((content, source_map))
+    }
+
+    fn set(&self, dir: &Path, key: &str, (content, source_map): &RenderedJs) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        if let Some(source_map) = source_map {
+            fs::write(dir.join(format!("{}.js.map", key)), source_map)?;
+        }
+        // written last, an entry is only read when its content exists
+        fs::write(dir.join(format!("{}.js", key)), content)?;
+        Ok(())
+    }
+
+    // remove the least recently used entries beyond `max_entries`
+    pub(crate) fn evict(&self) -> Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        if self.misses.load(Ordering::Relaxed) == 0 || !dir.exists() {
+            return Ok(());
+        }
+        let mut entries = fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "js" {
+                    return None;
+                }
+                let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+                Some((modified, path))
+            })
+            .collect::<Vec<_>>();
+        if entries.len() <= self.max_entries {
+            return Ok(());
+        }
+        entries.sort_by(|a, b| b.0.cmp(&a.0));
+        for (_, path) in entries.into_iter().skip(self.max_entries) {
+            fs::remove_file(&path)?;
+            let _ = fs::remove_file(path.with_extension("js.map"));
+        }
+        Ok(())
+    }
+}
+
+struct SpanEraser<'a, H: Hasher> {
+    comments: &'a SwcComments,
+    hasher: &'a mut H,
+}
+
+impl<H: Hasher> VisitMut for SpanEraser<'_, H> {
+    fn visit_mut_span(&mut self, span: &mut Span) {
+        // e.g. the pure annotations change the minified code
+        for comments in [
+            self.comments.get_leading(span.lo),
+            self.comments.get_trailing(span.hi),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for comment in comments {
+                self.hasher.write(comment.text.as_bytes());
+            }
+        }
+        *span = DUMMY_SP;
+    }
+
+    fn visit_mut_syntax_context(&mut self, ctxt: &mut SyntaxContext) {
+        *ctxt = SyntaxContext::empty();
+    }
+}
+
+// optimize and render the js of a normal chunk, the cache is only used when minifying
+pub(crate) fn optimize_and_render_js(
+    ast: &mut JsAst,
+    chunk_pot: &ChunkPot,
+    context: &Arc<Context>,
+) -> Result<RenderedJs> {
+    crate::mako_profile_function!(&chunk_pot.chunk_id);
+
+    if !(context.config.minify && matches!(context.config.mode, Mode::Production)) {
+        optimize_js(ast, &chunk_pot.chunk_id, context)?;
+        return util::render_module_js(&ast.ast, context);
+    }
+
+    let cache = &context.minify_cache;
+    let Some(dir) = &cache.dir else {
+        optimize_js(ast, &chunk_pot.chunk_id, context)?;
+        return util::render_module_js(&ast.ast, context);
+    };
+    let key = cache.key(&ast.ast, chunk_pot, context);
+    if let Some(rendered) = cache.get(dir, &key) {
+        cache.hits.fetch_add(1, Ordering::Relaxed);
+        return Ok(rendered);
+    }
+    cache.misses.fetch_add(1, Ordering::Relaxed);
+
+    optimize_js(ast, &chunk_pot.chunk_id, context)?;
+    let rendered = util::render_module_js(&ast.ast, context)?;
+    if let Err(e) = cache.set(dir, &key, &rendered) {
+        warn!(
+            "Failed to cache the minified chunk {}: {}",
+            chunk_pot.chunk_id, e
+        );
+    }
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, SystemTime};
+
+    use super::MinifyCache;
+    use crate::compiler::Compiler;
+    use crate::config::Config;
+
+    fn build(root: &Path, minify_workers: Option<usize>) -> (Compiler, BTreeMap<String, String>) {
+        let mut config = Config::new(root, None, None).unwrap();
+        config.optimization.as_mut().unwrap().minify_workers = minify_workers;
+        let compiler = Compiler::new(config, root.to_path_buf(), Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+
+        let files = fs::read_dir(&compiler.context.config.output.path)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                (name, fs::read_to_string(&path).unwrap())
+            })
+            .collect();
+        (compiler, files)
+    }
+
+    fn counts(compiler: &Compiler) -> (usize, usize) {
+        let cache = &compiler.context.minify_cache;
+        (
+            cache.hits.load(Ordering::Relaxed),
+            cache.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    #[test]
+    fn test_minify_cache() {
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/minify-cache");
+        let cache_dir = root.join("node_modules/.cache_mako/minify");
+        let _ = fs::remove_dir_all(&cache_dir);
+
+        // one chunk is minified at a time
+        let (sequential, sequential_files) = build(&root, Some(1));
+        assert_eq!(counts(&sequential), (0, 2));
+        assert_eq!(
+            sequential_files
+                .keys()
+                .filter(|name| name.ends_with("-async.js"))
+                .count(),
+            2
+        );
+
+        let _ = fs::remove_dir_all(&cache_dir);
+        let (parallel, parallel_files) = build(&root, None);
+        assert_eq!(counts(&parallel), (0, 2));
+        assert_eq!(parallel_files, sequential_files);
+
+        // the normal chunks are unchanged
+        let (rebuild, rebuild_files) = build(&root, None);
+        assert_eq!(counts(&rebuild), (2, 0));
+        assert_eq!(rebuild_files, sequential_files);
+    }
+
+    #[test]
+    fn test_minify_cache_evicts_least_recently_used() {
+        let dir = std::env::current_dir()
+            .unwrap()
+            .join("test/build/minify-cache/node_modules/.cache_mako/evict");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = MinifyCache {
+            dir: Some(dir.clone()),
+            max_entries: 2,
+            ..Default::default()
+        };
+        let now = SystemTime::now();
+        for (i, key) in ["a", "b", "c"].into_iter().enumerate() {
+            cache.set(&dir, key, &(vec![], Some(vec![]))).unwrap();
+            fs::File::options()
+                .append(true)
+                .open(dir.join(format!("{}.js", key)))
+                .unwrap()
+                .set_modified(now - Duration::from_secs(10 - i as u64))
+                .unwrap();
+        }
+        // a hit makes it the most recently used
+        assert!(cache.get(&dir, "a").is_some());
+
+        cache.misses.fetch_add(1, Ordering::Relaxed);
+        cache.evict().unwrap();
+        assert!(dir.join("a.js").exists());
+        assert!(!dir.join("b.js").exists());
+        assert!(!dir.join("b.js.map").exists());
+        assert!(dir.join("c.js").exists());
+    }
+
+    #[test]
+    fn test_minify_cache_is_disabled_without_root() {
+        assert!(MinifyCache::default().dir.is_none());
+    }
+}
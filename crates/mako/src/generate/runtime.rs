@@ -1,5 +1,10 @@
+use std::collections::BTreeMap;
+
 use sailfish::TemplateOnce;
 
+use crate::config::ChunkRetryConfig;
+use crate::generate::link_hints::ChunkLinkHints;
+
 #[derive(TemplateOnce)]
 #[template(path = "app_runtime.stpl")]
 pub struct AppRuntimeTemplate {
@@ -15,4 +20,7 @@ pub struct AppRuntimeTemplate {
     pub cross_origin_loading: Option<String>,
     pub global_module_registry: bool,
     pub chunk_matcher: Option<String>,
+    pub entry_link_hints: ChunkLinkHints,
+    pub async_link_hints: BTreeMap<String, ChunkLinkHints>,
+    pub chunk_retry: Option<ChunkRetryConfig>,
 }
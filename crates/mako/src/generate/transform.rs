@@ -21,7 +21,9 @@ use tracing::debug;
 
 use crate::ast::js_ast::JsAst;
 use crate::compiler::{Compiler, Context};
+use crate::generate::chunk_pot::get_js_chunk_filename;
 use crate::module::{generate_module_id, Dependency, ModuleAst, ModuleId, ModuleType, ResolveType};
+use crate::plugins::tree_shaking::deferred_exports::DeferredExports;
 use crate::share::helpers::SWC_HELPERS;
 use crate::utils::thread_pool;
 use crate::visitors::async_module::{mark_async, AsyncModule};
@@ -94,7 +96,10 @@ pub fn transform_modules_in_thread(
                             None => id.generate(&context),
                         };
                         let chunk_graph = context.chunk_graph.read().unwrap();
-                        let chunk_name = chunk_graph.chunk(&chunk_id.into()).unwrap().filename();
+                        let chunk_name = get_js_chunk_filename(
+                            chunk_graph.chunk(&chunk_id.into()).unwrap(),
+                            &context,
+                        );
 
                         ResolvedReplaceInfo {
                             chunk_id: None,
@@ -240,6 +245,16 @@ pub fn transform_js_generate(transform_js_param: TransformJsParam) -> Result<()>
                             import_interop,
                         ));
 
+                        if context
+                            .config
+                            .optimization
+                            .as_ref()
+                            .map_or(false, |o| o.deferred_exports.unwrap_or(false))
+                        {
+                            ast.ast
+                                .visit_mut_with(&mut DeferredExports::new(unresolved_mark));
+                        }
+
                         ast.ast.visit_mut_with(&mut OptimizeDefineUtils {
                             top_level_mark,
                             unresolved_mark,
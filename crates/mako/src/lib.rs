@@ -13,6 +13,7 @@ mod features;
 mod generate;
 pub mod module;
 mod module_graph;
+pub mod optimizer;
 pub mod plugin;
 mod plugins;
 pub mod resolve;
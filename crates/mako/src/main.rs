@@ -49,10 +49,15 @@ async fn run() -> Result<()> {
     let cli_args = format!(
         r#"
         {{
-            "mode": "{}"
+            "mode": "{}"{}
         }}
         "#,
-        cli.mode
+        cli.mode,
+        if cli.analyze {
+            r#", "analyze": {}"#
+        } else {
+            ""
+        }
     );
     let mut config = config::Config::new(&root, None, Some(cli_args.as_str()))
         .map_err(|e| anyhow!(format!("Load config failed: {}", e)))?;
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -20,6 +20,7 @@ use crate::ast::js_ast::JsAst;
 use crate::build::analyze_deps::AnalyzeDepsResult;
 use crate::compiler::Context;
 use crate::config::ModuleIdStrategy;
+use crate::plugins::const_enum::ConstEnumMembers;
 use crate::resolve::ResolverResource;
 
 pub type Dependencies = HashSet<Dependency>;
@@ -137,6 +138,8 @@ impl From<&NamedExport> for NamedExportType {
 pub struct ImportOptions {
     pub chunk_name: Option<String>,
     pub ignore: bool,
+    pub prefetch: bool,
+    pub preload: bool,
     pub _is_federation_expose: bool,
 }
 
@@ -202,6 +205,8 @@ pub struct ModuleInfo {
     pub source_map_chain: Vec<Vec<u8>>,
     pub module_system: ModuleSystem,
     pub federation: Option<FedereationModuleType>,
+    /// The members of the top level `const enum`s, which are inlined in the importers
+    pub const_enums: HashMap<String, ConstEnumMembers>,
 }
 
 impl Default for ModuleInfo {
@@ -220,6 +225,7 @@ impl Default for ModuleInfo {
             source_map_chain: vec![],
             is_ignored: false,
             federation: None,
+            const_enums: HashMap::new(),
         }
     }
 }
@@ -233,13 +239,11 @@ pub fn md5_hash(source_str: &str, lens: usize) -> String {
 
 pub fn generate_module_id(origin_module_id: &str, context: &Arc<Context>) -> String {
     match context.config.module_id_strategy {
-        ModuleIdStrategy::Hashed => md5_hash(origin_module_id, 8),
-        ModuleIdStrategy::Named => {
-            // readable ids for debugging usage
-            let absolute_path = PathBuf::from(origin_module_id);
-            let relative_path = diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
-            win_path(relative_path.to_str().unwrap())
-        }
+        // hash the path relative to root, so ids only change with the module itself,
+        // not when other files come and go or the project is built somewhere else
+        ModuleIdStrategy::Hashed => md5_hash(&root_relative_id(origin_module_id, context), 8),
+        // readable ids for debugging usage
+        ModuleIdStrategy::Named => root_relative_id(origin_module_id, context),
         ModuleIdStrategy::Numeric => {
             let numeric_ids_map = context.numeric_ids_map.read().unwrap();
             if let Some(numeric_id) = numeric_ids_map.get(origin_module_id) {
@@ -251,6 +255,12 @@ pub fn generate_module_id(origin_module_id: &str, context: &Arc<Context>) -> Str
     }
 }
 
+fn root_relative_id(origin_module_id: &str, context: &Arc<Context>) -> String {
+    let absolute_path = PathBuf::from(origin_module_id);
+    let relative_path = diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
+    win_path(relative_path.to_str().unwrap())
+}
+
 pub fn relative_to_root(module_path: &String, root: &PathBuf) -> String {
     let absolute_path = PathBuf::from(module_path);
     let relative_path = diff_paths(&absolute_path, root).unwrap_or(absolute_path);
@@ -562,3 +572,35 @@ fn empty_module_fn_expr() -> FnExpr {
         function: func.into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::md5_hash;
+    use crate::compiler::{Args, Compiler};
+    use crate::config::Config;
+    use crate::utils::test_helper::setup_logger;
+
+    fn build_index(entry: &str) -> String {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/module-ids-hashed");
+        let config_args = format!(r#"{{"entry":{{"index":"{}"}}}}"#, entry);
+        let config = Config::new(&root, None, Some(&config_args)).unwrap();
+        let output = config.output.path.join("index.js");
+        let compiler = Compiler::new(config, root, Args { watch: false }, None).unwrap();
+        compiler.compile().unwrap();
+        std::fs::read_to_string(output).unwrap()
+    }
+
+    #[test]
+    fn test_hashed_id_is_stable_when_files_are_added() {
+        let id = format!(r#""{}""#, md5_hash("a.js", 8));
+        let content = build_index("index.js");
+        assert!(content.contains(&id), "{}", content);
+        // more.js imports extra.js besides a.js
+        let content = build_index("more.js");
+        assert!(content.contains(&id), "{}", content);
+        assert!(content.contains(&format!(r#""{}""#, md5_hash("extra.js", 8))));
+    }
+}
@@ -1,15 +1,16 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt;
 
 use fixedbitset::FixedBitSet;
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::{DefaultIx, NodeIndex};
 use petgraph::prelude::{Dfs, EdgeRef};
-use petgraph::stable_graph::{StableDiGraph, WalkNeighbors};
-use petgraph::visit::IntoEdgeReferences;
+use petgraph::stable_graph::{EdgeReference, StableDiGraph, WalkNeighbors};
+use petgraph::visit::{EdgeFiltered, IntoEdgeReferences, IntoNeighbors};
 use petgraph::Direction;
 use tracing::{debug, warn};
 
-use crate::module::{Dependencies, Dependency, Module, ModuleId};
+use crate::module::{Dependencies, Dependency, Module, ModuleId, ResolveType};
 
 #[derive(Debug)]
 pub struct ModuleGraph {
@@ -76,6 +77,8 @@ impl ModuleGraph {
             .id_index_map
             .remove(module_id)
             .unwrap_or_else(|| panic!("module_id {:?} not found in the module graph", module_id));
+        // e.g. the entry discovered by entry.pattern is removed in watch mode
+        self.entries.remove(module_id);
         self.graph.remove_node(index).unwrap()
     }
 
@@ -384,6 +387,73 @@ impl ModuleGraph {
         (result, cyclic)
     }
 
+    /**
+     * Cycles made only of `require()` calls, each is a path which starts and ends with the
+     * same module, e.g. `[a, b, a]`, esm imports are hoisted so their cycles are not included
+     */
+    pub fn require_cycles(&self) -> Vec<Vec<ModuleId>> {
+        self.cycles(|dep| dep.resolve_type == ResolveType::Require)
+    }
+
+    /**
+     * Cycles of the dependencies which are loaded synchronously, i.e. esm imports and exports,
+     * `require()` calls and css imports, dynamic imports and workers are not included
+     */
+    pub fn dependency_cycles(&self) -> Vec<Vec<ModuleId>> {
+        self.cycles(|dep| {
+            !matches!(
+                dep.resolve_type,
+                ResolveType::DynamicImport(_) | ResolveType::Worker(_)
+            )
+        })
+    }
+
+    // the shortest cycle through the smallest module of each strongly connected component of
+    // the edges which have a matched dependency
+    fn cycles(&self, is_edge_dep: impl Fn(&Dependency) -> bool) -> Vec<Vec<ModuleId>> {
+        let is_edge =
+            |edge: EdgeReference<Dependencies>| edge.weight().iter().any(|dep| is_edge_dep(dep));
+        let filtered_graph = EdgeFiltered::from_fn(&self.graph, is_edge);
+
+        let mut cycles = tarjan_scc(&filtered_graph)
+            .into_iter()
+            .filter_map(|scc| {
+                let start = *scc.iter().min_by_key(|idx| &self.graph[**idx].id)?;
+                let members = scc.iter().collect::<HashSet<_>>();
+                // the shortest path back to the start inside the component
+                let mut parents = HashMap::new();
+                let mut queue = VecDeque::from([start]);
+                while let Some(idx) = queue.pop_front() {
+                    for next in filtered_graph.neighbors(idx) {
+                        if next == start {
+                            let mut path = vec![start, idx];
+                            let mut current = idx;
+                            while let Some(parent) = parents.get(&current) {
+                                path.push(*parent);
+                                current = *parent;
+                            }
+                            path.pop();
+                            path.reverse();
+                            path.insert(0, start);
+                            return Some(
+                                path.iter()
+                                    .map(|idx| self.graph[*idx].id.clone())
+                                    .collect::<Vec<_>>(),
+                            );
+                        }
+                        if members.contains(&next) && !parents.contains_key(&next) {
+                            parents.insert(next, idx);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                None
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        cycles
+    }
+
     pub fn get_reference(&self) -> Vec<String> {
         let mut references = self
             .graph
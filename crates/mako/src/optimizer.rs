@@ -0,0 +1,184 @@
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use swc_core::common::util::take::Take;
+use swc_core::common::Mark;
+use swc_core::ecma::ast::Program;
+
+use crate::ast::js_ast::JsAst;
+use crate::compiler::Context;
+use crate::config::{Config, Mode};
+use crate::generate::minify::minify_js;
+
+/// A pass over the js of each chunk after tree shaking, e.g. minifying.
+///
+/// The optimizers registered with [`Context::register_optimizer`] are run in order before
+/// the builtin ones, the program is a module and must be kept as a module.
+pub trait Optimizer: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn optimize(&self, program: &mut Program, context: &Arc<Context>) -> Result<()>;
+}
+
+/// Minifies with the marks of the chunk, the constant expressions are folded by its compressor.
+pub struct Minifier {
+    unresolved_mark: Mark,
+    top_level_mark: Mark,
+}
+
+impl Optimizer for Minifier {
+    fn name(&self) -> &str {
+        "minifier"
+    }
+
+    fn optimize(&self, program: &mut Program, context: &Arc<Context>) -> Result<()> {
+        minify_js(program, self.unresolved_mark, self.top_level_mark, context)
+    }
+}
+
+fn builtin_optimizers(config: &Config, ast: &JsAst) -> Vec<Arc<dyn Optimizer>> {
+    if config.minify && matches!(config.mode, Mode::Production) {
+        vec![Arc::new(Minifier {
+            unresolved_mark: ast.unresolved_mark,
+            top_level_mark: ast.top_level_mark,
+        })]
+    } else {
+        vec![]
+    }
+}
+
+// the pool of `optimization.minifyWorkers`, which bounds the chunks being optimized at the
+// same time and so the memory used by the minifier
+pub(crate) fn build_minify_pool(config: &Config) -> Result<Option<ThreadPool>> {
+    let Some(workers) = config.optimization.as_ref().and_then(|o| o.minify_workers) else {
+        return Ok(None);
+    };
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .thread_name(|i| format!("Mako minify thread {}", i))
+        .build()?;
+    Ok(Some(pool))
+}
+
+// the chunks emitted in parallel in watch mode are not optimized,
+// a panic of the optimizers fails the chunk with its id instead of aborting the build
+pub(crate) fn optimize_js(ast: &mut JsAst, chunk_id: &str, context: &Arc<Context>) -> Result<()> {
+    let optimizers = context
+        .optimizers
+        .read()
+        .unwrap()
+        .iter()
+        .cloned()
+        .chain(builtin_optimizers(&context.config, ast))
+        .collect::<Vec<_>>();
+    if optimizers.is_empty() {
+        return Ok(());
+    }
+
+    let mut program = Program::Module(ast.ast.take());
+    let optimize = || {
+        catch_unwind(AssertUnwindSafe(|| {
+            optimizers.iter().try_for_each(|optimizer| {
+                crate::mako_profile_scope!("optimize", optimizer.name());
+                optimizer.optimize(&mut program, context)
+            })
+        }))
+    };
+    let result = match &context.minify_pool {
+        Some(pool) => pool.install(optimize),
+        None => optimize(),
+    };
+    let result = result.unwrap_or_else(|panic| {
+        Err(anyhow!(
+            "Failed to optimize chunk {}: {}",
+            chunk_id,
+            panic_message(&*panic)
+        ))
+    });
+    ast.ast = program.expect_module();
+    result
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(|s| s.as_str()))
+        .unwrap_or("unknown panic")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use swc_core::ecma::ast::{Expr, ExprStmt, MemberExpr, MemberProp, Program, Stmt};
+    use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+    use super::Optimizer;
+    use crate::compiler::{Compiler, Context};
+    use crate::config::Config;
+    use crate::utils::test_helper::setup_logger;
+
+    // removes the `console.log(...);` statements
+    struct RemoveConsoleLog {}
+
+    impl Optimizer for RemoveConsoleLog {
+        fn name(&self) -> &str {
+            "remove_console_log"
+        }
+
+        fn optimize(&self, program: &mut Program, _context: &Arc<Context>) -> Result<()> {
+            program.visit_mut_with(&mut ConsoleLogRemover {});
+            Ok(())
+        }
+    }
+
+    struct ConsoleLogRemover {}
+
+    impl VisitMut for ConsoleLogRemover {
+        fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+            stmts.retain(|stmt| !is_console_log(stmt));
+            stmts.visit_mut_children_with(self);
+        }
+    }
+
+    fn is_console_log(stmt: &Stmt) -> bool {
+        if let Stmt::Expr(ExprStmt {
+            expr: box Expr::Call(call),
+            ..
+        }) = stmt
+            && let Some(box Expr::Member(MemberExpr {
+                obj: box Expr::Ident(obj),
+                prop: MemberProp::Ident(prop),
+                ..
+            })) = call.callee.as_expr()
+        {
+            obj.sym == "console" && prop.sym == "log"
+        } else {
+            false
+        }
+    }
+
+    #[test]
+    fn test_register_optimizer() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/optimizer");
+        let config = Config::new(&root, None, None).unwrap();
+        let output = config.output.path.clone();
+        let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+        compiler
+            .context
+            .register_optimizer(Arc::new(RemoveConsoleLog {}));
+        compiler.compile().unwrap();
+
+        let content = std::fs::read_to_string(output.join("index.js")).unwrap();
+        assert!(!content.contains("console.log"));
+        assert!(content.contains("kept"));
+    }
+}
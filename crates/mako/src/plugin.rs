@@ -1,21 +1,26 @@
 use std::any::Any;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use regex::Regex;
 use serde::Serialize;
 use swc_core::common::errors::Handler;
 use swc_core::common::Mark;
 use swc_core::ecma::ast::Module;
+use thiserror::Error;
 
 use crate::ast::file::{Content, File};
 use crate::build::analyze_deps::ResolvedDep;
-use crate::compiler::{Args, Compiler, Context};
+use crate::build::BuildError;
+use crate::compiler::{Args, BuildStats, Compiler, Context};
 use crate::config::{CodeSplittingAdvancedOptions, Config};
+use crate::generate::chunk::Chunk;
 use crate::generate::chunk_graph::ChunkGraph;
-use crate::generate::generate_chunks::ChunkFile;
+pub use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
 use crate::module::{Dependency, ModuleAst, ModuleId};
 use crate::module_graph::ModuleGraph;
+use crate::plugins::tree_shaking::statement_graph::StatementId;
 use crate::resolve::ResolverResource;
 use crate::stats::StatsJsonMap;
 
@@ -45,8 +50,198 @@ pub struct PluginTransformJsParam<'a> {
 #[serde(rename_all = "camelCase")]
 pub struct PluginGenerateEndParams {
     pub is_first_compile: bool,
+    // increases with each build, see `Context::build_id`
+    pub build_id: u64,
     pub time: i64,
     pub stats: StatsJsonMap,
+    // what a rebuild in watch mode changed, null for the first build
+    pub rebuild: Option<PluginRebuildInfo>,
+    // the timing of the build phases, null unless `profile` is enabled
+    pub build_stats: Option<BuildStats>,
+}
+
+/// A chunk file passed to `generate_bundle` before it's written, `file_name` is the name in
+/// the output directory, with the content hash if any.
+#[derive(Clone)]
+pub struct OutputChunk {
+    pub file_name: String,
+    pub code: Vec<u8>,
+    pub map: Option<Vec<u8>>,
+    pub type_: ChunkFileType,
+    // the index of the chunk file it's created from, none for the chunks added by plugins
+    pub(crate) origin: Option<usize>,
+}
+
+impl OutputChunk {
+    pub fn new(
+        file_name: String,
+        code: Vec<u8>,
+        map: Option<Vec<u8>>,
+        type_: ChunkFileType,
+    ) -> Self {
+        Self {
+            file_name,
+            code,
+            map,
+            type_,
+            origin: None,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginRebuildInfo {
+    pub added_modules: Vec<String>,
+    pub modified_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    // chunk files and assets written in the rebuild, empty when the output is unchanged
+    #[serde(flatten)]
+    pub output: OutputChanges,
+    // in milliseconds
+    pub timing: RebuildTiming,
+}
+
+#[derive(Clone, Serialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputChanges {
+    pub chunks: Vec<ChangedChunkFile>,
+    pub assets: Vec<ChangedAsset>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedChunkFile {
+    pub chunk_id: String,
+    // none for the files added in the rebuild
+    pub old_filename: Option<String>,
+    // none for the files removed in the rebuild
+    pub new_filename: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedAsset {
+    // the source file of the asset
+    pub path: String,
+    // none for the assets added in the rebuild
+    pub old_filename: Option<String>,
+    // none for the assets removed in the rebuild
+    pub new_filename: Option<String>,
+}
+
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildTiming {
+    // updating the module graph with the changed files
+    pub update: u64,
+    pub generate_hot_update: u64,
+    // generating and writing the chunk files
+    pub emit: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginWatchChangesParams {
+    // "create" | "update" | "delete" | "rename"
+    pub event: String,
+    // only for "rename"
+    pub from: Option<String>,
+    pub to: Option<String>,
+    // modules in the module graph which directly depend on the changed file
+    pub importers: Vec<String>,
+}
+
+/// An error thrown by a plugin hook for a module, e.g. a js plugin's `transform`, with the
+/// original message and stack, which is pushed to the dev overlay in watch mode.
+#[derive(Debug, Clone, Error, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[error("Plugin {plugin} failed in {hook} of {path}: {message}")]
+pub struct PluginError {
+    pub plugin: String,
+    // "load" | "transform" | "resolveId"
+    pub hook: String,
+    pub path: String,
+    pub message: String,
+    pub stack: Option<String>,
+    // the lines around the location in the message, e.g. `Unexpected token (3:4)`
+    pub snippet: Option<String>,
+}
+
+impl PluginError {
+    pub fn new(
+        plugin: &str,
+        hook: &str,
+        path: &str,
+        message: String,
+        stack: Option<String>,
+        content: Option<&str>,
+    ) -> Self {
+        let snippet = content.and_then(|content| {
+            let (line, column) = error_location(&message)?;
+            code_snippet(content, line, column)
+        });
+        Self {
+            plugin: plugin.to_string(),
+            hook: hook.to_string(),
+            path: path.to_string(),
+            message,
+            stack,
+            snippet,
+        }
+    }
+
+    // the plugin error which fails the build, including the ones of the build tasks
+    pub fn find(error: &anyhow::Error) -> Option<&PluginError> {
+        error.chain().find_map(|e| {
+            if let Some(e) = e.downcast_ref::<PluginError>() {
+                return Some(e);
+            }
+            match e.downcast_ref::<BuildError>() {
+                Some(BuildError::BuildTasksError { errors }) => errors.iter().find_map(Self::find),
+                None => None,
+            }
+        })
+    }
+}
+
+fn error_location_regex() -> &'static Regex {
+    static ERROR_LOCATION_REGEXP: OnceLock<Regex> = OnceLock::new();
+
+    ERROR_LOCATION_REGEXP.get_or_init(|| Regex::new(r"(\(|:)(\d+):(\d+)\)?$").unwrap())
+}
+
+// 1-based line and 0-based column, `(line:column)` is 0-based like babel's,
+// while `:line:column` is 1-based like the file locations
+fn error_location(message: &str) -> Option<(usize, usize)> {
+    let first_line = message.lines().next()?.trim_end();
+    let captures = error_location_regex().captures(first_line)?;
+    let line = captures[2].parse::<usize>().ok()?;
+    let column = captures[3].parse::<usize>().ok()?;
+    let column = if &captures[1] == ":" {
+        column.saturating_sub(1)
+    } else {
+        column
+    };
+    Some((line, column))
+}
+
+fn code_snippet(content: &str, line: usize, column: usize) -> Option<String> {
+    let lines = content.lines().collect::<Vec<_>>();
+    if line == 0 || line > lines.len() {
+        return None;
+    }
+    let start = line.saturating_sub(3).max(1);
+    let end = (line + 2).min(lines.len());
+    let width = end.to_string().len();
+    let mut snippet = vec![];
+    for n in start..=end {
+        let marker = if n == line { ">" } else { " " };
+        snippet.push(format!("{} {:>width$} | {}", marker, n, lines[n - 1]));
+        if n == line {
+            snippet.push(format!("  {:>width$} | {}^", "", " ".repeat(column)));
+        }
+    }
+    Some(snippet.join("\n"))
 }
 
 pub trait Plugin: Any + Send + Sync {
@@ -74,6 +269,18 @@ pub trait Plugin: Any + Send + Sync {
         Ok(None)
     }
 
+    /// Recover from a module which fails to be transformed or parsed, with a substitute
+    /// content, e.g. a component rendering the error, so that the build goes on. The
+    /// substitute content is parsed and transformed without the `load_transform` hooks.
+    fn transform_error(
+        &self,
+        _error: &anyhow::Error,
+        _path: &str,
+        _context: &Arc<Context>,
+    ) -> Option<Content> {
+        None
+    }
+
     fn resolve_id(
         &self,
         _source: &str,
@@ -84,6 +291,62 @@ pub trait Plugin: Any + Send + Sync {
         Ok(None)
     }
 
+    /// Decide whether `source` imported by `importer` is external, `Some(true)` for external,
+    /// `Some(false)` for not external even if it's in `externals` config, and `None` to let
+    /// other plugins and the config decide.
+    fn filter_externals(
+        &self,
+        _source: &str,
+        _importer: &str,
+        _context: &Arc<Context>,
+    ) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Rewrite the specifier of a dynamic import, e.g. `import('./old')`, before it's resolved,
+    /// `Some` replaces the specifier which is then resolved as usual, and `None` keeps it.
+    fn resolve_dynamic_import(
+        &self,
+        _specifier: &str,
+        _importer: &str,
+        _context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Override the file which a subpath of a package resolves to, e.g. to patch a broken
+    /// `exports` map. `subpath` is `.` or starts with `./`, `conditions` are the condition
+    /// names of the resolver. A relative result is resolved from `pkg_dir`, and `None` falls
+    /// back to the `exports` map.
+    fn on_resolve_package_exports(
+        &self,
+        _pkg_dir: &Path,
+        _subpath: &str,
+        _conditions: &[String],
+        _context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Whether `on_resolve_package_exports` is implemented, the package of a bare import is
+    /// only looked up for the plugins which implement it.
+    fn has_on_resolve_package_exports(&self) -> bool {
+        false
+    }
+
+    /// Remap a `url()` of css, e.g. `url(./logo.png)`, to the public url of the file, which
+    /// is then used as is instead of resolving the file and emitting it as an asset. `source`
+    /// is without the query and fragment, which are appended to the result. Remote, data
+    /// and hash urls are not passed to it.
+    fn resolve_file_url(
+        &self,
+        _source: &str,
+        _importer: &str,
+        _context: &Arc<Context>,
+    ) -> Option<String> {
+        None
+    }
+
     fn next_build(&self, _next_build_param: &NextBuildParam) -> bool {
         true
     }
@@ -118,7 +381,11 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
-    fn after_resolve(&self, _resolved_dep: &ResolvedDep, _context: &Arc<Context>) -> Result<()> {
+    fn after_resolve(
+        &self,
+        _resolved_dep: &mut ResolvedDep,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
         Ok(())
     }
 
@@ -134,6 +401,22 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
+    // all the output chunks before any of them is written, chunks could be added, removed
+    // or modified, but not renamed
+    fn generate_bundle(
+        &self,
+        _chunks: &mut Vec<OutputChunk>,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether `generate_bundle` is implemented, the output chunks are only collected for the
+    /// plugins which implement it.
+    fn has_generate_bundle(&self) -> bool {
+        false
+    }
+
     fn build_success(&self, _stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
         Ok(())
     }
@@ -158,7 +441,12 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
-    fn watch_changes(&self, _id: &str, _event: &str, _context: &Arc<Context>) -> Result<()> {
+    fn watch_changes(
+        &self,
+        _id: &str,
+        _params: &PluginWatchChangesParams,
+        _context: &Arc<Context>,
+    ) -> Result<()> {
         Ok(())
     }
 
@@ -178,6 +466,17 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
+    /// Statements of the module to keep when tree shaking, by their indexes in the module
+    /// body, as if they had side effects, so the statements they depend on are kept too.
+    fn treeshake_retain(
+        &self,
+        _module_id: &ModuleId,
+        _ast: &Module,
+        _context: &Arc<Context>,
+    ) -> Result<Option<Vec<StatementId>>> {
+        Ok(None)
+    }
+
     fn before_optimize_chunk(&self, _context: &Arc<Context>) -> Result<()> {
         Ok(())
     }
@@ -198,6 +497,22 @@ pub trait Plugin: Any + Send + Sync {
         Ok(())
     }
 
+    // custom output file name of the chunk, `[name]` and `[id]` are replaced,
+    // and the content hash is added by mako
+    fn resolve_file_name(&self, _chunk: &Chunk, _context: &Arc<Context>) -> Option<String> {
+        None
+    }
+
+    // raw text prepended to the chunk file, e.g. a license comment or a shebang
+    fn banner(&self, _chunk_file: &ChunkFile, _context: &Arc<Context>) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    // raw text appended to the chunk file, before the source map comment
+    fn footer(&self, _chunk_file: &ChunkFile, _context: &Arc<Context>) -> Result<Option<String>> {
+        Ok(None)
+    }
+
     fn before_write_fs(
         &self,
         _path: &Path,
@@ -216,6 +531,21 @@ pub trait Plugin: Any + Send + Sync {
     }
 }
 
+/// Create the compiled-in plugins by their names, so that embedders like the node binding
+/// can enable them without depending on the plugin types, e.g. `["svgr"]`.
+pub fn create_builtin_plugins(names: &[String]) -> Result<Vec<Arc<dyn Plugin>>> {
+    names
+        .iter()
+        .map(|name| -> Result<Arc<dyn Plugin>> {
+            match name.as_str() {
+                #[cfg(feature = "svgr")]
+                "svgr" => Ok(Arc::new(crate::plugins::svgr::SvgrPlugin {})),
+                _ => Err(anyhow!("Unknown builtin plugin: {}", name)),
+            }
+        })
+        .collect()
+}
+
 #[derive(Default)]
 pub struct PluginDriver {
     plugins: Vec<Arc<dyn Plugin>>,
@@ -311,7 +641,11 @@ impl PluginDriver {
         Ok(())
     }
 
-    pub fn after_resolve(&self, resolved_dep: &ResolvedDep, context: &Arc<Context>) -> Result<()> {
+    pub fn after_resolve(
+        &self,
+        resolved_dep: &mut ResolvedDep,
+        context: &Arc<Context>,
+    ) -> Result<()> {
         for plugin in &self.plugins {
             plugin.after_resolve(resolved_dep, context)?;
         }
@@ -334,6 +668,69 @@ impl PluginDriver {
         Ok(None)
     }
 
+    pub fn resolve_dynamic_import(
+        &self,
+        specifier: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        for plugin in &self.plugins {
+            let ret = plugin.resolve_dynamic_import(specifier, importer, context)?;
+            if ret.is_some() {
+                return Ok(ret);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn has_on_resolve_package_exports(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.has_on_resolve_package_exports())
+    }
+
+    pub fn on_resolve_package_exports(
+        &self,
+        pkg_dir: &Path,
+        subpath: &str,
+        conditions: &[String],
+        context: &Arc<Context>,
+    ) -> Result<Option<String>> {
+        for plugin in &self.plugins {
+            let ret = plugin.on_resolve_package_exports(pkg_dir, subpath, conditions, context)?;
+            if ret.is_some() {
+                return Ok(ret);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn resolve_file_url(
+        &self,
+        source: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Option<String> {
+        self.plugins
+            .iter()
+            .find_map(|plugin| plugin.resolve_file_url(source, importer, context))
+    }
+
+    pub fn filter_externals(
+        &self,
+        source: &str,
+        importer: &str,
+        context: &Arc<Context>,
+    ) -> Result<Option<bool>> {
+        for plugin in &self.plugins {
+            let ret = plugin.filter_externals(source, importer, context)?;
+            if ret.is_some() {
+                return Ok(ret);
+            }
+        }
+        Ok(None)
+    }
+
     pub fn before_generate(&self, context: &Arc<Context>) -> Result<()> {
         for plugin in &self.plugins {
             plugin.generate_begin(context)?;
@@ -353,6 +750,23 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn has_generate_bundle(&self) -> bool {
+        self.plugins
+            .iter()
+            .any(|plugin| plugin.has_generate_bundle())
+    }
+
+    pub fn generate_bundle(
+        &self,
+        chunks: &mut Vec<OutputChunk>,
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        for plugin in &self.plugins {
+            plugin.generate_bundle(chunks, context)?;
+        }
+        Ok(())
+    }
+
     pub fn build_start(&self, context: &Arc<Context>) -> Result<()> {
         for plugin in &self.plugins {
             plugin.build_start(context)?;
@@ -378,9 +792,14 @@ impl PluginDriver {
         Ok(())
     }
 
-    pub fn watch_changes(&self, id: &str, event: &str, context: &Arc<Context>) -> Result<()> {
+    pub fn watch_changes(
+        &self,
+        id: &str,
+        params: &PluginWatchChangesParams,
+        context: &Arc<Context>,
+    ) -> Result<()> {
         for plugin in &self.plugins {
-            plugin.watch_changes(id, event, context)?;
+            plugin.watch_changes(id, params, context)?;
         }
         Ok(())
     }
@@ -427,6 +846,21 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn treeshake_retain(
+        &self,
+        module_id: &ModuleId,
+        ast: &Module,
+        context: &Arc<Context>,
+    ) -> Result<Vec<StatementId>> {
+        let mut stmt_ids = vec![];
+        for p in &self.plugins {
+            if let Some(ids) = p.treeshake_retain(module_id, ast, context)? {
+                stmt_ids.extend(ids);
+            }
+        }
+        Ok(stmt_ids)
+    }
+
     pub fn before_optimize_chunk(&self, context: &Arc<Context>) -> Result<()> {
         for p in &self.plugins {
             p.before_optimize_chunk(context)?;
@@ -459,6 +893,33 @@ impl PluginDriver {
         Ok(())
     }
 
+    pub fn resolve_file_name(&self, chunk: &Chunk, context: &Arc<Context>) -> Option<String> {
+        self.plugins
+            .iter()
+            .find_map(|p| p.resolve_file_name(chunk, context))
+    }
+
+    // the banners of all the plugins, in the order of the plugins
+    pub fn banner(&self, chunk_file: &ChunkFile, context: &Arc<Context>) -> Result<Vec<String>> {
+        let mut banners = vec![];
+        for plugin in &self.plugins {
+            if let Some(banner) = plugin.banner(chunk_file, context)? {
+                banners.push(banner);
+            }
+        }
+        Ok(banners)
+    }
+
+    pub fn footer(&self, chunk_file: &ChunkFile, context: &Arc<Context>) -> Result<Vec<String>> {
+        let mut footers = vec![];
+        for plugin in &self.plugins {
+            if let Some(footer) = plugin.footer(chunk_file, context)? {
+                footers.push(footer);
+            }
+        }
+        Ok(footers)
+    }
+
     pub fn before_write_fs<P: AsRef<Path>, C: AsRef<[u8]>>(
         &self,
         path: P,
@@ -487,6 +948,17 @@ impl PluginDriver {
         Ok(content.clone())
     }
 
+    pub fn transform_error(
+        &self,
+        error: &anyhow::Error,
+        path: &str,
+        context: &Arc<Context>,
+    ) -> Option<Content> {
+        self.plugins
+            .iter()
+            .find_map(|plugin| plugin.transform_error(error, path, context))
+    }
+
     pub fn after_update(&self, compiler: &Compiler) -> Result<()> {
         for plugin in &self.plugins {
             plugin.after_update(compiler)?;
@@ -502,3 +974,34 @@ impl PluginDriver {
         Ok(paths)
     }
 }
+
+#[cfg(all(test, feature = "svgr"))]
+mod tests {
+    use super::create_builtin_plugins;
+    use crate::utils::test_helper::setup_compiler_with_plugins;
+
+    #[test]
+    fn test_create_builtin_plugins() {
+        let plugins = create_builtin_plugins(&["svgr".to_string()]).unwrap();
+        assert_eq!(plugins[0].name(), "svgr");
+        assert!(create_builtin_plugins(&["unknown".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_builtin_plugins_not_duplicated() {
+        // enabled by name as well, e.g. with `builtinPlugins` of the node binding
+        let plugins = create_builtin_plugins(&["svgr".to_string()]).unwrap();
+        let compiler = setup_compiler_with_plugins("test/build/write-bundle", plugins, |config| {
+            config.svgr = true;
+        });
+
+        let svgr_plugins = compiler
+            .context
+            .plugin_driver
+            .plugins
+            .iter()
+            .filter(|plugin| plugin.name() == "svgr")
+            .count();
+        assert_eq!(svgr_plugins, 1);
+    }
+}
@@ -2,6 +2,8 @@ pub mod async_runtime;
 pub mod bundless_compiler;
 pub mod case_sensitive;
 pub mod central_ensure;
+pub mod circular_require;
+pub mod const_enum;
 pub mod context_module;
 pub mod copy;
 pub mod detect_circular_dependence;
@@ -9,10 +11,14 @@ pub mod duplicate_package_checker;
 pub mod emotion;
 pub mod graphviz;
 pub mod hmr_runtime;
+pub mod html;
 pub mod ignore;
+pub mod ignore_missing;
 pub mod import;
 pub mod imports_checker;
 pub mod invalid_webpack_syntax;
+pub mod lazy_compilation;
+pub mod legacy_browsers;
 pub mod manifest;
 pub mod minifish;
 pub mod module_federation;
@@ -20,5 +26,7 @@ pub mod progress;
 pub mod require_context;
 pub mod runtime;
 pub mod ssu;
+#[cfg(feature = "svgr")]
+pub mod svgr;
 pub mod tree_shaking;
 pub mod wasm_runtime;
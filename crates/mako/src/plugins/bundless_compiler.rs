@@ -10,19 +10,23 @@ use rayon::prelude::*;
 use swc_core::base::try_with_handler;
 use swc_core::common::errors::HANDLER;
 use swc_core::common::GLOBALS;
+use swc_core::ecma::ast::{CallExpr, Expr, ExprOrSpread, Lit, Str};
 use swc_core::ecma::transforms::base::fixer::fixer;
 use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
 use swc_core::ecma::transforms::base::hygiene;
 use swc_core::ecma::transforms::base::hygiene::hygiene_with_config;
 use swc_core::ecma::transforms::module::import_analysis::import_analyzer;
 use swc_core::ecma::transforms::module::util::ImportInterop;
-use swc_core::ecma::visit::VisitMutWith;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 use tracing::warn;
 
 use crate::ast::js_ast::JsAst;
+use crate::ast::utils::is_dynamic_import;
+use crate::build::load::JS_EXTENSIONS;
 use crate::compiler::{Args, Context};
 use crate::config::Config;
-use crate::module::{ModuleAst, ModuleId};
+use crate::module::{Module, ModuleAst, ModuleId};
+use crate::module_graph::ModuleGraph;
 use crate::plugin::{Plugin, PluginTransformJsParam};
 use crate::visitors::dep_replacer::{DepReplacer, DependenciesToReplace, ResolvedReplaceInfo};
 use crate::visitors::dynamic_import::DynamicImport;
@@ -45,6 +49,12 @@ impl BundlessCompiler {
             .par_iter()
             .map(|module_id| {
                 let module_graph = context.module_graph.read().unwrap();
+                if context.config.output.is_preserve_modules() {
+                    let module = module_graph.get_module(module_id).unwrap();
+                    if module.is_external() || is_asset(module) {
+                        return Ok(());
+                    }
+                }
                 let deps = module_graph.get_dependencies(module_id);
 
                 let module_dist_path = to_dist_path(&module_id.id, context)
@@ -57,28 +67,13 @@ impl BundlessCompiler {
                     .into_iter()
                     // .map(|(id, dep)| (dep.source.clone(), id.generate(context)))
                     .map(|(id, dep)| {
-                        let dep_dist_path = to_dist_path(&id.id, context);
-
-                        let rel_path =
-                            diff_paths(&dep_dist_path, &module_dist_path).ok_or_else(|| {
-                                anyhow!(
-                                    "failed to get relative path from {:?} to {:?}",
-                                    dep_dist_path,
-                                    module_dist_path
-                                )
-                            })?;
-
-                        let rel_path = normalize_extension(rel_path);
-
-                        let replacement: String = {
-                            let mut to_path = rel_path.to_str().unwrap().to_string();
-                            if to_path.starts_with("./") || to_path.starts_with("../") {
-                                to_path
-                            } else {
-                                to_path.insert_str(0, "./");
-                                to_path
-                            }
-                        };
+                        let replacement = to_replace_source(
+                            id,
+                            &dep.source,
+                            &module_dist_path,
+                            &module_graph,
+                            context,
+                        )?;
 
                         Ok((
                             dep.source.clone(),
@@ -129,6 +124,10 @@ impl BundlessCompiler {
         let to = self.context.config.output.path.join(&filename);
         let to = normalize_extension(to);
 
+        self.write_file(to, content);
+    }
+
+    fn write_file<C: AsRef<[u8]>>(&self, to: PathBuf, content: C) {
         self.context
             .plugin_driver
             .before_write_fs(&to, content.as_ref(), &self.context)
@@ -143,8 +142,17 @@ impl BundlessCompiler {
         self.transform_all()?;
 
         let mg = self.context.module_graph.read().unwrap();
-
-        let ids = mg.get_module_ids();
+        let preserve_modules = self.context.config.output.is_preserve_modules();
+
+        let ids = mg
+            .get_module_ids()
+            .into_iter()
+            .filter(|id| {
+                // externals are kept as bare imports, assets are copied with their importers
+                let module = mg.get_module(id).expect("module not exits");
+                !preserve_modules || !(module.is_external() || is_asset(module))
+            })
+            .collect::<Vec<_>>();
 
         // TODO try tokio fs later
         ids.iter().for_each(|id| {
@@ -152,7 +160,7 @@ impl BundlessCompiler {
             create_dir_all(target.parent().unwrap()).unwrap();
         });
 
-        ids.par_iter().for_each(|id| {
+        ids.par_iter().try_for_each(|id| -> Result<()> {
             let module = mg.get_module(id).expect("module not exits");
 
             let info = module.info.as_ref().expect("module info missing");
@@ -168,13 +176,44 @@ impl BundlessCompiler {
                         self.write_to_dist(target, code);
                     }
                 }
-                ModuleAst::Css(_style) => {}
+                ModuleAst::Css(css_ast) => {
+                    if preserve_modules {
+                        let code = css_ast.generate(self.context.clone())?.code;
+                        let target = to_dist_path(&id.id, &self.context).with_extension("css");
+                        self.write_file(target, code);
+                    }
+                }
                 ModuleAst::None => {
                     let target = to_dist_path(&id.id, &self.context);
                     self.write_to_dist(target, &info.raw);
                 }
             }
-        });
+
+            Ok(())
+        })?;
+
+        if preserve_modules {
+            // assets keep their relative paths, so the ones with the same name don't collide
+            let asset_files = mg
+                .modules()
+                .into_iter()
+                .filter(|module| is_asset(module))
+                .map(|module| {
+                    let file = &module.info.as_ref().expect("module info missing").file;
+                    (module.id.id.clone(), file.pathname.clone())
+                })
+                .collect::<HashMap<_, _>>();
+            asset_files
+                .par_iter()
+                .try_for_each(|(id, pathname)| -> Result<()> {
+                    let content = fs::read(pathname)
+                        .map_err(|e| anyhow!("failed to copy asset {}: {}", id, e))?;
+                    let target = to_dist_path(pathname.to_string_lossy(), &self.context);
+                    create_dir_all(target.parent().unwrap())?;
+                    self.write_file(target, content);
+                    Ok(())
+                })?;
+        }
 
         Ok(())
     }
@@ -246,8 +285,14 @@ fn transform_js_generate(
                             };
                             ast.ast.visit_mut_with(&mut dep_replacer);
 
-                            let mut dynamic_import = DynamicImport::new(context.clone(), dep_map);
-                            ast.ast.visit_mut_with(&mut dynamic_import);
+                            if context.config.output.is_preserve_modules() {
+                                // dynamic imports are left to the consumer of the output
+                                ast.ast.visit_mut_with(&mut DynamicImportSource { dep_map });
+                            } else {
+                                let mut dynamic_import =
+                                    DynamicImport::new(context.clone(), dep_map);
+                                ast.ast.visit_mut_with(&mut dynamic_import);
+                            }
 
                             ast.ast
                                 .visit_mut_with(&mut hygiene_with_config(hygiene::Config {
@@ -284,6 +329,93 @@ fn transform_js_generate(
         .unwrap();
 }
 
+fn to_replace_source(
+    dep_id: &ModuleId,
+    source: &str,
+    module_dist_path: &Path,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Result<String> {
+    if context.config.output.is_preserve_modules()
+        && let Some(dep_module) = module_graph.get_module(dep_id)
+        && dep_module.is_external()
+    {
+        return Ok(source.to_string());
+    }
+
+    // assets are copied to their relative paths, without the query of the id
+    let asset_pathname = module_graph
+        .get_module(dep_id)
+        .filter(|dep_module| context.config.output.is_preserve_modules() && is_asset(dep_module))
+        .map(|dep_module| dep_module.info.as_ref().unwrap().file.pathname.clone());
+    let dep_dist_path = match &asset_pathname {
+        Some(pathname) => to_dist_path(pathname.to_string_lossy(), context),
+        None => to_dist_path(&dep_id.id, context),
+    };
+
+    let rel_path = diff_paths(&dep_dist_path, module_dist_path).ok_or_else(|| {
+        anyhow!(
+            "failed to get relative path from {:?} to {:?}",
+            dep_dist_path,
+            module_dist_path
+        )
+    })?;
+
+    let rel_path = match module_graph.get_module(dep_id) {
+        Some(dep_module)
+            if context.config.output.is_preserve_modules()
+                && matches!(
+                    dep_module.info.as_ref().map(|info| &info.ast),
+                    Some(ModuleAst::Css(_))
+                ) =>
+        {
+            // less etc. are emitted as css
+            rel_path.with_extension("css")
+        }
+        _ if asset_pathname.is_some() => rel_path,
+        _ => normalize_extension(rel_path),
+    };
+
+    let mut to_path = rel_path.to_str().unwrap().to_string();
+    if to_path.starts_with("./") || to_path.starts_with("../") {
+        Ok(to_path)
+    } else {
+        to_path.insert_str(0, "./");
+        Ok(to_path)
+    }
+}
+
+// files which are not js or css, e.g. images, are copied as is with preserveModules
+fn is_asset(module: &Module) -> bool {
+    module.info.as_ref().is_some_and(|info| {
+        matches!(info.ast, ModuleAst::Script(_))
+            && info.external.is_none()
+            && !JS_EXTENSIONS.contains(&info.file.extname.as_str())
+    })
+}
+
+// `import("./foo")` => `import("./foo.js")`
+struct DynamicImportSource<'a> {
+    dep_map: &'a DependenciesToReplace,
+}
+
+impl VisitMut for DynamicImportSource<'_> {
+    fn visit_mut_call_expr(&mut self, call_expr: &mut CallExpr) {
+        if is_dynamic_import(call_expr)
+            && let Some(ExprOrSpread {
+                expr: box Expr::Lit(Lit::Str(source)),
+                ..
+            }) = call_expr.args.first_mut()
+            && let Some(replace_info) = self.dep_map.resolved.get(source.value.as_ref())
+        {
+            let span = source.span;
+            *source = Str::from(replace_info.to_replace_source.clone());
+            source.span = span;
+        }
+        call_expr.visit_mut_children_with(self);
+    }
+}
+
 pub fn to_dist_path<P: AsRef<str>>(abs_path: P, context: &Arc<Context>) -> PathBuf {
     let str = abs_path.as_ref();
 
@@ -305,7 +437,7 @@ fn normalize_extension(to: PathBuf) -> PathBuf {
         let ext = ext.to_str().unwrap();
 
         return match ext {
-            "js" | "json" => to,
+            "js" | "json" | "css" => to,
             "mjs" => to.with_extension("mjs.js"),
             "cjs" => to.with_extension("cjs.js"),
             "jsx" | "tsx" | "ts" => to.with_extension("js"),
@@ -318,3 +450,52 @@ fn normalize_extension(to: PathBuf) -> PathBuf {
     }
     to
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_preserve_modules() {
+        let compiler = setup_compiler("test/build/preserve-modules", false);
+        compiler.compile().unwrap();
+        let dist = &compiler.context.config.output.path;
+        let read = |name: &str| fs::read_to_string(dist.join(name)).unwrap();
+
+        let index = read("index.js");
+        assert!(index.contains(r#"from "./utils/math.js""#));
+        assert!(index.contains(r#"from "./components/Button.js""#));
+        assert!(index.contains(r#"export * from "./utils/format.js""#));
+        assert!(index.contains(r#"import("./lazy.js")"#));
+
+        let button = read("components/Button.js");
+        // externals are kept as bare imports
+        assert!(button.contains(r#"from "react""#));
+        assert!(button.contains(r#"import "./Button.css""#));
+        assert!(button.contains(r#"from "./icon.png""#));
+        assert!(button.contains(r#"from "../icons/icon.png""#));
+        assert!(read("components/Button.css").contains(".button"));
+        // the assets with the same name keep their directories
+        let root = &compiler.context.root;
+        for icon in ["components/icon.png", "icons/icon.png"] {
+            assert_eq!(
+                fs::read(dist.join(icon)).unwrap(),
+                fs::read(root.join("src").join(icon)).unwrap()
+            );
+        }
+
+        let math = read("utils/math.js");
+        assert!(math.contains("function add"));
+        // unused statements are still shaken
+        assert!(!math.contains("function sub"));
+        assert!(dist.join("utils/format.js").exists());
+        assert!(dist.join("lazy.js").exists());
+
+        for file in ["index.js", "components/Button.js", "utils/math.js"] {
+            assert!(!read(file).contains("__mako_require__"));
+        }
+        assert!(!dist.join("react").exists());
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use pathdiff::diff_paths;
+use thiserror::Error;
+
+use crate::compiler::{Compiler, Context};
+use crate::config::LintLevel;
+use crate::module::ModuleId;
+use crate::plugin::Plugin;
+
+#[derive(Debug, Error)]
+enum CircularLintError {
+    #[error("Circular require: {}", .cycle.join(" -> "))]
+    CircularRequire { cycle: Vec<String> },
+    #[error("Circular dependency: {}", .cycle.join(" -> "))]
+    CircularDependency { cycle: Vec<String> },
+    #[error("{}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n"))]
+    Cycles(Vec<CircularLintError>),
+}
+
+/**
+ * Lint `require()` calls which create a cycle, the required module gets the incomplete
+ * `module.exports` of the module which is still being executed, e.g.
+ *
+ * "src/a.js" -> "src/b.js" -> "src/a.js"
+ *
+ * The level is set with `lint.circularRequire`, `"warn"` prints the cycles and `"error"`
+ * fails the build.
+ *
+ * `lint.circularDependency` lints the cycles of all the dependencies which are loaded
+ * synchronously in the same way, including esm imports.
+ *
+ * The cycles inside node_modules are left out, they can't be fixed in the project.
+ */
+pub struct CircularLint {}
+
+impl Plugin for CircularLint {
+    fn name(&self) -> &str {
+        "circular_lint"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        let lint = &context.config.lint;
+        let relative_cycle = |cycle: &Vec<ModuleId>| {
+            cycle
+                .iter()
+                .map(|id| relative_path(id, context))
+                .collect::<Vec<_>>()
+        };
+        let module_graph = context.module_graph.read().unwrap();
+        let in_node_modules = |cycle: &Vec<ModuleId>| {
+            cycle.iter().all(|id| {
+                module_graph
+                    .get_module(id)
+                    .and_then(|module| module.info.as_ref())
+                    .is_some_and(|info| info.file.is_under_node_modules)
+            })
+        };
+        let require_cycles = if lint.circular_require != LintLevel::Off {
+            module_graph.require_cycles()
+        } else {
+            vec![]
+        };
+        let mut cycles = require_cycles
+            .iter()
+            .filter(|cycle| !in_node_modules(cycle))
+            .map(|cycle| {
+                (
+                    lint.circular_require,
+                    CircularLintError::CircularRequire {
+                        cycle: relative_cycle(cycle),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        if lint.circular_dependency != LintLevel::Off {
+            let dependency_cycles = module_graph.dependency_cycles();
+            cycles.extend(
+                dependency_cycles
+                    .iter()
+                    // the cycles of `require()` calls are reported once, both of them start
+                    // with the smallest module
+                    .filter(|cycle| !in_node_modules(cycle) && !require_cycles.contains(cycle))
+                    .map(|cycle| {
+                        (
+                            lint.circular_dependency,
+                            CircularLintError::CircularDependency {
+                                cycle: relative_cycle(cycle),
+                            },
+                        )
+                    }),
+            );
+        }
+        drop(module_graph);
+
+        let (errors, warnings): (Vec<_>, Vec<_>) = cycles
+            .into_iter()
+            .partition(|(level, _)| *level == LintLevel::Error);
+        for (_, warning) in &warnings {
+            context.warn(warning.to_string());
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!(CircularLintError::Cycles(
+                errors.into_iter().map(|(_, error)| error).collect()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn relative_path(id: &ModuleId, context: &Context) -> String {
+    let absolute_path = PathBuf::from(&id.id);
+    let relative_path = diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
+    format!(r#""{}""#, relative_path.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{Args, CompileResult, Compiler};
+    use crate::config::{Config, LintLevel};
+    use crate::utils::test_helper::setup_logger;
+
+    fn build(
+        circular_require: LintLevel,
+        circular_dependency: LintLevel,
+    ) -> anyhow::Result<CompileResult> {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/circular-require");
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.lint.circular_require = circular_require;
+        config.lint.circular_dependency = circular_dependency;
+        let compiler = Compiler::new(config, root, Args { watch: false }, None).unwrap();
+        compiler.compile()
+    }
+
+    #[test]
+    fn test_circular_require_error() {
+        let message = build(LintLevel::Error, LintLevel::Off)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.contains(r#"Circular require: "a.js" -> "b.js" -> "a.js""#),
+            "{}",
+            message
+        );
+        // cycles of esm imports are fine
+        assert!(!message.contains("esm-a.js"), "{}", message);
+    }
+
+    #[test]
+    fn test_circular_require_warn() {
+        let result = build(LintLevel::Warn, LintLevel::Off).unwrap();
+        let messages = result
+            .warnings
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .collect::<Vec<_>>();
+        // the cycle inside node_modules/cyclic is left out
+        assert_eq!(
+            messages,
+            vec![r#"Circular require: "a.js" -> "b.js" -> "a.js""#]
+        );
+        assert!(result
+            .output_files
+            .iter()
+            .any(|file| file.path == "index.js"));
+    }
+
+    #[test]
+    fn test_circular_require_off() {
+        assert!(build(LintLevel::Off, LintLevel::Off).is_ok());
+    }
+
+    #[test]
+    fn test_circular_dependency_error() {
+        let message = build(LintLevel::Off, LintLevel::Error)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.contains(r#"Circular dependency: "esm-a.js" -> "esm-b.js" -> "esm-a.js""#),
+            "{}",
+            message
+        );
+        assert!(
+            message.contains(r#"Circular dependency: "a.js" -> "b.js" -> "a.js""#),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_circular_require_and_dependency() {
+        let result = build(LintLevel::Warn, LintLevel::Warn).unwrap();
+        let messages = result
+            .warnings
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .collect::<Vec<_>>();
+        // the cycle of require() calls is not reported again as a circular dependency
+        assert_eq!(
+            messages,
+            vec![
+                r#"Circular require: "a.js" -> "b.js" -> "a.js""#,
+                r#"Circular dependency: "esm-a.js" -> "esm-b.js" -> "esm-a.js""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_circular_dependency_warn() {
+        let result = build(LintLevel::Off, LintLevel::Warn).unwrap();
+        let messages = result
+            .warnings
+            .iter()
+            .map(|warning| warning.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                r#"Circular dependency: "a.js" -> "b.js" -> "a.js""#,
+                r#"Circular dependency: "esm-a.js" -> "esm-b.js" -> "esm-a.js""#,
+            ]
+        );
+    }
+}
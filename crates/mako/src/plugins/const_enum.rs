@@ -0,0 +1,667 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use pathdiff::diff_paths;
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::{
+    BinaryOp, Decl, ExportDecl, ExportSpecifier, Expr, Id, Ident, ImportDecl, ImportSpecifier,
+    KeyValueProp, Lit, MemberExpr, MemberProp, Module as SwcModule, ModuleDecl, ModuleExportName,
+    ModuleItem, Number, ObjectLit, Pat, Prop, PropName, PropOrSpread, Stmt, TsEnumDecl,
+    TsEnumMemberId, UnaryExpr, UnaryOp, VarDeclKind, VarDeclarator,
+};
+use swc_core::ecma::utils::{quote_str, ExprFactory};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::file::{win_path, File};
+use crate::compiler::{Compiler, Context};
+use crate::module::{ModuleAst, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstEnumValue {
+    Number(f64),
+    String(String),
+}
+
+impl ConstEnumValue {
+    fn to_expr(&self) -> Expr {
+        match self {
+            ConstEnumValue::Number(value) if *value < 0.0 => UnaryExpr {
+                span: DUMMY_SP,
+                op: UnaryOp::Minus,
+                arg: Box::new(ConstEnumValue::Number(-value).to_expr()),
+            }
+            .into(),
+            ConstEnumValue::Number(value) => Lit::Num(Number {
+                span: DUMMY_SP,
+                value: *value,
+                raw: None,
+            })
+            .into(),
+            ConstEnumValue::String(value) => quote_str!(value.clone()).into(),
+        }
+    }
+
+    fn to_js_string(&self) -> String {
+        match self {
+            ConstEnumValue::Number(value) => value.to_string(),
+            ConstEnumValue::String(value) => value.clone(),
+        }
+    }
+}
+
+// members of a const enum by name, in the declaration order
+pub type ConstEnumMembers = IndexMap<String, ConstEnumValue>;
+
+/**
+ * Inline the members of the ts `const enum`s imported from other modules, e.g.
+ * `Direction.Up` becomes `1`, through re-exports too.
+ *
+ * The const enums are erased by the ts transform, so they are lowered to plain objects in
+ * build with `lower_const_enums`. Once all the uses of an imported enum are inlined, the
+ * import specifier is removed, and the enum object can be dropped by tree shaking. The enums
+ * accessed dynamically, e.g. `Direction[key]`, keep the import and the object with a warning.
+ */
+pub struct ConstEnumPlugin {}
+
+impl Plugin for ConstEnumPlugin {
+    fn name(&self) -> &str {
+        "const_enum"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        inline_const_enums(context);
+        Ok(())
+    }
+
+    fn after_update(&self, compiler: &Compiler) -> Result<()> {
+        inline_const_enums(&compiler.context);
+        Ok(())
+    }
+}
+
+/// Replace the top level `const enum`s of a ts module with plain objects, which are not
+/// erased by the ts transform, and return their members by the enum name.
+///
+/// The enums whose members can't be evaluated at compile time are left to the ts transform.
+pub fn lower_const_enums(ast: &mut ModuleAst, file: &File) -> HashMap<String, ConstEnumMembers> {
+    match ast {
+        ModuleAst::Script(ast) if file.is_ts() => lower(&mut ast.ast),
+        _ => HashMap::new(),
+    }
+}
+
+fn lower(ast: &mut SwcModule) -> HashMap<String, ConstEnumMembers> {
+    let mut const_enums = HashMap::new();
+    for item in ast.body.iter_mut() {
+        let decl = match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => decl,
+            ModuleItem::Stmt(Stmt::Decl(decl)) => decl,
+            _ => continue,
+        };
+        let Decl::TsEnum(ts_enum) = decl else {
+            continue;
+        };
+        if !ts_enum.is_const || ts_enum.declare {
+            continue;
+        }
+        let Some(members) = eval_members(ts_enum) else {
+            continue;
+        };
+
+        let object = ObjectLit {
+            span: DUMMY_SP,
+            props: members
+                .iter()
+                .map(|(name, value)| {
+                    PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(quote_str!(name.clone())),
+                        value: Box::new(value.to_expr()),
+                    })))
+                })
+                .collect(),
+        };
+        let name = ts_enum.id.sym.to_string();
+        let mut var_decl =
+            Expr::Object(object).into_var_decl(VarDeclKind::Var, ts_enum.id.clone().into());
+        var_decl.span = ts_enum.span;
+        *decl = Decl::Var(Box::new(var_decl));
+        const_enums.insert(name, members);
+    }
+    const_enums
+}
+
+fn eval_members(ts_enum: &TsEnumDecl) -> Option<ConstEnumMembers> {
+    let mut members = ConstEnumMembers::new();
+    // members without initializers follow the previous numeric member
+    let mut next = Some(0.0);
+    for member in &ts_enum.members {
+        let name = match &member.id {
+            TsEnumMemberId::Ident(ident) => ident.sym.to_string(),
+            TsEnumMemberId::Str(str) => str.value.to_string(),
+        };
+        let value = match &member.init {
+            Some(init) => eval(init, &ts_enum.id.sym, &members)?,
+            None => ConstEnumValue::Number(next?),
+        };
+        next = match value {
+            ConstEnumValue::Number(value) => Some(value + 1.0),
+            ConstEnumValue::String(_) => None,
+        };
+        members.insert(name, value);
+    }
+    Some(members)
+}
+
+// a member initializer, which may refer to the earlier members, e.g. `C = A | B` and
+// `D = Enum.A * 2`
+fn eval(expr: &Expr, enum_name: &str, members: &ConstEnumMembers) -> Option<ConstEnumValue> {
+    let value = match expr {
+        Expr::Lit(Lit::Num(number)) => ConstEnumValue::Number(number.value),
+        Expr::Lit(Lit::Str(str)) => ConstEnumValue::String(str.value.to_string()),
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            ConstEnumValue::String(tpl.quasis.first()?.cooked.as_ref()?.to_string())
+        }
+        Expr::Paren(paren) => eval(&paren.expr, enum_name, members)?,
+        Expr::Ident(ident) => members.get(&*ident.sym)?.clone(),
+        Expr::Member(MemberExpr {
+            obj: box Expr::Ident(obj),
+            prop,
+            ..
+        }) if obj.sym == enum_name => members.get(&member_name(prop)?)?.clone(),
+        Expr::Unary(unary) => {
+            let ConstEnumValue::Number(value) = eval(&unary.arg, enum_name, members)? else {
+                return None;
+            };
+            ConstEnumValue::Number(match unary.op {
+                UnaryOp::Minus => -value,
+                UnaryOp::Plus => value,
+                UnaryOp::Tilde => !to_int32(value) as f64,
+                _ => return None,
+            })
+        }
+        Expr::Bin(bin) => {
+            let left = eval(&bin.left, enum_name, members)?;
+            let right = eval(&bin.right, enum_name, members)?;
+            match (left, right) {
+                (ConstEnumValue::Number(left), ConstEnumValue::Number(right)) => {
+                    ConstEnumValue::Number(eval_number_op(bin.op, left, right)?)
+                }
+                (left, right) if bin.op == BinaryOp::Add => {
+                    ConstEnumValue::String(left.to_js_string() + &right.to_js_string())
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    // NaN and Infinity are not allowed in const enums
+    match value {
+        ConstEnumValue::Number(value) if !value.is_finite() => None,
+        value => Some(value),
+    }
+}
+
+fn eval_number_op(op: BinaryOp, left: f64, right: f64) -> Option<f64> {
+    let shift = to_int32(right) as u32 & 31;
+    let value = match op {
+        BinaryOp::Add => left + right,
+        BinaryOp::Sub => left - right,
+        BinaryOp::Mul => left * right,
+        BinaryOp::Div => left / right,
+        BinaryOp::Mod => left % right,
+        BinaryOp::Exp => left.powf(right),
+        BinaryOp::BitOr => (to_int32(left) | to_int32(right)) as f64,
+        BinaryOp::BitAnd => (to_int32(left) & to_int32(right)) as f64,
+        BinaryOp::BitXor => (to_int32(left) ^ to_int32(right)) as f64,
+        BinaryOp::LShift => to_int32(left).wrapping_shl(shift) as f64,
+        BinaryOp::RShift => to_int32(left).wrapping_shr(shift) as f64,
+        BinaryOp::ZeroFillRShift => (to_int32(left) as u32).wrapping_shr(shift) as f64,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn to_int32(value: f64) -> i32 {
+    value as i64 as i32
+}
+
+fn member_name(prop: &MemberProp) -> Option<String> {
+    match prop {
+        MemberProp::Ident(ident) => Some(ident.sym.to_string()),
+        MemberProp::Computed(computed) => match computed.expr.as_ref() {
+            Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+// a const enum used by a module, imported from `source` or declared in the module
+struct UsedConstEnum {
+    source: Option<String>,
+    members: ConstEnumMembers,
+}
+
+fn inline_const_enums(context: &Arc<Context>) {
+    let mut module_graph = context.module_graph.write().unwrap();
+    let has_const_enums = module_graph.modules().iter().any(|module| {
+        module
+            .info
+            .as_ref()
+            .is_some_and(|info| !info.const_enums.is_empty())
+    });
+    if !has_const_enums {
+        return;
+    }
+
+    let mut used_const_enums: HashMap<ModuleId, HashMap<Id, UsedConstEnum>> = HashMap::new();
+    for module in module_graph.modules() {
+        let Some(info) = &module.info else {
+            continue;
+        };
+        let ModuleAst::Script(ast) = &info.ast else {
+            continue;
+        };
+        let mut const_enums = HashMap::new();
+        for item in &ast.ast.body {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) if !import.type_only => {
+                    let source = import.src.value.to_string();
+                    for specifier in &import.specifiers {
+                        let ImportSpecifier::Named(named) = specifier else {
+                            continue;
+                        };
+                        let imported = named
+                            .imported
+                            .as_ref()
+                            .map(export_name)
+                            .unwrap_or_else(|| named.local.sym.to_string());
+                        let members = find_const_enum_in_dep(
+                            &module_graph,
+                            &module.id,
+                            &source,
+                            &imported,
+                            &mut HashSet::new(),
+                        );
+                        if let Some(members) = members {
+                            const_enums.insert(
+                                named.local.to_id(),
+                                UsedConstEnum {
+                                    source: Some(source.clone()),
+                                    members,
+                                },
+                            );
+                        }
+                    }
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    decl: Decl::Var(var_decl),
+                    ..
+                }))
+                | ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => {
+                    for decl in &var_decl.decls {
+                        if let Pat::Ident(binding) = &decl.name
+                            && let Some(members) = info.const_enums.get(&*binding.id.sym)
+                        {
+                            const_enums.insert(
+                                binding.id.to_id(),
+                                UsedConstEnum {
+                                    source: None,
+                                    members: members.clone(),
+                                },
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !const_enums.is_empty() {
+            used_const_enums.insert(module.id.clone(), const_enums);
+        }
+    }
+
+    for (module_id, const_enums) in used_const_enums {
+        let module = module_graph.get_module_mut(&module_id).unwrap();
+        let ast = module.info.as_mut().unwrap().ast.as_script_ast_mut();
+        let mut inliner = ConstEnumInliner {
+            const_enums: const_enums
+                .iter()
+                .map(|(id, const_enum)| (id.clone(), &const_enum.members))
+                .collect(),
+            dynamic_uses: Default::default(),
+            re_exported: Default::default(),
+        };
+        ast.visit_mut_with(&mut inliner);
+
+        let inlined = const_enums
+            .iter()
+            .filter(|(id, const_enum)| {
+                const_enum.source.is_some()
+                    && !inliner.dynamic_uses.contains(*id)
+                    && !inliner.re_exported.contains(*id)
+            })
+            .map(|(id, _)| id.clone())
+            .collect::<HashSet<_>>();
+        remove_import_specifiers(ast, &inlined);
+
+        let mut dynamic_uses = const_enums
+            .iter()
+            .filter(|(id, const_enum)| {
+                const_enum.source.is_some() && inliner.dynamic_uses.contains(*id)
+            })
+            .collect::<Vec<_>>();
+        dynamic_uses.sort_by_key(|(id, _)| id.0.to_string());
+        for (id, const_enum) in dynamic_uses {
+            let path = PathBuf::from(&module_id.id);
+            context.warn(format!(
+                "const enum {} imported from {} is accessed dynamically in {}, it's kept as an object at runtime",
+                id.0,
+                const_enum.source.as_ref().unwrap(),
+                win_path(
+                    &diff_paths(&path, &context.root)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                )
+            ));
+        }
+    }
+}
+
+/// The modules which may have inlined the const enums of the module, i.e. its importers and
+/// the importers of the modules re-exporting them. They keep the stale values when the enums
+/// change, so they are rebuilt with the module in watch mode.
+pub fn const_enum_dependents(module_graph: &ModuleGraph, module_id: &ModuleId) -> Vec<ModuleId> {
+    let has_const_enums = module_graph
+        .get_module(module_id)
+        .and_then(|module| module.info.as_ref())
+        .is_some_and(|info| !info.const_enums.is_empty());
+    if !has_const_enums {
+        return vec![];
+    }
+
+    let mut dependents = vec![];
+    let mut visited = HashSet::from([module_id.clone()]);
+    let mut queue = vec![module_id.clone()];
+    while let Some(module_id) = queue.pop() {
+        for (dependent, dep) in module_graph.get_dependents(&module_id) {
+            if !visited.insert(dependent.clone()) {
+                continue;
+            }
+            dependents.push(dependent.clone());
+            if matches!(
+                dep.resolve_type,
+                ResolveType::ExportNamed(_) | ResolveType::ExportAll
+            ) {
+                queue.push(dependent.clone());
+            }
+        }
+    }
+    dependents
+}
+
+fn remove_import_specifiers(ast: &mut SwcModule, ids: &HashSet<Id>) {
+    if ids.is_empty() {
+        return;
+    }
+    for item in ast.body.iter_mut() {
+        if let ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl { specifiers, .. })) = item {
+            // `import "x"` is left when all the specifiers are removed, which is dropped by
+            // tree shaking if the module has no side effects
+            specifiers.retain(|specifier| {
+                !matches!(specifier, ImportSpecifier::Named(named) if ids.contains(&named.local.to_id()))
+            });
+        }
+    }
+}
+
+// the members of the const enum exported as `name` by the module imported with `source`
+fn find_const_enum_in_dep(
+    module_graph: &ModuleGraph,
+    module_id: &ModuleId,
+    source: &str,
+    name: &str,
+    visited: &mut HashSet<(ModuleId, String)>,
+) -> Option<ConstEnumMembers> {
+    let dep_id = module_graph.get_dependency_module_by_source(module_id, &source.to_string())?;
+    find_const_enum(module_graph, dep_id, name, visited)
+}
+
+// the members of the const enum exported as `name` by the module, follows the re-exports,
+// e.g. `export * from "./enums"` and `export { Kind as K } from "./kind"`
+fn find_const_enum(
+    module_graph: &ModuleGraph,
+    module_id: &ModuleId,
+    name: &str,
+    visited: &mut HashSet<(ModuleId, String)>,
+) -> Option<ConstEnumMembers> {
+    if !visited.insert((module_id.clone(), name.to_string())) {
+        return None;
+    }
+    let info = module_graph.get_module(module_id)?.info.as_ref()?;
+    let ast = &info.ast.as_script()?.ast;
+
+    for item in &ast.body {
+        let ModuleItem::ModuleDecl(module_decl) = item else {
+            continue;
+        };
+        match module_decl {
+            ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var_decl),
+                ..
+            }) => {
+                let declared = var_decl.decls.iter().any(
+                    |decl| matches!(&decl.name, Pat::Ident(binding) if binding.id.sym == name),
+                );
+                if declared {
+                    return info.const_enums.get(name).cloned();
+                }
+            }
+            ModuleDecl::ExportNamed(export_named) => {
+                for specifier in &export_named.specifiers {
+                    let ExportSpecifier::Named(named) = specifier else {
+                        continue;
+                    };
+                    let orig = export_name(&named.orig);
+                    let exported = named
+                        .exported
+                        .as_ref()
+                        .map(export_name)
+                        .unwrap_or_else(|| orig.clone());
+                    if exported != name {
+                        continue;
+                    }
+                    return match &export_named.src {
+                        Some(src) => find_const_enum_in_dep(
+                            module_graph,
+                            module_id,
+                            &src.value,
+                            &orig,
+                            visited,
+                        ),
+                        // `export { Kind }` of a local enum or an imported one
+                        None => info.const_enums.get(&orig).cloned().or_else(|| {
+                            let (source, imported) = find_import(ast, &orig)?;
+                            find_const_enum_in_dep(
+                                module_graph,
+                                module_id,
+                                &source,
+                                &imported,
+                                visited,
+                            )
+                        }),
+                    };
+                }
+            }
+            ModuleDecl::ExportAll(export_all) => {
+                let members = find_const_enum_in_dep(
+                    module_graph,
+                    module_id,
+                    &export_all.src.value,
+                    name,
+                    visited,
+                );
+                if members.is_some() {
+                    return members;
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// the source and the imported name of a local binding imported with a named import
+fn find_import(ast: &SwcModule, local: &str) -> Option<(String, String)> {
+    ast.body.iter().find_map(|item| {
+        let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+            return None;
+        };
+        import
+            .specifiers
+            .iter()
+            .find_map(|specifier| match specifier {
+                ImportSpecifier::Named(named) if named.local.sym == local => Some((
+                    import.src.value.to_string(),
+                    named
+                        .imported
+                        .as_ref()
+                        .map(export_name)
+                        .unwrap_or_else(|| local.to_string()),
+                )),
+                _ => None,
+            })
+    })
+}
+
+struct ConstEnumInliner<'a> {
+    const_enums: HashMap<Id, &'a ConstEnumMembers>,
+    // referenced other than the accesses of known members, e.g. `Enum[key]`
+    dynamic_uses: HashSet<Id>,
+    // re-exported with `export { Enum }`, the import is still needed
+    re_exported: HashSet<Id>,
+}
+
+impl VisitMut for ConstEnumInliner<'_> {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Member(MemberExpr {
+            obj: box Expr::Ident(obj),
+            prop,
+            ..
+        }) = expr
+            && let Some(members) = self.const_enums.get(&obj.to_id())
+            && let Some(value) = member_name(prop).and_then(|name| members.get(&name))
+        {
+            *expr = value.to_expr();
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if self.const_enums.contains_key(&ident.to_id()) {
+            self.dynamic_uses.insert(ident.to_id());
+        }
+    }
+
+    // the bindings are not uses
+    fn visit_mut_import_decl(&mut self, _import: &mut ImportDecl) {}
+
+    fn visit_mut_var_declarator(&mut self, declarator: &mut VarDeclarator) {
+        if let Pat::Ident(binding) = &declarator.name
+            && self.const_enums.contains_key(&binding.id.to_id())
+        {
+            declarator.init.visit_mut_with(self);
+            return;
+        }
+        declarator.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_export_specifier(&mut self, specifier: &mut ExportSpecifier) {
+        if let ExportSpecifier::Named(named) = specifier
+            && let ModuleExportName::Ident(orig) = &named.orig
+            && self.const_enums.contains_key(&orig.to_id())
+        {
+            self.re_exported.insert(orig.to_id());
+            return;
+        }
+        specifier.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lower, ConstEnumValue};
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+
+    #[test]
+    fn test_lower_const_enums() {
+        let mut test_utils = TestUtils::new(TestUtilsOpts {
+            file: Some("test.ts".to_string()),
+            content: Some(
+                r#"
+export const enum Direction { Up = 1, Down, Left = Up * 10, Right = -Direction.Up }
+const enum Color { Red = "RED", Green = `GREEN`, Mixed = Red + "_" + 1 }
+const enum Flags { A = 1 << 0, B = 1 << 1, AB = A | B }
+enum Kept { A }
+declare const enum Ambient { A }
+const enum Dynamic { A = Math.random() }
+"#
+                .to_string(),
+            ),
+        });
+        let const_enums = lower(&mut test_utils.ast.js_mut().ast);
+
+        let values = |name: &str| {
+            const_enums[name]
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            values("Direction"),
+            vec![
+                ("Up", ConstEnumValue::Number(1.0)),
+                ("Down", ConstEnumValue::Number(2.0)),
+                ("Left", ConstEnumValue::Number(10.0)),
+                ("Right", ConstEnumValue::Number(-1.0)),
+            ]
+        );
+        assert_eq!(
+            values("Color"),
+            vec![
+                ("Red", ConstEnumValue::String("RED".to_string())),
+                ("Green", ConstEnumValue::String("GREEN".to_string())),
+                ("Mixed", ConstEnumValue::String("RED_1".to_string())),
+            ]
+        );
+        assert_eq!(
+            values("Flags"),
+            vec![
+                ("A", ConstEnumValue::Number(1.0)),
+                ("B", ConstEnumValue::Number(2.0)),
+                ("AB", ConstEnumValue::Number(3.0)),
+            ]
+        );
+        assert!(!const_enums.contains_key("Kept"));
+        assert!(!const_enums.contains_key("Ambient"));
+        assert!(!const_enums.contains_key("Dynamic"));
+
+        let code = test_utils.js_ast_to_code();
+        assert!(code.contains(r#"export var Direction = {"#), "{}", code);
+        assert!(code.contains(r#""Right": -1"#), "{}", code);
+        assert!(code.contains("enum Kept"), "{}", code);
+    }
+}
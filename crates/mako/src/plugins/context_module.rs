@@ -27,7 +27,7 @@ impl Plugin for ContextModulePlugin {
         "context_module"
     }
 
-    fn load(&self, param: &PluginLoadParam, _context: &Arc<Context>) -> Result<Option<Content>> {
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
         if let (Some(glob_pattern), true) = (
             param
                 .file
@@ -64,7 +64,8 @@ impl Plugin for ContextModulePlugin {
                 // omit ext `./i18n/zh_CN`
                 if let Some(ext) = rlt_path.extension() {
                     if is_file
-                        && get_module_extensions().contains(&format!(".{}", ext.to_string_lossy()))
+                        && get_module_extensions(&context.config)
+                            .contains(&format!(".{}", ext.to_string_lossy()))
                     {
                         keys.insert(format!(
                             "./{}",
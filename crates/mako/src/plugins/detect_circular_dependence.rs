@@ -2,7 +2,6 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
-use colored::Colorize;
 use pathdiff::diff_paths;
 
 use crate::compiler::Context;
@@ -63,7 +62,7 @@ impl Plugin for LoopDetector {
 
             if !loop_lines.is_empty() {
                 for l in &loop_lines {
-                    println!("{} Circular Dependencies: {}", "Warning".yellow(), l);
+                    context.warn(format!("Circular Dependencies: {}", l));
                 }
 
                 if detect_loop.graphviz {
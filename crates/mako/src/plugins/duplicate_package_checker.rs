@@ -11,10 +11,10 @@ use crate::plugin::Plugin;
 use crate::resolve::ResolverResource;
 
 #[derive(Debug, Clone)]
-struct PackageInfo {
-    name: String,
-    version: Version,
-    path: PathBuf,
+pub(crate) struct PackageInfo {
+    pub name: String,
+    pub version: Version,
+    pub path: PathBuf,
 }
 
 #[derive(Default)]
@@ -47,7 +47,9 @@ fn clean_path_relative_to_context(module_path: &Path, context: &Path) -> PathBuf
     }
 }
 
-fn extract_package_info(module: &Module) -> Option<PackageInfo> {
+/// The package of the module, by the package.json it's resolved with, which is also used by
+/// the packages of the stats
+pub(crate) fn extract_package_info(module: &Module) -> Option<PackageInfo> {
     module
         .info
         .as_ref()
@@ -58,7 +60,7 @@ fn extract_package_info(module: &Module) -> Option<PackageInfo> {
                 let name = package_json.name.clone()?;
                 let raw_json = package_json.raw_json();
                 let version = raw_json.as_object()?.get("version")?;
-                let version = semver::Version::parse(version.as_str().unwrap()).ok()?;
+                let version = semver::Version::parse(version.as_str()?).ok()?;
 
                 Some(PackageInfo {
                     name,
@@ -1,5 +1,5 @@
 use std::fmt::Debug;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
@@ -9,9 +9,20 @@ use petgraph::dot::{Config, Dot};
 use petgraph::visit::{GraphProp, IntoEdgeReferences, IntoNodeReferences, NodeIndexable};
 
 use crate::compiler::Context;
+use crate::config::debug_dir;
+use crate::module::{relative_to_root, ResolveType};
 use crate::plugin::{Plugin, PluginGenerateEndParams};
 
-pub struct Graphviz {}
+pub const MODULE_GRAPH_DOT: &str = "module-graph.dot";
+
+/// Write the graphs in dot, all the module and chunk graphs of the build with `DEBUG_GRAPH`, or
+/// only the module graph of `debug.dumpModuleGraph` with `module_graph_only`, which is written
+/// to `module-graph.dot` in the debug dir with the root relative paths of the modules as the
+/// nodes, and an edge for each static import, i.e. dynamic imports and workers are left out.
+#[derive(Default)]
+pub struct Graphviz {
+    pub module_graph_only: bool,
+}
 
 impl Graphviz {
     fn write_graph<G, P>(dot_filename: P, graph: G) -> Result<()>
@@ -28,6 +39,30 @@ impl Graphviz {
         write!(file, "{:?}", dot)?;
         Ok(())
     }
+
+    fn write_module_graph(context: &Arc<Context>) -> Result<()> {
+        let module_graph = context.module_graph.read().unwrap();
+        let graph = module_graph.graph.filter_map(
+            |_, module| Some(relative_to_root(&module.id.id, &context.root)),
+            |_, deps| {
+                deps.iter()
+                    .any(|dep| {
+                        !matches!(
+                            dep.resolve_type,
+                            ResolveType::DynamicImport(_) | ResolveType::Worker(_)
+                        )
+                    })
+                    .then_some("")
+            },
+        );
+
+        // the labels are displayed without the quotes of `Debug`
+        let dot = Dot::with_config(&graph, &[Config::EdgeNoLabel]);
+        let dir = debug_dir(&context.root);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(MODULE_GRAPH_DOT), dot.to_string())?;
+        Ok(())
+    }
 }
 
 impl Plugin for Graphviz {
@@ -36,6 +71,9 @@ impl Plugin for Graphviz {
     }
 
     fn generate_begin(&self, context: &Arc<Context>) -> Result<()> {
+        if self.module_graph_only {
+            return Ok(());
+        }
         Graphviz::write_graph(
             context.root.join("_mako_module_graph_origin.dot"),
             &context.module_graph.read().unwrap().graph,
@@ -44,6 +82,9 @@ impl Plugin for Graphviz {
     }
 
     fn before_optimize_chunk(&self, context: &Arc<Context>) -> Result<()> {
+        if self.module_graph_only {
+            return Ok(());
+        }
         Graphviz::write_graph(
             context.root.join("_mako_chunk_graph_origin.dot"),
             &context.chunk_graph.read().unwrap().graph,
@@ -56,6 +97,10 @@ impl Plugin for Graphviz {
         _params: &PluginGenerateEndParams,
         context: &Arc<Context>,
     ) -> Result<()> {
+        if self.module_graph_only {
+            return Graphviz::write_module_graph(context);
+        }
+
         Graphviz::write_graph(
             context.root.join("_mako_chunk_graph_finale.dot"),
             &context.chunk_graph.read().unwrap().graph,
@@ -69,3 +114,34 @@ impl Plugin for Graphviz {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::MODULE_GRAPH_DOT;
+    use crate::config::debug_dir;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_dump_module_graph() {
+        let compiler = setup_compiler("test/build/dump-module-graph", false);
+        compiler.compile().unwrap();
+
+        let dot =
+            fs::read_to_string(debug_dir(&compiler.context.root).join(MODULE_GRAPH_DOT)).unwrap();
+        let nodes = dot.lines().filter(|line| line.contains("label =")).count();
+        let edges = dot.lines().filter(|line| line.contains(" -> ")).count();
+        // index, a, b and the lazily imported c
+        assert_eq!(nodes, 4, "{}", dot);
+        assert_eq!(edges, 3, "{}", dot);
+        assert!(dot.contains(r#"label = "./src/index.ts""#), "{}", dot);
+        assert!(!compiler
+            .context
+            .config
+            .output
+            .path
+            .join(MODULE_GRAPH_DOT)
+            .exists());
+    }
+}
@@ -50,32 +50,50 @@
     }
     return fn;
   };
-  var applyHotUpdate = function (_chunkId, update) {
-    var modules = update.modules,
-      removedModules = update.removedModules;
+  // the data passed to the dispose handlers, handed to the next instance as hot.data
+  var hotData = {};
+  var requireAccepted = function (moduleId, parentId) {
+    var module = modulesRegistry[moduleId];
+    if (module) {
+      if (!module.parents.includes(parentId)) {
+        module.parents.push(parentId);
+      }
+      return module.exports;
+    }
+    currentParents = [parentId];
+    currentChildModule = moduleId;
+    return requireModule(moduleId);
+  };
+  var applyModulesUpdate = function (updatedModuleIds, modules) {
     var outdatedModules = [];
-    for (var _i = 0, _a = Object.keys(modules); _i < _a.length; _i++) {
-      var moduleId = _a[_i];
-      if (!modulesRegistry[moduleId]) continue;
+    // parent id => the updated children which are accepted by the parent
+    var outdatedDependencies = {};
+    var queue = updatedModuleIds.slice();
+    while (queue.length) {
+      var moduleId = queue.pop();
+      var module = modulesRegistry[moduleId];
+      if (!module) continue;
       if (outdatedModules.includes(moduleId)) continue;
       outdatedModules.push(moduleId);
-      var queue = [moduleId];
-      while (queue.length) {
-        var item = queue.pop();
-        var module = modulesRegistry[item];
-        if (!module) continue;
-        if (module.hot._main) {
-          location.reload();
-        }
-        if (module.hot._selfAccepted) {
+      if (module.hot._main) {
+        location.reload();
+        return;
+      }
+      if (module.hot._selfAccepted && !module.hot._selfInvalidated) {
+        continue;
+      }
+      for (var _b = 0, _c = module.parents; _b < _c.length; _b++) {
+        var parentModule = _c[_b];
+        var parent = modulesRegistry[parentModule];
+        if (parent && parent.hot._acceptedDependencies[moduleId]) {
+          var accepted = (outdatedDependencies[parentModule] =
+            outdatedDependencies[parentModule] || []);
+          if (!accepted.includes(moduleId)) {
+            accepted.push(moduleId);
+          }
           continue;
         }
-        for (var _b = 0, _c = module.parents; _b < _c.length; _b++) {
-          var parentModule = _c[_b];
-          if (outdatedModules.includes(parentModule)) continue;
-          outdatedModules.push(parentModule);
-          queue.push(parentModule);
-        }
+        queue.push(parentModule);
       }
     }
     var outdatedSelfAcceptedModules = [];
@@ -86,7 +104,7 @@
     ) {
       var moduleId = outdatedModules_1[_d];
       var module = modulesRegistry[moduleId];
-      if (module.hot._selfAccepted) {
+      if (module.hot._selfAccepted && !module.hot._selfInvalidated) {
         outdatedSelfAcceptedModules.push(module);
       }
     }
@@ -97,10 +115,12 @@
     ) {
       var moduleId = outdatedModules_2[_e];
       var module = modulesRegistry[moduleId];
+      var data = {};
       for (var _f = 0, _g = module.hot._disposeHandlers; _f < _g.length; _f++) {
         var handler = _g[_f];
-        handler();
+        handler(data);
       }
+      hotData[moduleId] = data;
       module.hot.active = false;
       delete modulesRegistry[moduleId];
       for (var _j = 0, _k = module.children; _j < _k.length; _j++) {
@@ -121,7 +141,26 @@
     ) {
       var module = outdatedSelfAcceptedModules_1[_l];
       module.hot._requireSelf();
+      if (typeof module.hot._selfAccepted === 'function') {
+        module.hot._selfAccepted(modulesRegistry[module.id].exports);
+      }
     }
+    for (var parentModule in outdatedDependencies) {
+      var parent = modulesRegistry[parentModule];
+      if (!parent) continue;
+      var dependencies = outdatedDependencies[parentModule];
+      for (var _m = 0; _m < dependencies.length; _m++) {
+        var dependency = dependencies[_m];
+        var callback = parent.hot._acceptedDependencies[dependency];
+        var exports = requireAccepted(dependency, parentModule);
+        if (typeof callback === 'function') {
+          callback(exports);
+        }
+      }
+    }
+  };
+  var applyHotUpdate = function (_chunkId, update) {
+    applyModulesUpdate(Object.keys(update.modules), update.modules);
   };
   var createModuleHotObject = function (moduleId, me) {
     var _main = currentChildModule !== moduleId;
@@ -139,13 +178,33 @@
       },
       _main: _main,
       active: true,
-      accept: function () {
-        this._selfAccepted = true;
+      data: hotData[moduleId],
+      // accept(), accept(callback), accept(dependency, callback) or
+      // accept([dependencies], callback), the callbacks receive the new exports
+      accept: function (dependencies, callback) {
+        if (dependencies === undefined || typeof dependencies === 'function') {
+          this._selfAccepted = dependencies || true;
+          return;
+        }
+        if (typeof dependencies !== 'object') {
+          dependencies = [dependencies];
+        }
+        for (var i = 0; i < dependencies.length; i++) {
+          this._acceptedDependencies[dependencies[i]] = callback || true;
+        }
       },
       dispose: function (callback) {
         this._disposeHandlers.push(callback);
       },
-      invalidate: function () {},
+      // propagate the update to the importers, even if the module is accepted
+      invalidate: function () {
+        this._selfInvalidated = true;
+        Promise.resolve().then(function () {
+          if (modulesRegistry[moduleId] === me) {
+            applyModulesUpdate([moduleId], {});
+          }
+        });
+      },
       updateChunksUrlMap: function () {
         var current_hash = requireModule.currentHash();
         return fetch(
@@ -195,6 +254,7 @@
         return applyHotUpdate(update);
       },
     };
+    delete hotData[moduleId];
     currentChildModule = undefined;
     return hot;
   };
@@ -0,0 +1,443 @@
+use std::fs;
+use std::hash::Hasher;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use regex::Captures;
+use twox_hash::XxHash64;
+
+use crate::compiler::{Context, LinkHintRel};
+use crate::config::{HtmlConfig, HtmlInject};
+use crate::generate::chunk::ChunkType;
+use crate::generate::generate_chunks::{ChunkFile, ChunkFileType};
+use crate::plugin::Plugin;
+use crate::utils::create_cached_regex;
+
+const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="UTF-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+    <title><%= title %></title>
+  </head>
+  <body>
+    <div id="root"></div>
+  </body>
+</html>
+"#;
+
+const JS_FILES_PLACEHOLDER: &str = "htmlWebpackPlugin.files.js";
+const CSS_FILES_PLACEHOLDER: &str = "htmlWebpackPlugin.files.css";
+
+#[derive(Debug, Clone)]
+pub struct HtmlPluginConfig {
+    // the built-in template is used unless it's set
+    pub template: Option<PathBuf>,
+    pub filename: String,
+    pub inject: HtmlInject,
+    // entries whose initial chunks are injected, in order
+    pub entries: Vec<String>,
+    pub public_path: Option<String>,
+    pub title: Option<String>,
+}
+
+impl HtmlPluginConfig {
+    pub fn from_html_config(html_config: &HtmlConfig) -> Self {
+        Self {
+            // resolved against the root with the config
+            template: html_config.template.clone(),
+            filename: html_config.output_filename(),
+            inject: html_config.inject.clone(),
+            entries: vec![html_config.entry.clone()],
+            public_path: html_config.public_path.clone(),
+            title: html_config.title.clone(),
+        }
+    }
+}
+
+pub struct HtmlPlugin {
+    configs: Vec<HtmlPluginConfig>,
+    // `html: true` with the glob form of entry, every entry gets a html, including the ones
+    // discovered on rebuilds
+    per_entry: bool,
+}
+
+impl HtmlPlugin {
+    pub fn new(configs: Vec<HtmlPluginConfig>, per_entry: bool) -> Self {
+        Self { configs, per_entry }
+    }
+}
+
+impl Plugin for HtmlPlugin {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn after_generate_chunk_files(
+        &self,
+        chunk_files: &[ChunkFile],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let configs = if self.per_entry {
+            context
+                .entries
+                .read()
+                .unwrap()
+                .keys()
+                .map(|entry| HtmlPluginConfig::from_html_config(&HtmlConfig::for_entry(entry)))
+                .collect()
+        } else {
+            self.configs.clone()
+        };
+        for html_config in &configs {
+            let html = render_html(html_config, chunk_files, context)?;
+            let filename = &html_config.filename;
+            if context.args.watch {
+                // only written to disk when the content changes, e.g. hashed filenames changed
+                let mut hasher: XxHash64 = Default::default();
+                hasher.write(html.as_bytes());
+                context.write_static_content(filename, html.into_bytes(), hasher.finish())?;
+            } else {
+                let to = context.config.output.path.join(filename);
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(to, html)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn render_html(
+    html_config: &HtmlPluginConfig,
+    chunk_files: &[ChunkFile],
+    context: &Arc<Context>,
+) -> Result<String> {
+    let template = match &html_config.template {
+        Some(template) => fs::read_to_string(template).map_err(|e| {
+            anyhow!(
+                "failed to read html template {}: {}",
+                template.to_string_lossy(),
+                e
+            )
+        })?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+
+    let public_path = html_config.public_path.clone().unwrap_or_else(|| {
+        match context.config.public_path.as_str() {
+            // resolved at runtime, use relative urls
+            "runtime" | "auto" => "".to_string(),
+            p => p.to_string(),
+        }
+    });
+
+    let files = entry_files(&html_config.entries, chunk_files, context);
+
+    let styles = files
+        .css
+        .iter()
+        .map(|f| format!(r#"<link rel="stylesheet" href="{}{}" />"#, public_path, f))
+        .collect::<Vec<_>>();
+    // the chunks are classic scripts, except the esm files of preserveModules, and the modern
+    // build is loaded as module to pair with the nomodule one of legacyBrowsers
+    let script_type =
+        if context.config.output.is_preserve_modules() || context.config.output.legacy_browsers {
+            r#" type="module""#
+        } else {
+            ""
+        };
+    let scripts = files
+        .js
+        .iter()
+        .map(|f| {
+            format!(
+                r#"<script{} src="{}{}"></script>"#,
+                script_type, public_path, f
+            )
+        })
+        .chain(
+            legacy_files(&html_config.entries, context)
+                .into_iter()
+                .map(|f| format!(r#"<script nomodule src="{}{}"></script>"#, public_path, f)),
+        )
+        .collect::<Vec<_>>();
+    let hints = files
+        .hints
+        .iter()
+        .map(|(rel, file_name, as_type)| {
+            let rel = match rel {
+                LinkHintRel::Prefetch => "prefetch",
+                LinkHintRel::Preload => "preload",
+            };
+            format!(
+                r#"<link rel="{}" href="{}{}" as="{}" />"#,
+                rel, public_path, file_name, as_type
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let has_js_placeholder = template.contains(JS_FILES_PLACEHOLDER);
+    let has_css_placeholder = template.contains(CSS_FILES_PLACEHOLDER);
+
+    let html = interpolate(&template, |name| match name {
+        "title" => Some(escape_html(
+            html_config
+                .title
+                .as_deref()
+                .unwrap_or(&html_config.entries.join(",")),
+        )),
+        "publicPath" => Some(public_path.clone()),
+        JS_FILES_PLACEHOLDER => Some(scripts.join("\n")),
+        CSS_FILES_PLACEHOLDER => Some(styles.join("\n")),
+        _ => None,
+    });
+
+    let html = inject_before(&html, "</head>", &hints);
+    let html = if has_css_placeholder {
+        html
+    } else {
+        inject_before(&html, "</head>", &styles)
+    };
+    let html = if has_js_placeholder {
+        html
+    } else {
+        match html_config.inject {
+            HtmlInject::Head => inject_before(&html, "</head>", &scripts),
+            HtmlInject::Body => inject_before(&html, "</body>", &scripts),
+        }
+    };
+
+    Ok(html)
+}
+
+pub(crate) struct EntryFiles {
+    pub js: Vec<String>,
+    pub css: Vec<String>,
+    // (rel, file name, as)
+    hints: Vec<(LinkHintRel, String, String)>,
+}
+
+// initial files of entries in load order, shared entry chunks come before the entry chunk
+pub(crate) fn entry_files(
+    entries: &[String],
+    chunk_files: &[ChunkFile],
+    context: &Arc<Context>,
+) -> EntryFiles {
+    let chunk_graph = context.chunk_graph.read().unwrap();
+
+    let mut chunk_ids: Vec<String> = vec![];
+    for entry in entries {
+        let entry_chunk = chunk_graph.get_chunks().into_iter().find(
+            |chunk| matches!(&chunk.chunk_type, ChunkType::Entry(_, name, false) if name == entry),
+        );
+        let Some(entry_chunk) = entry_chunk else {
+            continue;
+        };
+        chunk_graph
+            .entry_dependencies_chunk(&entry_chunk.id)
+            .into_iter()
+            .map(|id| id.id)
+            .chain(std::iter::once(entry_chunk.id.id.clone()))
+            .for_each(|id| {
+                if !chunk_ids.contains(&id) {
+                    chunk_ids.push(id);
+                }
+            });
+    }
+
+    let files_of = |file_type: ChunkFileType| {
+        chunk_ids
+            .iter()
+            .flat_map(|chunk_id| {
+                chunk_files
+                    .iter()
+                    .filter(|f| &f.chunk_id == chunk_id && f.file_type == file_type)
+                    .map(|f| f.disk_name())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let hints = context
+        .link_hints
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|hint| chunk_ids.contains(&hint.parent_chunk_id))
+        .map(|hint| (hint.rel, hint.file_name.clone(), hint.as_type.clone()))
+        .collect();
+
+    EntryFiles {
+        js: files_of(ChunkFileType::JS),
+        css: files_of(ChunkFileType::Css),
+        hints,
+    }
+}
+
+// js files of the entries built for browsers without esm support, see
+// output.legacyBrowsers
+fn legacy_files(entries: &[String], context: &Arc<Context>) -> Vec<String> {
+    let legacy_entry_files = context.legacy_entry_files.lock().unwrap();
+    let mut files: Vec<String> = vec![];
+    for file in entries
+        .iter()
+        .filter_map(|entry| legacy_entry_files.get(entry))
+        .flatten()
+    {
+        if !files.contains(file) {
+            files.push(file.clone());
+        }
+    }
+    files
+}
+
+fn interpolate<F>(template: &str, get_value: F) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    create_cached_regex(r"<%=\s*([\w.]+)\s*%>")
+        .replace_all(template, |caps: &Captures| {
+            get_value(&caps[1]).unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+fn inject_before(html: &str, tag: &str, tags: &[String]) -> String {
+    if tags.is_empty() {
+        return html.to_string();
+    }
+    let content = tags.join("\n");
+    match html.rfind(tag) {
+        Some(pos) => format!("{}{}\n{}", &html[..pos], content, &html[pos..]),
+        None => format!("{}\n{}", html, content),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{render_html, HtmlPluginConfig};
+    use crate::compiler::Compiler;
+    use crate::config::HtmlInject;
+    use crate::utils::test_helper::setup_compiler;
+
+    fn read_dist_file(compiler: &Compiler, name: &str) -> String {
+        fs::read_to_string(compiler.context.config.output.path.join(name)).unwrap()
+    }
+
+    fn dist_files(compiler: &Compiler, prefix: &str, ext: &str) -> Vec<String> {
+        fs::read_dir(&compiler.context.config.output.path)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|f| f.starts_with(prefix) && f.ends_with(ext))
+            .collect()
+    }
+
+    #[test]
+    fn test_html_for_each_entry() {
+        let compiler = setup_compiler("test/build/html", false);
+        compiler.compile().unwrap();
+
+        let a_js = dist_files(&compiler, "a.", ".js");
+        let b_js = dist_files(&compiler, "b.", ".js");
+        let a_css = dist_files(&compiler, "a.", ".css");
+        assert_eq!(a_js.len(), 1);
+        assert_eq!(b_js.len(), 1);
+        assert_eq!(a_css.len(), 1);
+        // hashed
+        assert_ne!(a_js[0], "a.js");
+
+        let a_html = read_dist_file(&compiler, "a.html");
+        assert!(a_html.contains(&format!(r#"<script src="/{}"></script>"#, a_js[0])));
+        assert!(a_html.contains(&format!(
+            r#"<link rel="stylesheet" href="/{}" />"#,
+            a_css[0]
+        )));
+        assert!(!a_html.contains(&b_js[0]));
+        assert!(a_html.contains("<title>Page A</title>"));
+        // styles in head, scripts at the end of body
+        assert!(a_html.find(&a_css[0]).unwrap() < a_html.find("</head>").unwrap());
+        assert!(a_html.find(&a_js[0]).unwrap() > a_html.find("<body>").unwrap());
+
+        let b_html = read_dist_file(&compiler, "pages/b.html");
+        assert!(b_html.contains(&format!(r#"<script src="/{}"></script>"#, b_js[0])));
+        assert!(!b_html.contains(&a_js[0]));
+        assert!(!b_html.contains("stylesheet"));
+        assert!(b_html.contains("<title>b</title>"));
+    }
+
+    #[test]
+    fn test_html_plugin_placeholders() {
+        let compiler = setup_compiler("test/build/html", false);
+        compiler.compile().unwrap();
+
+        let chunk_files = compiler.generate_chunk_files(compiler.full_hash()).unwrap();
+        let disk_name = |chunk_name: &str, ext: &str| {
+            chunk_files
+                .iter()
+                .map(|f| f.disk_name())
+                .find(|f| f.starts_with(chunk_name) && f.ends_with(ext))
+                .unwrap()
+        };
+        let html = render_html(
+            &HtmlPluginConfig {
+                template: Some(compiler.context.root.join("src/placeholder.html")),
+                filename: "index.html".to_string(),
+                inject: HtmlInject::Head,
+                entries: vec!["a".to_string(), "b".to_string()],
+                public_path: Some("https://cdn/".to_string()),
+                title: None,
+            },
+            &chunk_files,
+            &compiler.context,
+        )
+        .unwrap();
+
+        let a_pos = html
+            .find(&format!(
+                r#"<script src="https://cdn/{}">"#,
+                disk_name("a.", ".js")
+            ))
+            .unwrap();
+        let b_pos = html
+            .find(&format!(
+                r#"<script src="https://cdn/{}">"#,
+                disk_name("b.", ".js")
+            ))
+            .unwrap();
+        // all entries in order, at the placeholder instead of head
+        assert!(a_pos < b_pos);
+        assert!(a_pos > html.find("<body>").unwrap());
+        assert!(html.contains(&format!(
+            r#"<link rel="stylesheet" href="https://cdn/{}" />"#,
+            disk_name("a.", ".css")
+        )));
+        // the hints are prefixed with the publicPath of the html too
+        let lazy = chunk_files
+            .iter()
+            .map(|f| f.disk_name())
+            .find(|f| f.contains("lazy") && f.ends_with(".js"))
+            .unwrap();
+        assert!(
+            html.contains(&format!(
+                r#"<link rel="prefetch" href="https://cdn/{}" as="script" />"#,
+                lazy
+            )),
+            "{}",
+            html
+        );
+        assert!(!html.contains("htmlWebpackPlugin"));
+        assert!(html.contains("<title>a,b</title>"));
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use pathdiff::diff_paths;
+
+use crate::compiler::{Compiler, Context};
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+use crate::plugin::Plugin;
+use crate::resolve::MISSING_MODULE_PREFIX;
+
+/**
+ * Warn about the specifiers which are replaced with modules throwing at runtime since
+ * they match `resolve.ignoreMissing`, with the importer chain from an entry, e.g.
+ *
+ * Module not found: Can't resolve 'supports-color', it throws at runtime
+ *   "src/index.ts" -> "node_modules/debug/src/node.js" -> 'supports-color'
+ */
+pub struct IgnoreMissingPlugin {}
+
+impl Plugin for IgnoreMissingPlugin {
+    fn name(&self) -> &str {
+        "ignore_missing"
+    }
+
+    fn after_build(&self, context: &Arc<Context>, _compiler: &Compiler) -> Result<()> {
+        let module_graph = context.module_graph.read().unwrap();
+        let mut missing_ids = module_graph
+            .get_module_ids()
+            .into_iter()
+            .filter(|id| id.id.starts_with(MISSING_MODULE_PREFIX))
+            .collect::<Vec<_>>();
+        missing_ids.sort();
+
+        for id in missing_ids {
+            let source = id.id.trim_start_matches(MISSING_MODULE_PREFIX);
+            let chain = importer_chain(&module_graph, &id)
+                .iter()
+                .map(|id| {
+                    let absolute_path = PathBuf::from(&id.id);
+                    let relative_path =
+                        diff_paths(&absolute_path, &context.root).unwrap_or(absolute_path);
+                    format!(r#""{}""#, relative_path.to_string_lossy())
+                })
+                .chain(std::iter::once(format!("'{}'", source)))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            context.warn(format!(
+                "Module not found: Can't resolve '{}', it throws at runtime\n  {}",
+                source, chain
+            ));
+        }
+        Ok(())
+    }
+}
+
+// the first importers up to an entry, the entry comes first
+fn importer_chain(module_graph: &ModuleGraph, id: &ModuleId) -> Vec<ModuleId> {
+    let mut chain = vec![];
+    let mut visited = HashSet::from([id.clone()]);
+    let mut current = id.clone();
+    loop {
+        let importer = module_graph
+            .get_dependents(&current)
+            .into_iter()
+            .map(|(importer, _)| importer.clone())
+            .find(|importer| !visited.contains(importer));
+        let Some(importer) = importer else {
+            break;
+        };
+        visited.insert(importer.clone());
+        chain.push(importer.clone());
+        if module_graph
+            .get_module(&importer)
+            .is_some_and(|m| m.is_entry)
+        {
+            break;
+        }
+        current = importer;
+    }
+    chain.reverse();
+    chain
+}
@@ -52,7 +52,10 @@ impl<'a> Visit for InvalidSyntaxVisitor<'a> {
             is_member_prop(expr, "require", "ensure", true, self.unresolved_mark);
         if is_require_ensure {
             self.handler
-                .struct_span_err(expr.span, "require.ensure syntax is not supported yet")
+                .struct_span_err(
+                    expr.span,
+                    "require.ensure is only supported with an array of dependencies and a function callback",
+                )
                 .emit();
         } else {
             expr.visit_children_with(self);
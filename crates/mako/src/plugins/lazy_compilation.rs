@@ -0,0 +1,240 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Result;
+
+use crate::ast::file::{Content, JsContent};
+use crate::build::analyze_deps::ResolvedDep;
+use crate::compiler::Context;
+use crate::config::Config;
+use crate::module::ResolveType;
+use crate::plugin::{Plugin, PluginLoadParam};
+use crate::resolve::ResolverResource;
+
+const LAZY_SUFFIX: &str = "?lazy";
+const JS_EXTENSIONS: [&str; 6] = ["js", "jsx", "ts", "tsx", "mjs", "cjs"];
+
+pub fn is_enabled(config: &Config, watch: bool) -> bool {
+    watch
+        && config.experimental.lazy_compilation
+        && config.dev_server.is_some()
+        && config.hmr.is_some()
+}
+
+pub fn proxy_id(path: &str) -> String {
+    format!("{}{}", path, LAZY_SUFFIX)
+}
+
+// the real module of a proxy module id
+pub fn proxy_target(id: &str) -> Option<&str> {
+    id.strip_suffix(LAZY_SUFFIX)
+}
+
+/**
+ * Targets of dynamic imports which have been requested by the browser.
+ *
+ * Before that, the importer depends on a proxy module (`<path>?lazy`) which
+ * asks the dev server to compile the target. Once activated, the proxy is
+ * rebuilt to require the target, so the target subtree is added to the proxy's
+ * chunk and delivered with the hot update.
+ */
+#[derive(Default)]
+pub struct LazyCompilation {
+    activated: RwLock<HashSet<String>>,
+}
+
+impl LazyCompilation {
+    /// Returns false if the target has already been activated.
+    pub fn activate(&self, path: &str) -> bool {
+        self.activated.write().unwrap().insert(path.to_string())
+    }
+
+    pub fn is_activated(&self, path: &str) -> bool {
+        self.activated.read().unwrap().contains(path)
+    }
+}
+
+pub struct LazyCompilationPlugin {}
+
+impl Plugin for LazyCompilationPlugin {
+    fn name(&self) -> &str {
+        "lazy_compilation"
+    }
+
+    fn after_resolve(&self, resolved_dep: &mut ResolvedDep, _context: &Arc<Context>) -> Result<()> {
+        if !matches!(
+            resolved_dep.dependency.resolve_type,
+            ResolveType::DynamicImport(_)
+        ) {
+            return Ok(());
+        }
+        let ResolverResource::Resolved(_) = &resolved_dep.resolver_resource else {
+            return Ok(());
+        };
+        let path = resolved_dep.resolver_resource.get_resolved_path();
+        let is_js = PathBuf::from(&path)
+            .extension()
+            .is_some_and(|ext| JS_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()));
+        if is_js {
+            // always points to the proxy, so the importer is not changed when activated
+            resolved_dep.resolver_resource = ResolverResource::Virtual(proxy_id(&path).into());
+        }
+        Ok(())
+    }
+
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+        let path = param.file.path.to_string_lossy();
+        let Some(target) = proxy_target(&path) else {
+            return Ok(None);
+        };
+        let content = if context.lazy_compilation.is_activated(target) {
+            activated_proxy(target)
+        } else {
+            pending_proxy(target)
+        };
+        Ok(Some(Content::Js(JsContent {
+            content,
+            ..Default::default()
+        })))
+    }
+}
+
+const REGISTRY: &str = r#"var global = typeof globalThis !== 'undefined' ? globalThis : self;
+var registry = global.__mako_lazy_compilation__ = global.__mako_lazy_compilation__ || {};"#;
+
+// resolves the dynamic import when the activated proxy is applied by hmr
+fn pending_proxy(target: &str) -> String {
+    let id = serde_json::to_string(target).unwrap();
+    format!(
+        r#"{registry}
+if (module.hot) module.hot.accept();
+module.exports = new Promise(function (resolve, reject) {{
+  (registry[{id}] = registry[{id}] || []).push(resolve);
+  fetch(__mako_require__.publicPath + '__/lazy-compile?id=' + encodeURIComponent({id})).then(function (res) {{
+    if (!res.ok) reject(new Error('Lazy compilation of ' + {id} + ' failed'));
+  }}, reject);
+}});
+"#,
+        registry = REGISTRY,
+        id = id
+    )
+}
+
+fn activated_proxy(target: &str) -> String {
+    let id = serde_json::to_string(target).unwrap();
+    format!(
+        r#"{registry}
+if (module.hot) module.hot.accept();
+var target = require({id});
+(registry[{id}] || []).forEach(function (resolve) {{ resolve(target); }});
+delete registry[{id}];
+module.exports = target;
+"#,
+        registry = REGISTRY,
+        id = id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use swc_core::ecma::ast::Module;
+
+    use super::proxy_id;
+    use crate::compiler::{Args, Compiler, Context};
+    use crate::config::Config;
+    use crate::plugin::{Plugin, PluginTransformJsParam};
+    use crate::utils::test_helper::setup_logger;
+
+    #[derive(Default)]
+    struct TransformCounter {
+        counts: Mutex<HashMap<String, usize>>,
+    }
+
+    impl Plugin for TransformCounter {
+        fn name(&self) -> &str {
+            "transform_counter"
+        }
+
+        fn transform_js(
+            &self,
+            param: &PluginTransformJsParam,
+            _ast: &mut Module,
+            _context: &Arc<Context>,
+        ) -> anyhow::Result<()> {
+            *self
+                .counts
+                .lock()
+                .unwrap()
+                .entry(param.path.to_string())
+                .or_default() += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lazy_compilation() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/lazy-compilation");
+        let config = Config::new(&root, None, None).unwrap();
+        let counter = Arc::new(TransformCounter::default());
+        let compiler = Compiler::new(
+            config,
+            root.clone(),
+            Args { watch: true },
+            Some(vec![counter.clone()]),
+        )
+        .unwrap();
+        compiler.compile().unwrap();
+
+        let path = |name: &str| root.join(name).to_string_lossy().to_string();
+        let has_module = |id: String| {
+            compiler
+                .context
+                .module_graph
+                .read()
+                .unwrap()
+                .has_module(&id.into())
+        };
+        let transformed = |name: &str| {
+            counter
+                .counts
+                .lock()
+                .unwrap()
+                .get(&path(name))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        // only the proxies are compiled
+        assert!(has_module(proxy_id(&path("a.ts"))));
+        assert!(has_module(proxy_id(&path("b.ts"))));
+        assert!(!has_module(path("a.ts")));
+        assert!(!has_module(path("b.ts")));
+        assert_eq!(transformed("a.ts"), 0);
+        assert_eq!(transformed("a-dep.ts"), 0);
+        assert_eq!(transformed("b.ts"), 0);
+
+        assert!(compiler.context.lazy_compilation.activate(&path("a.ts")));
+        let result = compiler
+            .update(vec![proxy_id(&path("a.ts")).into()])
+            .unwrap();
+        assert!(result.added.contains(&path("a.ts").into()));
+        assert!(result.added.contains(&path("a-dep.ts").into()));
+        assert!(has_module(path("a.ts")));
+        assert!(has_module(path("a-dep.ts")));
+        assert_eq!(transformed("a.ts"), 1);
+        assert_eq!(transformed("a-dep.ts"), 1);
+
+        // the other route is still not compiled
+        assert!(!has_module(path("b.ts")));
+        assert_eq!(transformed("b.ts"), 0);
+        // the importer keeps pointing to the proxy
+        assert_eq!(transformed("index.ts"), 1);
+    }
+}
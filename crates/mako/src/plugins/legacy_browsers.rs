@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::compiler::Context;
+use crate::generate::chunk::Chunk;
+use crate::generate::generate_chunks::ChunkFile;
+use crate::plugin::Plugin;
+use crate::plugins::html::entry_files;
+
+/// Added to the legacy build of `output.legacyBrowsers`, see `Compiler::compile_legacy`.
+///
+/// Chunks are named `[name].legacy.js` so that they don't overwrite the esm ones, and
+/// the js files of each entry are handed over to the main build, where the html plugin
+/// injects them with `<script nomodule>`.
+pub struct LegacyBrowsersPlugin {
+    pub main_context: Arc<Context>,
+}
+
+impl Plugin for LegacyBrowsersPlugin {
+    fn name(&self) -> &str {
+        "legacy_browsers"
+    }
+
+    fn resolve_file_name(&self, _chunk: &Chunk, _context: &Arc<Context>) -> Option<String> {
+        Some("[name].legacy".to_string())
+    }
+
+    fn after_generate_chunk_files(
+        &self,
+        chunk_files: &[ChunkFile],
+        context: &Arc<Context>,
+    ) -> Result<()> {
+        let mut legacy_entry_files = self.main_context.legacy_entry_files.lock().unwrap();
+        for entry in context.entries.read().unwrap().keys() {
+            let files = entry_files(&[entry.clone()], chunk_files, context);
+            legacy_entry_files.insert(entry.clone(), files.js);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use crate::ast::file::Content;
+    use crate::compiler::Context;
+    use crate::plugin::Plugin;
+    use crate::utils::test_helper::{setup_compiler, setup_compiler_with_plugins};
+
+    #[test]
+    fn test_legacy_browsers() {
+        let compiler = setup_compiler("test/build/legacy-browsers", false);
+        compiler.compile().unwrap();
+
+        let dist = &compiler.context.config.output.path;
+        let modern = fs::read_to_string(dist.join("index.js")).unwrap();
+        let legacy = fs::read_to_string(dist.join("index.legacy.js")).unwrap();
+        assert!(modern.contains("const greet"));
+        assert!(modern.contains("=>"));
+        // transpiled for ie 11
+        assert!(legacy.contains("greet"));
+        assert!(!legacy.contains("const "));
+        assert!(!legacy.contains("let "));
+        assert!(!legacy.contains("=>"));
+        assert!(!legacy.contains('`'));
+
+        let html = fs::read_to_string(dist.join("index.html")).unwrap();
+        let module_pos = html
+            .find(r#"<script type="module" src="/index.js"></script>"#)
+            .unwrap();
+        let nomodule_pos = html
+            .find(r#"<script nomodule src="/index.legacy.js"></script>"#)
+            .unwrap();
+        assert!(module_pos < nomodule_pos);
+    }
+
+    // counts the load_transform calls by path, like a js plugin with a transform hook
+    #[derive(Default)]
+    struct TransformCounterPlugin {
+        calls: Mutex<HashMap<String, usize>>,
+    }
+
+    impl Plugin for TransformCounterPlugin {
+        fn name(&self) -> &str {
+            "transform_counter_test"
+        }
+
+        fn load_transform(
+            &self,
+            _content: &mut Content,
+            path: &str,
+            _is_entry: bool,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Content>> {
+            *self
+                .calls
+                .lock()
+                .unwrap()
+                .entry(path.to_string())
+                .or_default() += 1;
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_legacy_browsers_transform_once() {
+        let plugin = Arc::new(TransformCounterPlugin::default());
+        let extra_plugin: Arc<dyn Plugin> = plugin.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/legacy-browsers", vec![extra_plugin], |_| {});
+        compiler.compile().unwrap();
+        let output = &compiler.context.config.output.path;
+
+        // the main build reuses the contents transformed by the legacy build
+        let calls = plugin.calls.lock().unwrap();
+        assert!(!calls.is_empty());
+        assert!(calls.values().all(|count| *count == 1), "{:?}", calls);
+        assert!(output.join("index.js").exists());
+        assert!(output.join("index.legacy.js").exists());
+    }
+}
@@ -106,7 +106,7 @@ impl Plugin for ModuleFederationPlugin {
         Ok(())
     }
 
-    fn after_resolve(&self, resolved_dep: &ResolvedDep, _context: &Arc<Context>) -> Result<()> {
+    fn after_resolve(&self, resolved_dep: &mut ResolvedDep, _context: &Arc<Context>) -> Result<()> {
         self.collect_provide_shared(resolved_dep);
         Ok(())
     }
@@ -42,6 +42,7 @@ impl ModuleFederationPlugin {
                         vacant_entry.insert(EntryItem {
                             filename: self.config.filename.clone(),
                             import: container_entry_path,
+                            ..Default::default()
                         });
                     }
                 }
@@ -12,7 +12,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::debug;
 
-use crate::ast::file::{Content, File, JsContent};
+use crate::ast::file::{Content, CssContent, File, JsContent};
 use crate::compiler::{Args, Compiler, Context};
 use crate::config::{
     AllowChunks, ChunkGroup, CodeSplitting, CodeSplittingAdvancedOptions, CodeSplittingStrategy,
@@ -284,7 +284,10 @@ module.export = Promise.all(
         }
 
         if param.file.path.starts_with(SSU_MOCK_CSS_FILE) {
-            return Ok(Some(Content::Css("._mako_mock_css { }".to_string())));
+            return Ok(Some(Content::Css(CssContent {
+                content: "._mako_mock_css { }".to_string(),
+                resolve_base: None,
+            })));
         }
 
         if param.file.path.starts_with(SSU_MOCK_JS_FILE) {
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use thiserror::Error;
+
+use crate::ast::file::{Content, JsContent};
+use crate::build::load::{FileSystem, Load};
+use crate::compiler::Context;
+use crate::plugin::{Plugin, PluginLoadParam};
+
+#[derive(Debug, Error)]
+enum SvgrError {
+    #[error("To svgr error: {path:?}, reason: {reason:?}")]
+    ToSvgrError { path: String, reason: String },
+}
+
+const SVG_EXTENSIONS: [&str; 1] = ["svg"];
+const SVGR_NAMED_EXPORT: &str = r#"ReactComponent"#;
+
+/**
+ * Load svg files as React components, the component is exported as `ReactComponent`
+ * and the props are spread onto the root `<svg>` element, the default export is
+ * still the url of the asset.
+ *
+ * `?url` and `?raw` imports are left to the default loaders.
+ */
+pub struct SvgrPlugin {}
+
+impl Plugin for SvgrPlugin {
+    fn name(&self) -> &str {
+        "svgr"
+    }
+
+    fn load(&self, param: &PluginLoadParam, context: &Arc<Context>) -> Result<Option<Content>> {
+        let file = param.file;
+        // TODO: Not all svg files need to be converted to React Component, unnecessary performance consumption here
+        if !SVG_EXTENSIONS.contains(&file.extname.as_str())
+            || file.has_param("url")
+            || file.has_param("raw")
+            || !file.pathname.is_file()
+        {
+            return Ok(None);
+        }
+
+        let content = FileSystem::read_file(&file.pathname)?;
+        let svgr_transformed = svgr_rs::transform(
+            content,
+            svgr_rs::Config {
+                named_export: SVGR_NAMED_EXPORT.to_string(),
+                export_type: Some(svgr_rs::ExportType::Named),
+                ..Default::default()
+            },
+            svgr_rs::State {
+                ..Default::default()
+            },
+        )
+        .map_err(|err| {
+            anyhow!(SvgrError::ToSvgrError {
+                path: file.path.to_string_lossy().to_string(),
+                reason: err.to_string(),
+            })
+        })?;
+        let asset_path = Load::handle_asset(file, true, true, context.clone())?;
+        Ok(Some(Content::Js(JsContent {
+            content: format!("{}\nexport default {};", svgr_transformed, asset_path),
+            is_jsx: true,
+        })))
+    }
+}
@@ -1,20 +1,25 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use anyhow::Result;
 use swc_core::common::util::take::Take;
+use swc_core::common::SyntaxContext;
 use swc_core::ecma::ast::{Decl, Module, ModuleItem, Stmt, VarDecl};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
+use crate::ast::comments::Comments;
 use crate::compiler::Context;
 use crate::module_graph::ModuleGraph;
 use crate::plugin::{Plugin, PluginTransformJsParam};
 
+pub(crate) mod cache;
 mod collect_explicit_prop;
+pub(crate) mod deferred_exports;
 mod module;
 mod module_side_effects_flag;
 mod remove_useless_stmts;
 mod shake;
-mod statement_graph;
+pub(crate) mod statement_graph;
 
 pub struct FarmTreeShake {}
 
@@ -45,6 +50,36 @@ impl Plugin for FarmTreeShake {
     }
 }
 
+/**
+ * Run only the statement level tree shaking on an esm module, out of the compiler pipeline,
+ * e.g. for tests and tooling. `used_exports` are the export names used by the importers,
+ * `default` for the default export. The statements with side effects are always kept, and
+ * the module is returned as is if it can't be analyzed, e.g. with string export names.
+ *
+ * It must be called within the `GLOBALS` that the module is parsed with.
+ */
+pub fn shake_module(
+    module: &Module,
+    unresolved_ctxt: SyntaxContext,
+    used_exports: &HashSet<String>,
+) -> Module {
+    let mut shaken = module.clone();
+    shaken.visit_mut_with(&mut TopLevelDeclSplitter {});
+
+    let comments = Comments::default();
+    let Ok(mut tree_shake_module) =
+        module::TreeShakeModule::from_swc_module(&shaken, unresolved_ctxt, &comments)
+    else {
+        return module.clone();
+    };
+    for used_export in used_exports {
+        tree_shake_module.add_used_export(Some(used_export));
+    }
+
+    remove_useless_stmts::remove_useless_stmts(&mut tree_shake_module, &mut shaken);
+    shaken
+}
+
 struct TopLevelDeclSplitter {}
 
 impl VisitMut for TopLevelDeclSplitter {
@@ -92,6 +127,7 @@ impl VisitMut for TopLevelDeclSplitter {
 
 #[cfg(test)]
 mod tests {
+    use swc_core::common::GLOBALS;
     use swc_core::ecma::visit::VisitMutWith;
 
     use super::*;
@@ -128,6 +164,65 @@ var b = 2;
         );
     }
 
+    #[test]
+    fn test_shake_module() {
+        let mut tu = TestUtils::gen_js_ast(
+            r#"
+import { a } from './a';
+import { b } from './b';
+const used = a + 1, unused = b;
+console.log('side effect');
+export { used, unused };
+export default function foo() {}
+"#,
+        );
+        let used_exports = HashSet::from(["used".to_string()]);
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let ast = tu.ast.js_mut();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(ast.unresolved_mark);
+            ast.ast = shake_module(&ast.ast, unresolved_ctxt, &used_exports);
+        });
+
+        assert_eq!(
+            tu.js_ast_to_code(),
+            r#"
+import { a } from './a';
+const used = a + 1;
+console.log('side effect');
+export { used };
+"#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_shake_module_mixed_import_specifiers() {
+        let mut tu = TestUtils::gen_js_ast(
+            r#"
+import def, { named } from './a';
+import unused, * as ns from './b';
+console.log(def, ns);
+"#,
+        );
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let ast = tu.ast.js_mut();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(ast.unresolved_mark);
+            ast.ast = shake_module(&ast.ast, unresolved_ctxt, &HashSet::new());
+        });
+
+        assert_eq!(
+            tu.js_ast_to_code(),
+            r#"
+import def from './a';
+import * as ns from './b';
+console.log(def, ns);
+"#
+            .trim()
+        );
+    }
+
     fn split_top_decl(code: &str) -> String {
         let mut tu = TestUtils::gen_js_ast(code);
 
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use crate::ast::js_ast::JsAst;
+use crate::module::{Module, ModuleAst, ModuleId};
+use crate::plugins::tree_shaking::statement_graph::StatementGraph;
+
+struct CacheEntry {
+    content_hash: u64,
+    // the ast before it's shaken, the cached statement graph is built from it
+    ast: JsAst,
+    stmt_graph: StatementGraph,
+}
+
+/**
+ * Caches the statement graph of each module between tree shaking runs.
+ * Entries are keyed by module id and validated by the module's content hash,
+ * so a module whose content is unchanged on rebuild skips the analysis.
+ *
+ * The cached ast is the one before shaking, so the used exports are applied again on each
+ * run. The ast of a module also depends on its dependencies, e.g. the const enums inlined
+ * from them, so the entries of the importers of the changed modules are invalidated too.
+ */
+#[derive(Default)]
+pub struct TreeShakingCache {
+    entries: HashMap<ModuleId, CacheEntry>,
+    // the content hashes of all the modules of the last run, the changed ones invalidate
+    // the entries of their importers
+    content_hashes: HashMap<ModuleId, u64>,
+    pub(crate) hits: usize,
+    pub(crate) misses: usize,
+}
+
+impl TreeShakingCache {
+    /// Whether the module is added or its content is changed since the last run.
+    pub(crate) fn is_changed(&self, module: &Module) -> bool {
+        self.content_hashes.get(&module.id).copied() != content_hash(module)
+    }
+
+    pub(crate) fn invalidate(&mut self, module_id: &ModuleId) {
+        self.entries.remove(module_id);
+    }
+
+    /// Record the content hashes of the modules of this run, to find the changed ones on
+    /// the next run.
+    pub(crate) fn record_content_hashes<'a>(&mut self, modules: impl Iterator<Item = &'a Module>) {
+        self.content_hashes = modules
+            .filter_map(|module| Some((module.id.clone(), content_hash(module)?)))
+            .collect();
+    }
+
+    /// Restore the un-shaken ast of an unchanged module and return its cached statement graph.
+    pub(crate) fn restore(&mut self, module: &mut Module) -> Option<StatementGraph> {
+        let content_hash = content_hash(module)?;
+
+        let entry = match self.entries.get(&module.id) {
+            Some(entry) if entry.content_hash == content_hash => entry,
+            Some(_) => {
+                self.entries.remove(&module.id);
+                self.misses += 1;
+                return None;
+            }
+            None => {
+                self.misses += 1;
+                return None;
+            }
+        };
+
+        // module ast may have been shaken or transformed in the last generate,
+        // or re-parsed with fresh marks, so always go back to the cached one
+        module.info.as_mut().unwrap().ast = ModuleAst::Script(entry.ast.clone());
+        self.hits += 1;
+        Some(entry.stmt_graph.clone())
+    }
+
+    pub(crate) fn insert(&mut self, module: &Module, stmt_graph: StatementGraph) {
+        let Some(content_hash) = content_hash(module) else {
+            return;
+        };
+        if let ModuleAst::Script(ast) = &module.info.as_ref().unwrap().ast {
+            self.entries.insert(
+                module.id.clone(),
+                CacheEntry {
+                    content_hash,
+                    ast: ast.clone(),
+                    stmt_graph,
+                },
+            );
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.content_hashes.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
+
+fn content_hash(module: &Module) -> Option<u64> {
+    let info = module.info.as_ref()?;
+    // raw_hash is only calculated in watch mode
+    let hash = if info.raw_hash != 0 {
+        info.raw_hash
+    } else {
+        info.file.get_raw_hash()
+    };
+    Some(hash)
+}
@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_core::common::{Mark, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::{
+    AssignExpr, AssignOp, AssignTarget, BinExpr, BinaryOp, BlockStmtOrExpr, CondExpr, Decl, Expr,
+    Function, Id, Ident, Module, ModuleItem, Pat, Prop, PropName, PropOrSpread, ReturnStmt,
+    SimpleAssignTarget, Stmt, UnaryOp, VarDeclKind,
+};
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use super::statement_graph::pure_iife::is_pure_iife;
+use super::statement_graph::reassigned_idents_collector::ReassignedIdentsCollector;
+use super::statement_graph::used_idents_collector::UsedIdentsCollector;
+
+/**
+ * Defer the initialization of the exported bindings which are only read by their export
+ * getters, i.e. a function which just returns the binding, after tree shaking and the esm to
+ * commonjs transform, e.g.
+ *
+ * const foo = createFoo();
+ * _export(exports, { foo: function() { return foo; } });
+ * =>
+ * let foo;
+ * _export(exports, { foo: function() { return foo === void 0 ? foo = createFoo() : foo; } });
+ *
+ * so that the exports of large barrel files which are never accessed cost nothing at startup.
+ *
+ * Only the initializers whose values don't depend on when they are computed are deferred, see
+ * `is_deferrable_init`, and the bindings they use must not be reassigned.
+ */
+pub(crate) struct DeferredExports {
+    unresolved_mark: Mark,
+}
+
+impl DeferredExports {
+    pub(crate) fn new(unresolved_mark: Mark) -> Self {
+        Self { unresolved_mark }
+    }
+}
+
+impl VisitMut for DeferredExports {
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let unresolved_ctxt = SyntaxContext::empty().apply_mark(self.unresolved_mark);
+        let mut refs = IdentRefs::default();
+        module.visit_with(&mut refs);
+        let mut reassigned = ReassignedIdentsCollector::new();
+        module.visit_with(&mut reassigned);
+
+        let mut inits = HashMap::new();
+        for item in &module.body {
+            let ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) = item else {
+                continue;
+            };
+            let [decl] = &var.decls[..] else {
+                continue;
+            };
+            let (Pat::Ident(binding), Some(init)) = (&decl.name, &decl.init) else {
+                continue;
+            };
+            // the declaration itself and the export getter
+            if refs.count(&binding.id.to_id()) != 2 || !is_deferrable_init(init, unresolved_ctxt) {
+                continue;
+            }
+            let mut used = UsedIdentsCollector::new();
+            init.visit_with(&mut used);
+            if used
+                .used_idents
+                .iter()
+                .any(|ident| reassigned.reassigned_idents.contains(ident))
+            {
+                continue;
+            }
+            inits.insert(binding.id.to_id(), init.clone());
+        }
+        if inits.is_empty() {
+            return;
+        }
+
+        let mut getters = LazyGetters {
+            inits,
+            deferred: HashSet::new(),
+        };
+        module.visit_mut_with(&mut getters);
+
+        for item in &mut module.body {
+            if let ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) = item
+                && let [decl] = &mut var.decls[..]
+                && let Pat::Ident(binding) = &decl.name
+                && getters.deferred.contains(&binding.id.to_id())
+            {
+                if var.kind == VarDeclKind::Const {
+                    var.kind = VarDeclKind::Let;
+                }
+                decl.init = None;
+            }
+        }
+    }
+}
+
+// Being pure in the sense of tree shaking is not enough, e.g. `/*#__PURE__*/ create()`, reading
+// the properties of an object or a global may give another value once the rest of the module
+// has run, so only literals, functions, the local bindings and the pure iifes are deferred.
+fn is_deferrable_init(expr: &Expr, unresolved_ctxt: SyntaxContext) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Fn(_) | Expr::Arrow(_) => true,
+        Expr::Ident(ident) => ident.ctxt != unresolved_ctxt || &*ident.sym == "undefined",
+        Expr::Paren(paren) => is_deferrable_init(&paren.expr, unresolved_ctxt),
+        Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+        // e.g. `-1`, `!0`, `void 0`
+        Expr::Unary(unary) => {
+            !matches!(unary.op, UnaryOp::Delete | UnaryOp::TypeOf)
+                && matches!(&*unary.arg, Expr::Lit(_))
+        }
+        Expr::Array(array) => {
+            array.elems.iter().flatten().all(|elem| {
+                elem.spread.is_none() && is_deferrable_init(&elem.expr, unresolved_ctxt)
+            })
+        }
+        Expr::Object(object) => object.props.iter().all(|prop| match prop {
+            PropOrSpread::Spread(_) => false,
+            PropOrSpread::Prop(prop) => match &**prop {
+                Prop::Shorthand(ident) => ident.ctxt != unresolved_ctxt,
+                Prop::KeyValue(key_value) => {
+                    is_deferrable_key(&key_value.key)
+                        && is_deferrable_init(&key_value.value, unresolved_ctxt)
+                }
+                Prop::Method(method) => is_deferrable_key(&method.key),
+                Prop::Getter(getter) => is_deferrable_key(&getter.key),
+                Prop::Setter(setter) => is_deferrable_key(&setter.key),
+                Prop::Assign(_) => false,
+            },
+        }),
+        Expr::Call(call) => is_pure_iife(call, unresolved_ctxt),
+        _ => false,
+    }
+}
+
+fn is_deferrable_key(key: &PropName) -> bool {
+    match key {
+        PropName::Computed(computed) => matches!(&*computed.expr, Expr::Lit(_)),
+        _ => true,
+    }
+}
+
+#[derive(Default)]
+struct IdentRefs {
+    counts: HashMap<Id, usize>,
+}
+
+impl IdentRefs {
+    fn count(&self, id: &Id) -> usize {
+        self.counts.get(id).copied().unwrap_or(0)
+    }
+}
+
+impl Visit for IdentRefs {
+    fn visit_ident(&mut self, ident: &Ident) {
+        *self.counts.entry(ident.to_id()).or_default() += 1;
+    }
+}
+
+// `function() { return foo; }` => `function() { return foo === void 0 ? foo = init : foo; }`
+struct LazyGetters {
+    inits: HashMap<Id, Box<Expr>>,
+    deferred: HashSet<Id>,
+}
+
+impl LazyGetters {
+    fn lazy_value(&mut self, ident: &Ident) -> Option<Box<Expr>> {
+        let init = self.inits.remove(&ident.to_id())?;
+        self.deferred.insert(ident.to_id());
+        Some(Box::new(Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Bin(BinExpr {
+                span: DUMMY_SP,
+                op: BinaryOp::EqEqEq,
+                left: Box::new(Expr::Ident(ident.clone())),
+                right: Expr::undefined(DUMMY_SP),
+            })),
+            cons: Box::new(Expr::Assign(AssignExpr {
+                span: DUMMY_SP,
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Ident(ident.clone().into())),
+                right: init,
+            })),
+            alt: Box::new(Expr::Ident(ident.clone())),
+        })))
+    }
+}
+
+impl VisitMut for LazyGetters {
+    fn visit_mut_function(&mut self, function: &mut Function) {
+        if function.params.is_empty()
+            && let Some(body) = &mut function.body
+            && let [Stmt::Return(ReturnStmt { arg: Some(arg), .. })] = &mut body.stmts[..]
+            && let Expr::Ident(ident) = &**arg
+            && let Some(value) = self.lazy_value(ident)
+        {
+            *arg = value;
+            return;
+        }
+        function.visit_mut_children_with(self);
+    }
+
+    fn visit_mut_block_stmt_or_expr(&mut self, body: &mut BlockStmtOrExpr) {
+        // the arrow getter, `() => foo`
+        if let BlockStmtOrExpr::Expr(expr) = body
+            && let Expr::Ident(ident) = &**expr
+            && let Some(value) = self.lazy_value(ident)
+        {
+            *expr = value;
+            return;
+        }
+        body.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
+    use swc_core::ecma::transforms::module::util::ImportInterop;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::DeferredExports;
+    use crate::ast::tests::TestUtils;
+    use crate::visitors::common_js::common_js;
+
+    #[test]
+    fn test_defer_pure_exports() {
+        let code = run(r#"
+export const list = [1, 2, 3];
+export const sdk = (function() { const client = { ok: true }; return client; })();
+"#);
+        assert!(code.contains("let list;"), "{}", code);
+        assert!(
+            code.contains("return list === void 0 ? list = ["),
+            "{}",
+            code
+        );
+        assert!(code.contains("let sdk;"), "{}", code);
+        assert!(code.contains("return sdk === void 0 ? sdk = "), "{}", code);
+    }
+
+    #[test]
+    fn test_keep_exports_used_or_impure() {
+        let code = run(r#"
+export const used = [1];
+export const other = used.length;
+export const impure = create();
+let count = 0;
+export const counted = [count];
+count++;
+"#);
+        assert!(code.contains("const used = ["), "{}", code);
+        assert!(code.contains("const impure = create();"), "{}", code);
+        assert!(code.contains("const counted = ["), "{}", code);
+        assert!(!code.contains("let used;"), "{}", code);
+    }
+
+    #[test]
+    fn test_keep_exports_depending_on_evaluation_time() {
+        let code = run(r#"
+import { config } from './config';
+export const annotated = /*#__PURE__*/ createFoo();
+export const fromGlobal = window.innerWidth;
+export const fromImport = { mode: config.mode };
+export const spread = [...list];
+export const local = { fn() {}, value: -1 };
+const list = [];
+"#);
+        assert!(code.contains("const annotated = "), "{}", code);
+        assert!(
+            code.contains("const fromGlobal = window.innerWidth;"),
+            "{}",
+            code
+        );
+        assert!(code.contains("const fromImport = {"), "{}", code);
+        assert!(code.contains("const spread = ["), "{}", code);
+        assert!(code.contains("let local;"), "{}", code);
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let context = test_utils.context.clone();
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&context.meta.script.globals, || {
+            HELPERS.set(&Helpers::new(true), || {
+                ast.ast.visit_mut_with(&mut common_js(
+                    context.clone(),
+                    ast.unresolved_mark,
+                    ImportInterop::Swc,
+                ));
+                ast.ast
+                    .visit_mut_with(&mut DeferredExports::new(ast.unresolved_mark));
+            });
+        });
+        test_utils.js_ast_to_code()
+    }
+}
@@ -1,10 +1,14 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::Display;
 
+use anyhow::Result;
 use swc_core::common::SyntaxContext;
 use swc_core::ecma::ast::{Module as SwcModule, ModuleItem};
 
+use crate::ast::comments::Comments;
+use crate::compiler::Context;
 use crate::module::{Module, ModuleId, ModuleSystem};
+use crate::plugins::tree_shaking::shake::strip_context;
 use crate::plugins::tree_shaking::statement_graph::{
     ExportInfo, ExportInfoMatch, ExportSource, ExportSpecifierInfo, ImportInfo, StatementGraph,
     StatementId,
@@ -143,6 +147,13 @@ impl AllExports {
         }
     }
 
+    pub fn remove_idents(&mut self, idents: &HashSet<String>) {
+        match self {
+            AllExports::Precise(s) => s.retain(|id| !idents.contains(id)),
+            AllExports::Ambiguous(s) => s.retain(|id| !idents.contains(id)),
+        }
+    }
+
     pub fn as_ambiguous(&mut self) {
         match self {
             AllExports::Precise(s) => {
@@ -169,13 +180,59 @@ pub struct TreeShakeModule {
     pub updated_ast: Option<SwcModule>,
     pub side_effect_dep_sources: HashSet<String>,
     pub unresolved_ctxt: SyntaxContext,
+    /// Local idents of the functions known to have no side effects, either annotated with
+    /// `/*#__NO_SIDE_EFFECTS__*/` in this module or imported from such exports
+    pub no_side_effects_fns: HashSet<String>,
+    /// Export names of the functions known to have no side effects
+    pub no_side_effects_exports: HashSet<String>,
+    /// Names provided by more than one `export * from` with different bindings, which are
+    /// not exported by this module per the ESM spec
+    pub star_export_conflicts: HashSet<String>,
 }
 
 impl TreeShakeModule {
-    pub fn update_stmt_graph(&mut self, module: &SwcModule) {
-        let stmt_graph = StatementGraph::new(module, self.unresolved_ctxt);
+    pub fn update_stmt_graph(
+        &mut self,
+        module: &SwcModule,
+        comments: &Comments,
+        context: &Context,
+    ) -> Result<()> {
+        let stmt_graph = StatementGraph::new(
+            module,
+            &self.module_id.id,
+            &context.meta.script.cm,
+            self.unresolved_ctxt,
+            comments,
+        )?;
 
         self.stmt_graph = stmt_graph;
+        self.apply_no_side_effects_fns();
+        Ok(())
+    }
+
+    /// Statements which only call functions without side effects are not self executed,
+    /// so they are removed when their results are not used.
+    pub fn apply_no_side_effects_fns(&mut self) {
+        if self.no_side_effects_fns.is_empty() {
+            return;
+        }
+
+        let pure_stmts = self
+            .stmt_graph
+            .stmts()
+            .into_iter()
+            .filter(|stmt| {
+                stmt.is_self_executed
+                    && stmt.side_effect_callees.as_ref().is_some_and(|callees| {
+                        !callees.is_empty() && callees.is_subset(&self.no_side_effects_fns)
+                    })
+            })
+            .map(|stmt| stmt.id)
+            .collect::<Vec<_>>();
+
+        pure_stmts.iter().for_each(|id| {
+            self.stmt_graph.stmt_mut(id).is_self_executed = false;
+        });
     }
 
     pub fn has_side_effect(&self) -> bool {
@@ -198,7 +255,7 @@ impl TreeShakeModule {
         }
 
         self.stmt_graph.stmts().iter().for_each(|&s| {
-            if s.is_self_executed {
+            if s.is_self_executed || s.droppable {
                 return;
             }
             if let Some(source) = s
@@ -273,7 +330,28 @@ impl TreeShakeModule {
         self.used_exports.is_empty()
     }
 
-    pub fn new(module: &Module, order: usize) -> Self {
+    pub fn uses_all_exports(&self) -> bool {
+        matches!(self.used_exports, UsedExports::All)
+    }
+
+    pub fn new(
+        module: &Module,
+        order: usize,
+        comments: &Comments,
+        context: &Context,
+    ) -> Result<Self> {
+        Self::new_with_stmt_graph(module, order, None, comments, context)
+    }
+
+    /// Same as `new`, but reuses the given statement graph (e.g. from the tree shaking cache)
+    /// instead of analyzing the module again.
+    pub fn new_with_stmt_graph(
+        module: &Module,
+        order: usize,
+        cached_stmt_graph: Option<StatementGraph>,
+        comments: &Comments,
+        context: &Context,
+    ) -> Result<Self> {
         let module_info = module.info.as_ref().unwrap();
 
         let mut unresolved_ctxt = SyntaxContext::empty();
@@ -288,7 +366,16 @@ impl TreeShakeModule {
                     .any(|s| matches!(s, ModuleItem::ModuleDecl(_)));
                 if is_esm {
                     unresolved_ctxt = unresolved_ctxt.apply_mark(module.unresolved_mark);
-                    StatementGraph::new(&module.ast, unresolved_ctxt)
+                    match cached_stmt_graph {
+                        Some(stmt_graph) => stmt_graph,
+                        None => StatementGraph::new(
+                            &module.ast,
+                            &module.path,
+                            &context.meta.script.cm,
+                            unresolved_ctxt,
+                            comments,
+                        )?,
+                    }
                 } else {
                     StatementGraph::empty()
                 }
@@ -303,11 +390,11 @@ impl TreeShakeModule {
             UsedExports::Partial(Default::default())
         };
 
-        Self {
+        Ok(Self {
             module_id: module.id.clone(),
             stmt_graph,
             used_exports,
-            described_side_effects: module.info.as_ref().unwrap().described_side_effect(),
+            described_side_effects: module.info.as_ref().unwrap().described_side_effect(context),
             side_effects: module_system != ModuleSystem::ESModule,
             side_effect_dep_sources: Default::default(),
             is_async: module.info.as_ref().unwrap().is_async,
@@ -321,10 +408,46 @@ impl TreeShakeModule {
             topo_order: order,
             updated_ast: None,
             unresolved_ctxt,
-        }
+            no_side_effects_fns: Default::default(),
+            no_side_effects_exports: Default::default(),
+            star_export_conflicts: Default::default(),
+        })
+    }
+
+    /// A standalone esm module which is not in the module graph, nothing of it is used
+    /// until `add_used_export` is called.
+    pub fn from_swc_module(
+        module: &SwcModule,
+        unresolved_ctxt: SyntaxContext,
+        comments: &Comments,
+    ) -> Result<Self> {
+        let mut tree_shake_module = Self {
+            module_id: ModuleId::new(String::new()),
+            side_effects: false,
+            described_side_effects: None,
+            stmt_graph: StatementGraph::new(
+                module,
+                "",
+                &Default::default(),
+                unresolved_ctxt,
+                comments,
+            )?,
+            used_exports: UsedExports::Partial(Default::default()),
+            module_system: ModuleSystem::ESModule,
+            all_exports: AllExports::Precise(Default::default()),
+            is_async: false,
+            topo_order: 0,
+            updated_ast: None,
+            side_effect_dep_sources: Default::default(),
+            unresolved_ctxt,
+            no_side_effects_fns: Default::default(),
+            no_side_effects_exports: Default::default(),
+            star_export_conflicts: Default::default(),
+        };
+        tree_shake_module.apply_no_side_effects_fns();
+        Ok(tree_shake_module)
     }
 
-    #[allow(dead_code)]
     pub fn imports(&self) -> Vec<ImportInfo> {
         let mut imports = vec![];
 
@@ -433,6 +556,64 @@ impl TreeShakeModule {
             .analyze_used_statements_and_idents(stmt_used_idents_map)
     }
 
+    /// Export names used by other modules, `*` means the exports of an `export * from`
+    /// statement are all used. It's derived from the same used exports that the used
+    /// statements are analyzed from, for debugging and stats.
+    pub fn used_export_names(&self) -> BTreeSet<String> {
+        match &self.used_exports {
+            UsedExports::All => self
+                .exports()
+                .into_iter()
+                .flat_map(|export_info| export_info.specifiers)
+                .flat_map(|sp| match sp {
+                    ExportSpecifierInfo::All(_) | ExportSpecifierInfo::Ambiguous(_) => {
+                        vec![UsedIdent::ExportAll.to_string()]
+                    }
+                    _ => sp.to_idents(),
+                })
+                .collect(),
+            UsedExports::Partial(idents) | UsedExports::ReferredPartial(idents) => {
+                idents.iter().map(|ident| strip_context(ident)).collect()
+            }
+        }
+    }
+
+    /// The used idents which no export of the module matches, but may come from the
+    /// `export * from` of modules whose exports are unknown (e.g. commonjs), grouped by
+    /// the sources of those star exports.
+    pub fn ambiguous_used_exports(&self) -> BTreeMap<String, BTreeSet<String>> {
+        let mut ambiguous: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let (UsedExports::Partial(idents) | UsedExports::ReferredPartial(idents)) =
+            &self.used_exports
+        else {
+            return ambiguous;
+        };
+        let exports = self.exports();
+
+        for ident in idents {
+            // `export *` never re-exports the default export
+            if ident == "default"
+                || exports
+                    .iter()
+                    .any(|export_info| export_info.matches_ident(ident) == ExportInfoMatch::Matched)
+            {
+                continue;
+            }
+            for export_info in &exports {
+                if export_info.matches_ident(ident) == ExportInfoMatch::Ambiguous
+                    && let Some(source) = &export_info.source
+                {
+                    ambiguous
+                        .entry(source.clone())
+                        .or_default()
+                        .insert(strip_context(ident));
+                }
+            }
+        }
+
+        ambiguous
+    }
+
     pub fn used_exports_idents(&self) -> Vec<(UsedIdent, StatementId)> {
         match &self.used_exports {
             UsedExports::All => {
@@ -1,13 +1,31 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 use glob::Pattern;
 use glob_match::glob_match;
+use path_clean::PathClean;
+use swc_core::common::comments::{CommentKind, Comments};
+use tracing::{debug, warn};
 
+use crate::compiler::Context;
 use crate::module::{relative_to_root, ModuleInfo};
 use crate::resolve::{ResolvedResource, ResolverResource};
 
 impl ModuleInfo {
-    pub fn described_side_effect(&self) -> Option<bool> {
+    pub fn described_side_effect(&self, context: &Context) -> Option<bool> {
+        // the project level overrides go first, for packages whose package.json is wrong about
+        // their side effects, and forceSideEffects wins if both match
+        if self.matches_root_globs(&context.config.force_side_effects, context) {
+            return Some(true);
+        }
+        if self.matches_root_globs(&context.config.no_side_effect_modules, context) {
+            return Some(false);
+        }
+        // a file declares itself side effect free without touching package.json
+        if self.has_side_effects_free_comment(context) {
+            return Some(false);
+        }
         if let Some(ResolverResource::Resolved(ResolvedResource(source))) = &self.resolved_resource
         {
             match &source.package_json() {
@@ -15,16 +33,8 @@ impl ModuleInfo {
                     let value = desc.raw_json();
                     let side_effects = value.get("sideEffects".to_string());
 
-                    let root: &Path = desc.directory();
-                    let root: PathBuf = root.into();
-
-                    side_effects.map(|side_effect| {
-                        Self::match_flag(
-                            side_effect,
-                            relative_to_root(&self.file.path.to_string_lossy().to_string(), &root)
-                                .as_str(),
-                        )
-                    })
+                    side_effects
+                        .map(|side_effect| self.match_side_effects(side_effect, desc.directory()))
                 }
                 None => None,
             }
@@ -46,19 +56,7 @@ impl ModuleInfo {
                     let side_effects = value.get("sideEffects".to_string());
 
                     match side_effects {
-                        Some(side_effect) => {
-                            let root: &Path = desc.directory();
-                            let root: PathBuf = root.into();
-
-                            Self::match_flag(
-                                side_effect,
-                                relative_to_root(
-                                    &self.file.path.to_string_lossy().to_string(),
-                                    &root,
-                                )
-                                .as_str(),
-                            )
-                        }
+                        Some(side_effect) => self.match_side_effects(side_effect, desc.directory()),
                         None => true,
                     }
                 }
@@ -68,6 +66,54 @@ impl ModuleInfo {
             true
         }
     }
+
+    // `/* sideEffects: false */` or `/** sideEffects: false */` in the leading comments of the
+    // file, which are attached to its first token
+    fn has_side_effects_free_comment(&self, context: &Context) -> bool {
+        let Some(ast) = self.ast.as_script() else {
+            return false;
+        };
+        context
+            .meta
+            .script
+            .origin_comments
+            .read()
+            .unwrap()
+            .get_swc_comments()
+            .get_leading(ast.ast.span.lo)
+            .is_some_and(|comments| {
+                comments.iter().any(|comment| {
+                    comment.kind == CommentKind::Block
+                        && is_side_effects_free_comment(&comment.text)
+                })
+            })
+    }
+
+    fn matches_root_globs(&self, patterns: &[String], context: &Context) -> bool {
+        if patterns.is_empty() {
+            return false;
+        }
+        let path = relative_to_root(&self.file.path.to_string_lossy().to_string(), &context.root)
+            .replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        patterns
+            .iter()
+            .any(|pattern| glob_match(&pattern.replace('\\', "/"), path))
+    }
+
+    fn match_side_effects(&self, side_effects: &serde_json::Value, root: &Path) -> bool {
+        let path = relative_to_root(
+            &self.file.path.to_string_lossy().to_string(),
+            &root.to_path_buf(),
+        );
+        let flag = Self::match_flag(side_effects, &path);
+        debug!(
+            "sideEffects of {:?}: {} (path relative to package: {}, sideEffects: {})",
+            self.file.path, flag, path, side_effects
+        );
+        flag
+    }
+
     fn match_flag(flag: &serde_json::Value, path: &str) -> bool {
         match flag {
             // NOTE: 口径需要对齐这里：https://github.com/webpack/webpack/blob/main/lib/optimize/SideEffectsFlagPlugin.js#L331
@@ -81,22 +127,63 @@ impl ModuleInfo {
     }
 }
 
+// the text of a block comment, with the leading `*` of the lines in jsdoc style
+fn is_side_effects_free_comment(text: &str) -> bool {
+    let text = text
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*'))
+        .collect::<Vec<_>>()
+        .join(" ");
+    text.split_once(':')
+        .is_some_and(|(key, value)| key.trim() == "sideEffects" && value.trim() == "false")
+}
+
 fn match_glob_pattern(pattern: &str, path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    // files outside of the package are not described by its sideEffects
+    if path == ".." || path.starts_with("../") {
+        return false;
+    }
     let trimmed = path.trim_start_matches("./");
 
-    // TODO: cache
-    if !pattern.contains('/') {
-        return Pattern::new(format!("**/{}", pattern).as_str())
-            .unwrap()
-            .matches(trimmed);
+    let pattern = pattern.replace('\\', "/");
+    let is_basename = !pattern.contains('/');
+    let pattern = PathBuf::from(&pattern)
+        .clean()
+        .to_string_lossy()
+        .replace('\\', "/");
+    // e.g. `../shared/*.css` in monorepos, which never matches a file of the package
+    if pattern == ".." || pattern.starts_with("../") {
+        return false;
     }
 
-    glob_match(pattern.trim_start_matches("./"), trimmed)
+    // TODO: cache
+    match Pattern::new(&pattern) {
+        Ok(_) if is_basename => Pattern::new(format!("**/{}", pattern).as_str())
+            .map(|pattern| pattern.matches(trimmed))
+            .unwrap_or(true),
+        Ok(_) => glob_match(&pattern, trimmed),
+        Err(e) => {
+            // keep the module, dropping it by a broken pattern is much worse
+            // than some bytes more
+            static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+            let mut warned = WARNED.get_or_init(Default::default).lock().unwrap();
+            if warned.insert(pattern.clone()) {
+                warn!(
+                    "invalid sideEffects pattern {:?}: {}, modules are treated as side-effectful",
+                    pattern, e
+                );
+            }
+            true
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::match_glob_pattern;
+    use std::fs;
+
+    use super::{is_side_effects_free_comment, match_glob_pattern};
     use crate::utils::test_helper::{get_module, setup_compiler};
 
     #[test]
@@ -130,6 +217,34 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_invalid_pattern_is_side_effectful() {
+        assert!(match_glob_pattern("[unclosed", "./src/index.js"));
+        assert!(match_glob_pattern("./src/[unclosed", "./src/index.js"));
+    }
+
+    #[test]
+    fn test_pattern_outside_of_package() {
+        assert!(!match_glob_pattern("../shared/*.css", "./shared/a.css"));
+        assert!(!match_glob_pattern("../shared/*.css", "../shared/a.css"));
+        assert!(match_glob_pattern("./lib/../src/*.css", "./src/a.css"));
+    }
+
+    #[test]
+    fn test_backslash_paths() {
+        assert!(match_glob_pattern("src/*.css", ".\\src\\a.css"));
+        assert!(match_glob_pattern(".\\src\\*.css", "./src/a.css"));
+        assert!(match_glob_pattern("*.css", ".\\src\\deep\\a.css"));
+    }
+
+    #[test]
+    fn test_side_effects_free_comment() {
+        assert!(is_side_effects_free_comment(" sideEffects: false "));
+        assert!(is_side_effects_free_comment("*\n * sideEffects:false\n "));
+        assert!(!is_side_effects_free_comment(" sideEffects: true "));
+        assert!(!is_side_effects_free_comment(" eslint-disable "));
+    }
+
     #[test]
     fn test_side_effects_flag() {
         let compiler = setup_compiler("test/build/side-effects-flag", false);
@@ -145,4 +260,38 @@ mod tests {
         assert!(!four.info.unwrap().get_side_effects_flag());
         assert!(four_s.info.unwrap().get_side_effects_flag());
     }
+
+    #[test]
+    fn test_side_effects_overrides() {
+        let compiler = setup_compiler("test/build/side-effects-overrides", false);
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let content = fs::read_to_string(output.join("index.js")).unwrap();
+        // (module, whether its side effect is kept), none of the exports is used
+        let cases = [
+            // no sideEffects in package.json, matched by noSideEffectModules
+            ("matched", false),
+            ("plain", true),
+            // sideEffects: false in package.json, matched by forceSideEffects
+            ("forced", true),
+            ("pure", false),
+            // matched by both, forceSideEffects wins
+            ("both", true),
+            ("commented", false),
+            ("jsdoc", false),
+            ("uncommented", true),
+            // the comment is not a leading one of the file
+            ("trailing", true),
+        ];
+        for (name, kept) in cases {
+            assert_eq!(
+                content.contains(&format!("side-effect-of-{}", name)),
+                kept,
+                "{}: {}",
+                name,
+                content
+            );
+        }
+    }
 }
@@ -46,7 +46,10 @@ pub fn remove_useless_stmts(
             module_item,
             Some(used_defined_idents.clone()),
             tree_shake_module.unresolved_ctxt,
-        );
+            None,
+        )
+        // the statement was analyzed without errors when building the statement graph
+        .unwrap();
 
         if let Some(import_info) = import_info {
             used_import_infos.push(import_info.clone());
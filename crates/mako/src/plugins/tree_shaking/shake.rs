@@ -1,30 +1,55 @@
+mod dump_statement_graph;
 mod find_export_source;
+mod mangle_exports;
 mod module_concatenate;
+mod prune_modules;
+mod shake_metadata;
 mod skip_module;
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::DerefMut;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use pathdiff::diff_paths;
 use rayon::prelude::*;
 use swc_core::common::util::take::Take;
 use swc_core::common::GLOBALS;
+use swc_core::ecma::ast::{Decl, Expr, Ident, Module as SwcModule, ModuleItem, Stmt};
 use swc_core::ecma::transforms::base::helpers::{Helpers, HELPERS};
+use swc_core::ecma::utils::{find_pat_ids, ExprFactory};
 
+use self::dump_statement_graph::dump_statement_graphs;
+use self::mangle_exports::mangle_exports;
+use self::prune_modules::prune_side_effects_free_modules;
+use self::shake_metadata::emit_shake_metadata;
 use self::skip_module::skip_module_optimize;
+use crate::ast::file::win_path;
+use crate::ast::utils::{id, member_call, member_prop};
 use crate::compiler::Context;
+use crate::config::{Mode, ModuleWrapper, OutputMode, TreeShakingGranularity};
 use crate::module::{ModuleAst, ModuleId, ModuleSystem, ModuleType, ResolveType};
 use crate::module_graph::ModuleGraph;
 use crate::plugins::tree_shaking::module::{AllExports, TreeShakeModule};
 use crate::plugins::tree_shaking::shake::module_concatenate::optimize_module_graph;
-use crate::plugins::tree_shaking::statement_graph::{ExportInfo, ExportSpecifierInfo, ImportInfo};
+use crate::plugins::tree_shaking::statement_graph::analyze_imports_and_exports::NO_SIDE_EFFECTS_DEFAULT;
+use crate::plugins::tree_shaking::statement_graph::{
+    ExportInfo, ExportSpecifierInfo, ImportInfo, StatementGraph,
+};
 use crate::plugins::tree_shaking::{module, remove_useless_stmts, statement_graph};
+use crate::stats::{human_readable_size, AmbiguousExportsInfo};
 use crate::{mako_profile_function, mako_profile_scope};
 
 type TreeShakingModuleMap = HashMap<ModuleId, RefCell<TreeShakeModule>>;
 
+const DEFAULT_AMBIGUOUS_EXPORTS_THRESHOLD: u64 = 20 * 1024;
+
+// (module id, local export name) of the binding an export name resolves to
+type ExportOrigin = (ModuleId, String);
+
 pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>) -> Result<()> {
     let (topo_sorted_modules, _cyclic_modules) = {
         mako_profile_scope!("tree shake topo-sort");
@@ -61,9 +86,64 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
             false
         });
 
+    // with module granularity, the used modules are kept as a whole without analyzing the
+    // statements of them
+    if context
+        .config
+        .optimization
+        .as_ref()
+        .and_then(|o| o.tree_shaking)
+        == Some(TreeShakingGranularity::Modules)
+    {
+        prune_side_effects_free_modules(module_graph, &tree_shake_modules_ids, context);
+        return Ok(());
+    }
+
     let tree_shake_modules_map = {
         mako_profile_scope!("init_tree_shake_modules_map");
-        tree_shake_modules_ids
+        let mut cache = context.tree_shaking_cache.lock().unwrap();
+        // modules are only shaken again on rebuilds in watch mode
+        let use_cache = context.args.watch;
+
+        if use_cache {
+            // the importers of the changed modules are analyzed again, e.g. they inline the
+            // const enums of them
+            let mut changed = module_graph
+                .modules()
+                .into_iter()
+                .filter(|module| cache.is_changed(module))
+                .map(|module| module.id.clone())
+                .collect::<VecDeque<_>>();
+            let mut invalidated = HashSet::new();
+            while let Some(module_id) = changed.pop_front() {
+                if !invalidated.insert(module_id.clone()) {
+                    continue;
+                }
+                cache.invalidate(&module_id);
+                changed.extend(
+                    module_graph
+                        .get_dependents(&module_id)
+                        .into_iter()
+                        .map(|(dependent, _)| dependent.clone()),
+                );
+            }
+            cache.record_content_hashes(module_graph.modules().into_iter());
+        }
+
+        // unchanged modules reuse the statement graph of last run
+        let cached_stmt_graphs = tree_shake_modules_ids
+            .iter()
+            .filter(|_| use_cache)
+            .filter_map(|module_id| {
+                let module = module_graph.get_module_mut(module_id).unwrap();
+                cache
+                    .restore(module)
+                    .map(|stmt_graph| (module_id.clone(), stmt_graph))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let comments = context.meta.script.origin_comments.read().unwrap();
+        let tree_shake_modules_map = tree_shake_modules_ids
             .par_iter()
             .enumerate()
             .map(|(index, module_id)| {
@@ -72,13 +152,36 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
                 let module = module_graph.get_module(module_id).unwrap();
 
                 let tree_shake_module = GLOBALS.set(&context.meta.script.globals, || {
-                    TreeShakeModule::new(module, index)
-                });
-
-                (module_id.clone(), RefCell::new(tree_shake_module))
+                    TreeShakeModule::new_with_stmt_graph(
+                        module,
+                        index,
+                        cached_stmt_graphs.get(module_id).cloned(),
+                        &comments,
+                        context,
+                    )
+                })?;
+
+                Ok((module_id.clone(), RefCell::new(tree_shake_module)))
             })
-            .collect::<HashMap<_, _>>()
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        for (module_id, tsm) in tree_shake_modules_map.iter().filter(|_| use_cache) {
+            if !cached_stmt_graphs.contains_key(module_id) {
+                let module = module_graph.get_module(module_id).unwrap();
+                cache.insert(module, tsm.borrow().stmt_graph.clone());
+            }
+        }
+
+        tree_shake_modules_map
     };
+    retain_statements(&tree_shake_modules_map, module_graph, context)?;
+    dump_statement_graphs(&tree_shake_modules_map, context)?;
+    propagate_no_side_effects_fns(
+        &tree_shake_modules_ids,
+        &tree_shake_modules_map,
+        module_graph,
+    );
+
     let mut current_index = (tree_shake_modules_ids.len() - 1) as i64;
 
     // update tree-shake module side_effects flag in reversed topo-sort order
@@ -116,6 +219,12 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
         &tree_shake_modules_map,
         module_graph,
     );
+    check_star_export_conflicts(
+        &tree_shake_modules_ids,
+        &tree_shake_modules_map,
+        module_graph,
+        context,
+    )?;
 
     if let Some(optimization) = &context.config.optimization
         && optimization.skip_modules.unwrap_or(false)
@@ -156,40 +265,168 @@ pub fn optimize_modules(module_graph: &mut ModuleGraph, context: &Arc<Context>)
         }
     });
 
+    {
+        let used_exports = tree_shake_modules_map
+            .iter()
+            .filter(|(_, tsm)| !tsm.borrow().not_used())
+            .map(|(module_id, tsm)| {
+                (
+                    module_id.generate(context),
+                    tsm.borrow().used_export_names(),
+                )
+            })
+            .collect();
+        context.stats_info.set_used_exports(used_exports);
+    }
+
+    warn_ambiguous_exports(&tree_shake_modules_map, module_graph, context);
+
+    emit_shake_metadata(&tree_shake_modules_map, module_graph, context)?;
+
+    // the modules are wrapped with `__mako_require__` in scope
+    let keep_bindings = context.config.minify
+        && matches!(context.config.mode, Mode::Production)
+        && context.config.output.mode == OutputMode::Bundle
+        && !context.config.output.is_preserve_modules()
+        && context.config.module_wrapper != ModuleWrapper::None;
+
     {
         mako_profile_scope!("update ast");
         for (module_id, tsm) in &tree_shake_modules_map {
             mako_profile_scope!("update ast", &module_id.id);
             let mut tsm = tsm.borrow_mut();
+            let tsm = tsm.deref_mut();
 
             if tsm.not_used() {
-                module_graph.remove_module(module_id);
+                // every module is emitted with preserveModules, keep it untouched
+                if !context.config.output.is_preserve_modules() {
+                    module_graph.remove_module(module_id);
+                }
             } else if let Some(swc_module) = &mut tsm.updated_ast {
-                module_graph
+                let ast = module_graph
                     .get_module_mut(module_id)
                     .unwrap()
                     .info
                     .as_mut()
                     .unwrap()
                     .ast
-                    .as_script_ast_mut()
-                    .body = swc_module.body.take();
+                    .as_script_ast_mut();
+                if keep_bindings && let Some(stmt) = keep_bindings_stmt(&tsm.stmt_graph, ast) {
+                    swc_module.body.push(stmt);
+                }
+                ast.body = swc_module.body.take();
             }
         }
     }
 
-    if context
-        .config
-        .optimization
-        .as_ref()
-        .map_or(false, |o| o.concatenate_modules.unwrap_or(false))
+    let concatenated_modules = if !context.config.output.is_preserve_modules()
+        && context
+            .config
+            .optimization
+            .as_ref()
+            .map_or(false, |o| o.concatenate_modules.unwrap_or(false))
+    {
+        optimize_module_graph(module_graph, &tree_shake_modules_map, context)?
+    } else {
+        HashSet::new()
+    };
+
+    // every module's exports are public with preserveModules
+    if !context.config.output.is_preserve_modules()
+        && context
+            .config
+            .optimization
+            .as_ref()
+            .map_or(false, |o| o.mangle_exports.unwrap_or(false))
     {
-        optimize_module_graph(module_graph, &tree_shake_modules_map, context)?;
+        mangle_exports(module_graph, &tree_shake_modules_map, &concatenated_modules);
     }
 
     Ok(())
 }
 
+// Functions annotated with `/*#__NO_SIDE_EFFECTS__*/` are propagated from the exporting
+// modules to their importers in reversed topo-sort order, so the calls to them in
+// importers are treated as pure too.
+fn propagate_no_side_effects_fns(
+    tree_shake_modules_ids: &[ModuleId],
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &ModuleGraph,
+) {
+    for module_id in tree_shake_modules_ids.iter().rev() {
+        let tsm = tree_shake_modules_map.get(module_id).unwrap().borrow();
+
+        let dep_no_side_effects_exports = |source: &String| -> HashSet<String> {
+            module_graph
+                .get_dependency_module_by_source(module_id, source)
+                .filter(|&dep_id| dep_id != module_id)
+                .and_then(|dep_id| tree_shake_modules_map.get(dep_id))
+                .map(|dep_tsm| dep_tsm.borrow().no_side_effects_exports.clone())
+                .unwrap_or_default()
+        };
+
+        let mut no_side_effects_fns = tsm.stmt_graph.no_side_effects_fns();
+        for import_info in tsm.imports() {
+            let dep_exports = dep_no_side_effects_exports(&import_info.source);
+            if dep_exports.is_empty() {
+                continue;
+            }
+
+            for sp in &import_info.specifiers {
+                let (local, imported) = match sp {
+                    statement_graph::ImportSpecifierInfo::Named { local, imported } => {
+                        (local, strip_context(imported.as_ref().unwrap_or(local)))
+                    }
+                    statement_graph::ImportSpecifierInfo::Default(local) => {
+                        (local, "default".to_string())
+                    }
+                    statement_graph::ImportSpecifierInfo::Namespace(_) => continue,
+                };
+                if dep_exports.contains(&imported) {
+                    no_side_effects_fns.insert(local.clone());
+                }
+            }
+        }
+
+        let mut no_side_effects_exports = HashSet::new();
+        for export_info in tsm.exports() {
+            let dep_exports = export_info.source.as_ref().map(dep_no_side_effects_exports);
+
+            for sp in &export_info.specifiers {
+                match sp {
+                    ExportSpecifierInfo::Named { local, exported } => {
+                        let no_side_effects = match &dep_exports {
+                            Some(dep_exports) => dep_exports.contains(&strip_context(local)),
+                            None => no_side_effects_fns.contains(local),
+                        };
+                        if no_side_effects {
+                            no_side_effects_exports
+                                .insert(strip_context(exported.as_ref().unwrap_or(local)));
+                        }
+                    }
+                    ExportSpecifierInfo::Default(Some(local)) => {
+                        if no_side_effects_fns.contains(local) {
+                            no_side_effects_exports.insert("default".to_string());
+                        }
+                    }
+                    ExportSpecifierInfo::Default(None) => {
+                        if no_side_effects_fns.contains(NO_SIDE_EFFECTS_DEFAULT) {
+                            no_side_effects_exports.insert("default".to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        drop(tsm);
+
+        let mut tsm = tree_shake_modules_map.get(module_id).unwrap().borrow_mut();
+        tsm.no_side_effects_fns = no_side_effects_fns;
+        tsm.no_side_effects_exports = no_side_effects_exports;
+        tsm.apply_no_side_effects_fns();
+    }
+}
+
 // Add all imported to used_exports
 // returns (added, imported_module_topo_order)
 fn add_used_exports_by_import_info(
@@ -302,7 +539,8 @@ fn add_used_exports_by_export_info(
                             added |= exported_tree_shake_module.use_all_exports();
                         }
                         statement_graph::ExportSpecifierInfo::Named { local, .. } => {
-                            if local == &"default".to_string() {
+                            // export { default } from "x", local is `default#0`
+                            if strip_context(local) == "default" {
                                 added |= exported_tree_shake_module
                                     .add_used_export(Some(&module::UsedIdent::Default));
                             } else {
@@ -367,6 +605,8 @@ fn fill_all_export_start_export_info(
 ) {
     mako_profile_function!();
 
+    let mut export_origins_collector = ExportOriginsCollector::default();
+
     for module_id in tree_shake_modules_ids.iter() {
         let tsm = tree_shake_modules_map.get(module_id).unwrap().borrow();
 
@@ -377,6 +617,7 @@ fn fill_all_export_start_export_info(
         let mut all_exports = AllExports::default();
 
         let mut exports_stmts_export_info = vec![];
+        let mut external_star_stmts = vec![];
 
         for exp_info in tsm.exports() {
             if let Some(source) = exp_info.source {
@@ -406,6 +647,17 @@ fn fill_all_export_start_export_info(
                                     });
 
                                     all_exports.extends(dep_all_exports);
+                                } else if is_external_module(dependent_id, module_graph) {
+                                    // the exports of an external module are unknown, any name
+                                    // may come from it, so the re-export is kept as is
+                                    exports_stmts_export_info.push(ExportInfo {
+                                        source: Some(source.clone()),
+                                        specifiers: vec![ExportSpecifierInfo::Ambiguous(vec![])],
+                                        stmt_id: exp_info.stmt_id,
+                                    });
+                                    external_star_stmts.push(exp_info.stmt_id);
+
+                                    all_exports.as_ambiguous();
                                 }
                             }
                         }
@@ -424,17 +676,110 @@ fn fill_all_export_start_export_info(
 
         drop(tsm);
 
+        // names provided by several star exports with different bindings are not exported
+        let star_export_conflicts = export_origins_collector
+            .collect(module_id, tree_shake_modules_map, module_graph)
+            .map(|export_origins| export_origins.conflicts.clone())
+            .unwrap_or_default();
+        if !star_export_conflicts.is_empty() {
+            all_exports.remove_idents(&star_export_conflicts);
+            for exp_info in exports_stmts_export_info.iter_mut() {
+                for sp_info in exp_info.specifiers.iter_mut() {
+                    if let ExportSpecifierInfo::All(names) | ExportSpecifierInfo::Ambiguous(names) =
+                        sp_info
+                    {
+                        names.retain(|name| !star_export_conflicts.contains(name));
+                    }
+                }
+            }
+        }
+
         let mut tsm = tree_shake_modules_map.get(module_id).unwrap().borrow_mut();
 
         tsm.all_exports = all_exports;
+        tsm.star_export_conflicts = star_export_conflicts;
 
         for exp_info in exports_stmts_export_info {
             let stmt_id = exp_info.stmt_id;
             tsm.stmt_graph.stmt_mut(&stmt_id).export_info = Some(exp_info);
         }
+        for stmt_id in external_star_stmts {
+            tsm.stmt_graph.stmt_mut(&stmt_id).is_self_executed = true;
+        }
     }
 }
 
+// the statements retained by the plugins are kept like the ones with side effects
+fn retain_statements(
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Result<()> {
+    for (module_id, tsm) in tree_shake_modules_map {
+        let module = module_graph.get_module(module_id).unwrap();
+        let Some(ModuleAst::Script(ast)) = module.info.as_ref().map(|info| &info.ast) else {
+            continue;
+        };
+        let stmt_ids = context
+            .plugin_driver
+            .treeshake_retain(module_id, &ast.ast, context)?;
+
+        let mut tsm = tsm.borrow_mut();
+        for stmt_id in stmt_ids {
+            if !tsm.stmt_graph.has_stmt(&stmt_id) {
+                return Err(anyhow!(
+                    "statement {} to retain is not found in {}",
+                    stmt_id,
+                    module_id.id
+                ));
+            }
+            let stmt = tsm.stmt_graph.stmt_mut(&stmt_id);
+            stmt.is_self_executed = true;
+            stmt.has_side_effects = true;
+            stmt.side_effect_callees = None;
+            stmt.droppable = false;
+            stmt.keep = true;
+        }
+    }
+    Ok(())
+}
+
+// The bindings of the statements kept by `/*#__KEEP__*/` or the plugins are passed to
+// `__mako_require__.k`, a no-op of the runtime, otherwise the minifier removes the unused ones.
+fn keep_bindings_stmt(stmt_graph: &StatementGraph, ast: &SwcModule) -> Option<ModuleItem> {
+    let mut stmts = stmt_graph.stmts();
+    stmts.sort_by_key(|stmt| stmt.id);
+    let args = stmts
+        .into_iter()
+        .filter(|stmt| stmt.keep)
+        .flat_map(|stmt| match &ast.body[stmt.id] {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl))) => {
+                find_pat_ids::<_, Ident>(&var_decl.decls)
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(fn_decl))) => vec![fn_decl.ident.clone()],
+            ModuleItem::Stmt(Stmt::Decl(Decl::Class(class_decl))) => {
+                vec![class_decl.ident.clone()]
+            }
+            _ => vec![],
+        })
+        .map(|ident| ident.as_arg())
+        .collect::<Vec<_>>();
+    if args.is_empty() {
+        return None;
+    }
+    Some(
+        member_call(Expr::Ident(id("__mako_require__")), member_prop("k"), args)
+            .into_stmt()
+            .into(),
+    )
+}
+
+fn is_external_module(module_id: &ModuleId, module_graph: &ModuleGraph) -> bool {
+    module_graph
+        .get_module(module_id)
+        .is_some_and(|module| module.is_external())
+}
+
 fn collect_all_exports_of(
     module_id: &ModuleId,
     tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
@@ -475,6 +820,8 @@ fn collect_all_exports_of(
                                 );
 
                                 all_exports.extends(dep_all_exports);
+                            } else if is_external_module(dependent_id, module_graph) {
+                                all_exports.as_ambiguous();
                             }
                         }
                     }
@@ -492,6 +839,298 @@ fn collect_all_exports_of(
     }
 }
 
+#[derive(Default)]
+struct ExportOrigins {
+    origins: HashMap<String, ExportOrigin>,
+    conflicts: HashSet<String>,
+}
+
+// Resolve the export names of the modules to the bindings they come from, names provided by
+// several `export * from` with different bindings and not exported by the module itself are
+// conflicts. The origins of each module are collected once and shared by all the importers and
+// re-exported names of it.
+#[derive(Default)]
+struct ExportOriginsCollector {
+    cache: HashMap<ModuleId, Option<Rc<ExportOrigins>>>,
+    visiting: HashSet<ModuleId>,
+    // the origins are partial when a circular star export is hit, they are not cached unless
+    // the cycle is entered from the module itself
+    hit_cycle: bool,
+}
+
+impl ExportOriginsCollector {
+    // Returns None if the exports are not statically known, e.g. commonjs.
+    fn collect(
+        &mut self,
+        module_id: &ModuleId,
+        tree_shake_modules_map: &TreeShakingModuleMap,
+        module_graph: &ModuleGraph,
+    ) -> Option<Rc<ExportOrigins>> {
+        if let Some(export_origins) = self.cache.get(module_id) {
+            return export_origins.clone();
+        }
+
+        let tsm = tree_shake_modules_map.get(module_id)?.borrow();
+
+        if tsm.module_system != ModuleSystem::ESModule {
+            self.cache.insert(module_id.clone(), None);
+            return None;
+        }
+        // circular star exports
+        if !self.visiting.insert(module_id.clone()) {
+            self.hit_cycle = true;
+            return Some(Rc::default());
+        }
+        let parent_hit_cycle = std::mem::take(&mut self.hit_cycle);
+
+        let mut origins = HashMap::new();
+        let mut star_origins: HashMap<String, ExportOrigin> = HashMap::new();
+        let mut conflicts = HashSet::new();
+
+        for exp_info in tsm.exports() {
+            let dep_id = exp_info
+                .source
+                .as_ref()
+                .and_then(|source| module_graph.get_dependency_module_by_source(module_id, source));
+
+            for sp_info in exp_info.specifiers {
+                match (&sp_info, dep_id) {
+                    // export * from "xx"
+                    (
+                        ExportSpecifierInfo::All(_) | ExportSpecifierInfo::Ambiguous(_),
+                        Some(dep_id),
+                    ) => {
+                        let Some(dep_origins) =
+                            self.collect(dep_id, tree_shake_modules_map, module_graph)
+                        else {
+                            continue;
+                        };
+
+                        conflicts.extend(dep_origins.conflicts.iter().cloned());
+                        for (name, origin) in dep_origins.origins.iter() {
+                            if name == "default" {
+                                continue;
+                            }
+                            match star_origins.get(name) {
+                                Some(existing) if existing != origin => {
+                                    conflicts.insert(name.clone());
+                                }
+                                Some(_) => {}
+                                None => {
+                                    star_origins.insert(name.clone(), origin.clone());
+                                }
+                            }
+                        }
+                    }
+                    // export { foo as bar } from "xx"
+                    (ExportSpecifierInfo::Named { local, exported }, Some(dep_id)) => {
+                        let imported = strip_context(local);
+                        let exported = strip_context(exported.as_ref().unwrap_or(local));
+                        let origin = self
+                            .collect(dep_id, tree_shake_modules_map, module_graph)
+                            .and_then(|dep_origins| dep_origins.origins.get(&imported).cloned())
+                            .unwrap_or_else(|| (dep_id.clone(), imported));
+
+                        origins.insert(exported, origin);
+                    }
+                    _ => {
+                        for name in sp_info.to_idents() {
+                            origins.insert(name.clone(), (module_id.clone(), name));
+                        }
+                    }
+                }
+            }
+        }
+
+        // exports of the module itself take precedence over star exports
+        conflicts.retain(|name| !origins.contains_key(name));
+        for (name, origin) in star_origins {
+            if !conflicts.contains(&name) {
+                origins.entry(name).or_insert(origin);
+            }
+        }
+
+        self.visiting.remove(module_id);
+
+        let export_origins = Rc::new(ExportOrigins { origins, conflicts });
+        if !self.hit_cycle || self.visiting.is_empty() {
+            self.cache
+                .insert(module_id.clone(), Some(export_origins.clone()));
+        }
+        self.hit_cycle |= parent_hit_cycle;
+
+        Some(export_origins)
+    }
+}
+
+// Importing a name which is ambiguous between star exports is a SyntaxError in ESM, report it
+// instead of picking one of the bindings arbitrarily.
+fn check_star_export_conflicts(
+    tree_shake_modules_ids: &[ModuleId],
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Result<()> {
+    for module_id in tree_shake_modules_ids {
+        let tsm = tree_shake_modules_map.get(module_id).unwrap().borrow();
+
+        let mut requested = vec![];
+        for import_info in tsm.imports() {
+            for sp in &import_info.specifiers {
+                if let statement_graph::ImportSpecifierInfo::Named { local, imported } = sp {
+                    requested.push((
+                        import_info.source.clone(),
+                        strip_context(imported.as_ref().unwrap_or(local)),
+                        import_info.stmt_id,
+                    ));
+                }
+            }
+        }
+        for export_info in tsm.exports() {
+            let Some(source) = export_info.source else {
+                continue;
+            };
+            for sp in &export_info.specifiers {
+                if let ExportSpecifierInfo::Named { local, .. } = sp {
+                    requested.push((source.clone(), strip_context(local), export_info.stmt_id));
+                }
+            }
+        }
+
+        for (source, name, stmt_id) in requested {
+            let conflicted = module_graph
+                .get_dependency_module_by_source(module_id, &source)
+                .and_then(|dep_id| tree_shake_modules_map.get(dep_id))
+                .is_some_and(|dep_tsm| dep_tsm.borrow().star_export_conflicts.contains(&name));
+            if conflicted {
+                return Err(anyhow!(
+                    "The requested module '{}' contains conflicting star exports for name '{}', imported by {}",
+                    source,
+                    name,
+                    stmt_location(&tsm, stmt_id, module_id, context)
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the used exports which can only come from `export * from` modules with unknown exports
+// (e.g. commonjs) keep the whole source modules, warn once per module when too much is kept
+fn warn_ambiguous_exports(
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) {
+    let threshold = context
+        .config
+        .optimization
+        .as_ref()
+        .and_then(|o| o.ambiguous_exports_threshold)
+        .unwrap_or(DEFAULT_AMBIGUOUS_EXPORTS_THRESHOLD);
+    let relative_path = |id: &ModuleId| {
+        let absolute_path = PathBuf::from(&id.id);
+        win_path(
+            &diff_paths(&absolute_path, &context.root)
+                .unwrap_or(absolute_path)
+                .to_string_lossy(),
+        )
+    };
+
+    let mut module_ids = tree_shake_modules_map
+        .iter()
+        .filter(|(_, tsm)| !tsm.borrow().not_used())
+        .map(|(module_id, _)| module_id)
+        .collect::<Vec<_>>();
+    module_ids.sort();
+
+    let mut infos = vec![];
+    for module_id in module_ids {
+        let ambiguous = tree_shake_modules_map[module_id]
+            .borrow()
+            .ambiguous_used_exports();
+        if ambiguous.is_empty() {
+            continue;
+        }
+
+        let module_infos = ambiguous
+            .into_iter()
+            .map(|(source, idents)| {
+                let kept_bytes = module_graph
+                    .get_dependency_module_by_source(module_id, &source)
+                    .and_then(|dep_id| tree_shake_modules_map.get(dep_id))
+                    .map_or(0, |dep_tsm| {
+                        dep_tsm
+                            .borrow()
+                            .stmt_graph
+                            .stmts()
+                            .iter()
+                            .map(|stmt| (stmt.span.hi.0 - stmt.span.lo.0) as u64)
+                            .sum()
+                    });
+                AmbiguousExportsInfo {
+                    module: module_id.generate(context),
+                    source,
+                    idents,
+                    kept_bytes,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let kept_bytes = module_infos.iter().map(|info| info.kept_bytes).sum::<u64>();
+        if kept_bytes > threshold {
+            let mut message = format!(
+                "About {} is kept for the ambiguous exports used from {}:",
+                human_readable_size(kept_bytes),
+                relative_path(module_id)
+            );
+            let tsm = tree_shake_modules_map[module_id].borrow();
+            for info in &module_infos {
+                let stmt_id = tsm
+                    .exports()
+                    .into_iter()
+                    .find(|export_info| export_info.source.as_ref() == Some(&info.source))
+                    .map(|export_info| export_info.stmt_id);
+                message.push_str(&format!(
+                    "\n  {} may come from `export * from '{}'`",
+                    info.idents.iter().cloned().collect::<Vec<_>>().join(", "),
+                    info.source
+                ));
+                if let Some(stmt_id) = stmt_id {
+                    message.push_str(&format!(
+                        " at {}",
+                        stmt_location(&tsm, stmt_id, module_id, context)
+                    ));
+                }
+            }
+            message.push_str(
+                "\nThe exports of the star-exported modules are unknown, so they are kept as a whole, re-export the used names with named exports instead, e.g. `export { foo } from './foo'`",
+            );
+            context.warn(message);
+        }
+        infos.extend(module_infos);
+    }
+
+    context.stats_info.set_ambiguous_exports(infos);
+}
+
+// `path:line:column` of the statement, or the module id if it has no position
+fn stmt_location(
+    tsm: &TreeShakeModule,
+    stmt_id: statement_graph::StatementId,
+    module_id: &ModuleId,
+    context: &Arc<Context>,
+) -> String {
+    tsm.stmt_graph
+        .stmt(&stmt_id)
+        .source_location(&context.meta.script.cm)
+        .map_or_else(
+            || module_id.id.clone(),
+            |(file, line, column)| format!("{}:{}:{}", file, line, column),
+        )
+}
+
 fn shake_module(
     module_graph: &ModuleGraph,
     tree_shake_modules_ids: &[ModuleId],
@@ -621,3 +1260,288 @@ fn greater_equal_than(a: usize, b: i64) -> bool {
         (a as i64) >= b
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Arc;
+
+    use anyhow::Result;
+    use swc_core::ecma::ast::Module as SwcModule;
+
+    use crate::compiler::{Args, Compiler, Context};
+    use crate::config::{Config, OptimizationConfig, TreeShakingGranularity};
+    use crate::module::ModuleId;
+    use crate::plugin::Plugin;
+    use crate::plugins::tree_shaking::statement_graph::StatementId;
+    use crate::utils::test_helper::{setup_compiler, setup_compiler_with_plugins, setup_logger};
+
+    #[test]
+    fn test_tree_shaking_cache_on_rebuild() {
+        let compiler = setup_compiler("test/build/tree-shaking-cache", false);
+        compiler.compile().unwrap();
+        // not used without watch
+        {
+            let cache = compiler.context.tree_shaking_cache.lock().unwrap();
+            assert_eq!((cache.hits, cache.misses), (0, 0));
+        }
+
+        setup_logger();
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/build/tree-shaking-cache");
+        let root = std::env::temp_dir().join("mako-test-tree-shaking-cache-on-rebuild");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        for file in ["index.ts", "foo.ts", "bar.ts", "mako.config.json"] {
+            fs::copy(fixture.join(file), root.join(file)).unwrap();
+        }
+        let config = Config::new(&root, None, None).unwrap();
+        let compiler = Compiler::new(config, root.clone(), Args { watch: true }, None).unwrap();
+        assert!(compiler.context.rebuilds_fully());
+        compiler.compile().unwrap();
+        let context = &compiler.context;
+
+        let misses = {
+            let cache = context.tree_shaking_cache.lock().unwrap();
+            assert_eq!(cache.hits, 0);
+            cache.misses
+        };
+        assert!(misses > 0);
+
+        // rebuild on changes like `build --watch`, bar.ts and its importer are analyzed again
+        fs::write(root.join("bar.ts"), "export const bar = 'baz';\n").unwrap();
+        compiler.compile().unwrap();
+        {
+            let cache = context.tree_shaking_cache.lock().unwrap();
+            assert_eq!(cache.hits, misses - 2);
+            assert_eq!(cache.misses, misses + 2);
+        }
+        let index = fs::read_to_string(context.config.output.path.join("index.js")).unwrap();
+        assert!(index.contains("baz"), "{}", index);
+
+        context.clear_tree_shaking_cache();
+        compiler.compile().unwrap();
+        {
+            let cache = context.tree_shaking_cache.lock().unwrap();
+            assert_eq!(cache.hits, 0);
+            assert_eq!(cache.misses, misses);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_used_exports() {
+        let compiler = setup_compiler("test/build/tree-shaking-used-exports", false);
+        compiler.compile().unwrap();
+        let used_exports = compiler.context.stats_info.get_used_exports();
+
+        let names = |id: &str| {
+            used_exports
+                .get(id)
+                .unwrap()
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("utils.ts"), vec!["a", "b", "default"]);
+        assert_eq!(names("all.ts"), vec!["x", "y"]);
+        assert_eq!(names("reexport.ts"), vec!["z"]);
+        assert_eq!(names("z.ts"), vec!["z"]);
+        assert!(names("index.ts").is_empty());
+    }
+
+    #[test]
+    fn test_shake_statements() {
+        let compiler = setup_compiler("test/build/tree-shaking-statements", false);
+        compiler.compile().unwrap();
+
+        let used_exports = compiler.context.stats_info.get_used_exports();
+        assert_eq!(
+            used_exports
+                .get("dep.js")
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec!["used"]
+        );
+        let output = &compiler.context.config.output.path;
+        let content = fs::read_to_string(output.join("index.js")).unwrap();
+        assert!(content.contains("const used = "), "{}", content);
+        assert!(
+            content.contains("console.log('side effect')"),
+            "{}",
+            content
+        );
+        assert!(!content.contains("unused"), "{}", content);
+        assert!(!content.contains("function foo"), "{}", content);
+        // the call annotated with #__PURE__ is removed with its unused binding
+        assert!(!content.contains("make()"), "{}", content);
+    }
+
+    #[test]
+    fn test_mixed_import_specifiers() {
+        let compiler = setup_compiler("test/build/tree-shaking-mixed-import", false);
+        compiler.compile().unwrap();
+
+        // only the default specifier of `import dep, { named }` is used
+        let used_exports = compiler.context.stats_info.get_used_exports();
+        assert_eq!(
+            used_exports
+                .get("dep.js")
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec!["default"]
+        );
+        let output = &compiler.context.config.output.path;
+        let content = fs::read_to_string(output.join("index.js")).unwrap();
+        assert!(content.contains("dep-default"), "{}", content);
+        assert!(!content.contains("dep-named"), "{}", content);
+    }
+
+    #[test]
+    fn test_export_star_conflicts() {
+        let compiler = setup_compiler("test/build/tree-shaking-export-star-conflicts", false);
+        let err = compiler.compile().unwrap_err().to_string();
+        // `shared` comes from the same binding, `bar` is overridden by mid.ts and the circular
+        // star export of c.ts is resolved once
+        assert!(err.contains(
+            "The requested module './mid' contains conflicting star exports for name 'foo', imported by index.ts:1:1"
+        ), "{}", err);
+    }
+
+    #[test]
+    fn test_warn_ambiguous_exports() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/tree-shaking-ambiguous-exports");
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.optimization = Some(OptimizationConfig {
+            ambiguous_exports_threshold: Some(0),
+            ..Default::default()
+        });
+        let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+        let result = compiler.compile().unwrap();
+
+        // `foo` and `bar` are aggregated into one warning of barrel.ts
+        assert_eq!(result.warnings.len(), 1);
+        let message = &result.warnings[0].message;
+        assert!(message.contains("barrel.ts"), "{}", message);
+        // the line of `export * from './cjs'` in barrel.ts
+        assert!(
+            message.contains("bar, foo may come from `export * from './cjs'` at barrel.ts:2:1"),
+            "{}",
+            message
+        );
+
+        let ambiguous_exports = compiler.context.stats_info.get_ambiguous_exports();
+        assert_eq!(ambiguous_exports.len(), 1);
+        assert_eq!(ambiguous_exports[0].source, "./cjs");
+        assert_eq!(
+            ambiguous_exports[0].idents.iter().collect::<Vec<_>>(),
+            vec!["bar", "foo"]
+        );
+        assert!(ambiguous_exports[0].kept_bytes > 0);
+    }
+
+    // retains the first statement of lib.ts
+    struct RetainPlugin {}
+
+    impl Plugin for RetainPlugin {
+        fn name(&self) -> &str {
+            "retain_test"
+        }
+
+        fn treeshake_retain(
+            &self,
+            module_id: &ModuleId,
+            _ast: &SwcModule,
+            _context: &Arc<Context>,
+        ) -> Result<Option<Vec<StatementId>>> {
+            Ok(module_id.id.ends_with("lib.ts").then(|| vec![0]))
+        }
+    }
+
+    #[test]
+    fn test_treeshake_retain() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/tree-shaking-retain",
+            vec![Arc::new(RetainPlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let content = fs::read_to_string(output.join("index.js")).unwrap();
+        assert!(content.contains("pinned-by-plugin"), "{}", content);
+        // the dependencies of the retained statement are kept too
+        assert!(content.contains("pinned-helper"), "{}", content);
+        assert!(!content.contains("dead-code"), "{}", content);
+    }
+
+    #[test]
+    fn test_tree_shaking_keeps_objects_with_accessors() {
+        let compiler = setup_compiler("test/build/tree-shaking-accessors", false);
+        compiler.compile().unwrap();
+
+        let output = &compiler.context.config.output.path;
+        let content = fs::read_to_string(output.join("index.js")).unwrap();
+        // the used object is kept as a whole, including the getter which is not read here
+        assert!(content.contains("level-getter-called"), "{}", content);
+        assert!(!content.contains("theme-setter-called"), "{}", content);
+        assert!(
+            content.contains("destructured-getter-called"),
+            "{}",
+            content
+        );
+        // the accessors in functions only run after the functions are called
+        assert!(!content.contains("lazy-getter"), "{}", content);
+    }
+
+    // names of the modules kept in the module graph after compiling
+    fn compile_with_granularity(granularity: TreeShakingGranularity) -> Vec<&'static str> {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/tree-shaking-granularity");
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.optimization = Some(OptimizationConfig {
+            tree_shaking: Some(granularity),
+            ..Default::default()
+        });
+        let compiler = Compiler::new(config, root.clone(), Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        ["used.ts", "unused.ts", "dead.ts"]
+            .into_iter()
+            .filter(|name| {
+                let id = root.join(name).to_string_lossy().to_string();
+                module_graph.has_module(&id.into())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_shaking_granularity() {
+        // the re-export of `unused` is removed with the unused statements of lib.ts
+        assert_eq!(
+            compile_with_granularity(TreeShakingGranularity::Statements),
+            vec!["used.ts"]
+        );
+        // lib.ts is used so it's kept as a whole, only the module not used at all is removed
+        assert_eq!(
+            compile_with_granularity(TreeShakingGranularity::Modules),
+            vec!["used.ts", "unused.ts"]
+        );
+        assert_eq!(
+            compile_with_granularity(TreeShakingGranularity::Disabled),
+            vec!["used.ts", "unused.ts", "dead.ts"]
+        );
+    }
+}
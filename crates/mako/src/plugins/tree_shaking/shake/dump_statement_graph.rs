@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::Result;
+use glob_match::glob_match;
+use serde_json::{json, Value};
+
+use super::TreeShakingModuleMap;
+use crate::compiler::Context;
+use crate::config::debug_dir;
+use crate::module::relative_to_root;
+use crate::plugins::tree_shaking::statement_graph::{Statement, StatementGraph};
+
+pub const STATEMENT_GRAPH_DIR: &str = "statement-graph";
+
+/// Write the statement graphs of the modules matched by `debug.dumpStatementGraph`, a glob
+/// of the root relative paths matched like `forceSideEffects`, to `statement-graph/<path>.json`
+/// in the debug dir, with `/` and the characters not allowed in file names, e.g. `?` of the
+/// queries, in the path replaced by `_`.
+pub(super) fn dump_statement_graphs(
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let Some(pattern) = &context.config.debug.dump_statement_graph else {
+        return Ok(());
+    };
+    let pattern = pattern.replace('\\', "/");
+
+    let dir = debug_dir(&context.root).join(STATEMENT_GRAPH_DIR);
+    for (module_id, tsm) in tree_shake_modules_map {
+        let path = relative_to_root(&module_id.id, &context.root).replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        if !glob_match(&pattern, path) {
+            continue;
+        }
+
+        let graph = to_json(path, &tsm.borrow().stmt_graph, context);
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join(format!("{}.json", to_file_name(path))),
+            serde_json::to_string_pretty(&graph)?,
+        )?;
+    }
+    Ok(())
+}
+
+fn to_file_name(path: &str) -> String {
+    path.replace(['/', '?', ':', '*', '"', '<', '>', '|'], "_")
+}
+
+fn to_json(path: &str, stmt_graph: &StatementGraph, context: &Arc<Context>) -> Value {
+    let mut stmts = stmt_graph.stmts();
+    stmts.sort_by_key(|stmt| stmt.id);
+    let nodes = stmts
+        .into_iter()
+        .map(|stmt| stmt_to_json(stmt, context))
+        .collect::<Vec<_>>();
+
+    let mut edges = stmt_graph
+        .edges()
+        .into_iter()
+        .map(|(from, to, edge)| (from.id, to.id, edge.idents.iter().collect::<BTreeSet<_>>()))
+        .collect::<Vec<_>>();
+    edges.sort_by_key(|(from, to, _)| (*from, *to));
+    let edges = edges
+        .into_iter()
+        .map(|(from, to, idents)| json!({ "from": from, "to": to, "idents": idents }))
+        .collect::<Vec<_>>();
+
+    json!({ "module": path, "nodes": nodes, "edges": edges })
+}
+
+fn stmt_to_json(stmt: &Statement, context: &Arc<Context>) -> Value {
+    let loc = stmt
+        .source_location(&context.meta.script.cm)
+        .map(|(_, line, column)| format!("{}:{}", line, column));
+    json!({
+        "id": stmt.id,
+        "loc": loc,
+        "definedIdents": stmt.defined_idents.iter().collect::<BTreeSet<_>>(),
+        "usedIdents": stmt.used_idents.iter().collect::<BTreeSet<_>>(),
+        "isSelfExecuted": stmt.is_self_executed,
+        "importSource": stmt.import_info.as_ref().map(|info| &info.source),
+        "exportSource": stmt.export_info.as_ref().and_then(|info| info.source.as_ref()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+
+    use super::{to_file_name, STATEMENT_GRAPH_DIR};
+    use crate::config::debug_dir;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_dump_statement_graph() {
+        let compiler = setup_compiler("test/build/dump-statement-graph", false);
+        compiler.compile().unwrap();
+
+        let dir = debug_dir(&compiler.context.root).join(STATEMENT_GRAPH_DIR);
+        // only the matched module is dumped
+        assert!(!dir.join("src_foo.ts.json").exists());
+        let json = fs::read_to_string(dir.join("src_index.ts.json")).unwrap();
+        let graph: Value = serde_json::from_str(&json).unwrap();
+
+        let nodes = graph["nodes"].as_array().unwrap();
+        let import_id = nodes
+            .iter()
+            .find(|node| node["importSource"] == "./foo")
+            .map(|node| node["id"].clone())
+            .expect(&json);
+        let edges = graph["edges"].as_array().unwrap();
+        assert!(
+            edges.iter().any(|edge| {
+                edge["to"] == import_id
+                    && edge["idents"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .any(|ident| ident.as_str().unwrap().starts_with("foo#"))
+            }),
+            "{}",
+            json
+        );
+    }
+
+    #[test]
+    fn test_file_name_of_queries() {
+        assert_eq!(to_file_name("src/a.css?modules"), "src_a.css_modules");
+        assert_eq!(to_file_name("src/a.ts?a=1|2"), "src_a.ts_a=1_2");
+    }
+}
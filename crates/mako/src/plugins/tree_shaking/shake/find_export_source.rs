@@ -479,8 +479,9 @@ mod tests {
             side_effects: false,
         };
 
+        let comments = context.meta.script.origin_comments.read().unwrap();
         GLOBALS.set(&context.meta.script.globals, || {
-            TreeShakeModule::new(&mako_module, 0)
+            TreeShakeModule::new(&mako_module, 0, &comments, &context).unwrap()
         })
     }
 }
@@ -0,0 +1,479 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::Hasher;
+
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::{
+    Decl, ExportNamedSpecifier, ExportSpecifier, Ident, ImportDecl, ImportSpecifier,
+    Module as SwcModule, ModuleDecl, ModuleExportName, ModuleItem, NamedExport, Stmt,
+};
+use swc_core::ecma::utils::{find_pat_ids, quote_ident};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+use twox_hash::XxHash64;
+
+use crate::mako_profile_function;
+use crate::module::{ModuleId, ModuleSystem, ResolveType};
+use crate::module_graph::ModuleGraph;
+use crate::plugins::tree_shaking::module::TreeShakeModule;
+
+// names not longer than this are kept as is
+const MIN_MANGLED_LEN: usize = 2;
+// 64 bits of hash are enough for 10 chars
+const MAX_MANGLED_LEN: usize = 10;
+const FIRST_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$0123456789";
+// the reserved words of ecmascript, including the ones of strict mode and the literals, and
+// the names which can't be bound in strict mode
+const RESERVED: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Rename the named exports of esm modules to short names, and the specifiers of
+/// their importers accordingly. The short name of an export is derived from a hash
+/// of its original name, and the exports colliding on a short name all get longer
+/// ones, so adding or removing an export only renames the exports colliding with it.
+///
+/// Modules are skipped when any importer may access the exports dynamically,
+/// e.g. namespace imports, `require`, `import()` or `export *`, and `skipped`
+/// ones, e.g. the concatenated modules whose imports are already rewritten.
+pub fn mangle_exports(
+    module_graph: &mut ModuleGraph,
+    tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+    skipped: &HashSet<ModuleId>,
+) {
+    mako_profile_function!();
+
+    let mut mangled: HashMap<ModuleId, HashMap<String, String>> = HashMap::new();
+
+    for module_id in tree_shake_modules_map.keys() {
+        if skipped.contains(module_id)
+            || !module_graph.has_module(module_id)
+            || !can_mangle(module_id, module_graph, tree_shake_modules_map, skipped)
+        {
+            continue;
+        }
+
+        let module = module_graph.get_module(module_id).unwrap();
+        if let Some(names) = exported_names(module.info.as_ref().unwrap().ast.as_script_ast()) {
+            let names = mangle_names(&names);
+            if !names.is_empty() {
+                mangled.insert(module_id.clone(), names);
+            }
+        }
+    }
+
+    let importers = mangled
+        .keys()
+        .flat_map(|module_id| {
+            module_graph
+                .get_dependents(module_id)
+                .into_iter()
+                .map(|(importer, _)| importer.clone())
+        })
+        .collect::<HashSet<_>>();
+
+    for importer in importers {
+        let sources = module_graph
+            .get_dependencies(&importer)
+            .into_iter()
+            .filter_map(|(dep_id, dep)| {
+                mangled.get(dep_id).map(|names| (dep.source.clone(), names))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let module = module_graph.get_module_mut(&importer).unwrap();
+        let ast = module.info.as_mut().unwrap().ast.as_script_ast_mut();
+        ast.visit_mut_with(&mut ImportRenamer { sources: &sources });
+    }
+
+    for (module_id, names) in &mangled {
+        let module = module_graph.get_module_mut(module_id).unwrap();
+        let ast = module.info.as_mut().unwrap().ast.as_script_ast_mut();
+        rename_exports(ast, names);
+    }
+}
+
+fn can_mangle(
+    module_id: &ModuleId,
+    module_graph: &ModuleGraph,
+    tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
+    skipped: &HashSet<ModuleId>,
+) -> bool {
+    {
+        let tsm = tree_shake_modules_map.get(module_id).unwrap().borrow();
+        if tsm.module_system != ModuleSystem::ESModule || tsm.uses_all_exports() || tsm.not_used() {
+            return false;
+        }
+    }
+
+    // the exports of entries are the public api
+    let module = module_graph.get_module(module_id).unwrap();
+    if module.is_entry || module.is_external() || module.is_consume_share() {
+        return false;
+    }
+
+    // every importer must refer to the exports by name
+    module_graph
+        .get_dependents(module_id)
+        .iter()
+        .all(|&(importer, dependency)| {
+            !skipped.contains(importer)
+                && matches!(
+                    dependency.resolve_type,
+                    ResolveType::Import(_) | ResolveType::ExportNamed(_)
+                )
+                && tree_shake_modules_map
+                    .get(importer)
+                    .is_some_and(|tsm| tsm.borrow().module_system == ModuleSystem::ESModule)
+        })
+}
+
+// returns None if any export can't be renamed
+fn exported_names(module: &SwcModule) -> Option<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+
+    for item in &module.body {
+        let ModuleItem::ModuleDecl(module_decl) = item else {
+            continue;
+        };
+        match module_decl {
+            ModuleDecl::ExportDecl(export_decl) => {
+                names.extend(
+                    decl_idents(&export_decl.decl)?
+                        .iter()
+                        .map(|ident| ident.sym.to_string()),
+                );
+            }
+            ModuleDecl::ExportNamed(named_export) => {
+                if named_export.src.is_some() {
+                    return None;
+                }
+                for specifier in &named_export.specifiers {
+                    let ExportSpecifier::Named(named) = specifier else {
+                        return None;
+                    };
+                    match named.exported.as_ref().unwrap_or(&named.orig) {
+                        ModuleExportName::Ident(ident) => {
+                            names.insert(ident.sym.to_string());
+                        }
+                        ModuleExportName::Str(_) => return None,
+                    }
+                }
+            }
+            ModuleDecl::ExportAll(_) => return None,
+            _ => {}
+        }
+    }
+
+    // default export is kept for interop
+    names.remove("default");
+
+    Some(names)
+}
+
+fn decl_idents(decl: &Decl) -> Option<Vec<Ident>> {
+    match decl {
+        Decl::Class(class_decl) => Some(vec![class_decl.ident.clone()]),
+        Decl::Fn(fn_decl) => Some(vec![fn_decl.ident.clone()]),
+        Decl::Var(var_decl) => Some(find_pat_ids(&var_decl.decls)),
+        _ => None,
+    }
+}
+
+fn mangle_names(names: &BTreeSet<String>) -> HashMap<String, String> {
+    let mut mangled = HashMap::new();
+    let mut pending = names
+        .iter()
+        .filter(|name| name.len() > MIN_MANGLED_LEN)
+        .collect::<Vec<_>>();
+    for len in MIN_MANGLED_LEN..=MAX_MANGLED_LEN {
+        // the original name is kept if no shorter name is available
+        pending.retain(|name| name.len() > len);
+        let mut candidates: HashMap<String, Vec<&String>> = HashMap::new();
+        for name in pending {
+            candidates
+                .entry(short_name(name, len))
+                .or_default()
+                .push(name);
+        }
+
+        // the names colliding on a candidate all move on to a longer one, instead of the first
+        // one taking it, so that the result doesn't depend on the order of the names
+        pending = vec![];
+        for (candidate, colliding) in candidates {
+            // never collide with the original names, they may be kept
+            if colliding.len() == 1
+                && !names.contains(&candidate)
+                && !RESERVED.contains(&candidate.as_str())
+            {
+                mangled.insert(colliding[0].clone(), candidate);
+            } else {
+                pending.extend(colliding);
+            }
+        }
+    }
+    mangled
+}
+
+fn short_name(name: &str, len: usize) -> String {
+    let mut hasher: XxHash64 = Default::default();
+    hasher.write(name.as_bytes());
+    let mut hash = hasher.finish();
+
+    let mut short_name = String::with_capacity(len);
+    short_name.push(FIRST_CHARS[(hash % FIRST_CHARS.len() as u64) as usize] as char);
+    hash /= FIRST_CHARS.len() as u64;
+    while short_name.len() < len {
+        short_name.push(CHARS[(hash % CHARS.len() as u64) as usize] as char);
+        hash /= CHARS.len() as u64;
+    }
+    short_name
+}
+
+fn rename_exports(module: &mut SwcModule, names: &HashMap<String, String>) {
+    let mut specifiers = vec![];
+
+    // `export const a = 1` => `const a = 1; export { a as xY }`
+    module.body = std::mem::take(&mut module.body)
+        .into_iter()
+        .map(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                specifiers.extend(
+                    decl_idents(&export_decl.decl)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|ident| {
+                            let exported = names.get(ident.sym.as_ref()).map(|short_name| {
+                                ModuleExportName::Ident(quote_ident!(short_name.as_str()))
+                            });
+                            ExportSpecifier::Named(ExportNamedSpecifier {
+                                span: DUMMY_SP,
+                                orig: ModuleExportName::Ident(ident),
+                                exported,
+                                is_type_only: false,
+                            })
+                        }),
+                );
+                ModuleItem::Stmt(Stmt::Decl(export_decl.decl))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(mut named_export))
+                if named_export.src.is_none() =>
+            {
+                for specifier in named_export.specifiers.iter_mut() {
+                    if let ExportSpecifier::Named(named) = specifier
+                        && let Some(short_name) =
+                            names.get(&export_name(named.exported.as_ref().unwrap_or(&named.orig)))
+                    {
+                        named.exported =
+                            Some(ModuleExportName::Ident(quote_ident!(short_name.as_str())));
+                    }
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(named_export))
+            }
+            _ => item,
+        })
+        .collect();
+
+    if !specifiers.is_empty() {
+        module
+            .body
+            .push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+                NamedExport {
+                    span: DUMMY_SP,
+                    specifiers,
+                    src: None,
+                    type_only: false,
+                    with: None,
+                },
+            )));
+    }
+}
+
+fn export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str_lit) => str_lit.value.to_string(),
+    }
+}
+
+struct ImportRenamer<'a> {
+    sources: &'a HashMap<String, &'a HashMap<String, String>>,
+}
+
+impl VisitMut for ImportRenamer<'_> {
+    fn visit_mut_import_decl(&mut self, import_decl: &mut ImportDecl) {
+        let Some(names) = self.sources.get(import_decl.src.value.as_ref()) else {
+            return;
+        };
+        for specifier in import_decl.specifiers.iter_mut() {
+            if let ImportSpecifier::Named(named) = specifier {
+                let imported = named
+                    .imported
+                    .as_ref()
+                    .map_or_else(|| named.local.sym.to_string(), export_name);
+                if let Some(short_name) = names.get(&imported) {
+                    named.imported =
+                        Some(ModuleExportName::Ident(quote_ident!(short_name.as_str())));
+                }
+            }
+        }
+    }
+
+    fn visit_mut_named_export(&mut self, named_export: &mut NamedExport) {
+        let Some(names) = named_export
+            .src
+            .as_ref()
+            .and_then(|src| self.sources.get(src.value.as_ref()))
+        else {
+            return;
+        };
+        for specifier in named_export.specifiers.iter_mut() {
+            if let ExportSpecifier::Named(named) = specifier
+                && let Some(short_name) = names.get(&export_name(&named.orig))
+            {
+                if named.exported.is_none() {
+                    named.exported = Some(named.orig.clone());
+                }
+                named.orig = ModuleExportName::Ident(quote_ident!(short_name.as_str()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    use super::{mangle_names, short_name, MAX_MANGLED_LEN, MIN_MANGLED_LEN};
+    use crate::compiler::Compiler;
+    use crate::config::Config;
+    use crate::utils::test_helper::setup_logger;
+
+    #[test]
+    fn test_mangle_names() {
+        let names = BTreeSet::from([
+            "a".to_string(),
+            "formatDate".to_string(),
+            "parseDate".to_string(),
+        ]);
+        let mangled = mangle_names(&names);
+
+        // short names are kept
+        assert!(!mangled.contains_key("a"));
+        assert_eq!(mangled.len(), 2);
+        assert_ne!(mangled["formatDate"], mangled["parseDate"]);
+        assert!(mangled.values().all(|name| name.len() < "parseDate".len()));
+        // unrelated exports don't change the name
+        assert_eq!(
+            mangled["formatDate"],
+            mangle_names(&BTreeSet::from(["formatDate".to_string()]))["formatDate"]
+        );
+        assert!(mangled["formatDate"].starts_with(&short_name("formatDate", 2)));
+    }
+
+    #[test]
+    fn test_mangle_names_kept_when_export_added() {
+        let names = (0..500)
+            .map(|i| format!("export{}", i))
+            .collect::<BTreeSet<_>>();
+        let mangled = mangle_names(&names);
+        // some of them collide on the shortest names
+        assert!(mangled.values().any(|name| name.len() > MIN_MANGLED_LEN));
+
+        // an export not colliding with the existing ones
+        let added = (0..)
+            .map(|i| format!("added{}", i))
+            .find(|added| {
+                (MIN_MANGLED_LEN..=MAX_MANGLED_LEN).all(|len| {
+                    names
+                        .iter()
+                        .all(|name| short_name(name, len) != short_name(added, len))
+                })
+            })
+            .unwrap();
+        let mut names = names;
+        names.insert(added.clone());
+        let mangled_with_added = mangle_names(&names);
+
+        assert!(mangled_with_added.contains_key(&added));
+        for (name, short_name) in &mangled {
+            assert_eq!(&mangled_with_added[name], short_name);
+        }
+    }
+
+    #[test]
+    fn test_mangle_exports_stable_across_builds() {
+        setup_logger();
+        let build = || {
+            let root = std::env::current_dir()
+                .unwrap()
+                .join("test/build/mangle-exports");
+            let config = Config::new(&root, None, None).unwrap();
+            let compiler = Compiler::new(config, root, Default::default(), None).unwrap();
+            compiler.compile().unwrap();
+            fs::read_to_string(compiler.context.config.output.path.join("index.js")).unwrap()
+        };
+        let code = build();
+        assert_eq!(code, build());
+
+        let names = BTreeSet::from([
+            "DateRange".to_string(),
+            "formatDate".to_string(),
+            "parseDate".to_string(),
+        ]);
+        for (name, mangled) in mangle_names(&names) {
+            assert!(!code.contains(&format!("{}: function() {{", name)));
+            assert!(code.contains(&format!("{}: function() {{", mangled)));
+        }
+        // namespace imported
+        assert!(code.contains("nsValue: function() {"));
+    }
+}
@@ -34,7 +34,7 @@ pub fn optimize_module_graph(
     module_graph: &mut ModuleGraph,
     tree_shake_modules_map: &HashMap<ModuleId, RefCell<TreeShakeModule>>,
     context: &Arc<Context>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<HashSet<ModuleId>> {
     mako_profile_function!();
 
     let (sorted_module_ids, circles) = module_graph.toposort();
@@ -242,6 +242,8 @@ pub fn optimize_module_graph(
         src_2_module_id
     }
 
+    let mut concatenated_roots = HashSet::new();
+
     GLOBALS.set(&context.meta.script.globals, || {
         for config in &concat_configurations {
             mako_profile_scope!("concatenate", &config.root.id);
@@ -432,11 +434,12 @@ pub fn optimize_module_graph(
                 for inner in config.inners.iter() {
                     module_graph.remove_module(inner);
                 }
+                concatenated_roots.insert(config.root.clone());
             } else {
                 continue;
             }
         }
-        Ok(())
+        Ok(concatenated_roots)
     })
 }
 
@@ -7,11 +7,13 @@ use swc_core::base::atoms::JsWord;
 use swc_core::common::collections::AHashSet;
 use swc_core::common::{Mark, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::{
-    ClassExpr, DefaultDecl, ExportDefaultDecl, Expr, FnExpr, Id, Ident, KeyValueProp, MemberExpr,
-    Module, ModuleDecl, ModuleItem, ObjectLit, Prop, PropOrSpread, VarDeclKind,
+    ClassExpr, ComputedPropName, DefaultDecl, ExportDefaultDecl, Expr, FnExpr, Id, Ident,
+    KeyValueProp, MemberExpr, MemberProp, Module, ModuleDecl, ModuleItem, ObjectLit, Prop,
+    PropOrSpread, VarDeclKind,
 };
 use swc_core::ecma::utils::{
-    collect_decls, collect_decls_with_ctxt, member_expr, quote_ident, quote_str, ExprFactory,
+    collect_decls, collect_decls_with_ctxt, is_valid_prop_ident, member_expr, quote_ident,
+    quote_str, ExprFactory,
 };
 use swc_core::ecma::visit::{Visit, VisitWith};
 
@@ -252,6 +254,16 @@ pub type ImportModuleRefMap = HashMap<Id, ModuleRef>;
 pub fn module_ref_to_expr(module_ref: &ModuleRef) -> Expr {
     match module_ref {
         (id, None) => quote_ident!(DUMMY_CTXT, id.sym.clone()).into(),
+        // e.g. the string names imported from the externals, `import { "a-b" as ab } from "a"`
+        (id, Some(field)) if !is_valid_prop_ident(field) => MemberExpr {
+            span: DUMMY_SP,
+            obj: quote_ident!(DUMMY_CTXT, id.sym.clone()).into(),
+            prop: MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: quote_str!(field.clone()).into(),
+            }),
+        }
+        .into(),
         (id, Some(field)) => MemberExpr {
             span: DUMMY_SP,
             obj: quote_ident!(DUMMY_CTXT, id.sym.clone()).into(),
@@ -277,6 +277,7 @@ fn run_test(code: &str, ccn_ctx: &mut ConcatenateContext) -> String {
             optimization: Some(OptimizationConfig {
                 concatenate_modules: Some(true),
                 skip_modules: Some(true),
+                ..Default::default()
             }),
             mode: Mode::Production,
             minify: true,
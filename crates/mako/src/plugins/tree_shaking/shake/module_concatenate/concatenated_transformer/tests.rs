@@ -727,6 +727,7 @@ fn inner_trans_code_imported_as(
             optimization: Some(OptimizationConfig {
                 concatenate_modules: Some(true),
                 skip_modules: Some(true),
+                ..Default::default()
             }),
             mode: Mode::Production,
             minify: false,
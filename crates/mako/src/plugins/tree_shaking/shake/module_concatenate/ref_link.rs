@@ -160,9 +160,8 @@ impl Visit for ModuleDeclMapCollector {
                     || local.clone(),
                     |imported| match imported {
                         ModuleExportName::Ident(imported_ident) => imported_ident.clone(),
-                        ModuleExportName::Str(_) => {
-                            unimplemented!("import as string not supported now")
-                        }
+                        // e.g. `import { "a-b" as ab } from "./a"`
+                        ModuleExportName::Str(str) => quote_ident!(DUMMY_CTXT, str.value.clone()),
                     },
                 );
 
@@ -34,6 +34,14 @@ y => b from ./src"#
     )
 }
 
+#[test]
+fn import_named_string_as() {
+    assert_eq!(
+        extract_import_map(r#"import {"a-b" as x} from 'foo'"#),
+        "x => a-b from foo"
+    )
+}
+
 #[test]
 fn export_default_expr() {
     assert_eq!(
@@ -0,0 +1,91 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use swc_core::ecma::ast::{ModuleDecl, ModuleItem};
+
+use crate::compiler::Context;
+use crate::mako_profile_function;
+use crate::module::{ImportType, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+
+/// Tree shaking of the `"modules"` granularity, the statements are not analyzed and the used
+/// modules are kept as a whole. A module is removed only if it's imported by nothing but
+/// `import "x"` and has no side effects, e.g. with `sideEffects: false` in its package.json,
+/// and so are the `import "x"` of it.
+pub(super) fn prune_side_effects_free_modules(
+    module_graph: &mut ModuleGraph,
+    tree_shake_modules_ids: &[ModuleId],
+    context: &Arc<Context>,
+) {
+    mako_profile_function!();
+
+    // every module is emitted with preserveModules
+    if context.config.output.is_preserve_modules() {
+        return;
+    }
+
+    let tree_shake_modules_ids = tree_shake_modules_ids.iter().collect::<HashSet<_>>();
+    let is_droppable = |module_id: &ModuleId| {
+        tree_shake_modules_ids.contains(module_id)
+            && module_graph.get_module(module_id).is_some_and(|module| {
+                !module.side_effects
+                    && module
+                        .info
+                        .as_ref()
+                        .is_some_and(|info| info.described_side_effect(context) == Some(false))
+            })
+    };
+
+    let mut used = HashSet::new();
+    // the sources of `import "x"` of the droppable modules, by importer
+    let mut side_effect_imports: HashMap<ModuleId, HashSet<(ModuleId, String)>> = HashMap::new();
+    let mut queue = module_graph
+        .get_entry_modules()
+        .into_iter()
+        .collect::<VecDeque<_>>();
+    while let Some(module_id) = queue.pop_front() {
+        if !used.insert(module_id.clone()) {
+            continue;
+        }
+        for (dep_id, dep) in module_graph.get_dependencies(&module_id) {
+            if dep.resolve_type == ResolveType::Import(ImportType::SideEffect)
+                && is_droppable(dep_id)
+            {
+                side_effect_imports
+                    .entry(module_id.clone())
+                    .or_default()
+                    .insert((dep_id.clone(), dep.source.clone()));
+                continue;
+            }
+            queue.push_back(dep_id.clone());
+        }
+    }
+
+    for (module_id, imports) in side_effect_imports {
+        // the module is still used by the other imports of it
+        let removed_sources = imports
+            .into_iter()
+            .filter(|(dep_id, _)| !used.contains(dep_id))
+            .map(|(_, source)| source)
+            .collect::<HashSet<_>>();
+        if removed_sources.is_empty() {
+            continue;
+        }
+        let module = module_graph.get_module_mut(&module_id).unwrap();
+        let ast = module.info.as_mut().unwrap().ast.as_script_ast_mut();
+        ast.body.retain(|item| match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl))
+                if import_decl.specifiers.is_empty() =>
+            {
+                !removed_sources.contains(&import_decl.src.value.to_string())
+            }
+            _ => true,
+        });
+    }
+
+    for module_id in tree_shake_modules_ids {
+        if !used.contains(module_id) {
+            module_graph.remove_module(module_id);
+        }
+    }
+}
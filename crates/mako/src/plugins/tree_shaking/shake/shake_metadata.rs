@@ -0,0 +1,323 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use swc_core::common::BytePos;
+
+use super::{strip_context, TreeShakingModuleMap};
+use crate::compiler::Context;
+use crate::module::{relative_to_root, ModuleId, ResolveType};
+use crate::module_graph::ModuleGraph;
+use crate::plugins::tree_shaking::module::{TreeShakeModule, UsedIdent};
+use crate::plugins::tree_shaking::statement_graph::{
+    ExportSpecifierInfo, ImportSpecifierInfo, StatementId,
+};
+
+/// The content of the `stats.shakeMetadata` file, what tree shaking kept and removed in
+/// the first-party modules, i.e. the modules not under node_modules.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShakeMetadata {
+    /// sorted by path
+    pub modules: Vec<ModuleShakeMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleShakeMetadata {
+    /// root relative, e.g. `src/utils.ts`
+    pub path: String,
+    /// in the order of the export statements, the exports of `export * from` are `*`
+    pub exports: Vec<ExportShakeMetadata>,
+    /// the statements removed from the module, all of them when the module is removed
+    pub removed_statements: Vec<SourceRange>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportShakeMetadata {
+    pub name: String,
+    pub retained: bool,
+    /// the root relative path of an importer which uses the export, none for the exports
+    /// of entries or removed exports
+    pub retained_by: Option<String>,
+}
+
+/// Lines and columns are 1-based, the end is exclusive.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceRange {
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Write the [ShakeMetadata] to `stats.shakeMetadata`, a path relative to the root. It must
+/// be called after the modules are shaken and before the unused modules are removed.
+pub(super) fn emit_shake_metadata(
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &ModuleGraph,
+    context: &Arc<Context>,
+) -> Result<()> {
+    let Some(path) = context
+        .config
+        .stats
+        .as_ref()
+        .and_then(|stats| stats.shake_metadata.as_ref())
+    else {
+        return Ok(());
+    };
+
+    let mut used_stmts_cache = HashMap::new();
+    let mut modules = vec![];
+    for (module_id, tsm) in tree_shake_modules_map {
+        let is_first_party = module_graph
+            .get_module(module_id)
+            .and_then(|module| module.info.as_ref())
+            .is_some_and(|info| !info.file.is_under_node_modules);
+        if !is_first_party {
+            continue;
+        }
+
+        let tsm = tsm.borrow();
+        let used_names = if tsm.not_used() {
+            BTreeSet::new()
+        } else {
+            tsm.used_export_names()
+        };
+        let export_names = export_names(&tsm);
+        let exports = export_names
+            .iter()
+            .map(|name| {
+                let is_export_all = *name == UsedIdent::ExportAll.to_string();
+                // the names which no named export matches come from `export * from`
+                let wants = |used: &str| {
+                    if is_export_all {
+                        used != "default" && !export_names.iter().any(|export| export == used)
+                    } else {
+                        name == used
+                    }
+                };
+                let retained = used_names.iter().any(|used| {
+                    (is_export_all && *used == UsedIdent::ExportAll.to_string())
+                        || wants(used.as_str())
+                });
+                let retained_by = retained
+                    .then(|| {
+                        retaining_importer(
+                            module_id,
+                            &wants,
+                            tree_shake_modules_map,
+                            module_graph,
+                            &mut used_stmts_cache,
+                        )
+                    })
+                    .flatten()
+                    .map(|importer| to_path(importer, context));
+                ExportShakeMetadata {
+                    name: name.clone(),
+                    retained,
+                    retained_by,
+                }
+            })
+            .collect();
+
+        let used_stmts = if tsm.not_used() {
+            None
+        } else {
+            Some(tsm.used_statements())
+        };
+        let mut stmts = tsm.stmt_graph.stmts();
+        stmts.sort_by_key(|stmt| stmt.id);
+        let removed_statements = stmts
+            .into_iter()
+            .filter(|stmt| !stmt.span.is_dummy())
+            .filter(|stmt| match &used_stmts {
+                Some(used_stmts) => !used_stmts.contains_key(&stmt.id),
+                None => tsm.not_used(),
+            })
+            .map(|stmt| SourceRange {
+                start: to_position(stmt.span.lo, context),
+                end: to_position(stmt.span.hi, context),
+            })
+            .collect();
+
+        modules.push(ModuleShakeMetadata {
+            path: to_path(module_id, context),
+            exports,
+            removed_statements,
+        });
+    }
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let path = context.root.join(path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(
+        path,
+        serde_json::to_string_pretty(&ShakeMetadata { modules })?,
+    )?;
+    Ok(())
+}
+
+fn export_names(tsm: &TreeShakeModule) -> Vec<String> {
+    let mut exports = tsm.exports();
+    exports.sort_by_key(|export_info| export_info.stmt_id);
+    let mut names = vec![];
+    for specifier in exports.into_iter().flat_map(|info| info.specifiers) {
+        let idents = match specifier {
+            ExportSpecifierInfo::All(_) | ExportSpecifierInfo::Ambiguous(_) => {
+                vec![UsedIdent::ExportAll.to_string()]
+            }
+            _ => specifier.to_idents(),
+        };
+        for ident in idents {
+            if !names.contains(&ident) {
+                names.push(ident);
+            }
+        }
+    }
+    names
+}
+
+// the first used importer whose used import or export-from statements refer to the wanted
+// names, or which requires or dynamically imports the module
+fn retaining_importer<'a>(
+    module_id: &ModuleId,
+    wants: &dyn Fn(&str) -> bool,
+    tree_shake_modules_map: &TreeShakingModuleMap,
+    module_graph: &'a ModuleGraph,
+    used_stmts_cache: &mut HashMap<ModuleId, HashSet<StatementId>>,
+) -> Option<&'a ModuleId> {
+    let mut fallback = None;
+    for (importer_id, dependency) in module_graph.get_dependents(module_id) {
+        let Some(importer) = tree_shake_modules_map.get(importer_id) else {
+            continue;
+        };
+        let importer = importer.borrow();
+        if importer.not_used() {
+            continue;
+        }
+        let used_stmts = used_stmts_cache
+            .entry(importer_id.clone())
+            .or_insert_with(|| importer.used_statements().into_keys().collect());
+
+        let imports = importer
+            .imports()
+            .into_iter()
+            .filter(|import_info| {
+                import_info.source == dependency.source && used_stmts.contains(&import_info.stmt_id)
+            })
+            .flat_map(|import_info| import_info.specifiers)
+            .any(|specifier| match specifier {
+                ImportSpecifierInfo::Namespace(_) => true,
+                ImportSpecifierInfo::Named { local, imported } => {
+                    wants(&strip_context(&imported.unwrap_or(local)))
+                }
+                ImportSpecifierInfo::Default(_) => wants("default"),
+            });
+        let exports = importer
+            .exports()
+            .into_iter()
+            .filter(|export_info| {
+                export_info.source.as_ref() == Some(&dependency.source)
+                    && used_stmts.contains(&export_info.stmt_id)
+            })
+            .flat_map(|export_info| export_info.specifiers)
+            .any(|specifier| match specifier {
+                ExportSpecifierInfo::All(_)
+                | ExportSpecifierInfo::Ambiguous(_)
+                | ExportSpecifierInfo::Namespace(_) => true,
+                ExportSpecifierInfo::Named { local, .. } => wants(&strip_context(&local)),
+                ExportSpecifierInfo::Default(_) => false,
+            });
+        if imports || exports {
+            return Some(importer_id);
+        }
+
+        if fallback.is_none()
+            && matches!(
+                dependency.resolve_type,
+                ResolveType::Require | ResolveType::DynamicImport(_) | ResolveType::Worker(_)
+            )
+        {
+            fallback = Some(importer_id);
+        }
+    }
+    fallback
+}
+
+fn to_path(module_id: &ModuleId, context: &Arc<Context>) -> String {
+    relative_to_root(&module_id.id, &context.root)
+        .trim_start_matches("./")
+        .to_string()
+}
+
+fn to_position(pos: BytePos, context: &Arc<Context>) -> SourcePosition {
+    let loc = context.meta.script.cm.lookup_char_pos(pos);
+    SourcePosition {
+        line: loc.line,
+        column: loc.col.0 + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{ShakeMetadata, SourcePosition, SourceRange};
+    use crate::compiler::Compiler;
+    use crate::config::{Config, Mode};
+    use crate::utils::test_helper::setup_logger;
+
+    #[test]
+    fn test_emit_shake_metadata() {
+        setup_logger();
+        let root = std::env::current_dir()
+            .unwrap()
+            .join("test/build/shake-metadata");
+        let config = Config::new(&root, None, None).unwrap();
+        assert_eq!(config.mode, Mode::Development);
+        let compiler = Compiler::new(config, root.clone(), Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+
+        let metadata = fs::read_to_string(root.join("dist/shake-metadata.json")).unwrap();
+        let metadata: ShakeMetadata = serde_json::from_str(&metadata).unwrap();
+        let paths = metadata
+            .modules
+            .iter()
+            .map(|module| module.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["index.js", "utils.js"]);
+
+        let utils = &metadata.modules[1];
+        assert_eq!(utils.exports.len(), 2);
+        assert_eq!(utils.exports[0].name, "kept");
+        assert!(utils.exports[0].retained);
+        assert_eq!(utils.exports[0].retained_by.as_deref(), Some("index.js"));
+        assert_eq!(utils.exports[1].name, "removed");
+        assert!(!utils.exports[1].retained);
+        assert_eq!(utils.exports[1].retained_by, None);
+
+        assert_eq!(
+            utils.removed_statements,
+            vec![SourceRange {
+                start: SourcePosition { line: 3, column: 1 },
+                end: SourcePosition { line: 5, column: 2 },
+            }]
+        );
+        let source = fs::read_to_string(root.join("utils.js")).unwrap();
+        let lines = source.lines().collect::<Vec<_>>();
+        assert!(lines[2].starts_with("export function removed"));
+        assert_eq!(lines[4], "}");
+    }
+}
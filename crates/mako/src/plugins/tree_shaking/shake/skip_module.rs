@@ -214,9 +214,7 @@ pub(super) fn skip_module_optimize(
                                             ModuleExportName::Ident(ident) => {
                                                 ident.sym.clone().to_string()
                                             }
-                                            ModuleExportName::Str(_) => {
-                                                unreachable!();
-                                            }
+                                            ModuleExportName::Str(str) => str.value.to_string(),
                                         },
                                     );
 
@@ -510,7 +508,8 @@ pub(super) fn skip_module_optimize(
                 .ast
                 .as_script_ast();
 
-            tsm.update_stmt_graph(swc_module);
+            let comments = context.meta.script.origin_comments.read().unwrap();
+            tsm.update_stmt_graph(swc_module, &comments, context)?;
         }
     }
 
@@ -1,16 +1,22 @@
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
+use anyhow::{Context, Result};
 use petgraph;
 use petgraph::stable_graph::NodeIndex;
 use swc_core::ecma::ast::{Module as SwcModule, ModuleItem};
+use swc_core::ecma::visit::VisitWith;
 
 pub(crate) mod analyze_imports_and_exports;
 pub(crate) mod defined_idents_collector;
+pub(crate) mod pure_iife;
+pub(crate) mod reassigned_idents_collector;
 pub(crate) mod used_idents_collector;
 
-use analyze_imports_and_exports::analyze_imports_and_exports;
-use swc_core::common::{Span, SyntaxContext};
+use analyze_imports_and_exports::{analyze_imports_and_exports, NO_SIDE_EFFECTS_DEFAULT};
+use reassigned_idents_collector::ReassignedIdentsCollector;
+use swc_core::common::{SourceMap, Span, Spanned, SyntaxContext};
 
+use crate::ast::comments::Comments;
 use crate::plugins::tree_shaking::module::{is_ident_equal, UsedIdent};
 use crate::plugins::tree_shaking::shake::strip_context;
 use crate::plugins::tree_shaking::statement_graph::analyze_imports_and_exports::StatementInfo;
@@ -31,7 +37,6 @@ pub enum ImportSpecifierInfo {
 pub struct ImportInfo {
     pub source: String,
     pub specifiers: Vec<ImportSpecifierInfo>,
-    #[allow(dead_code)]
     pub stmt_id: StatementId,
 }
 
@@ -234,7 +239,10 @@ impl ExportInfo {
     }
 }
 
-#[derive(Debug)]
+/// Idents are keyed by `Ident.to_string()`, i.e. `name#ctxt`, so hygienically distinct
+/// bindings with the same name are never merged. Contexts are only stripped where the
+/// names are matched across modules, i.e. the exported names.
+#[derive(Debug, Clone)]
 pub struct Statement {
     pub id: StatementId,
     pub import_info: Option<ImportInfo>,
@@ -247,24 +255,75 @@ pub struct Statement {
     pub is_self_executed: bool,
     #[allow(dead_code)]
     pub has_side_effects: bool,
-    #[allow(dead_code)]
     pub span: Span,
+    /// Functions defined by the statement which are annotated with `/*#__NO_SIDE_EFFECTS__*/`
+    /// and never reassigned, calls to them can be treated as pure
+    pub no_side_effects_fns: HashSet<String>,
+    /// Callees of the statement if calling them is its only side effect, so it's not self
+    /// executed once all of them are known to have no side effects
+    pub side_effect_callees: Option<HashSet<String>>,
+    /// Idents assigned or updated anywhere in the statement, e.g. `x = 1` and `x++`
+    pub reassigned_idents: HashSet<String>,
+    /// Annotated with `/*#__DROPPABLE__*/`, it's not self executed even if it imports a
+    /// module with side effects
+    pub droppable: bool,
+    /// Annotated with `/*#__KEEP__*/` or retained by the plugins, the bindings it defines are
+    /// kept even if they are not used
+    pub keep: bool,
 }
 
 impl Statement {
-    pub fn new(id: StatementId, stmt: &ModuleItem, unresolved_ctxt: SyntaxContext) -> Self {
+    pub fn new(
+        id: StatementId,
+        stmt: &ModuleItem,
+        path: &str,
+        source_map: &SourceMap,
+        unresolved_ctxt: SyntaxContext,
+        comments: &Comments,
+    ) -> Result<Self> {
         let StatementInfo {
             import_info,
             export_info,
             defined_idents,
             used_idents,
             defined_idents_map,
-            is_self_executed,
+            mut is_self_executed,
             span,
-            has_side_effects,
-        } = analyze_imports_and_exports(&id, stmt, None, unresolved_ctxt);
+            mut has_side_effects,
+            no_side_effects_fns,
+            mut side_effect_callees,
+        } = analyze_imports_and_exports(&id, stmt, None, unresolved_ctxt, Some(comments))
+            .with_context(|| {
+                let item_span = stmt.span();
+                // no location for the statements added by the transforms, or the modules
+                // shaken out of the compiler, e.g. with `shake_module`
+                match source_map.try_lookup_char_pos(item_span.lo) {
+                    Ok(loc) if !item_span.is_dummy() => format!(
+                        "failed to analyze statement {} of {}:{}:{}",
+                        id,
+                        path,
+                        loc.line,
+                        loc.col.0 + 1
+                    ),
+                    _ => format!("failed to analyze statement {} of {}", id, path),
+                }
+            })?;
+
+        // `/*#__KEEP__*/` keeps the statement whether it has side effects or not, and
+        // `/*#__DROPPABLE__*/` lets it be removed when nothing it defines is used
+        let item_span = stmt.span();
+        let keep = comments.has_keep(item_span);
+        let droppable = !keep && comments.has_droppable(item_span);
+        if keep || droppable {
+            is_self_executed = keep;
+            has_side_effects = keep;
+            side_effect_callees = None;
+        }
 
-        Self {
+        let mut reassigned_idents_collector = ReassignedIdentsCollector::new();
+        stmt.visit_with(&mut reassigned_idents_collector);
+
+        Ok(Self {
             id,
             import_info,
             export_info,
@@ -274,27 +333,101 @@ impl Statement {
             is_self_executed,
             has_side_effects,
             span,
+            no_side_effects_fns,
+            side_effect_callees,
+            reassigned_idents: reassigned_idents_collector.reassigned_idents,
+            droppable,
+            keep,
+        })
+    }
+
+    /// The file, 1-based line and column of the statement in the parsed source, for the
+    /// diagnostics of tree shaking, `None` for the statements added by the transforms.
+    pub fn source_location(&self, source_map: &SourceMap) -> Option<(String, u32, u32)> {
+        if self.span.is_dummy() {
+            return None;
         }
+        let loc = source_map.lookup_char_pos(self.span.lo);
+        Some((
+            loc.file.name.to_string(),
+            loc.line as u32,
+            loc.col.0 as u32 + 1,
+        ))
     }
 }
 
+#[derive(Clone)]
 pub struct StatementGraphEdge {
     pub idents: HashSet<String>,
 }
 
+#[derive(Clone)]
 pub struct StatementGraph {
     g: petgraph::graph::Graph<Statement, StatementGraphEdge>,
     id_index_map: HashMap<StatementId, NodeIndex>,
 }
 
 impl StatementGraph {
-    pub fn new(module: &SwcModule, unresolved_ctxt: SyntaxContext) -> Self {
+    pub fn new(
+        module: &SwcModule,
+        path: &str,
+        source_map: &SourceMap,
+        unresolved_ctxt: SyntaxContext,
+        comments: &Comments,
+    ) -> Result<Self> {
         let mut g = petgraph::graph::Graph::new();
         let mut id_index_map = HashMap::new();
 
-        for (index, stmt) in module.body.iter().enumerate() {
-            let statement = Statement::new(index, stmt, unresolved_ctxt);
+        let mut statements = module
+            .body
+            .iter()
+            .enumerate()
+            .map(|(index, stmt)| {
+                Statement::new(index, stmt, path, source_map, unresolved_ctxt, comments)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let reassigned_idents = statements
+            .iter()
+            .flat_map(|stmt| stmt.reassigned_idents.iter().cloned())
+            .collect::<HashSet<_>>();
+        // bindings reassigned by the statements with side effects, the value of such a binding
+        // depends on the reassignments, so they are kept together
+        let side_effect_reassigned_idents = statements
+            .iter()
+            .filter(|stmt| stmt.is_self_executed)
+            .flat_map(|stmt| stmt.reassigned_idents.iter().cloned())
+            .collect::<HashSet<_>>();
+
+        for statement in statements.iter_mut() {
+            let annotated = statement.no_side_effects_fns.len();
+            statement
+                .no_side_effects_fns
+                .retain(|ident| !reassigned_idents.contains(ident));
+            // `export default function foo() {}` is not pure either once `foo` is reassigned
+            if statement.no_side_effects_fns.len() < annotated {
+                statement
+                    .no_side_effects_fns
+                    .remove(NO_SIDE_EFFECTS_DEFAULT);
+            }
+
+            for (ident, dep_idents) in statement.defined_idents_map.iter_mut() {
+                if side_effect_reassigned_idents.contains(ident) {
+                    dep_idents.insert(ident.clone());
+                }
+            }
+            if statement.is_self_executed {
+                for ident in &statement.reassigned_idents {
+                    statement
+                        .defined_idents_map
+                        .entry(ident.clone())
+                        .or_insert_with(|| statement.used_idents.clone());
+                }
+            }
+        }
 
+        for statement in statements {
+            let index = statement.id;
             let node = g.add_node(statement);
             id_index_map.insert(index, node);
         }
@@ -316,6 +449,19 @@ impl StatementGraph {
                 if !deps_idents.is_empty() {
                     edges_to_add.push((stmt.id, def_stmt.id, deps_idents));
                 }
+
+                // the binding to its reassignments with side effects
+                if def_stmt.is_self_executed && def_stmt.id != stmt.id {
+                    let reassigned_idents = stmt
+                        .defined_idents
+                        .intersection(&def_stmt.reassigned_idents)
+                        .cloned()
+                        .collect::<HashSet<_>>();
+
+                    if !reassigned_idents.is_empty() {
+                        edges_to_add.push((stmt.id, def_stmt.id, reassigned_idents));
+                    }
+                }
             }
         }
 
@@ -323,7 +469,7 @@ impl StatementGraph {
             graph.add_edge(from, to, idents);
         }
 
-        graph
+        Ok(graph)
     }
 
     pub fn empty() -> Self {
@@ -349,6 +495,10 @@ impl StatementGraph {
             .add_edge(*from_node, *to_node, StatementGraphEdge { idents });
     }
 
+    pub fn has_stmt(&self, id: &StatementId) -> bool {
+        self.id_index_map.contains_key(id)
+    }
+
     pub fn stmt(&self, id: &StatementId) -> &Statement {
         let node = self.id_index_map.get(id).unwrap();
         &self.g[*node]
@@ -376,7 +526,13 @@ impl StatementGraph {
         self.g.node_indices().map(|i| &self.g[i]).collect()
     }
 
-    #[allow(dead_code)]
+    pub fn no_side_effects_fns(&self) -> HashSet<String> {
+        self.stmts()
+            .into_iter()
+            .flat_map(|stmt| stmt.no_side_effects_fns.iter().cloned())
+            .collect()
+    }
+
     pub fn edges(&self) -> Vec<(&Statement, &Statement, &StatementGraphEdge)> {
         self.g
             .edge_indices()
@@ -441,7 +597,7 @@ impl StatementGraph {
                     used_defined_idents.iter().cloned().collect::<Vec<String>>();
                 sorted_idents.sort();
 
-                format!("{}:{}", stmt_id, sorted_idents.join(""))
+                format!("{}:{}", stmt_id, sorted_idents.join(","))
             };
 
             while let Some((stmt_id, used_defined_idents, used_dep_idents)) = stmts.pop_front() {
@@ -501,3 +657,609 @@ impl StatementGraph {
         used_statements
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use swc_core::common::{Mark, SyntaxContext, GLOBALS};
+    use swc_core::ecma::ast::Ident;
+    use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+    use super::{
+        ExportInfo, ExportInfoMatch, ExportSpecifierInfo, ImportSpecifierInfo, StatementGraph,
+    };
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::plugins::tree_shaking::module::UsedIdent;
+    use crate::plugins::tree_shaking::shake::strip_context;
+
+    // rename `from` to `to` with a fresh syntax context, like a binding introduced by a transform
+    struct HygienicRename {
+        from: &'static str,
+        to: &'static str,
+        ctxt: SyntaxContext,
+    }
+
+    impl VisitMut for HygienicRename {
+        fn visit_mut_ident(&mut self, ident: &mut Ident) {
+            if &*ident.sym == self.from {
+                ident.sym = self.to.into();
+                ident.ctxt = self.ctxt;
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_name_bindings_with_different_contexts() {
+        let mut tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+const config = "unused";
+const config2 = "used";
+export { config2 as config };
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js_mut();
+            js_ast.ast.visit_mut_with(&mut HygienicRename {
+                from: "config2",
+                to: "config",
+                ctxt: SyntaxContext::empty().apply_mark(Mark::new()),
+            });
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            assert_ne!(graph.stmt(&0).defined_idents, graph.stmt(&1).defined_idents);
+
+            let local = match &graph.stmt(&2).export_info.as_ref().unwrap().specifiers[0] {
+                ExportSpecifierInfo::Named { local, .. } => local.clone(),
+                _ => panic!("should be a named export"),
+            };
+            let used_exports = BTreeMap::from([(2, HashSet::from([UsedIdent::SwcIdent(local)]))]);
+            let used_stmts = graph.analyze_used_statements_and_idents(used_exports);
+
+            assert_eq!(used_stmts.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn test_no_side_effects_annotation() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+/*#__NO_SIDE_EFFECTS__*/ function foo() {}
+export const bar = /*#__NO_SIDE_EFFECTS__*/ () => {};
+let baz = /*#__NO_SIDE_EFFECTS__*/ () => {};
+baz = () => {};
+foo(bar());
+foo(...[]);
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+            let stripped = |idents: &HashSet<String>| {
+                let mut idents = idents.iter().map(|i| strip_context(i)).collect::<Vec<_>>();
+                idents.sort();
+                idents
+            };
+
+            assert_eq!(stripped(&graph.no_side_effects_fns()), vec!["bar", "foo"]);
+            // reassigned
+            assert!(graph.stmt(&2).no_side_effects_fns.is_empty());
+
+            let call = graph.stmt(&4);
+            assert!(call.is_self_executed);
+            assert_eq!(
+                stripped(call.side_effect_callees.as_ref().unwrap()),
+                vec!["bar", "foo"]
+            );
+            // spread arguments
+            assert!(graph.stmt(&5).side_effect_callees.is_none());
+        });
+    }
+
+    #[test]
+    fn test_no_side_effects_annotation_on_default_export() {
+        let graph_fns = |content: &str| {
+            let tu = TestUtils::new(TestUtilsOpts {
+                file: Some("test.js".to_string()),
+                content: Some(content.to_string()),
+            });
+
+            GLOBALS.set(&tu.context.meta.script.globals, || {
+                let js_ast = tu.ast.js();
+                let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+                let comments = tu.context.meta.script.origin_comments.read().unwrap();
+                let graph = StatementGraph::new(
+                    &js_ast.ast,
+                    "test.js",
+                    &tu.context.meta.script.cm,
+                    unresolved_ctxt,
+                    &comments,
+                )
+                .unwrap();
+                let mut fns = graph
+                    .no_side_effects_fns()
+                    .iter()
+                    .map(|i| strip_context(i))
+                    .collect::<Vec<_>>();
+                fns.sort();
+                fns
+            })
+        };
+
+        assert_eq!(
+            graph_fns("export default /*#__NO_SIDE_EFFECTS__*/ function foo() {}"),
+            vec!["default", "foo"]
+        );
+        assert_eq!(
+            graph_fns("/*#__NO_SIDE_EFFECTS__*/ export default function () {}"),
+            vec!["default"]
+        );
+        assert_eq!(
+            graph_fns("export default /*#__NO_SIDE_EFFECTS__*/ () => {};"),
+            vec!["default"]
+        );
+        assert!(graph_fns("export default () => {};").is_empty());
+        // the default export follows the reassignment
+        assert!(graph_fns(
+            "export default /*#__NO_SIDE_EFFECTS__*/ function foo() {}\nfoo = () => {};"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_reassigned_in_self_executed_statement() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+let count = 0;
+{
+    count = init();
+}
+function init() { return 1; }
+export { count };
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            let block = graph.stmt(&1);
+            assert!(block.is_self_executed);
+            assert_eq!(
+                block
+                    .reassigned_idents
+                    .iter()
+                    .map(|i| strip_context(i))
+                    .collect::<Vec<_>>(),
+                vec!["count"]
+            );
+            assert!(graph
+                .dependencies(&0)
+                .iter()
+                .any(|(dep_stmt, _)| dep_stmt.id == 1));
+
+            let local = match &graph.stmt(&3).export_info.as_ref().unwrap().specifiers[0] {
+                ExportSpecifierInfo::Named { local, .. } => local.clone(),
+                _ => panic!("should be a named export"),
+            };
+            let used_exports = BTreeMap::from([(3, HashSet::from([UsedIdent::SwcIdent(local)]))]);
+            let used_stmts = graph.analyze_used_statements_and_idents(used_exports);
+
+            assert_eq!(
+                used_stmts.keys().copied().collect::<Vec<_>>(),
+                vec![0, 1, 2, 3]
+            );
+        });
+    }
+
+    #[test]
+    fn test_ts_enum_iife() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+var Foo;
+(function(Foo) {
+    Foo[Foo["A"] = 0] = "A";
+    Foo["B"] = "b";
+})(Foo || (Foo = {}));
+var Bar = function(Bar) {
+    Bar[Bar["A"] = 0] = "A";
+    return Bar;
+}({});
+(function(Baz) {
+    Baz[Baz["A"] = init()] = "A";
+})(Baz || (Baz = {}));
+export { Foo };
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            let members = graph.stmt(&1);
+            assert!(!members.is_self_executed);
+            assert_eq!(
+                members
+                    .defined_idents
+                    .iter()
+                    .map(|i| strip_context(i))
+                    .collect::<Vec<_>>(),
+                vec!["Foo"]
+            );
+            assert!(!graph.stmt(&2).is_self_executed);
+            // the member values have side effects
+            assert!(graph.stmt(&3).is_self_executed);
+
+            let local = match &graph.stmt(&4).export_info.as_ref().unwrap().specifiers[0] {
+                ExportSpecifierInfo::Named { local, .. } => local.clone(),
+                _ => panic!("should be a named export"),
+            };
+            let used_exports = BTreeMap::from([(4, HashSet::from([UsedIdent::SwcIdent(local)]))]);
+            let used_stmts = graph.analyze_used_statements_and_idents(used_exports);
+
+            assert_eq!(
+                used_stmts.keys().copied().collect::<Vec<_>>(),
+                vec![0, 1, 3, 4]
+            );
+        });
+    }
+
+    #[test]
+    fn test_iife_initialized_bindings() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+export const sdk = (function() {
+    var client = {};
+    client.init = function() { this.ready = true; };
+    return client;
+})();
+export const counter = (() => { let n = 0; n++; return n; })();
+export const writesWindow = (function() { window.x = 1; return 1; })();
+export const callsOuter = (function() { init(); return 1; })();
+export const awaits = (async function() { await 1; })();
+export const requires = (function() { require('./polyfill'); return 1; })();
+export const patches = (function() {
+    var proto = Array.prototype;
+    Object.defineProperty(proto, 'last', { value: 1 });
+    return proto;
+})();
+export const defines = (() => { customElements.define('x-a', class {}); return 1; })();
+export const evals = (function() { return eval('1'); })();
+export const writesThis = (function() { this.x = 1; return 1; })();
+export const pure = (function() {
+    var keys = Object.keys({ a: 1 });
+    return Math.max(keys.length, [1].concat(keys).length);
+})();
+function init() {}
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            assert!(!graph.stmt(&0).is_self_executed);
+            assert!(!graph.stmt(&1).is_self_executed);
+            assert!(graph.stmt(&2).is_self_executed);
+            assert!(graph.stmt(&3).is_self_executed);
+            assert!(graph.stmt(&4).is_self_executed);
+            assert!(graph.stmt(&5).is_self_executed);
+            assert!(graph.stmt(&6).is_self_executed);
+            assert!(graph.stmt(&7).is_self_executed);
+            assert!(graph.stmt(&8).is_self_executed);
+            assert!(graph.stmt(&9).is_self_executed);
+            assert!(!graph.stmt(&10).is_self_executed);
+        });
+    }
+
+    #[test]
+    fn test_destructuring_exports() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+const obj = { a: 1, b: 2 };
+export const { a, b: [c] } = obj;
+export const { f, g } = obj;
+export const { d = init() } = obj;
+export const { [key()]: e } = obj;
+function init() {}
+function key() {}
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            let stmt = graph.stmt(&1);
+            assert!(!stmt.is_self_executed);
+            let mut defined = stmt
+                .defined_idents_map
+                .iter()
+                .map(|(ident, used)| {
+                    let mut used = used.iter().map(|i| strip_context(i)).collect::<Vec<_>>();
+                    used.sort();
+                    (strip_context(ident), used)
+                })
+                .collect::<Vec<_>>();
+            defined.sort();
+            assert_eq!(
+                defined,
+                vec![
+                    ("a".to_string(), vec!["obj".to_string()]),
+                    ("c".to_string(), vec!["obj".to_string()]),
+                ]
+            );
+            // the defaults and computed keys are evaluated with the declaration
+            assert!(!graph.stmt(&2).is_self_executed);
+            assert!(graph.stmt(&3).is_self_executed);
+            assert!(graph.stmt(&4).is_self_executed);
+
+            let a = stmt
+                .defined_idents
+                .iter()
+                .find(|i| strip_context(i) == "a")
+                .unwrap()
+                .clone();
+            let used_exports = BTreeMap::from([(1, HashSet::from([UsedIdent::SwcIdent(a)]))]);
+            let used_stmts = graph.analyze_used_statements_and_idents(used_exports);
+
+            assert_eq!(
+                used_stmts.keys().copied().collect::<Vec<_>>(),
+                vec![0, 1, 3, 4, 5, 6]
+            );
+        });
+    }
+
+    #[test]
+    fn test_keep_and_droppable_annotations() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+/*#__KEEP__*/ const bootstrap = setup();
+/*#__DROPPABLE__*/ track();
+/*#__DROPPABLE__*/ export const config = load();
+const plain = 1;
+function setup() {}
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            assert!(graph.stmt(&0).is_self_executed);
+            assert!(graph.stmt(&0).has_side_effects);
+            assert!(!graph.stmt(&1).is_self_executed);
+            assert!(graph.stmt(&1).droppable);
+            assert!(!graph.stmt(&2).is_self_executed);
+            assert!(!graph.stmt(&3).is_self_executed);
+
+            // the kept statement keeps the function it calls
+            let deps = graph
+                .dependencies(&0)
+                .into_iter()
+                .map(|(stmt, _)| stmt.id)
+                .collect::<Vec<_>>();
+            assert_eq!(deps, vec![4]);
+        });
+    }
+
+    #[test]
+    fn test_destructured_objects_with_accessors_are_self_executed() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+export const config = { get level() { return 1; } };
+const nested = { theme: { set value(v) {} } };
+const plain = { level: 1 };
+export const create = () => ({ get lazy() { return 1; } });
+export const { level } = { get level() { return 1; } };
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            assert!(!graph.stmt(&0).is_self_executed);
+            assert!(!graph.stmt(&1).is_self_executed);
+            assert!(!graph.stmt(&2).is_self_executed);
+            assert!(!graph.stmt(&3).is_self_executed);
+            // the getter runs when the object is destructured
+            assert!(graph.stmt(&4).is_self_executed);
+        });
+    }
+
+    #[test]
+    fn test_import_string_name() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(r#"import { "a-b" as ab } from "./a";"#.to_string()),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let graph = StatementGraph::new(
+                &js_ast.ast,
+                "test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .unwrap();
+
+            let import_info = graph.stmt(&0).import_info.as_ref().unwrap();
+            assert!(matches!(
+                &import_info.specifiers[..],
+                [ImportSpecifierInfo::Named { imported: Some(imported), .. }] if imported == "a-b"
+            ));
+        });
+    }
+
+    #[test]
+    fn test_analyze_error_with_path() {
+        let tu = TestUtils::new(TestUtilsOpts {
+            file: Some("test.js".to_string()),
+            content: Some(
+                r#"
+const a = 1;
+export { a as "a-b" };
+"#
+                .to_string(),
+            ),
+        });
+
+        GLOBALS.set(&tu.context.meta.script.globals, || {
+            let js_ast = tu.ast.js();
+            let unresolved_ctxt = SyntaxContext::empty().apply_mark(js_ast.unresolved_mark);
+            let comments = tu.context.meta.script.origin_comments.read().unwrap();
+            let err = StatementGraph::new(
+                &js_ast.ast,
+                "src/test.js",
+                &tu.context.meta.script.cm,
+                unresolved_ctxt,
+                &comments,
+            )
+            .err()
+            .unwrap();
+
+            assert_eq!(
+                format!("{:#}", err),
+                "failed to analyze statement 1 of src/test.js:3:1: non-ident exported is not supported when tree shaking"
+            );
+        });
+    }
+
+    #[test]
+    fn test_find_default_re_export_specifier() {
+        let export_info = ExportInfo {
+            source: Some("./x".to_string()),
+            specifiers: vec![
+                ExportSpecifierInfo::Named {
+                    local: "a#0".to_string(),
+                    exported: None,
+                },
+                ExportSpecifierInfo::Named {
+                    local: "default#0".to_string(),
+                    exported: None,
+                },
+            ],
+            stmt_id: 0,
+        };
+
+        assert!(matches!(
+            export_info.find_export_specifier(&"default".to_string()),
+            Some(ExportSpecifierInfo::Named { local, .. }) if local == "default#0"
+        ));
+        assert_eq!(
+            export_info.matches_ident(&"default".to_string()),
+            ExportInfoMatch::Matched
+        );
+    }
+}
@@ -1,15 +1,18 @@
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 
+use anyhow::{bail, Result};
 use swc_core::common::{Span, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast as swc_ecma_ast;
 use swc_core::ecma::ast::{Expr, ModuleExportName, ModuleItem, VarDecl};
 use swc_core::ecma::utils::{ExprCtx, ExprExt};
-use swc_core::ecma::visit::VisitWith;
+use swc_core::ecma::visit::{Visit, VisitWith};
 
 use super::defined_idents_collector::DefinedIdentsCollector;
+use super::pure_iife::is_pure_iife;
 use super::used_idents_collector::{self, UsedIdentsCollector};
 use super::{ExportInfo, ExportSpecifierInfo, ImportInfo, ImportSpecifierInfo, StatementId};
+use crate::ast::comments::Comments;
 
 #[derive(Debug)]
 pub struct StatementInfo {
@@ -21,6 +24,8 @@ pub struct StatementInfo {
     pub is_self_executed: bool,
     pub has_side_effects: bool,
     pub span: Span,
+    pub no_side_effects_fns: HashSet<String>,
+    pub side_effect_callees: Option<HashSet<String>>,
 }
 
 pub fn analyze_imports_and_exports(
@@ -28,7 +33,8 @@ pub fn analyze_imports_and_exports(
     stmt: &ModuleItem,
     used_defined_idents: Option<HashSet<String>>,
     unresolve_ctxt: SyntaxContext,
-) -> StatementInfo {
+    comments: Option<&Comments>,
+) -> Result<StatementInfo> {
     let mut defined_idents = HashSet::new();
     let mut used_idents = HashSet::new();
     let mut defined_idents_map = HashMap::new();
@@ -39,6 +45,9 @@ pub fn analyze_imports_and_exports(
     let mut is_self_executed = false;
     let mut span = DUMMY_SP;
 
+    let mut no_side_effects_fns = HashSet::new();
+    let mut side_effect_callees = None;
+
     let mut analyze_and_insert_used_idents =
         |stmt: &dyn VisitWith<UsedIdentsCollector>, ident: Option<String>| {
             // skip if used_defined_idents is not None as it is only uses the imports and exports for now
@@ -85,12 +94,15 @@ pub fn analyze_imports_and_exports(
                                 continue;
                             }
 
+                            let imported = match &named.imported {
+                                Some(ModuleExportName::Ident(i)) => Some(i.to_string()),
+                                // e.g. `import { "a-b" as ab } from "./a"`
+                                Some(ModuleExportName::Str(s)) => Some(s.value.to_string()),
+                                None => None,
+                            };
                             specifiers.push(ImportSpecifierInfo::Named {
                                 local: named.local.to_string(),
-                                imported: named.imported.as_ref().map(|i| match i {
-                                    ModuleExportName::Ident(i) => i.to_string(),
-                                    _ => panic!("non-ident imported is not supported when tree shaking"),
-                                }),
+                                imported,
                             });
                             defined_idents.insert(named.local.to_string());
                         }
@@ -152,12 +164,23 @@ pub fn analyze_imports_and_exports(
                             stmt_id: *id,
                         });
                         defined_idents.insert(fn_decl.ident.to_string());
+                        if has_no_side_effects(comments, &[export_decl.span, fn_decl.function.span]) {
+                            no_side_effects_fns.insert(fn_decl.ident.to_string());
+                        }
                         analyze_and_insert_used_idents(&fn_decl.function, Some(fn_decl.ident.to_string()));
                     }
                     swc_ecma_ast::Decl::Var(var_decl) => {
                         let mut specifiers = vec![];
 
                         is_self_executed = !is_pure_var_decl(var_decl, unresolve_ctxt);
+                        if is_self_executed {
+                            side_effect_callees = var_decl_impure_callees(var_decl, unresolve_ctxt);
+                        }
+                        no_side_effects_fns.extend(annotated_var_fns(
+                            var_decl,
+                            comments,
+                            &[export_decl.span, var_decl.span],
+                        ));
 
                         for v_decl in &var_decl.decls {
                             let mut defined_idents_collector = DefinedIdentsCollector::new();
@@ -220,6 +243,12 @@ pub fn analyze_imports_and_exports(
                         if let Some(ident) = &fn_decl.ident {
                             defined_idents.insert(ident.to_string());
                         }
+                        if has_no_side_effects(comments, &[export_default_decl.span, fn_decl.function.span]) {
+                            no_side_effects_fns.insert(NO_SIDE_EFFECTS_DEFAULT.to_string());
+                            if let Some(ident) = &fn_decl.ident {
+                                no_side_effects_fns.insert(ident.to_string());
+                            }
+                        }
                         analyze_and_insert_used_idents(
                             &fn_decl.function,
                             fn_decl.ident.as_ref().map(|i| i.to_string()),
@@ -241,6 +270,16 @@ pub fn analyze_imports_and_exports(
                     specifiers: vec![ExportSpecifierInfo::Default(default_ident)],
                     stmt_id: *id,
                 });
+                let fn_span = match export_default_expr.expr.deref() {
+                    Expr::Arrow(arrow) => Some(arrow.span),
+                    Expr::Fn(fn_expr) => Some(fn_expr.function.span),
+                    _ => None,
+                };
+                if let Some(fn_span) = fn_span
+                    && has_no_side_effects(comments, &[export_default_expr.span, fn_span])
+                {
+                    no_side_effects_fns.insert(NO_SIDE_EFFECTS_DEFAULT.to_string());
+                }
                 analyze_and_insert_used_idents(&export_default_expr.expr, None);
             }
             swc_ecma_ast::ModuleDecl::ExportNamed(export_named) => {
@@ -254,7 +293,7 @@ pub fn analyze_imports_and_exports(
                             let local = match &named.orig {
                                 ModuleExportName::Ident(i) => i.clone(),
                                 ModuleExportName::Str(_) => {
-                                    unimplemented!("exporting a string is not supported")
+                                    bail!("exporting a string is not supported")
                                 }
                             };
 
@@ -267,12 +306,16 @@ pub fn analyze_imports_and_exports(
                                 defined_idents_map.insert(local.to_string(), [local.to_string()].into());
                             }
 
+                            let exported = match &named.exported {
+                                Some(ModuleExportName::Ident(i)) => Some(i.to_string()),
+                                Some(ModuleExportName::Str(_)) => {
+                                    bail!("non-ident exported is not supported when tree shaking")
+                                }
+                                None => None,
+                            };
                             specifiers.push(ExportSpecifierInfo::Named {
                                 local: local.to_string(),
-                                exported: named.exported.as_ref().map(|i| match i {
-                                    ModuleExportName::Ident(i) => i.to_string(),
-                                    _ => panic!("non-ident exported is not supported when tree shaking"),
-                                }),
+                                exported,
                             });
                         }
                         swc_ecma_ast::ExportSpecifier::Default(_) => {
@@ -282,7 +325,7 @@ pub fn analyze_imports_and_exports(
                             let ident = match &ns.name {
                                 ModuleExportName::Ident(ident) => ident.to_string(),
                                 ModuleExportName::Str(_) => {
-                                    unreachable!("exporting a string is not supported")
+                                    bail!("exporting a string is not supported")
                                 }
                             };
 
@@ -314,7 +357,7 @@ pub fn analyze_imports_and_exports(
                 analyze_and_insert_used_idents(with, None)
             }
             swc_ecma_ast::Stmt::Return(_) => {
-                unreachable!("return statement should not be present in a module root")
+                bail!("return statement should not be present in a module root")
             }
             swc_ecma_ast::Stmt::Labeled(label) => {
                 span = label.span;
@@ -323,10 +366,10 @@ pub fn analyze_imports_and_exports(
                 analyze_and_insert_used_idents(label, None)
             }
             swc_ecma_ast::Stmt::Break(_) => {
-                unreachable!("break statement should not be present in a module root")
+                bail!("break statement should not be present in a module root")
             }
             swc_ecma_ast::Stmt::Continue(_) => {
-                unreachable!("continue statement should not be present in a module root")
+                bail!("continue statement should not be present in a module root")
             }
             swc_ecma_ast::Stmt::If(if_stmt) => {
                 span = if_stmt.span;
@@ -390,9 +433,14 @@ pub fn analyze_imports_and_exports(
                 }
                 swc_ecma_ast::Decl::Fn(fn_decl) => {
                     defined_idents.insert(fn_decl.ident.to_string());
+                    if has_no_side_effects(comments, &[fn_decl.function.span]) {
+                        no_side_effects_fns.insert(fn_decl.ident.to_string());
+                    }
                     analyze_and_insert_used_idents(&fn_decl.function, Some(fn_decl.ident.to_string()));
                 }
                 swc_ecma_ast::Decl::Var(var_decl) => {
+                    no_side_effects_fns.extend(annotated_var_fns(var_decl, comments, &[var_decl.span]));
+
                     for v_decl in &var_decl.decls {
                         let mut defined_idents_collector = DefinedIdentsCollector::new();
                         v_decl.name.visit_with(&mut defined_idents_collector);
@@ -411,10 +459,11 @@ pub fn analyze_imports_and_exports(
                             defined_idents.insert(defined_ident.clone());
                             defined_idents_map.insert(defined_ident.clone(), local_used_idents.clone());
                         }
+                    }
 
-                        if !is_pure_var_decl(var_decl, unresolve_ctxt) {
-                            is_self_executed = true;
-                        }
+                    if !is_pure_var_decl(var_decl, unresolve_ctxt) {
+                        is_self_executed = true;
+                        side_effect_callees = var_decl_impure_callees(var_decl, unresolve_ctxt);
                     }
                 }
                 _ => unreachable!("decl should not be anything other than a class, function, or variable declaration"),
@@ -422,15 +471,20 @@ pub fn analyze_imports_and_exports(
             swc_ecma_ast::Stmt::Expr(expr) => {
                 span = expr.span;
 
-                if !is_pure_expression(&expr.expr, unresolve_ctxt) {
+                // the members of an enum, which are kept or removed together with its binding
+                let enum_binding = ts_enum_binding(&expr.expr, unresolve_ctxt);
+                if let Some(binding) = &enum_binding {
+                    defined_idents.insert(binding.clone());
+                } else if !is_pure_expression(&expr.expr, unresolve_ctxt) {
                     is_self_executed = true;
+                    side_effect_callees = impure_callees(&expr.expr, unresolve_ctxt);
                 }
-                analyze_and_insert_used_idents(expr, None);
+                analyze_and_insert_used_idents(expr, enum_binding);
             }
         },
     };
 
-    StatementInfo {
+    Ok(StatementInfo {
         import_info: imports,
         export_info: exports,
         defined_idents,
@@ -439,20 +493,305 @@ pub fn analyze_imports_and_exports(
         is_self_executed,
         has_side_effects: false,
         span,
-    }
+        no_side_effects_fns,
+        side_effect_callees,
+    })
+}
+
+/// Marks the annotated function of `export default`, it's never a binding name so it can't
+/// collide with the local functions.
+pub const NO_SIDE_EFFECTS_DEFAULT: &str = "default";
+
+// `/*#__NO_SIDE_EFFECTS__*/ function foo() {}`
+fn has_no_side_effects(comments: Option<&Comments>, spans: &[Span]) -> bool {
+    comments.is_some_and(|comments| spans.iter().any(|span| comments.has_no_side_effects(*span)))
+}
+
+// `const foo = /*#__NO_SIDE_EFFECTS__*/ () => {}`, the annotation may also be put before the
+// declaration when there is only one declarator
+fn annotated_var_fns(
+    var: &VarDecl,
+    comments: Option<&Comments>,
+    decl_spans: &[Span],
+) -> Vec<String> {
+    var.decls
+        .iter()
+        .filter_map(|decl| {
+            let fn_span = match decl.init.as_deref()? {
+                Expr::Arrow(arrow) => arrow.span,
+                Expr::Fn(fn_expr) => fn_expr.function.span,
+                _ => return None,
+            };
+            let swc_ecma_ast::Pat::Ident(binding) = &decl.name else {
+                return None;
+            };
+            let annotated = has_no_side_effects(comments, &[fn_span])
+                || (var.decls.len() == 1 && has_no_side_effects(comments, decl_spans));
+
+            annotated.then(|| binding.id.to_string())
+        })
+        .collect()
 }
 
-fn is_pure_var_decl(var: &VarDecl, unresolved_ctxt: SyntaxContext) -> bool {
+pub(crate) fn is_pure_var_decl(var: &VarDecl, unresolved_ctxt: SyntaxContext) -> bool {
     var.decls.iter().all(|decl| {
-        if let Some(ref init) = decl.init {
+        let is_pure_init = if let Some(ref init) = decl.init {
             is_pure_expression(init, unresolved_ctxt)
+                || matches!(
+                    init.unwrap_parens(),
+                    Expr::Call(call) if is_ts_enum_iife(call, unresolved_ctxt) || is_pure_iife(call, unresolved_ctxt)
+                )
         } else {
             true
-        }
+        };
+        // destructuring runs the getters of the initializer, while the objects bound to a name
+        // are kept as a whole when used
+        let runs_accessors =
+            !matches!(decl.name, swc_ecma_ast::Pat::Ident(_)) && decl.init.as_deref().is_some_and(has_accessor_props);
+        is_pure_init && is_pure_pat(&decl.name, unresolved_ctxt) && !runs_accessors
     })
 }
 
-fn is_pure_expression(expr: &Expr, unresolved_ctxt: SyntaxContext) -> bool {
+/// The default values and computed keys of a destructuring pattern are evaluated with the
+/// declaration, e.g. `const { a = init(), [key()]: b } = obj`.
+fn is_pure_pat(pat: &swc_ecma_ast::Pat, unresolved_ctxt: SyntaxContext) -> bool {
+    match pat {
+        swc_ecma_ast::Pat::Ident(_)
+        | swc_ecma_ast::Pat::Invalid(_)
+        | swc_ecma_ast::Pat::Expr(_) => true,
+        swc_ecma_ast::Pat::Array(array_pat) => array_pat
+            .elems
+            .iter()
+            .flatten()
+            .all(|elem| is_pure_pat(elem, unresolved_ctxt)),
+        swc_ecma_ast::Pat::Rest(rest_pat) => is_pure_pat(&rest_pat.arg, unresolved_ctxt),
+        swc_ecma_ast::Pat::Object(obj_pat) => obj_pat.props.iter().all(|prop| match prop {
+            swc_ecma_ast::ObjectPatProp::KeyValue(kv_prop) => {
+                let is_pure_key = match &kv_prop.key {
+                    swc_ecma_ast::PropName::Computed(computed) => {
+                        is_pure_expression(&computed.expr, unresolved_ctxt)
+                    }
+                    _ => true,
+                };
+                is_pure_key && is_pure_pat(&kv_prop.value, unresolved_ctxt)
+            }
+            swc_ecma_ast::ObjectPatProp::Assign(assign_prop) => assign_prop
+                .value
+                .as_ref()
+                .map_or(true, |value| is_pure_expression(value, unresolved_ctxt)),
+            swc_ecma_ast::ObjectPatProp::Rest(rest_prop) => {
+                is_pure_pat(&rest_prop.arg, unresolved_ctxt)
+            }
+        }),
+        swc_ecma_ast::Pat::Assign(assign_pat) => {
+            is_pure_pat(&assign_pat.left, unresolved_ctxt)
+                && is_pure_expression(&assign_pat.right, unresolved_ctxt)
+        }
+    }
+}
+
+/// Whether the expression has object literals with getters or setters, including the nested
+/// ones, e.g. `{ get level() { console.log('read'); return 1; } }`. The accessors of the nested
+/// functions and classes are not counted.
+fn has_accessor_props(expr: &Expr) -> bool {
+    let mut visitor = AccessorPropsVisitor { found: false };
+    expr.visit_with(&mut visitor);
+    visitor.found
+}
+
+struct AccessorPropsVisitor {
+    found: bool,
+}
+
+impl Visit for AccessorPropsVisitor {
+    fn visit_prop(&mut self, prop: &swc_ecma_ast::Prop) {
+        if matches!(
+            prop,
+            swc_ecma_ast::Prop::Getter(_) | swc_ecma_ast::Prop::Setter(_)
+        ) {
+            self.found = true;
+            return;
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, _function: &swc_ecma_ast::Function) {}
+
+    fn visit_arrow_expr(&mut self, _arrow: &swc_ecma_ast::ArrowExpr) {}
+
+    fn visit_class(&mut self, _class: &swc_ecma_ast::Class) {}
+}
+
+fn var_decl_impure_callees(
+    var: &VarDecl,
+    unresolved_ctxt: SyntaxContext,
+) -> Option<HashSet<String>> {
+    let mut callees = HashSet::new();
+
+    for init in var.decls.iter().filter_map(|decl| decl.init.as_ref()) {
+        callees.extend(impure_callees(init, unresolved_ctxt)?);
+    }
+
+    Some(callees)
+}
+
+/// Returns the callees if calling them is the only side effect of the expression, e.g. `foo(bar())`
+/// gives `foo` and `bar`. Calls with spread arguments are not counted.
+fn impure_callees(expr: &Expr, unresolved_ctxt: SyntaxContext) -> Option<HashSet<String>> {
+    if is_pure_expression(expr, unresolved_ctxt) {
+        return Some(HashSet::new());
+    }
+
+    match expr {
+        Expr::Paren(paren) => impure_callees(&paren.expr, unresolved_ctxt),
+        Expr::Seq(seq) => {
+            let mut callees = HashSet::new();
+            for expr in &seq.exprs {
+                callees.extend(impure_callees(expr, unresolved_ctxt)?);
+            }
+            Some(callees)
+        }
+        Expr::Call(call) => {
+            let swc_ecma_ast::Callee::Expr(callee) = &call.callee else {
+                return None;
+            };
+            let Expr::Ident(ident) = callee.as_ref() else {
+                return None;
+            };
+            if call.args.iter().any(|arg| arg.spread.is_some()) {
+                return None;
+            }
+
+            let mut callees = HashSet::from([ident.to_string()]);
+            for arg in &call.args {
+                callees.extend(impure_callees(&arg.expr, unresolved_ctxt)?);
+            }
+            Some(callees)
+        }
+        _ => None,
+    }
+}
+
+/// The binding of an enum compiled by the ts transform as a statement, e.g.
+///
+/// ```js
+/// (function(Foo) {
+///     Foo[Foo["A"] = 0] = "A";
+/// })(Foo || (Foo = {}));
+/// ```
+fn ts_enum_binding(expr: &Expr, unresolved_ctxt: SyntaxContext) -> Option<String> {
+    let Expr::Call(call) = expr.unwrap_parens() else {
+        return None;
+    };
+    if !is_ts_enum_iife(call, unresolved_ctxt) {
+        return None;
+    }
+    let Expr::Bin(swc_ecma_ast::BinExpr {
+        op: swc_ecma_ast::BinaryOp::LogicalOr,
+        left: box Expr::Ident(binding),
+        right,
+        ..
+    }) = call.args[0].expr.unwrap_parens()
+    else {
+        return None;
+    };
+    let Expr::Assign(swc_ecma_ast::AssignExpr {
+        op: swc_ecma_ast::AssignOp::Assign,
+        left: swc_ecma_ast::AssignTarget::Simple(swc_ecma_ast::SimpleAssignTarget::Ident(target)),
+        ..
+    }) = right.unwrap_parens()
+    else {
+        return None;
+    };
+    (target.id.to_id() == binding.to_id()).then(|| binding.to_string())
+}
+
+/// Whether the call is the iife of an enum compiled by the ts transform, which only assigns the
+/// members to its argument, e.g. `function(Foo) { Foo[Foo["A"] = 0] = "A"; return Foo; }({})`.
+/// Regular enums and const enums which are not inlined are compiled this way, they are free of side
+/// effects as long as the member values are.
+fn is_ts_enum_iife(call: &swc_ecma_ast::CallExpr, unresolved_ctxt: SyntaxContext) -> bool {
+    let swc_ecma_ast::Callee::Expr(callee) = &call.callee else {
+        return false;
+    };
+    let Expr::Fn(fn_expr) = callee.unwrap_parens() else {
+        return false;
+    };
+    let ([param], [arg], Some(body)) = (
+        fn_expr.function.params.as_slice(),
+        call.args.as_slice(),
+        &fn_expr.function.body,
+    ) else {
+        return false;
+    };
+    let swc_ecma_ast::Pat::Ident(param) = &param.pat else {
+        return false;
+    };
+    // `{}`, `Foo || {}` or `Foo || (Foo = {})`
+    let is_empty_object = |expr: &Expr| matches!(expr.unwrap_parens(), Expr::Object(object) if object.props.is_empty());
+    let is_enum_object = match arg.expr.unwrap_parens() {
+        Expr::Bin(swc_ecma_ast::BinExpr {
+            op: swc_ecma_ast::BinaryOp::LogicalOr,
+            left: box Expr::Ident(_),
+            right,
+            ..
+        }) => match right.unwrap_parens() {
+            Expr::Assign(swc_ecma_ast::AssignExpr {
+                op: swc_ecma_ast::AssignOp::Assign,
+                left: swc_ecma_ast::AssignTarget::Simple(swc_ecma_ast::SimpleAssignTarget::Ident(_)),
+                right,
+                ..
+            }) => is_empty_object(right),
+            right => is_empty_object(right),
+        },
+        arg => is_empty_object(arg),
+    };
+
+    arg.spread.is_none()
+        && is_enum_object
+        && body.stmts.iter().all(|stmt| match stmt {
+            swc_ecma_ast::Stmt::Expr(expr) => {
+                is_ts_enum_member_assign(&expr.expr, &param.id, unresolved_ctxt)
+            }
+            swc_ecma_ast::Stmt::Return(swc_ecma_ast::ReturnStmt {
+                arg: Some(box Expr::Ident(ident)),
+                ..
+            }) => ident.to_id() == param.id.to_id(),
+            _ => false,
+        })
+}
+
+// `Foo[Foo["A"] = 0] = "A"` or `Foo["B"] = "b"`
+fn is_ts_enum_member_assign(
+    expr: &Expr,
+    enum_ident: &swc_ecma_ast::Ident,
+    unresolved_ctxt: SyntaxContext,
+) -> bool {
+    let Expr::Assign(swc_ecma_ast::AssignExpr {
+        op: swc_ecma_ast::AssignOp::Assign,
+        left: swc_ecma_ast::AssignTarget::Simple(swc_ecma_ast::SimpleAssignTarget::Member(member)),
+        right,
+        ..
+    }) = expr
+    else {
+        return false;
+    };
+    let is_enum = matches!(&*member.obj, Expr::Ident(obj) if obj.to_id() == enum_ident.to_id());
+    let is_pure_key = match &member.prop {
+        swc_ecma_ast::MemberProp::Computed(computed) => match &*computed.expr {
+            Expr::Assign(_) => {
+                is_ts_enum_member_assign(&computed.expr, enum_ident, unresolved_ctxt)
+            }
+            key => is_pure_expression(key, unresolved_ctxt),
+        },
+        swc_ecma_ast::MemberProp::Ident(_) => true,
+        swc_ecma_ast::MemberProp::PrivateName(_) => false,
+    };
+
+    is_enum && is_pure_key && is_pure_expression(right, unresolved_ctxt)
+}
+
+pub(super) fn is_pure_expression(expr: &Expr, unresolved_ctxt: SyntaxContext) -> bool {
     !expr.may_have_side_effects(&ExprCtx {
         unresolved_ctxt,
         is_unresolved_ref_safe: false,
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use swc_core::ecma::ast::{ObjectPatProp, Pat};
+use swc_core::ecma::ast::{ObjectPatProp, Pat, PropName};
 use swc_core::ecma::visit::{Visit, VisitWith};
 
 use super::used_idents_collector::UsedIdentsCollector;
@@ -37,6 +37,13 @@ impl Visit for DefinedIdentsCollector {
                 for prop in &obj_pat.props {
                     match prop {
                         ObjectPatProp::KeyValue(kv_prop) => {
+                            // e.g. `const { [key]: a } = obj`
+                            if let PropName::Computed(computed) = &kv_prop.key {
+                                let mut used_idents_collector = UsedIdentsCollector::new();
+                                computed.expr.visit_with(&mut used_idents_collector);
+
+                                self.used_idents.extend(used_idents_collector.used_idents);
+                            }
                             self.visit_pat(&kv_prop.value);
                         }
                         ObjectPatProp::Assign(assign_prop) => {
@@ -53,8 +60,14 @@ impl Visit for DefinedIdentsCollector {
                     }
                 }
             }
+            // e.g. `const [a = fallback] = arr`
             Pat::Assign(assign_pat) => {
                 self.visit_pat(&assign_pat.left);
+
+                let mut used_idents_collector = UsedIdentsCollector::new();
+                assign_pat.right.visit_with(&mut used_idents_collector);
+
+                self.used_idents.extend(used_idents_collector.used_idents);
             }
             Pat::Invalid(_) => {}
             Pat::Expr(_) => {}
@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+
+use swc_core::common::SyntaxContext;
+use swc_core::ecma::ast as swc_ecma_ast;
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, Id, Ident, MemberExpr, MemberProp};
+use swc_core::ecma::utils::collect_decls;
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use super::analyze_imports_and_exports::is_pure_expression;
+
+/// Whether the call is an iife whose only effect is to compute the value of the binding, e.g.
+/// `const sdk = (function() { ... return client; })()`, it's kept as long as the binding is used.
+///
+/// It's conservative, the iife is impure unless everything it calls is declared in it or known
+/// to be pure, e.g. `Object.keys`, and it only writes the bindings declared in it.
+pub(crate) fn is_pure_iife(call: &CallExpr, unresolved_ctxt: SyntaxContext) -> bool {
+    let Callee::Expr(callee) = &call.callee else {
+        return false;
+    };
+    if call
+        .args
+        .iter()
+        .any(|arg| arg.spread.is_some() || !is_pure_expression(&arg.expr, unresolved_ctxt))
+    {
+        return false;
+    }
+
+    let mut visitor = match callee.unwrap_parens() {
+        Expr::Fn(fn_expr) if !fn_expr.function.is_async && !fn_expr.function.is_generator => {
+            let mut visitor =
+                IifeEffectsVisitor::new(collect_decls(&*fn_expr.function), unresolved_ctxt);
+            // the function itself is not a nested scope of `this`
+            fn_expr.function.visit_children_with(&mut visitor);
+            visitor
+        }
+        Expr::Arrow(arrow) if !arrow.is_async && !arrow.is_generator => {
+            let mut visitor = IifeEffectsVisitor::new(collect_decls(arrow), unresolved_ctxt);
+            arrow.visit_children_with(&mut visitor);
+            visitor
+        }
+        _ => return false,
+    };
+    !visitor.impure
+}
+
+// the globals which are safe to read, the calls of them are checked by `check_call`
+const PURE_GLOBALS: [&str; 27] = [
+    "Array",
+    "Boolean",
+    "Date",
+    "Error",
+    "Infinity",
+    "JSON",
+    "Map",
+    "Math",
+    "NaN",
+    "Number",
+    "Object",
+    "RegExp",
+    "Set",
+    "String",
+    "Symbol",
+    "TypeError",
+    "WeakMap",
+    "WeakSet",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+    "undefined",
+];
+
+const PURE_GLOBAL_FNS: [&str; 12] = [
+    "Boolean",
+    "Number",
+    "String",
+    "Symbol",
+    "decodeURI",
+    "decodeURIComponent",
+    "encodeURI",
+    "encodeURIComponent",
+    "isFinite",
+    "isNaN",
+    "parseFloat",
+    "parseInt",
+];
+
+const PURE_GLOBAL_CONSTRUCTORS: [&str; 10] = [
+    "Array",
+    "Date",
+    "Error",
+    "Map",
+    "Object",
+    "RegExp",
+    "Set",
+    "TypeError",
+    "WeakMap",
+    "WeakSet",
+];
+
+const PURE_GLOBAL_METHODS: [(&str, &str); 22] = [
+    ("Array", "from"),
+    ("Array", "isArray"),
+    ("Array", "of"),
+    ("Date", "now"),
+    ("JSON", "parse"),
+    ("JSON", "stringify"),
+    ("Number", "isFinite"),
+    ("Number", "isInteger"),
+    ("Number", "isNaN"),
+    ("Number", "parseFloat"),
+    ("Number", "parseInt"),
+    ("Object", "create"),
+    ("Object", "entries"),
+    ("Object", "freeze"),
+    ("Object", "fromEntries"),
+    ("Object", "getOwnPropertyNames"),
+    ("Object", "getPrototypeOf"),
+    ("Object", "is"),
+    ("Object", "keys"),
+    ("Object", "values"),
+    ("String", "fromCharCode"),
+    ("Symbol", "for"),
+];
+
+struct IifeEffectsVisitor {
+    locals: HashSet<Id>,
+    unresolved_ctxt: SyntaxContext,
+    // `this` of the nested functions and classes is not the one of the iife, while the arrow
+    // functions share it
+    nested_this_depth: usize,
+    impure: bool,
+}
+
+impl IifeEffectsVisitor {
+    fn new(locals: impl IntoIterator<Item = Id>, unresolved_ctxt: SyntaxContext) -> Self {
+        Self {
+            locals: locals.into_iter().collect(),
+            unresolved_ctxt,
+            nested_this_depth: 0,
+            impure: false,
+        }
+    }
+
+    fn is_global(&self, ident: &Ident) -> bool {
+        ident.ctxt == self.unresolved_ctxt
+    }
+
+    fn is_local(&self, ident: &Ident) -> bool {
+        self.locals.contains(&ident.to_id())
+    }
+
+    // `a`, `a.b.c`, `a[b].c` or `this.a` is written, only the locals can be written
+    fn check_write(&mut self, expr: &Expr) {
+        match expr.unwrap_parens() {
+            Expr::Ident(ident) => {
+                if !self.is_local(ident) {
+                    self.impure = true;
+                }
+            }
+            Expr::Member(member) => self.check_write(&member.obj),
+            Expr::This(_) => {
+                if self.nested_this_depth == 0 {
+                    self.impure = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // `a()`, `a.b()` or `new a.B()` is called, only the locals and the known pure globals can be
+    // called, and the methods of the values created in the iife, e.g. `[].concat()`
+    fn check_call(&mut self, callee: &Expr, is_new: bool) {
+        if let Expr::Ident(ident) = callee.unwrap_parens()
+            && self.is_global(ident)
+        {
+            let names: &[&str] = if is_new {
+                &PURE_GLOBAL_CONSTRUCTORS
+            } else {
+                &PURE_GLOBAL_FNS
+            };
+            if !names.contains(&&*ident.sym) {
+                self.impure = true;
+            }
+            return;
+        }
+        if let Expr::Member(MemberExpr {
+            obj: box Expr::Ident(obj),
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = callee.unwrap_parens()
+            && self.is_global(obj)
+        {
+            let is_pure =
+                &*obj.sym == "Math" || PURE_GLOBAL_METHODS.contains(&(&*obj.sym, &*prop.sym));
+            if is_new || !is_pure {
+                self.impure = true;
+            }
+            return;
+        }
+
+        let mut obj = callee.unwrap_parens();
+        while let Expr::Member(member) = obj {
+            obj = member.obj.unwrap_parens();
+        }
+        match obj {
+            Expr::Ident(ident) if !self.is_local(ident) => self.impure = true,
+            Expr::This(_) if self.nested_this_depth == 0 => self.impure = true,
+            _ => {}
+        }
+    }
+}
+
+impl Visit for IifeEffectsVisitor {
+    fn visit_ident(&mut self, ident: &Ident) {
+        if self.is_global(ident) && !PURE_GLOBALS.contains(&&*ident.sym) {
+            self.impure = true;
+        }
+    }
+
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        // e.g. `Array.prototype`, which may be patched through a local alias
+        if let Expr::Ident(obj) = member.obj.unwrap_parens()
+            && self.is_global(obj)
+            && matches!(&member.prop, MemberProp::Ident(prop) if prop.sym == "prototype")
+        {
+            self.impure = true;
+        }
+        member.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, function: &swc_ecma_ast::Function) {
+        self.nested_this_depth += 1;
+        function.visit_children_with(self);
+        self.nested_this_depth -= 1;
+    }
+
+    fn visit_class(&mut self, class: &swc_ecma_ast::Class) {
+        self.nested_this_depth += 1;
+        class.visit_children_with(self);
+        self.nested_this_depth -= 1;
+    }
+
+    fn visit_assign_expr(&mut self, assign: &swc_ecma_ast::AssignExpr) {
+        match &assign.left {
+            swc_ecma_ast::AssignTarget::Simple(swc_ecma_ast::SimpleAssignTarget::Ident(
+                binding,
+            )) => {
+                if !self.is_local(&binding.id) {
+                    self.impure = true;
+                }
+            }
+            swc_ecma_ast::AssignTarget::Simple(swc_ecma_ast::SimpleAssignTarget::Member(
+                member,
+            )) => self.check_write(&member.obj),
+            _ => self.impure = true,
+        }
+        assign.visit_children_with(self);
+    }
+
+    fn visit_update_expr(&mut self, update: &swc_ecma_ast::UpdateExpr) {
+        self.check_write(&update.arg);
+        update.visit_children_with(self);
+    }
+
+    fn visit_unary_expr(&mut self, unary: &swc_ecma_ast::UnaryExpr) {
+        match unary.op {
+            swc_ecma_ast::UnaryOp::Delete => self.check_write(&unary.arg),
+            // feature detection, e.g. `typeof window !== 'undefined'`
+            swc_ecma_ast::UnaryOp::TypeOf
+                if matches!(unary.arg.unwrap_parens(), Expr::Ident(_)) =>
+            {
+                return
+            }
+            _ => {}
+        }
+        unary.visit_children_with(self);
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        match &call.callee {
+            Callee::Expr(callee) => self.check_call(callee, false),
+            // `super()` of the nested classes, `import()`
+            Callee::Super(_) => {}
+            Callee::Import(_) => self.impure = true,
+        }
+        call.visit_children_with(self);
+    }
+
+    fn visit_new_expr(&mut self, new: &swc_ecma_ast::NewExpr) {
+        self.check_call(&new.callee, true);
+        new.visit_children_with(self);
+    }
+
+    fn visit_await_expr(&mut self, _await: &swc_ecma_ast::AwaitExpr) {
+        self.impure = true;
+    }
+}
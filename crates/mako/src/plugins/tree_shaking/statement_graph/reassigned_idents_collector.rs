@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use swc_core::ecma::ast::{AssignExpr, AssignTarget, Expr, SimpleAssignTarget, UpdateExpr};
+use swc_core::ecma::visit::{Visit, VisitWith};
+
+use super::defined_idents_collector::DefinedIdentsCollector;
+
+pub struct ReassignedIdentsCollector {
+    pub reassigned_idents: HashSet<String>,
+}
+
+impl ReassignedIdentsCollector {
+    pub fn new() -> Self {
+        Self {
+            reassigned_idents: HashSet::new(),
+        }
+    }
+}
+
+impl Visit for ReassignedIdentsCollector {
+    fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+        match &assign.left {
+            AssignTarget::Simple(SimpleAssignTarget::Ident(bi)) => {
+                self.reassigned_idents.insert(bi.id.to_string());
+            }
+            AssignTarget::Pat(pat) => {
+                let mut defined_idents_collector = DefinedIdentsCollector::new();
+                pat.visit_with(&mut defined_idents_collector);
+                self.reassigned_idents
+                    .extend(defined_idents_collector.defined_idents);
+            }
+            _ => {}
+        }
+
+        assign.visit_children_with(self);
+    }
+
+    fn visit_update_expr(&mut self, update: &UpdateExpr) {
+        if let Expr::Ident(ident) = update.arg.as_ref() {
+            self.reassigned_idents.insert(ident.to_string());
+        }
+
+        update.visit_children_with(self);
+    }
+}
@@ -1,19 +1,33 @@
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::sync::Arc;
 
 use anyhow;
+use indexmap::IndexMap;
+use pathdiff::diff_paths;
 use wasmparser::{Import, Parser, Payload};
 
-use crate::ast::file::{Content, JsContent};
+use crate::ast::file::{win_path, Content, JsContent};
 use crate::compiler::Context;
+use crate::config::Platform;
 use crate::plugin::{Plugin, PluginLoadParam};
 
 pub struct WasmRuntimePlugin {}
 
 const WASM_EXTENSIONS: [&str; 1] = ["wasm"];
 
+/**
+ * `.wasm` files are emitted as assets with content hash, and imported in three ways:
+ *
+ * - `import { add } from './add.wasm'`, an async module of the wasm exports, which is
+ *   instantiated with the modules of the wasm imports
+ * - `import init from './add.wasm?init'`, `init(imports?)` instantiates the wasm with the
+ *   given imports object and resolves with the wasm exports
+ * - `import url from './add.wasm?url'`, the url of the wasm
+ *
+ * With the browser platform, the wasm is fetched from the publicPath, and with the node
+ * platform, it's read from disk next to the output file instead.
+ */
 impl Plugin for WasmRuntimePlugin {
     fn name(&self) -> &str {
         "wasm_runtime"
@@ -27,8 +41,14 @@ impl Plugin for WasmRuntimePlugin {
             .values()
             .any(|info| info.ends_with(".wasm"))
         {
+            let instantiate = if context.config.platform == Platform::Node {
+                include_str!("./wasm_runtime/instantiate_node.js")
+            } else {
+                include_str!("./wasm_runtime/instantiate_browser.js")
+            };
             Ok(vec![
-                include_str!("./wasm_runtime/wasm_runtime.js").to_string()
+                instantiate.to_string(),
+                include_str!("./wasm_runtime/wasm_runtime.js").to_string(),
             ])
         } else {
             Ok(vec![])
@@ -38,7 +58,7 @@ impl Plugin for WasmRuntimePlugin {
     fn load(
         &self,
         param: &PluginLoadParam,
-        _context: &Arc<Context>,
+        context: &Arc<Context>,
     ) -> anyhow::Result<Option<Content>> {
         let file = param.file;
 
@@ -49,36 +69,46 @@ impl Plugin for WasmRuntimePlugin {
                 file.get_content_hash()?,
                 file.extname
             );
-            _context.emit_assets(
+            context.emit_assets(
                 file.pathname.to_string_lossy().to_string(),
                 final_file_name.clone(),
             );
 
+            // ?url
+            if file.has_param("url") {
+                return Ok(Some(Content::Js(JsContent {
+                    content: format!(
+                        "module.exports = `${{require.publicPath}}{}`;",
+                        final_file_name
+                    ),
+                    ..Default::default()
+                })));
+            }
+
             let mut buffer = Vec::new();
-            File::open(&file.path)?.read_to_end(&mut buffer)?;
-            // Parse wasm file to get imports
-            let mut wasm_import_object_map: HashMap<&str, Vec<String>> = HashMap::new();
-            Parser::new(0).parse_all(&buffer).for_each(|payload| {
-                if let Ok(Payload::ImportSection(imports)) = payload {
-                    imports.into_iter_with_offsets().for_each(|import| {
-                        if let Ok((
-                            _,
-                            Import {
-                                module,
-                                name,
-                                ty: _,
-                            },
-                        )) = import
-                        {
-                            if let Some(import_object) = wasm_import_object_map.get_mut(module) {
-                                import_object.push(name.to_string());
-                            } else {
-                                wasm_import_object_map.insert(module, vec![name.to_string()]);
-                            }
-                        }
-                    });
-                }
-            });
+            File::open(&file.pathname)?.read_to_end(&mut buffer)?;
+            let wasm_import_object_map = parse_wasm_imports(&buffer);
+            context.stats_info.add_wasm_imports(
+                win_path(
+                    &diff_paths(&file.pathname, &context.root)
+                        .unwrap_or(file.pathname.clone())
+                        .to_string_lossy(),
+                ),
+                wasm_import_object_map.clone().into_iter().collect(),
+            );
+
+            // ?init
+            if file.has_param("init") {
+                return Ok(Some(Content::Js(JsContent {
+                    content: format!(
+                        r#"export default function init(importsObj) {{
+  return require._instantiateWasm("{}", importsObj || {{}}).then((instance) => instance.exports);
+}}"#,
+                        final_file_name
+                    ),
+                    ..Default::default()
+                })));
+            }
 
             let mut module_import_code = String::new();
             let mut wasm_import_object_code = String::new();
@@ -126,6 +156,32 @@ impl Plugin for WasmRuntimePlugin {
     }
 }
 
+// names of the imports by module, from the import section of the wasm
+fn parse_wasm_imports(buffer: &[u8]) -> IndexMap<String, Vec<String>> {
+    let mut wasm_import_object_map: IndexMap<String, Vec<String>> = IndexMap::new();
+    Parser::new(0).parse_all(buffer).for_each(|payload| {
+        if let Ok(Payload::ImportSection(imports)) = payload {
+            imports.into_iter_with_offsets().for_each(|import| {
+                if let Ok((
+                    _,
+                    Import {
+                        module,
+                        name,
+                        ty: _,
+                    },
+                )) = import
+                {
+                    wasm_import_object_map
+                        .entry(module.to_string())
+                        .or_default()
+                        .push(name.to_string());
+                }
+            });
+        }
+    });
+    wasm_import_object_map
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -169,4 +225,32 @@ mod tests {
             assert!(!js_content.content.contains("import * as module0 from"))
         }
     }
+
+    #[test]
+    fn test_wasm_runtime_load_init() {
+        let plugin = WasmRuntimePlugin {};
+        let context = Arc::new(Context {
+            ..Default::default()
+        });
+        let wasm_relative_path =
+            std::path::Path::new("../../e2e/fixtures/assets.wasm.node/src/add.wasm");
+        let wasm_path = std::fs::canonicalize(wasm_relative_path).unwrap();
+        let file = File::new(
+            format!("{}?init", wasm_path.to_string_lossy()),
+            context.clone(),
+        );
+        let param = PluginLoadParam { file: &file };
+        let result = plugin.load(&param, &context).unwrap();
+
+        let Some(Content::Js(js_content)) = result else {
+            panic!("wasm should be loaded as js");
+        };
+        assert!(js_content
+            .content
+            .contains("export default function init(importsObj)"));
+        assert!(!js_content.content.contains("import * as module0 from"));
+        let wasm_imports = context.stats_info.get_wasm_imports();
+        let imports = wasm_imports.values().next().unwrap();
+        assert_eq!(imports["env"], vec!["log"]);
+    }
 }
@@ -0,0 +1,15 @@
+requireModule._instantiateWasm = (wasmModulePath, importsObj) => {
+  const url = requireModule.publicPath + wasmModulePath;
+  const instantiateArrayBuffer = () =>
+    fetch(url)
+      .then((body) => body.arrayBuffer())
+      .then((bytes) => WebAssembly.instantiate(bytes, importsObj))
+      .then((res) => res.instance);
+  if (typeof WebAssembly.instantiateStreaming === 'function') {
+    // e.g. the wasm is not served with the application/wasm mime type
+    return WebAssembly.instantiateStreaming(fetch(url), importsObj)
+      .then((res) => res.instance)
+      .catch(instantiateArrayBuffer);
+  }
+  return instantiateArrayBuffer();
+};
@@ -0,0 +1,6 @@
+requireModule._instantiateWasm = (wasmModulePath, importsObj) => {
+  const bytes = require('fs').readFileSync(
+    require('path').join(__dirname, wasmModulePath),
+  );
+  return WebAssembly.instantiate(bytes, importsObj).then((res) => res.instance);
+};
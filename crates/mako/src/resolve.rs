@@ -1,10 +1,11 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::vec;
 
 use anyhow::{anyhow, Result};
 use convert_case::{Case, Casing};
+use glob_match::glob_match;
 use oxc_resolver::{Alias, AliasValue, ResolveError as OxcResolveError, ResolveOptions, Resolver};
 use regex::Captures;
 use thiserror::Error;
@@ -21,8 +22,9 @@ use crate::ast::file::parse_path;
 use crate::compiler::Context;
 use crate::config::{
     Config, ExternalAdvancedSubpathConverter, ExternalAdvancedSubpathTarget, ExternalConfig,
-    Platform,
+    Platform, ResolveFallback,
 };
+use crate::features::node::Node;
 use crate::features::rsc::Rsc;
 use crate::module::{Dependency, ResolveType};
 use crate::plugin::PluginResolveIdParams;
@@ -45,6 +47,11 @@ pub enum ResolverType {
 
 pub type Resolvers = HashMap<ResolverType, Resolver>;
 
+// modules which throw at runtime, for the missing specifiers matching `resolve.ignoreMissing`
+pub const MISSING_MODULE_PREFIX: &str = "virtual:missing_";
+
+const GLOBAL_OBJ: &str = "(typeof globalThis !== 'undefined' ? globalThis : self)";
+
 pub fn resolve(
     path: &str,
     dep: &Dependency,
@@ -87,16 +94,201 @@ pub fn resolve(
     .unwrap();
 
     let source = dep.resolve_as.as_ref().unwrap_or(&dep.source);
+    // resolved to the polyfills, see Node::modify_config
+    let source = if context.config.platform == Platform::Browser
+        && !context.config.externals.contains_key(source)
+    {
+        Node::polyfill_source(source, &context.config)
+    } else {
+        source
+    };
+
+    // plugins decide whether the source is external before the externals config
+    let is_external = context
+        .plugin_driver
+        .filter_externals(source, path, context)?;
+    if is_external == Some(true) {
+        // required at runtime when targeting node, like the built-in node modules
+        let external = if context.config.platform == Platform::Node {
+            format!("require(\"{}\")", source)
+        } else {
+            get_external_target_from_global_obj(GLOBAL_OBJ, source)
+        };
+        return Ok(ResolverResource::External(ExternalResource {
+            source: source.to_string(),
+            external,
+            script: None,
+        }));
+    }
+    let externals = is_external.is_none().then_some(&context.config.externals);
+
+    // built-in node modules are required at runtime when targeting node
+    if context.config.platform == Platform::Node
+        && is_external.is_none()
+        && !context.config.externals.contains_key(source)
+        && Node::is_builtin_module(source)
+    {
+        return Ok(ResolverResource::External(ExternalResource {
+            source: source.to_string(),
+            external: format!("require(\"{}\")", source),
+            script: None,
+        }));
+    }
+
+    if let Some(resolved) = resolve_package_exports(path, source, resolver, context)? {
+        return Ok(resolved);
+    }
+
+    do_resolve(path, source, resolver, externals).or_else(|err| {
+        // the modules emitted by plugins only exist in memory
+        if let Some(emitted) = context.emitted_modules.resolve(source, path) {
+            return Ok(ResolverResource::Virtual(PathBuf::from(emitted)));
+        }
+        resolve_missing(source, resolver, context).ok_or(err)
+    })
+}
+
+// plugins may override the file of a package subpath, e.g. to patch a broken `exports` map,
+// the aliases are applied by the resolver when the package is looked up
+fn resolve_package_exports(
+    path: &str,
+    source: &str,
+    resolver: &Resolver,
+    context: &Arc<Context>,
+) -> Result<Option<ResolverResource>> {
+    if !context.plugin_driver.has_on_resolve_package_exports() {
+        return Ok(None);
+    }
+    let Some((name, subpath)) = parse_package_specifier(source) else {
+        return Ok(None);
+    };
+    let Some(pkg_dir) = find_package_dir(path, name, resolver) else {
+        return Ok(None);
+    };
+    let Some(target) = context.plugin_driver.on_resolve_package_exports(
+        &pkg_dir,
+        &subpath,
+        &resolver.options().condition_names,
+        context,
+    )?
+    else {
+        return Ok(None);
+    };
+    debug!("resolve {} to {} from the plugins", source, target);
+    let from = pkg_dir.join("package.json");
+    let target = pkg_dir.join(target);
+    do_resolve(
+        &from.to_string_lossy(),
+        &target.to_string_lossy(),
+        resolver,
+        None,
+    )
+    .map(Some)
+}
+
+// e.g. `@scope/pkg/foo` => (`@scope/pkg`, `./foo`), `pkg` => (`pkg`, `.`)
+fn parse_package_specifier(source: &str) -> Option<(&str, String)> {
+    if source.is_empty() || source.starts_with(['.', '/', '#']) || source.contains([':', '?']) {
+        return None;
+    }
+    let name_end = if source.starts_with('@') {
+        source.match_indices('/').nth(1).map(|(index, _)| index)
+    } else {
+        source.find('/')
+    }
+    .unwrap_or(source.len());
+    let subpath = format!(".{}", &source[name_end..]);
+    Some((&source[..name_end], subpath))
+}
+
+// the package is looked up by the resolver, with the aliases and the symlinks, while its
+// `exports` map is ignored since the package.json may not be exported
+fn find_package_dir(path: &str, name: &str, resolver: &Resolver) -> Option<PathBuf> {
+    let resolver = resolver.clone_with_options(ResolveOptions {
+        exports_fields: vec![],
+        ..resolver.options().clone()
+    });
+    let resolution = resolver
+        .resolve(Path::new(path).parent()?, &format!("{}/package.json", name))
+        .ok()?;
+    resolution.path().parent().map(Path::to_path_buf)
+}
 
-    do_resolve(path, source, resolver, Some(&context.config.externals))
+// `resolve.fallback` first, the exact specifiers and then the prefixes, then
+// `resolve.ignoreMissing`
+fn resolve_missing(
+    source: &str,
+    resolver: &Resolver,
+    context: &Arc<Context>,
+) -> Option<ResolverResource> {
+    let resolve_config = &context.config.resolve;
+    match resolve_config.fallback.get(source) {
+        Some(ResolveFallback::Target(target)) => {
+            // targets are resolved from the root
+            let from = context.root.join("package.json");
+            return do_resolve(&from.to_string_lossy(), target, resolver, None).ok();
+        }
+        Some(ResolveFallback::Empty(false)) => {
+            return Some(ResolverResource::Ignored(PathBuf::from(format!(
+                "virtual:empty_{}",
+                source
+            ))));
+        }
+        _ => {}
+    }
+    if let Some(resolved) = resolve_fallback_candidates(source, resolver, context) {
+        return Some(resolved);
+    }
+    resolve_config
+        .ignore_missing
+        .iter()
+        .any(|pattern| glob_match(pattern, source))
+        .then(|| {
+            ResolverResource::Virtual(PathBuf::from(format!(
+                "{}{}",
+                MISSING_MODULE_PREFIX, source
+            )))
+        })
+}
+
+// the longest prefix of the source with candidates wins, since the keys are not ordered
+fn resolve_fallback_candidates(
+    source: &str,
+    resolver: &Resolver,
+    context: &Arc<Context>,
+) -> Option<ResolverResource> {
+    let (prefix, candidates) = context
+        .config
+        .resolve
+        .fallback
+        .iter()
+        .filter_map(|(prefix, fallback)| match fallback {
+            ResolveFallback::Candidates(candidates) if source.starts_with(prefix.as_str()) => {
+                Some((prefix, candidates))
+            }
+            _ => None,
+        })
+        .max_by_key(|(prefix, _)| prefix.len())?;
+    let from = context.root.join("package.json");
+    candidates.iter().find_map(|candidate| {
+        let target = format!("{}{}", candidate, &source[prefix.len()..]);
+        debug!("try the fallback {} of {}", target, source);
+        do_resolve(&from.to_string_lossy(), &target, resolver, None).ok()
+    })
+}
+
+pub fn missing_module_code(source: &str) -> String {
+    let message = serde_json::to_string(&format!("Cannot find module '{}'", source)).unwrap();
+    format!(
+        "var e = new Error({});\ne.code = 'MODULE_NOT_FOUND';\nthrow e;\n",
+        message
+    )
 }
 
 fn get_external_target(
     externals: &HashMap<String, ExternalConfig>,
     source: &str,
 ) -> Option<(String, Option<String>)> {
-    let global_obj = "(typeof globalThis !== 'undefined' ? globalThis : self)";
-
     if let Some(external) = externals.get(source) {
         // handle full match
         // ex. import React from 'react';
@@ -107,7 +299,7 @@ fn get_external_target(
                 } else if external.starts_with("commonjs ") {
                     format!("require(\"{}\")", external.replace("commonjs ", ""))
                 } else {
-                    get_external_target_from_global_obj(global_obj, external)
+                    get_external_target_from_global_obj(GLOBAL_OBJ, external)
                 },
                 None,
             )),
@@ -117,7 +309,7 @@ fn get_external_target(
                 } else if config.module_type.as_ref().is_some_and(|t| t == "commonjs") {
                     format!("require(\"{}\")", config.root)
                 } else {
-                    get_external_target_from_global_obj(global_obj, &config.root)
+                    get_external_target_from_global_obj(GLOBAL_OBJ, &config.root)
                 },
                 config.script.clone(),
             )),
@@ -200,7 +392,7 @@ fn get_external_target(
                     Some((
                         format!(
                             "{}.{}",
-                            get_external_target_from_global_obj(global_obj, &advanced_config.root),
+                            get_external_target_from_global_obj(GLOBAL_OBJ, &advanced_config.root),
                             replaced
                         ),
                         advanced_config.script.clone(),
@@ -312,26 +504,37 @@ pub fn get_resolvers(config: &Config) -> Resolvers {
     resolvers
 }
 
-pub fn get_module_extensions() -> Vec<String> {
-    vec![
-        ".js".to_string(),
-        ".jsx".to_string(),
-        ".ts".to_string(),
-        ".tsx".to_string(),
-        ".mjs".to_string(),
-        ".cjs".to_string(),
-        ".json".to_string(),
-    ]
+// `resolve.extensions` with or without the leading dot
+pub fn get_module_extensions(config: &Config) -> Vec<String> {
+    config
+        .resolve
+        .extensions
+        .iter()
+        .map(|ext| {
+            if ext.starts_with('.') {
+                ext.clone()
+            } else {
+                format!(".{}", ext)
+            }
+        })
+        .collect()
 }
 
 fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
     let alias = parse_alias(config.resolve.alias.clone());
     let is_browser = config.platform == Platform::Browser;
-    let extensions = get_module_extensions();
-    let options = match (resolver_type, is_browser) {
+    let extensions = get_module_extensions(config);
+    let modules = config
+        .resolve
+        .modules
+        .iter()
+        .map(|m| m.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    let mut options = match (resolver_type, is_browser) {
         (ResolverType::Cjs, true) => ResolveOptions {
             alias,
             extensions,
+            modules,
             condition_names: Rsc::generate_resolve_conditions(
                 config,
                 vec![
@@ -352,6 +555,7 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         (ResolverType::Esm, true) => ResolveOptions {
             alias,
             extensions,
+            modules,
             condition_names: Rsc::generate_resolve_conditions(
                 config,
                 vec![
@@ -372,6 +576,7 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         (ResolverType::Esm, false) => ResolveOptions {
             alias,
             extensions,
+            modules,
             condition_names: Rsc::generate_resolve_conditions(
                 config,
                 vec![
@@ -387,6 +592,7 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         (ResolverType::Cjs, false) => ResolveOptions {
             alias,
             extensions,
+            modules,
             condition_names: Rsc::generate_resolve_conditions(
                 config,
                 vec![
@@ -403,6 +609,7 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         (ResolverType::Css, _) => ResolveOptions {
             extensions: vec![".css".to_string(), ".less".to_string(), ".scss".to_string()],
             alias,
+            modules,
             main_fields: vec!["css".to_string(), "style".to_string(), "main".to_string()],
             condition_names: vec!["style".to_string()],
             prefer_relative: true,
@@ -411,10 +618,16 @@ fn get_resolver(config: &Config, resolver_type: ResolverType) -> Resolver {
         },
         (ResolverType::Ctxt, _) => ResolveOptions {
             alias,
+            modules,
             resolve_to_context: true,
             ..Default::default()
         },
     };
+    if !config.resolve.browser_field {
+        options.main_fields.retain(|field| field != "browser");
+        options.alias_fields.clear();
+    }
+    options.symlinks = config.resolve.symlinks;
 
     Resolver::new(options)
 }
@@ -437,12 +650,21 @@ pub fn clear_resolver_cache(resolvers: &Resolvers) {
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use anyhow::Result;
 
+    use crate::compiler::Context;
     use crate::config::{
         Config, ExternalAdvanced, ExternalAdvancedSubpath, ExternalAdvancedSubpathConverter,
-        ExternalAdvancedSubpathRule, ExternalAdvancedSubpathTarget, ExternalConfig,
+        ExternalAdvancedSubpathRule, ExternalAdvancedSubpathTarget, ExternalConfig, Platform,
+        ResolveFallback,
     };
+    use crate::module::ModuleId;
+    use crate::plugin::Plugin;
     use crate::resolve::ResolverType;
+    use crate::utils::test_helper::setup_compiler_with_plugins;
 
     #[test]
     fn test_resolve() {
@@ -450,6 +672,26 @@ mod tests {
         assert_eq!(x, "source.ts".to_string());
     }
 
+    #[test]
+    fn test_parse_package_specifier() {
+        let parse = |source| {
+            super::parse_package_specifier(source)
+                .map(|(name, subpath)| (name.to_string(), subpath))
+        };
+        assert_eq!(parse("foo"), Some(("foo".into(), ".".into())));
+        assert_eq!(
+            parse("foo/bar/baz"),
+            Some(("foo".into(), "./bar/baz".into()))
+        );
+        assert_eq!(parse("@scope/foo"), Some(("@scope/foo".into(), ".".into())));
+        assert_eq!(
+            parse("@scope/foo/bar"),
+            Some(("@scope/foo".into(), "./bar".into()))
+        );
+        assert_eq!(parse("./foo"), None);
+        assert_eq!(parse("node:fs"), None);
+    }
+
     #[test]
     fn test_resolve_dep() {
         let x = resolve("test/resolve/normal", None, None, "index.ts", "foo");
@@ -479,6 +721,31 @@ mod tests {
         assert_eq!(x, "node_modules/foo/esm-browser.js".to_string());
     }
 
+    #[test]
+    fn test_resolve_browser_field_disabled() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/browser_field");
+        let resolve = |browser_field: bool, source: &str| {
+            let mut config: Config = Default::default();
+            config.resolve.browser_field = browser_field;
+            let resolver = super::get_resolver(&config, ResolverType::Esm);
+            super::do_resolve(
+                &fixture.join("index.ts").to_string_lossy(),
+                source,
+                &resolver,
+                None,
+            )
+            .unwrap()
+            .get_resolved_path()
+            .replace(&format!("{}/", fixture.to_string_lossy()), "")
+        };
+        assert_eq!(resolve(true, "foo"), "node_modules/foo/browser.js");
+        assert_eq!(resolve(true, "bar"), "node_modules/bar/browser.js");
+        assert_eq!(resolve(false, "foo"), "node_modules/foo/index.js");
+        assert_eq!(resolve(false, "bar"), "node_modules/bar/index.js");
+    }
+
     #[test]
     fn test_resolve_alias() {
         let alias = vec![("bar".to_string(), "foo".to_string())];
@@ -500,6 +767,138 @@ mod tests {
         assert_eq!(x, "node_modules/foo/foo.js".to_string());
     }
 
+    #[test]
+    fn test_resolve_extensions() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/extensions");
+        let importer = fixture.join("index.ts").to_string_lossy().to_string();
+
+        let config: Config = Default::default();
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        assert!(super::do_resolve(&importer, "./utils", &resolver, None).is_err());
+
+        let mut config: Config = Default::default();
+        config.resolve.extensions.push("vue".to_string());
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        let resource = super::do_resolve(&importer, "./utils", &resolver, None).unwrap();
+        assert_eq!(
+            resource.get_resolved_path(),
+            fixture.join("utils.vue").to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn test_resolve_modules() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/modules");
+        let mut config: Config = Default::default();
+        config.resolve.modules = vec!["shared".into(), "node_modules".into()];
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        let resolve = |source: &str| {
+            super::do_resolve(
+                &fixture.join("index.ts").to_string_lossy(),
+                source,
+                &resolver,
+                None,
+            )
+            .unwrap()
+            .get_resolved_path()
+            .replace(&format!("{}/", fixture.to_string_lossy()), "")
+        };
+        // directories are searched in order
+        assert_eq!(resolve("utils"), "shared/utils/index.js");
+        assert_eq!(resolve("foo"), "node_modules/foo/index.js");
+    }
+
+    #[test]
+    fn test_resolve_missing() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/modules");
+        let mut config: Config = Default::default();
+        config.resolve.fallback = HashMap::from([
+            (
+                "fallback-foo".to_string(),
+                ResolveFallback::Target("foo".to_string()),
+            ),
+            ("fallback-empty".to_string(), ResolveFallback::Empty(false)),
+        ]);
+        config.resolve.ignore_missing = vec!["optional-*".to_string()];
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        let context = Arc::new(Context {
+            config,
+            root: fixture.clone(),
+            ..Default::default()
+        });
+        let resolve = |source: &str| {
+            super::resolve_missing(source, &resolver, &context).map(|resource| {
+                resource
+                    .get_resolved_path()
+                    .replace(&format!("{}/", fixture.to_string_lossy()), "")
+            })
+        };
+        assert_eq!(
+            resolve("fallback-foo"),
+            Some("node_modules/foo/index.js".to_string())
+        );
+        assert_eq!(
+            resolve("fallback-empty"),
+            Some("virtual:empty_fallback-empty".to_string())
+        );
+        assert_eq!(
+            resolve("optional-dep"),
+            Some("virtual:missing_optional-dep".to_string())
+        );
+        assert_eq!(resolve("other"), None);
+    }
+
+    #[test]
+    fn test_resolve_fallback_candidates() {
+        let fixture = std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/modules");
+        let mut config: Config = Default::default();
+        config.resolve.fallback = HashMap::from([
+            (
+                "@/".to_string(),
+                ResolveFallback::Candidates(vec![
+                    "./missing/".to_string(),
+                    "./shared/".to_string(),
+                ]),
+            ),
+            (
+                "@/pkg/".to_string(),
+                ResolveFallback::Candidates(vec!["".to_string()]),
+            ),
+        ]);
+        let resolver = super::get_resolver(&config, ResolverType::Esm);
+        let context = Arc::new(Context {
+            config,
+            root: fixture.clone(),
+            ..Default::default()
+        });
+        let resolve = |source: &str| {
+            super::resolve_missing(source, &resolver, &context).map(|resource| {
+                resource
+                    .get_resolved_path()
+                    .replace(&format!("{}/", fixture.to_string_lossy()), "")
+            })
+        };
+        // the first candidate doesn't exist
+        assert_eq!(
+            resolve("@/utils"),
+            Some("shared/utils/index.js".to_string())
+        );
+        // the longest prefix wins
+        assert_eq!(
+            resolve("@/pkg/foo"),
+            Some("node_modules/foo/index.js".to_string())
+        );
+        assert_eq!(resolve("@/other"), None);
+    }
+
     #[test]
     fn test_resolve_externals() {
         let externals = HashMap::from([
@@ -817,4 +1216,103 @@ mod tests {
         let path = path.replace(format!("{}/", fixture.to_str().unwrap()).as_str(), "");
         (path, external, script)
     }
+
+    // keeps `foo` external, which can not be resolved in the fixture
+    struct FilterExternalsPlugin {}
+
+    impl Plugin for FilterExternalsPlugin {
+        fn name(&self) -> &str {
+            "filter_externals_test"
+        }
+
+        fn filter_externals(
+            &self,
+            source: &str,
+            _importer: &str,
+            _context: &Arc<Context>,
+        ) -> Result<Option<bool>> {
+            Ok((source == "foo").then_some(true))
+        }
+    }
+
+    #[test]
+    fn test_filter_externals() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/filter-externals",
+            vec![Arc::new(FilterExternalsPlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let foo = module_graph.get_module(&"foo".into()).unwrap();
+        assert!(foo.is_external());
+        let bar = compiler.context.root.join("bar.ts");
+        let bar = bar.to_string_lossy().to_string();
+        assert!(!module_graph.get_module(&bar.into()).unwrap().is_external());
+        let external = foo.info.as_ref().unwrap().external.clone().unwrap();
+        assert!(external.contains("globalThis"), "{}", external);
+        drop(module_graph);
+
+        // required at runtime when targeting node
+        let compiler = setup_compiler_with_plugins(
+            "test/build/filter-externals",
+            vec![Arc::new(FilterExternalsPlugin {})],
+            |config| config.platform = Platform::Node,
+        );
+        compiler.compile().unwrap();
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let foo = module_graph.get_module(&"foo".into()).unwrap();
+        assert_eq!(
+            foo.info.as_ref().unwrap().external.as_deref(),
+            Some(r#"require("foo")"#)
+        );
+    }
+
+    struct PackageExportsPlugin {}
+
+    impl Plugin for PackageExportsPlugin {
+        fn name(&self) -> &str {
+            "package_exports_test"
+        }
+
+        fn on_resolve_package_exports(
+            &self,
+            pkg_dir: &Path,
+            subpath: &str,
+            conditions: &[String],
+            _context: &Arc<Context>,
+        ) -> Result<Option<String>> {
+            assert!(conditions.contains(&"import".to_string()));
+            if !pkg_dir.ends_with("node_modules/broken") {
+                return Ok(None);
+            }
+            Ok(match subpath {
+                "./foo" => Some("./lib/foo.js".to_string()),
+                "./bar" => Some("./lib/bar.js".to_string()),
+                _ => None,
+            })
+        }
+
+        fn has_on_resolve_package_exports(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_on_resolve_package_exports() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/package-exports-hook",
+            vec![Arc::new(PackageExportsPlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let root = &compiler.context.root;
+        let id = |name: &str| ModuleId::new(root.join(name).to_string_lossy().to_string());
+        assert!(module_graph.has_module(&id("node_modules/broken/lib/foo.js")));
+        // `patched` is an alias of the package
+        assert!(module_graph.has_module(&id("node_modules/broken/lib/bar.js")));
+    }
 }
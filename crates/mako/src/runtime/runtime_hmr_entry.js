@@ -71,8 +71,56 @@ self.$RefreshSig$ = function () {
       return Promise.resolve();
     }
   }
+  var overlay = null;
+  function showErrorOverlay(error) {
+    hideErrorOverlay();
+    overlay = document.createElement('div');
+    overlay.style.cssText =
+      'position:fixed;inset:0;z-index:2147483647;overflow:auto;padding:24px;' +
+      'background:rgba(0,0,0,0.85);color:#e8e8e8;' +
+      'font:13px/1.5 Menlo,Consolas,monospace;white-space:pre-wrap;';
+    var title = document.createElement('div');
+    title.style.cssText = 'color:#ff5555;font-size:16px;margin-bottom:8px;';
+    title.textContent =
+      '[plugin ' + error.plugin + '] ' + error.hook + ' failed';
+    var file = document.createElement('div');
+    file.style.cssText = 'color:#8be9fd;margin-bottom:16px;';
+    file.textContent = error.path;
+    var message = document.createElement('div');
+    message.textContent = error.message;
+    overlay.appendChild(title);
+    overlay.appendChild(file);
+    overlay.appendChild(message);
+    if (error.snippet) {
+      var snippet = document.createElement('div');
+      snippet.style.cssText =
+        'margin-top:16px;padding:12px;background:rgba(255,255,255,0.08);';
+      snippet.textContent = error.snippet;
+      overlay.appendChild(snippet);
+    }
+    if (error.stack) {
+      var stack = document.createElement('div');
+      stack.style.cssText = 'margin-top:16px;color:#999;';
+      stack.textContent = error.stack;
+      overlay.appendChild(stack);
+    }
+    document.body.appendChild(overlay);
+  }
+  function hideErrorOverlay() {
+    if (overlay) {
+      overlay.remove();
+      overlay = null;
+    }
+  }
   socket.addEventListener('message', function (rawMessage) {
     var msg = JSON.parse(rawMessage.data);
+    // the previous modules are kept, until the next successful rebuild
+    if (msg.type === 'error') {
+      console.error('[HMR] Build failed', msg.error);
+      showErrorOverlay(msg.error);
+      return;
+    }
+    hideErrorOverlay();
     latestHash = msg.hash;
     if (!updating) {
       runHotUpdate();
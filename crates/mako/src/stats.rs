@@ -1,7 +1,8 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs;
-use std::path::Path;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
@@ -10,11 +11,16 @@ use indexmap::IndexMap;
 use pathdiff::diff_paths;
 use serde::Serialize;
 use swc_core::common::source_map::SmallPos;
+use twox_hash::XxHash64;
 
 use crate::ast::file::win_path;
-use crate::compiler::{Compiler, Context};
+use crate::compiler::{Compiler, Context, PluginHookStats};
 use crate::features::rsc::{RscClientInfo, RscCssModules};
 use crate::generate::chunk::ChunkType;
+use crate::generate::link_hints::LinkHintRel;
+use crate::module::ModuleId;
+use crate::module_graph::ModuleGraph;
+use crate::plugins::duplicate_package_checker::{extract_package_info, PackageInfo};
 
 impl Compiler {
     pub fn create_stats_info(&self) -> StatsJsonMap {
@@ -59,6 +65,7 @@ impl Compiler {
             });
 
         // 获取 assets
+        let assets_meta = self.context.assets_meta.lock().unwrap();
         stats_map.assets = stats_info
             .get_assets()
             .iter()
@@ -67,8 +74,12 @@ impl Compiler {
                 size: asset.size,
                 name: asset.hashname.clone(),
                 path: asset.path.clone(),
+                side_effects: assets_meta
+                    .get(&asset.hashname)
+                    .and_then(|meta| meta.side_effects),
             })
             .collect();
+        drop(assets_meta);
 
         let chunk_graph = self.context.chunk_graph.read().unwrap();
         let module_graph = self.context.module_graph.read().unwrap();
@@ -163,6 +174,18 @@ impl Compiler {
                     }
                 }
                 let origins = origins_set.into_values().collect::<Vec<_>>();
+                let mut link_hints = vec![];
+                for hint in context.link_hints.lock().unwrap().iter() {
+                    if hint.parent_chunk_id == id {
+                        let hint = StatsJsonChunkLinkHintItem {
+                            rel: hint.rel,
+                            chunk_id: hint.chunk_id.clone(),
+                        };
+                        if !link_hints.contains(&hint) {
+                            link_hints.push(hint);
+                        }
+                    }
+                }
 
                 StatsJsonChunkItem {
                     chunk_type: StatsJsonType::Chunk("chunk".to_string()),
@@ -172,6 +195,7 @@ impl Compiler {
                     modules: chunk_modules,
                     siblings,
                     origins,
+                    link_hints,
                 }
             })
             .collect();
@@ -199,14 +223,156 @@ impl Compiler {
             })
             .collect::<HashMap<_, _>>();
         stats_map.chunk_modules = chunk_modules;
+        stats_map.packages = packages_stats(
+            &stats_map.chunks,
+            &stats_info.get_assets(),
+            &module_graph,
+            &context.root,
+        );
 
         stats_map.modules = stats_info.get_modules();
+        stats_map.used_exports = stats_info.get_used_exports();
+        stats_map.tree_shaking.ambiguous_exports = stats_info.get_ambiguous_exports();
+        stats_map.css_dedupe = stats_info.get_css_dedupe();
+        stats_map.wasm_imports = stats_info.get_wasm_imports();
         stats_map.rsc_client_components = stats_info.get_rsc_client_components();
         stats_map.rsc_css_modules = stats_info.get_rsc_css_modules();
 
         stats_map
     }
 
+    /// Warn about the packages bundled with more than one version, whose total size is
+    /// over the threshold, with an import chain of each version.
+    pub fn print_duplicate_packages(&self, stats: &StatsJsonMap, threshold: u64) {
+        let mut duplicates: BTreeMap<&str, Vec<&StatsJsonPackageItem>> = BTreeMap::new();
+        for package in stats.packages.iter().filter(|p| p.duplicated) {
+            duplicates.entry(&package.name).or_default().push(package);
+        }
+        for (name, versions) in duplicates {
+            let size = versions.iter().map(|p| p.size).sum::<u64>();
+            if size < threshold {
+                continue;
+            }
+            let mut message = format!(
+                "Multiple versions of {} ({}) are bundled:",
+                name,
+                human_readable_size(size)
+            );
+            for package in versions {
+                message.push_str(&format!(
+                    "\n  {}@{} ({}) from {}",
+                    package.name,
+                    package.version,
+                    human_readable_size(package.size),
+                    package.chain.join(" -> ")
+                ));
+            }
+            self.context.warn(message);
+        }
+    }
+
+    /// Warn about the chunks and the entrypoints whose files are larger than
+    /// `performance.maxChunkSize` and `performance.maxEntrypointSize`.
+    pub fn print_oversized_chunks(&self, stats: &StatsJsonMap) {
+        let performance = &self.context.config.performance;
+        if performance.max_chunk_size.is_none() && performance.max_entrypoint_size.is_none() {
+            return;
+        }
+        let asset_sizes = stats
+            .assets
+            .iter()
+            .map(|asset| (asset.name.as_str(), asset.size))
+            .collect::<HashMap<_, _>>();
+        let chunk_size = |chunk: &StatsJsonChunkItem| {
+            chunk
+                .files
+                .iter()
+                .filter(|file| !file.ends_with(".map"))
+                .filter_map(|file| asset_sizes.get(file.as_str()))
+                .sum::<u64>()
+        };
+
+        if let Some(limit) = performance.max_chunk_size {
+            for chunk in &stats.chunks {
+                let size = chunk_size(chunk);
+                if size <= limit as u64 {
+                    continue;
+                }
+                self.context.warn(format!(
+                    "Chunk {} ({}) is {} with {} modules, over performance.maxChunkSize ({}), consider enabling codeSplitting to split it",
+                    chunk.id,
+                    chunk.files.join(", "),
+                    human_readable_size(size),
+                    chunk.modules.len(),
+                    human_readable_size(limit as u64)
+                ));
+            }
+        }
+
+        if let Some(limit) = performance.max_entrypoint_size {
+            let mut entrypoints = stats.entrypoints.values().collect::<Vec<_>>();
+            entrypoints.sort_by(|a, b| a.name.cmp(&b.name));
+            for entrypoint in entrypoints {
+                let size = stats
+                    .chunks
+                    .iter()
+                    .filter(|chunk| entrypoint.chunks.contains(&chunk.id))
+                    .map(chunk_size)
+                    .sum::<u64>();
+                if size <= limit as u64 {
+                    continue;
+                }
+                self.context.warn(format!(
+                    "Entrypoint {} is {}, over performance.maxEntrypointSize ({}), consider enabling codeSplitting to load parts of it on demand",
+                    entrypoint.name,
+                    human_readable_size(size),
+                    human_readable_size(limit as u64)
+                ));
+            }
+        }
+    }
+
+    /// Warn about the files of a package bundled more than once through different paths,
+    /// e.g. copies of a workspace package, with an import chain of each path.
+    pub fn print_duplicate_modules(&self) {
+        let module_graph = self.context.module_graph.read().unwrap();
+        let duplicates = duplicate_modules(&module_graph);
+        if duplicates.is_empty() {
+            return;
+        }
+        let importers = shortest_importers(&module_graph);
+        let relative_path = |id: &ModuleId| {
+            let absolute_path = PathBuf::from(&id.id);
+            win_path(
+                &diff_paths(&absolute_path, &self.context.root)
+                    .unwrap_or(absolute_path)
+                    .to_string_lossy(),
+            )
+        };
+        for (name, ids) in duplicates {
+            let mut message = format!(
+                "Identical modules of {} are bundled from different paths:",
+                name
+            );
+            for id in ids {
+                let chain = import_chain(&importers, &id)
+                    .iter()
+                    .map(relative_path)
+                    .collect::<Vec<_>>();
+                message.push_str(&format!("\n  {}", chain.join(" -> ")));
+            }
+            self.context.warn(message);
+        }
+    }
+
+    pub fn print_plugin_hooks(&self, stats: &[PluginHookStats]) {
+        if stats.is_empty() {
+            return;
+        }
+        println!("{}", "Plugin hooks:".bold());
+        println!("{}", plugin_hooks_table(stats).trim_end_matches('\n'));
+    }
+
     pub fn print_stats(&self) {
         let mut assets = self.context.stats_info.get_assets();
         // 按照产物名称排序
@@ -342,6 +508,32 @@ pub struct StatsInfo {
     pub rsc_client_components: Mutex<Vec<RscClientInfo>>,
     pub rsc_css_modules: Mutex<Vec<RscCssModules>>,
     pub modules: Mutex<HashMap<String, ModuleInfo>>,
+    pub used_exports: Mutex<BTreeMap<String, BTreeSet<String>>>,
+    pub ambiguous_exports: Mutex<Vec<AmbiguousExportsInfo>>,
+    pub css_dedupe: Mutex<Vec<CssDedupeInfo>>,
+    // names of the imports by module of each wasm file, to be provided when instantiating
+    pub wasm_imports: Mutex<BTreeMap<String, BTreeMap<String, Vec<String>>>>,
+}
+
+/// Used exports of a module which can only come from `export * from` a module whose
+/// exports are unknown, the whole source module is kept for them.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AmbiguousExportsInfo {
+    pub module: String,
+    pub source: String,
+    pub idents: BTreeSet<String>,
+    // estimated from the statements of the source module
+    pub kept_bytes: u64,
+}
+
+/// A css module in more than one entry which is moved to a shared chunk by
+/// `optimization.cssDedupe`.
+#[derive(Serialize, Debug, Clone)]
+pub struct CssDedupeInfo {
+    pub module: String,
+    pub chunk: String,
+    pub entries: Vec<String>,
 }
 
 impl StatsInfo {
@@ -351,6 +543,10 @@ impl StatsInfo {
             rsc_client_components: Mutex::new(vec![]),
             rsc_css_modules: Mutex::new(vec![]),
             modules: Mutex::new(HashMap::new()),
+            used_exports: Mutex::new(BTreeMap::new()),
+            ambiguous_exports: Mutex::new(vec![]),
+            css_dedupe: Mutex::new(vec![]),
+            wasm_imports: Mutex::new(BTreeMap::new()),
         }
     }
 
@@ -411,6 +607,38 @@ impl StatsInfo {
         self.modules.lock().unwrap().clone()
     }
 
+    pub fn set_used_exports(&self, used_exports: BTreeMap<String, BTreeSet<String>>) {
+        *self.used_exports.lock().unwrap() = used_exports;
+    }
+
+    pub fn get_used_exports(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.used_exports.lock().unwrap().clone()
+    }
+
+    pub fn set_ambiguous_exports(&self, ambiguous_exports: Vec<AmbiguousExportsInfo>) {
+        *self.ambiguous_exports.lock().unwrap() = ambiguous_exports;
+    }
+
+    pub fn get_ambiguous_exports(&self) -> Vec<AmbiguousExportsInfo> {
+        self.ambiguous_exports.lock().unwrap().clone()
+    }
+
+    pub fn set_css_dedupe(&self, css_dedupe: Vec<CssDedupeInfo>) {
+        *self.css_dedupe.lock().unwrap() = css_dedupe;
+    }
+
+    pub fn get_css_dedupe(&self) -> Vec<CssDedupeInfo> {
+        self.css_dedupe.lock().unwrap().clone()
+    }
+
+    pub fn add_wasm_imports(&self, path: String, imports: BTreeMap<String, Vec<String>>) {
+        self.wasm_imports.lock().unwrap().insert(path, imports);
+    }
+
+    pub fn get_wasm_imports(&self) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+        self.wasm_imports.lock().unwrap().clone()
+    }
+
     pub fn get_rsc_client_components(&self) -> Vec<RscClientInfo> {
         self.rsc_client_components.lock().unwrap().clone()
     }
@@ -454,6 +682,8 @@ pub struct StatsJsonAssetsItem {
     pub size: u64,
     pub name: String,
     pub path: String,
+    #[serde(rename = "sideEffects", skip_serializing_if = "Option::is_none")]
+    pub side_effects: Option<bool>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -490,6 +720,28 @@ pub struct StatsJsonChunkItem {
     pub modules: Vec<StatsJsonChunkModuleItem>,
     pub siblings: Vec<String>,
     pub origins: Vec<StatsJsonChunkOriginItem>,
+    // async chunks hinted by `makoPrefetch` / `makoPreload` in this chunk
+    #[serde(rename = "linkHints")]
+    pub link_hints: Vec<StatsJsonChunkLinkHintItem>,
+}
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsJsonChunkLinkHintItem {
+    pub rel: LinkHintRel,
+    pub chunk_id: String,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct StatsJsonPackageItem {
+    pub name: String,
+    pub version: String,
+    // the minified size, a chunk's size is shared by its modules in proportion to their
+    // source size
+    pub size: u64,
+    pub modules: Vec<String>,
+    // other versions of the package are bundled too
+    pub duplicated: bool,
+    // an import chain from an entry to the package
+    pub chain: Vec<String>,
 }
 #[derive(Serialize, Debug, Clone)]
 pub struct StatsJsonEntryItem {
@@ -498,6 +750,11 @@ pub struct StatsJsonEntryItem {
 }
 #[derive(Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
+pub struct StatsJsonTreeShaking {
+    ambiguous_exports: Vec<AmbiguousExportsInfo>,
+}
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct StatsJsonMap {
     hash: u64,
     built_at: i64,
@@ -506,11 +763,23 @@ pub struct StatsJsonMap {
     assets: Vec<StatsJsonAssetsItem>,
     pub chunk_modules: Vec<StatsJsonChunkModuleItem>,
     modules: HashMap<String, ModuleInfo>,
+    // used export names of each tree shaken module
+    used_exports: BTreeMap<String, BTreeSet<String>>,
+    tree_shaking: StatsJsonTreeShaking,
+    // css modules moved to shared chunks by optimization.cssDedupe
+    css_dedupe: Vec<CssDedupeInfo>,
+    // imports of the wasm files by path
+    wasm_imports: BTreeMap<String, BTreeMap<String, Vec<String>>>,
     pub chunks: Vec<StatsJsonChunkItem>,
+    // npm packages by name@version
+    pub packages: Vec<StatsJsonPackageItem>,
     entrypoints: HashMap<String, StatsJsonEntryItem>,
     rsc_client_components: Vec<RscClientInfo>,
     #[serde(rename = "rscCSSModules")]
     rsc_css_modules: Vec<RscCssModules>,
+    // calls of the js plugin hooks, only when profilePlugins is enabled
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugin_hooks: Vec<PluginHookStats>,
     pub start_time: i64,
     pub end_time: i64,
 }
@@ -524,17 +793,192 @@ impl StatsJsonMap {
             output_path: String::new(),
             assets: vec![],
             modules: HashMap::new(),
+            used_exports: BTreeMap::new(),
+            tree_shaking: StatsJsonTreeShaking {
+                ambiguous_exports: vec![],
+            },
+            css_dedupe: vec![],
+            wasm_imports: BTreeMap::new(),
             chunk_modules: vec![],
             chunks: vec![],
+            packages: vec![],
             entrypoints: HashMap::new(),
             rsc_client_components: vec![],
             rsc_css_modules: vec![],
+            plugin_hooks: vec![],
             start_time: 0,
             end_time: 0,
         }
     }
 }
 
+fn packages_stats(
+    chunks: &[StatsJsonChunkItem],
+    assets: &[AssetsInfo],
+    module_graph: &ModuleGraph,
+    root: &Path,
+) -> Vec<StatsJsonPackageItem> {
+    let relative_path = |id: &str| {
+        let absolute_path = PathBuf::from(id);
+        win_path(
+            &diff_paths(&absolute_path, root)
+                .unwrap_or(absolute_path)
+                .to_string_lossy(),
+        )
+    };
+    let importers = shortest_importers(module_graph);
+
+    let mut packages: BTreeMap<(String, String), StatsJsonPackageItem> = BTreeMap::new();
+    for chunk in chunks {
+        let chunk_size = assets
+            .iter()
+            .filter(|asset| asset.chunk_id == chunk.id && asset.hashname.ends_with(".js"))
+            .map(|asset| asset.size)
+            .sum::<u64>();
+        let modules_size = chunk.modules.iter().map(|m| m.size).sum::<u64>();
+        for chunk_module in &chunk.modules {
+            let module_id = ModuleId::new(chunk_module.id.clone());
+            let Some(module) = module_graph.get_module(&module_id) else {
+                continue;
+            };
+            // the nearest package.json of the project's own modules is the project's
+            if !module
+                .info
+                .as_ref()
+                .is_some_and(|info| info.file.is_under_node_modules)
+            {
+                continue;
+            }
+            let Some(PackageInfo { name, version, .. }) = extract_package_info(module) else {
+                continue;
+            };
+            let version = version.to_string();
+            let size = if modules_size == 0 {
+                0
+            } else {
+                chunk_module.size * chunk_size / modules_size
+            };
+            let package = packages
+                .entry((name.clone(), version.clone()))
+                .or_insert_with(|| StatsJsonPackageItem {
+                    name,
+                    version,
+                    size: 0,
+                    modules: vec![],
+                    duplicated: false,
+                    chain: vec![],
+                });
+            package.size += size;
+            let id = relative_path(&chunk_module.id);
+            if !package.modules.contains(&id) {
+                package.modules.push(id);
+                // the module closest to an entry
+                let chain = import_chain(&importers, &module_id)
+                    .iter()
+                    .map(|id| relative_path(&id.id))
+                    .collect::<Vec<_>>();
+                if package.chain.is_empty() || chain.len() < package.chain.len() {
+                    package.chain = chain;
+                }
+            }
+        }
+    }
+
+    let mut versions: HashMap<String, usize> = HashMap::new();
+    for (name, _) in packages.keys() {
+        *versions.entry(name.clone()).or_default() += 1;
+    }
+    packages
+        .into_values()
+        .map(|mut package| {
+            package.duplicated = versions[&package.name] > 1;
+            package.modules.sort();
+            package
+        })
+        .collect()
+}
+
+// modules of the same package with the same content but different ids, by package
+// name, which happens when a package is reachable through different paths and
+// `resolve.symlinks` is off or it's copied
+pub(crate) fn duplicate_modules(module_graph: &ModuleGraph) -> Vec<(String, Vec<ModuleId>)> {
+    // hash the contents of the modules of the same size only
+    let mut candidates: BTreeMap<(String, usize), Vec<(&ModuleId, &str)>> = BTreeMap::new();
+    for module in module_graph.modules() {
+        let Some(info) = module.info.as_ref() else {
+            continue;
+        };
+        if info.external.is_some() || info.raw.is_empty() {
+            continue;
+        }
+        let Some(name) = info
+            .resolved_resource
+            .as_ref()
+            .and_then(|r| r.get_pkg_info())
+            .and_then(|pkg_info| pkg_info.name)
+        else {
+            continue;
+        };
+        candidates
+            .entry((name, info.raw.len()))
+            .or_default()
+            .push((&module.id, info.raw.as_str()));
+    }
+
+    let mut duplicates = vec![];
+    for ((name, _), modules) in candidates {
+        if modules.len() < 2 {
+            continue;
+        }
+        let mut by_hash: BTreeMap<u64, Vec<ModuleId>> = BTreeMap::new();
+        for (id, raw) in modules {
+            let mut hasher: XxHash64 = Default::default();
+            hasher.write(raw.as_bytes());
+            by_hash.entry(hasher.finish()).or_default().push(id.clone());
+        }
+        for (_, mut ids) in by_hash {
+            if ids.len() > 1 {
+                ids.sort_by(|a, b| a.id.cmp(&b.id));
+                duplicates.push((name.clone(), ids));
+            }
+        }
+    }
+    duplicates
+}
+
+// the importer of each module on a shortest path from the entries
+fn shortest_importers(module_graph: &ModuleGraph) -> HashMap<ModuleId, Option<ModuleId>> {
+    let mut importers = HashMap::new();
+    let mut queue = VecDeque::new();
+    for entry in module_graph.get_entry_modules() {
+        importers.insert(entry.clone(), None);
+        queue.push_back(entry);
+    }
+    while let Some(id) = queue.pop_front() {
+        for (dep, _) in module_graph.get_dependencies(&id) {
+            if !importers.contains_key(dep) {
+                importers.insert(dep.clone(), Some(id.clone()));
+                queue.push_back(dep.clone());
+            }
+        }
+    }
+    importers
+}
+
+fn import_chain(
+    importers: &HashMap<ModuleId, Option<ModuleId>>,
+    module_id: &ModuleId,
+) -> Vec<ModuleId> {
+    let mut chain = vec![module_id.clone()];
+    let mut current = module_id;
+    while let Some(Some(importer)) = importers.get(current) {
+        chain.push(importer.clone());
+        current = importer;
+    }
+    chain.reverse();
+    chain
+}
+
 pub fn write_stats(path: &Path, stats: &StatsJsonMap) {
     let path = path.join("stats.json");
     let stats_json = serde_json::to_string_pretty(stats).unwrap();
@@ -556,6 +1000,51 @@ pub fn human_readable_size(size: u64) -> String {
     format!("{:.2} {}", size, units[i])
 }
 
+// one row per plugin hook with the calls, how many of them returned a result or passed
+// through, and the total and average time
+fn plugin_hooks_table(stats: &[PluginHookStats]) -> String {
+    let header = [
+        "plugin", "hook", "calls", "returned", "passed", "total", "avg",
+    ]
+    .map(String::from)
+    .to_vec();
+    let rows = stats
+        .iter()
+        .map(|stats| {
+            vec![
+                stats.plugin.clone(),
+                stats.hook.clone(),
+                stats.calls.to_string(),
+                stats.returned.to_string(),
+                (stats.calls - stats.returned).to_string(),
+                format!("{:.2}ms", stats.ms),
+                format!("{:.2}ms", stats.ms / stats.calls as f64),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .chain(std::iter::once(&header))
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let mut s = String::new();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        let line = row
+            .iter()
+            .enumerate()
+            // names are aligned to the left and numbers to the right
+            .map(|(i, cell)| pad_string(cell, widths[i], i > 1))
+            .collect::<Vec<_>>()
+            .join("  ");
+        s.push_str(&format!("  {}\n", line));
+    }
+    s
+}
+
 fn pad_string(text: &str, max_length: usize, front: bool) -> String {
     let mut padded_text = String::from(text);
     let pad_length = max_length - text.chars().count();
@@ -573,3 +1062,278 @@ fn file_size(path: &str) -> Result<u64> {
     let metadata = fs::metadata(path)?;
     Ok(metadata.len())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::Result;
+
+    use super::{duplicate_modules, plugin_hooks_table, StatsJsonMap};
+    use crate::compiler::{AssetMeta, Compiler, Context, PluginHookStats};
+    use crate::config::Config;
+    use crate::plugin::Plugin;
+    use crate::utils::test_helper::{setup_compiler, setup_compiler_with_plugins, setup_logger};
+
+    #[test]
+    fn test_plugin_hooks_table() {
+        let stats = vec![
+            PluginHookStats {
+                plugin: "sass".to_string(),
+                hook: "transform".to_string(),
+                calls: 4,
+                returned: 1,
+                ms: 30.0,
+            },
+            PluginHookStats {
+                plugin: "icons".to_string(),
+                hook: "resolveId".to_string(),
+                calls: 10,
+                returned: 0,
+                ms: 2.5,
+            },
+        ];
+        assert_eq!(
+            plugin_hooks_table(&stats),
+            r#"  plugin  hook       calls  returned  passed    total     avg
+  sass    transform      4         1       3  30.00ms  7.50ms
+  icons   resolveId     10         0      10   2.50ms  0.25ms
+"#
+        );
+    }
+
+    #[test]
+    fn test_packages() {
+        let compiler = setup_compiler("test/build/stats-packages", false);
+        compiler.compile().unwrap();
+        let stats = compiler.create_stats_info();
+
+        let packages = stats
+            .packages
+            .iter()
+            .map(|p| (format!("{}@{}", p.name, p.version), p))
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(packages.len(), 3);
+
+        let tiny_1 = packages["tiny@1.0.0"];
+        assert!(tiny_1.duplicated);
+        assert!(tiny_1.size > 0);
+        assert_eq!(tiny_1.modules, vec!["node_modules/tiny/index.js"]);
+        assert_eq!(tiny_1.chain, vec!["index.ts", "node_modules/tiny/index.js"]);
+
+        let tiny_2 = packages["tiny@2.0.0"];
+        assert!(tiny_2.duplicated);
+        assert_eq!(
+            tiny_2.chain,
+            vec![
+                "index.ts",
+                "node_modules/wrapper/index.js",
+                "node_modules/wrapper/node_modules/tiny/index.js"
+            ]
+        );
+
+        assert!(!packages["wrapper@1.0.0"].duplicated);
+        // the project itself is not a package
+        assert!(!packages.keys().any(|k| k.starts_with("stats-packages")));
+    }
+
+    #[test]
+    fn test_oversized_chunks() {
+        setup_logger();
+        let root =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/build/performance-warnings");
+        let build = |max_chunk_size, max_entrypoint_size| {
+            let mut config = Config::new(&root, None, None).unwrap();
+            assert_eq!(config.performance.max_chunk_size, Some(1000));
+            config.performance.max_chunk_size = max_chunk_size;
+            config.performance.max_entrypoint_size = max_entrypoint_size;
+            let compiler = Compiler::new(config, root.clone(), Default::default(), None).unwrap();
+            compiler
+                .compile()
+                .unwrap()
+                .warnings
+                .into_iter()
+                .map(|warning| warning.message)
+                .collect::<Vec<_>>()
+        };
+
+        let warnings = build(Some(1000), None);
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].starts_with("Chunk "), "{}", warnings[0]);
+        assert!(warnings[0].contains("(index.js)"), "{}", warnings[0]);
+        assert!(warnings[0].contains(" modules, over"), "{}", warnings[0]);
+        assert!(
+            warnings[0].contains("over performance.maxChunkSize (1.00 kB)"),
+            "{}",
+            warnings[0]
+        );
+        assert!(warnings[0].contains("codeSplitting"), "{}", warnings[0]);
+
+        let warnings = build(Some(10_000_000), Some(1000));
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(
+            warnings[0].starts_with("Entrypoint index is "),
+            "{}",
+            warnings[0]
+        );
+
+        assert!(build(Some(10_000_000), Some(10_000_000)).is_empty());
+    }
+
+    // the link is created here rather than checked in, since git may check it out as a file
+    fn link_shared_package() -> PathBuf {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test/build/symlinks")
+            .canonicalize()
+            .unwrap();
+        let link = root.join("node_modules/shared");
+        if fs::symlink_metadata(&link).is_err() {
+            fs::create_dir_all(root.join("node_modules")).unwrap();
+            #[cfg(unix)]
+            std::os::unix::fs::symlink("../packages/shared", &link).unwrap();
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_dir(root.join("packages/shared"), &link).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_symlinked_package_is_one_module() {
+        link_shared_package();
+        let compiler = setup_compiler("test/build/symlinks", false);
+        compiler.compile().unwrap();
+
+        let shared = compiler
+            .context
+            .root
+            .join("packages/shared/index.js")
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let shared_modules = module_graph
+            .modules()
+            .iter()
+            .map(|m| m.id.id.clone())
+            .filter(|id| id.ends_with("shared/index.js"))
+            .map(|id| {
+                PathBuf::from(id)
+                    .canonicalize()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(shared_modules, vec![shared.clone()]);
+        assert!(duplicate_modules(&module_graph).is_empty());
+        drop(module_graph);
+
+        let stats = compiler.create_stats_info();
+        let shared_modules = stats
+            .chunk_modules
+            .iter()
+            .filter(|m| m.id.ends_with("shared/index.js"))
+            .map(|m| {
+                PathBuf::from(&m.id)
+                    .canonicalize()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(shared_modules, vec![shared]);
+    }
+
+    #[test]
+    fn test_duplicate_modules_without_symlinks() {
+        // the ids are compared with the paths under the real root
+        let root = link_shared_package();
+        let mut config = Config::new(&root, None, None).unwrap();
+        config.resolve.symlinks = false;
+        let compiler = Compiler::new(config, root.clone(), Default::default(), None).unwrap();
+        compiler.compile().unwrap();
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let duplicates = duplicate_modules(&module_graph);
+        assert_eq!(duplicates.len(), 1);
+        let (name, ids) = &duplicates[0];
+        assert_eq!(name, "shared");
+        let ids = ids.iter().map(|id| id.id.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                root.join("node_modules/shared/index.js")
+                    .to_string_lossy()
+                    .to_string(),
+                root.join("packages/shared/index.js")
+                    .to_string_lossy()
+                    .to_string(),
+            ]
+        );
+    }
+
+    // emits a side-effect-free asset, without the meta after the first build, and records the
+    // assets in the stats of the last build
+    #[derive(Default)]
+    struct AssetMetaPlugin {
+        built: AtomicBool,
+        assets: Mutex<Vec<serde_json::Value>>,
+    }
+
+    impl Plugin for AssetMetaPlugin {
+        fn name(&self) -> &str {
+            "asset_meta_test"
+        }
+
+        fn build_start(&self, context: &Arc<Context>) -> Result<()> {
+            if self.built.swap(true, Ordering::SeqCst) {
+                context.emit_assets("extra.txt".to_string(), "extra.txt".to_string());
+            } else {
+                context.emit_assets_with_meta(
+                    "extra.txt".to_string(),
+                    "extra.txt".to_string(),
+                    AssetMeta {
+                        side_effects: Some(false),
+                    },
+                );
+            }
+            Ok(())
+        }
+
+        fn build_success(&self, stats: &StatsJsonMap, _context: &Arc<Context>) -> Result<()> {
+            let stats = serde_json::to_value(stats)?;
+            if let Some(assets) = stats["assets"].as_array() {
+                *self.assets.lock().unwrap() = assets.clone();
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_emit_assets_with_meta() {
+        let plugin = Arc::new(AssetMetaPlugin::default());
+        let extra_plugin: Arc<dyn Plugin> = plugin.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/write-bundle", vec![extra_plugin], |_| {});
+        compiler.compile().unwrap();
+
+        let assets = plugin.assets.lock().unwrap();
+        let asset = |name: &str| assets.iter().find(|asset| asset["name"] == name).unwrap();
+        assert_eq!(asset("extra.txt")["sideEffects"], false);
+        assert!(asset("index.js").get("sideEffects").is_none());
+        drop(assets);
+
+        // the meta of the last build is not kept
+        compiler.compile().unwrap();
+        let assets = plugin.assets.lock().unwrap();
+        let asset = assets
+            .iter()
+            .find(|asset| asset["name"] == "extra.txt")
+            .unwrap();
+        assert!(asset.get("sideEffects").is_none());
+    }
+}
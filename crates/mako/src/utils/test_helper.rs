@@ -1,10 +1,12 @@
 use std::fs;
+use std::sync::Arc;
 
 use tracing_subscriber::{fmt, EnvFilter};
 
 use crate::compiler::{self, Compiler};
 use crate::config::{Config, Mode};
 use crate::module::{Module, ModuleId};
+use crate::plugin::Plugin;
 
 #[macro_export]
 macro_rules! assert_display_snapshot {
@@ -69,6 +71,22 @@ pub fn setup_compiler(base: &str, cleanup: bool) -> Compiler {
     compiler::Compiler::new(config, root, Default::default(), None).unwrap()
 }
 
+/// Set up a compiler of the fixture with its own config and the extra plugins, the config
+/// can be changed before the compiler is created.
+#[allow(dead_code)]
+pub fn setup_compiler_with_plugins(
+    base: &str,
+    plugins: Vec<Arc<dyn Plugin>>,
+    update_config: impl FnOnce(&mut Config),
+) -> Compiler {
+    setup_logger();
+    let root = std::env::current_dir().unwrap().join(base);
+    let mut config = Config::new(&root, None, None).unwrap();
+    update_config(&mut config);
+
+    compiler::Compiler::new(config, root, Default::default(), Some(plugins)).unwrap()
+}
+
 pub fn setup_logger() {
     let _result = fmt()
         .with_env_filter(EnvFilter::from_default_env())
@@ -11,9 +11,11 @@ pub(crate) mod default_export_namer;
 pub(crate) mod dep_analyzer;
 pub(crate) mod dep_replacer;
 pub(crate) mod dynamic_import;
+pub(crate) mod dynamic_import_rewriter;
 pub(crate) mod dynamic_import_to_require;
 pub(crate) mod env_replacer;
 pub(crate) mod fix_symbol_conflict;
+pub(crate) mod hot_api_replacer;
 pub(crate) mod import_meta_env_replacer;
 pub(crate) mod import_template_to_string_literal;
 pub(crate) mod mako_require;
@@ -21,8 +23,10 @@ pub(crate) mod meta_url_replacer;
 pub(crate) mod new_url_assets;
 pub(crate) mod optimize_define_utils;
 pub(crate) mod provide;
-pub(crate) mod public_path_assignment;
+pub(crate) mod public_path_replacer;
 pub(crate) mod react;
+pub(crate) mod require_ensure;
+pub(crate) mod require_resolve;
 pub(crate) mod try_resolve;
 pub(crate) mod ts_strip;
 pub(crate) mod tsx_strip;
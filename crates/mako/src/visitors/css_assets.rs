@@ -13,11 +13,24 @@ use crate::resolve;
 pub struct CSSAssets {
     pub context: Arc<Context>,
     pub path: String,
+    // the deprecated `~` is warned once per module
+    warned_tilde: bool,
+}
+
+impl CSSAssets {
+    pub fn new(path: String, context: Arc<Context>) -> Self {
+        Self {
+            context,
+            path,
+            warned_tilde: false,
+        }
+    }
 }
 
 impl VisitMut for CSSAssets {
     // e.g.
     // .foo { background: url(foo.png) }
+    // @font-face { src: url(foo.eot?#iefix) }
     fn visit_mut_url(&mut self, n: &mut Url) {
         if n.value.is_none() {
             return;
@@ -32,10 +45,29 @@ impl VisitMut for CSSAssets {
             return;
         }
 
-        let url = remove_first_tilde(url);
+        // the query and fragment are not part of the file, keep them in the rewritten url
+        let (url, suffix) = split_query_and_fragment(&url);
+        let source = remove_first_tilde(url.to_string());
+        // the css of the packages can't be changed by the users
+        if source != url && !self.warned_tilde && !self.path.contains("node_modules") {
+            self.warned_tilde = true;
+            self.context.warn(format!(
+                "url({}) in {} is deprecated, the leading `~` is not needed to resolve from node_modules, use url({}) instead",
+                url, self.path, source
+            ));
+        }
+
+        if let Some(file_url) =
+            self.context
+                .plugin_driver
+                .resolve_file_url(&source, &self.path, &self.context)
+        {
+            set_url(n, format!("{}{}", file_url, suffix));
+            return;
+        }
 
         let dep = Dependency {
-            source: url,
+            source,
             resolve_as: None,
             resolve_type: ResolveType::Css,
             order: 0,
@@ -51,26 +83,52 @@ impl VisitMut for CSSAssets {
                 self.context.clone(),
             );
             let asset_content = asset_content.unwrap_or(resolved_path);
-            match n.value {
-                Some(box UrlValue::Str(ref mut s)) => {
-                    s.value = asset_content.into();
-                    s.raw = None;
-                }
-                Some(box UrlValue::Raw(ref mut s)) => {
-                    s.value = asset_content.into();
-                    s.raw = None;
-                }
-                None => {}
-            }
+            let asset_content = if asset_content.starts_with("data:") {
+                // a query breaks the inlined content, while a fragment still refers to it
+                let fragment = suffix.find('#').map_or("", |i| &suffix[i..]);
+                format!("{}{}", asset_content, fragment)
+            } else {
+                format!("{}{}", asset_content, suffix)
+            };
+            set_url(n, asset_content);
         }
     }
 }
 
+fn set_url(n: &mut Url, url: String) {
+    match n.value {
+        Some(box UrlValue::Str(ref mut s)) => {
+            s.value = url.into();
+            s.raw = None;
+        }
+        Some(box UrlValue::Raw(ref mut s)) => {
+            s.value = url.into();
+            s.raw = None;
+        }
+        None => {}
+    }
+}
+
+// e.g. `font.eot?#iefix` => (`font.eot`, `?#iefix`)
+fn split_query_and_fragment(url: &str) -> (&str, &str) {
+    match url.find(['?', '#']) {
+        Some(i) => url.split_at(i),
+        None => (url, ""),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use swc_core::css::visit::VisitMutWith;
 
-    use crate::ast::tests::TestUtils;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::compiler::Context;
+    use crate::config::Mode;
+    use crate::plugin::Plugin;
+    use crate::resolve::get_resolvers;
+    use crate::utils::test_helper::setup_compiler_with_plugins;
 
     #[test]
     fn test_base64() {
@@ -84,6 +142,68 @@ mod tests {
             .contains(".foo{background:url(data:image/png;base64,"));
     }
 
+    #[test]
+    fn test_tilde() {
+        let (code, context) = run_with_context(
+            r#".foo { background: url(~pkg/logo.png) } .bar { background: url(~pkg/logo.png) }"#,
+            create_context(vec![]),
+        );
+        assert!(code.contains(".foo{background:url(data:image/png;base64,"));
+        let warnings = context.warnings.lock().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(
+            warnings[0]
+                .message
+                .contains("use url(pkg/logo.png) instead"),
+            "{}",
+            warnings[0].message
+        );
+    }
+
+    #[test]
+    fn test_tilde_in_node_modules() {
+        let context = create_context(vec![]);
+        let test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: Some("test.css".to_string()),
+                content: Some(r#".foo { background: url(~pkg/logo.png) }"#.to_string()),
+            },
+            context.clone(),
+        );
+        let code = transform_at(
+            test_utils,
+            "src/visitors/fixtures/css_assets/node_modules/pkg/index.css",
+        );
+        assert!(code.contains(".foo{background:url(data:image/png;base64,"));
+        assert!(context.warnings.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_alias() {
+        let assets = std::env::current_dir()
+            .unwrap()
+            .join("src/visitors/fixtures/css_assets/assets");
+        let context = create_context(vec![(
+            "@".to_string(),
+            assets.to_string_lossy().to_string(),
+        )]);
+        let (code, _) = run_with_context(r#".foo { background: url(@/logo.png) }"#, context);
+        assert!(code.contains(".foo{background:url(data:image/png;base64,"));
+    }
+
+    #[test]
+    fn test_query_and_fragment() {
+        // emitted as a file, e.g. the `?#iefix` hack of @font-face
+        let code = run(r#"@font-face { src: url(big.jpg?#iefix) }"#);
+        assert!(code.contains("src:url(big."), "{}", code);
+        assert!(code.contains(".jpg?#iefix)"), "{}", code);
+        // inlined, only the fragment is kept
+        let code = run(r#".foo { background: url(umi.png?v=1#logo) }"#);
+        assert!(code.contains("url(data:image/png;base64,"), "{}", code);
+        assert!(code.contains("#logo)}"), "{}", code);
+        assert!(!code.contains("?v=1"), "{}", code);
+    }
+
     #[test]
     fn test_remote() {
         assert_eq!(
@@ -102,6 +222,10 @@ mod tests {
             run(r#".foo { background: url(data://a.png) }"#),
             ".foo{background:url(data://a.png)}"
         );
+        assert_eq!(
+            run(r#".foo { fill: url(#gradient) }"#),
+            ".foo{fill:url(#gradient)}"
+        );
     }
 
     #[test]
@@ -126,15 +250,103 @@ mod tests {
     }
 
     fn run(css_code: &str) -> String {
-        let mut test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        let test_utils = TestUtils::gen_css_ast(css_code.to_string(), true);
+        transform(test_utils)
+    }
+
+    fn run_with_context(css_code: &str, context: Arc<Context>) -> (String, Arc<Context>) {
+        let test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: Some("test.css".to_string()),
+                content: Some(css_code.to_string()),
+            },
+            context.clone(),
+        );
+        (transform(test_utils), context)
+    }
+
+    fn create_context(alias: Vec<(String, String)>) -> Arc<Context> {
+        let mut context = Context::default();
+        context.config.devtool = None;
+        context.config.mode = Mode::Production;
+        context.config.resolve.alias = alias;
+        context.resolvers = get_resolvers(&context.config);
+        Arc::new(context)
+    }
+
+    fn transform(test_utils: TestUtils) -> String {
+        transform_at(test_utils, "src/visitors/fixtures/css_assets/test.css")
+    }
+
+    fn transform_at(mut test_utils: TestUtils, path: &str) -> String {
         let ast = test_utils.ast.css_mut();
         let current_dir = std::env::current_dir().unwrap();
-        let css_path = current_dir.join("src/visitors/fixtures/css_assets/test.css");
-        let mut visitor = super::CSSAssets {
-            context: test_utils.context.clone(),
-            path: css_path.to_string_lossy().to_string(),
-        };
+        let css_path = current_dir.join(path);
+        let mut visitor = super::CSSAssets::new(
+            css_path.to_string_lossy().to_string(),
+            test_utils.context.clone(),
+        );
         ast.ast.visit_mut_with(&mut visitor);
         test_utils.css_ast_to_code()
     }
+
+    // serves the png files of css from a cdn, except icon.png
+    #[derive(Default)]
+    struct FileUrlPlugin {
+        importers: Mutex<Vec<String>>,
+    }
+
+    impl Plugin for FileUrlPlugin {
+        fn name(&self) -> &str {
+            "file_url_test"
+        }
+
+        fn resolve_file_url(
+            &self,
+            source: &str,
+            importer: &str,
+            _context: &Arc<Context>,
+        ) -> Option<String> {
+            self.importers.lock().unwrap().push(importer.to_string());
+            let name = source.strip_prefix("./")?;
+            (name != "icon.png").then(|| format!("https://cdn.example.com/{}", name))
+        }
+    }
+
+    #[test]
+    fn test_resolve_file_url() {
+        let plugin = Arc::new(FileUrlPlugin::default());
+        let extra_plugin: Arc<dyn Plugin> = plugin.clone();
+        let compiler =
+            setup_compiler_with_plugins("test/build/resolve-file-url", vec![extra_plugin], |_| {});
+        compiler.compile().unwrap();
+
+        let importers = plugin.importers.lock().unwrap();
+        assert!(!importers.is_empty());
+        assert!(
+            importers
+                .iter()
+                .all(|importer| importer.ends_with("index.css")),
+            "{:?}",
+            importers
+        );
+
+        let output = &compiler.context.config.output.path;
+        let content = std::fs::read_to_string(output.join("index.css")).unwrap();
+        assert!(
+            content.contains("url(\"https://cdn.example.com/logo.png\")"),
+            "{}",
+            content
+        );
+        assert!(
+            content.contains("url(\"https://cdn.example.com/font.woff2?#iefix\")"),
+            "{}",
+            content
+        );
+        // passed through to the builtin asset handling, which inlines it
+        assert!(content.contains("data:image/png;base64,"), "{}", content);
+        assert!(!content.contains("./icon.png"), "{}", content);
+        // remapped files are not emitted
+        assert!(!output.join("logo.png").exists());
+    }
 }
@@ -3,7 +3,10 @@ use std::sync::Arc;
 use regex::Regex;
 use swc_core::common::comments::Comments;
 use swc_core::common::{BytePos, Mark, Span, Spanned};
-use swc_core::ecma::ast::{CallExpr, Expr, Lit, ModuleDecl, NewExpr, Str};
+use swc_core::ecma::ast::{
+    ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Lit, MemberExpr, MemberProp, ModuleDecl,
+    NewExpr, Str,
+};
 use swc_core::ecma::visit::{Visit, VisitWith};
 
 use crate::ast::utils;
@@ -16,6 +19,8 @@ pub struct DepAnalyzer {
     order: usize,
     unresolved_mark: Mark,
     context: Arc<Context>,
+    // the sources loaded by the enclosing `Promise.all([import('a')]).then(...)`
+    loaded_sources: Vec<String>,
 }
 
 impl DepAnalyzer {
@@ -25,6 +30,7 @@ impl DepAnalyzer {
             order: 1,
             unresolved_mark,
             context,
+            loaded_sources: vec![],
         }
     }
     fn add_dependency(&mut self, source: String, resolve_type: ResolveType, span: Option<Span>) {
@@ -74,9 +80,27 @@ impl DepAnalyzer {
                 })
         });
 
+        let prefetch = comments_texts.iter().any(|t| {
+            get_magic_comment_prefetch_regex()
+                .captures(t.trim())
+                .map_or(false, |cap| {
+                    cap.get(2).map_or(false, |m| m.as_str() == "true")
+                })
+        });
+
+        let preload = comments_texts.iter().any(|t| {
+            get_magic_comment_preload_regex()
+                .captures(t.trim())
+                .map_or(false, |cap| {
+                    cap.get(2).map_or(false, |m| m.as_str() == "true")
+                })
+        });
+
         ImportOptions {
             chunk_name,
             ignore,
+            prefetch,
+            preload,
             _is_federation_expose,
         }
     }
@@ -124,11 +148,25 @@ impl Visit for DepAnalyzer {
     }
 
     fn visit_call_expr(&mut self, expr: &CallExpr) {
+        // e.g.
+        // Promise.all([import('a')]).then(function() { require('a') })
+        // which require.ensure is converted to, `a` is already loaded in the callbacks, so
+        // the require of it is not a sync dependency and `a` is kept in the async chunk
+        if let Some(sources) = promise_all_import_sources(expr) {
+            expr.callee.visit_with(self);
+            let len = self.loaded_sources.len();
+            self.loaded_sources.extend(sources);
+            expr.args.visit_with(self);
+            self.loaded_sources.truncate(len);
+            return;
+        }
         // e.g.
         // require('a')
         if utils::is_commonjs_require(expr, &self.unresolved_mark) {
             if let Some(src) = utils::get_first_str_arg(expr) {
-                self.add_dependency(src, ResolveType::Require, Some(expr.span));
+                if !self.loaded_sources.contains(&src) {
+                    self.add_dependency(src, ResolveType::Require, Some(expr.span));
+                }
                 return;
             }
         }
@@ -200,6 +238,44 @@ impl Visit for DepAnalyzer {
     }
 }
 
+// the sources of `Promise.all([import('a'), import('b')]).then(...)`
+fn promise_all_import_sources(expr: &CallExpr) -> Option<Vec<String>> {
+    let Callee::Expr(box Expr::Member(MemberExpr {
+        obj: box Expr::Call(promise_all),
+        prop: MemberProp::Ident(then),
+        ..
+    })) = &expr.callee
+    else {
+        return None;
+    };
+    let Callee::Expr(box Expr::Member(MemberExpr {
+        obj: box Expr::Ident(promise),
+        prop: MemberProp::Ident(all),
+        ..
+    })) = &promise_all.callee
+    else {
+        return None;
+    };
+    if then.sym != "then" || promise.sym != "Promise" || all.sym != "all" {
+        return None;
+    }
+    let Some(box Expr::Array(ArrayLit { elems, .. })) =
+        promise_all.args.first().map(|arg| &arg.expr)
+    else {
+        return None;
+    };
+    elems
+        .iter()
+        .map(|elem| match elem {
+            Some(ExprOrSpread {
+                spread: None,
+                expr: box Expr::Call(call),
+            }) if utils::is_dynamic_import(call) => utils::get_first_str_arg(call),
+            _ => None,
+        })
+        .collect()
+}
+
 // get the value of url when the following conditions are met
 // notice: only add dependency when the second argument is import.meta.url
 // e.g.
@@ -245,6 +321,16 @@ fn resolve_web_worker(expr: &NewExpr, unresolved_mark: Mark) -> Option<&Str> {
     None
 }
 
+// the magic comments are mako directives, which are removed from the output
+pub(crate) fn is_magic_comment(text: &str) -> bool {
+    let text = text.trim();
+    get_magic_comment_chunk_name_regex().is_match(text)
+        || get_magic_comment_ignore_regex().is_match(text)
+        || get_magic_comment_prefetch_regex().is_match(text)
+        || get_magic_comment_preload_regex().is_match(text)
+        || get_magic_federation_expose_regex().is_match(text)
+}
+
 fn get_magic_comment_chunk_name_regex() -> Regex {
     create_cached_regex(r#"(makoChunkName|webpackChunkName):\s*['"`](\w+)['"`]"#)
 }
@@ -253,6 +339,14 @@ fn get_magic_comment_ignore_regex() -> Regex {
     create_cached_regex(r#"(makoIgnore|webpackIgnore):\s*(true|false)"#)
 }
 
+fn get_magic_comment_prefetch_regex() -> Regex {
+    create_cached_regex(r#"(makoPrefetch|webpackPrefetch):\s*(true|false)"#)
+}
+
+fn get_magic_comment_preload_regex() -> Regex {
+    create_cached_regex(r#"(makoPreload|webpackPreload):\s*(true|false)"#)
+}
+
 fn get_magic_federation_expose_regex() -> Regex {
     create_cached_regex(r#"(federationExpose):\s*(true|false)"#)
 }
@@ -263,6 +357,7 @@ mod tests {
     use swc_core::ecma::visit::VisitWith;
 
     use crate::ast::tests::TestUtils;
+    use crate::module::{ImportOptions, ResolveType};
 
     #[test]
     fn test_normal() {
@@ -286,6 +381,24 @@ mod tests {
         assert_eq!(run(r#"import('a');"#), vec!["a"]);
     }
 
+    #[test]
+    fn test_dynamic_import_link_hints() {
+        let options = run_import_options(r#"import(/* webpackPrefetch: true */ 'a');"#);
+        assert!(options.prefetch);
+        assert!(!options.preload);
+        let options = run_import_options(r#"import(/* webpackPreload: true */ 'a');"#);
+        assert!(!options.prefetch);
+        assert!(options.preload);
+        let options = run_import_options(r#"import(/* makoPrefetch: true */ 'a');"#);
+        assert!(options.prefetch);
+        let options = run_import_options(r#"import(/* makoPreload: true */ 'a');"#);
+        assert!(options.preload);
+        let options = run_import_options(r#"import(/* webpackPrefetch: false */ 'a');"#);
+        assert!(!options.prefetch);
+        let options = run_import_options(r#"import('a');"#);
+        assert!(!options.prefetch && !options.preload);
+    }
+
     #[test]
     fn test_require() {
         assert_eq!(run(r#"require('a');"#), vec!["a"]);
@@ -318,6 +431,31 @@ mod tests {
         assert_eq!(run(r#"require(require("b"))"#), vec!["b"]);
     }
 
+    #[test]
+    fn test_require_of_loaded_imports() {
+        assert_eq!(
+            run(r#"Promise.all([import('a')]).then(function() { require('a'); require('b'); });"#),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            run(r#"Promise.all([import('a')]).then(() => {}); require('a');"#),
+            vec!["a", "a"]
+        );
+    }
+
+    fn run_import_options(js_code: &str) -> ImportOptions {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        let mut analyzer = super::DepAnalyzer::new(ast.unresolved_mark, test_utils.context.clone());
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast.visit_with(&mut analyzer);
+        });
+        match &analyzer.dependencies[0].resolve_type {
+            ResolveType::DynamicImport(options) => options.clone(),
+            _ => panic!("not a dynamic import"),
+        }
+    }
+
     fn run(js_code: &str) -> Vec<String> {
         let mut test_utils = TestUtils::gen_js_ast(js_code);
         let ast = test_utils.ast.js_mut();
@@ -3,14 +3,17 @@ use std::sync::Arc;
 
 use swc_core::common::{Mark, DUMMY_SP};
 use swc_core::ecma::ast::{
-    AssignOp, BlockStmt, Expr, ExprOrSpread, FnExpr, Function, Ident, ImportDecl, Lit, NamedExport,
-    NewExpr, Stmt, Str, ThrowStmt, VarDeclKind,
+    ArrayLit, AssignOp, BlockStmt, CallExpr, Callee, ExportAll, Expr, ExprOrSpread, FnExpr,
+    Function, Ident, ImportDecl, Lit, MemberExpr, MemberProp, NamedExport, NewExpr, Stmt, Str,
+    ThrowStmt, VarDeclKind,
 };
 use swc_core::ecma::utils::{member_expr, quote_ident, quote_str, ExprFactory};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
 use crate::ast::file::parse_path;
-use crate::ast::utils::{is_commonjs_require, is_dynamic_import, is_remote_or_data};
+use crate::ast::utils::{
+    is_commonjs_require, is_dynamic_import, is_ident_undefined, is_remote_or_data,
+};
 use crate::ast::DUMMY_CTXT;
 use crate::compiler::Context;
 use crate::module::{Dependency, ModuleId};
@@ -175,6 +178,22 @@ impl VisitMut for DepReplacer<'_> {
                     }
                 }
             }
+            // module.hot.accept("./dep", cb) or module.hot.accept(["./a", "./b"], cb)
+            if self.is_hot_accept(call_expr)
+                && let Some(ExprOrSpread { expr: deps, .. }) = call_expr.args.first_mut()
+            {
+                match &mut **deps {
+                    Expr::Lit(Lit::Str(source)) => self.replace_source(source),
+                    Expr::Array(ArrayLit { elems, .. }) => {
+                        elems.iter_mut().flatten().for_each(|elem| {
+                            if let box Expr::Lit(Lit::Str(source)) = &mut elem.expr {
+                                self.replace_source(source);
+                            }
+                        });
+                    }
+                    _ => {}
+                }
+            }
         }
         expr.visit_mut_children_with(self);
     }
@@ -195,9 +214,33 @@ impl VisitMut for DepReplacer<'_> {
             self.replace_source(src.as_mut());
         }
     }
+
+    fn visit_mut_export_all(&mut self, n: &mut ExportAll) {
+        self.replace_source(n.src.as_mut());
+    }
 }
 
 impl DepReplacer<'_> {
+    fn is_hot_accept(&self, call_expr: &CallExpr) -> bool {
+        if let Callee::Expr(box Expr::Member(MemberExpr {
+            obj:
+                box Expr::Member(MemberExpr {
+                    obj: box Expr::Ident(module),
+                    prop: MemberProp::Ident(hot),
+                    ..
+                }),
+            prop: MemberProp::Ident(accept),
+            ..
+        })) = &call_expr.callee
+        {
+            is_ident_undefined(module, "module", &self.unresolved_mark)
+                && hot.sym == "hot"
+                && accept.sym == "accept"
+        } else {
+            false
+        }
+    }
+
     fn replace_source(&mut self, source: &mut Str) {
         if let Some(replacement) = self.to_replace.resolved.get(&source.value.to_string()) {
             let module_id = replacement.to_replace_source.clone();
@@ -305,6 +348,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hot_accept() {
+        assert_eq!(
+            run(
+                r#"module.hot.accept("x", cb); module.hot.accept(["x", "y"], cb);"#,
+                build_resolved("x", "/x/index.js"),
+                Default::default()
+            ),
+            r#"module.hot.accept("/x/index.js", cb);
+module.hot.accept([
+    "/x/index.js",
+    "y"
+], cb);"#,
+        );
+    }
+
     #[test]
     fn test_missing_dep() {
         assert_eq!(
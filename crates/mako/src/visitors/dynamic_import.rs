@@ -9,6 +9,7 @@ use swc_core::ecma::utils::{
 };
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
 
+use super::dep_analyzer::is_magic_comment;
 use super::dep_replacer::{miss_throw_stmt, ResolvedReplaceInfo};
 use crate::ast::utils::{is_dynamic_import, promise_all, require_ensure};
 use crate::ast::DUMMY_CTXT;
@@ -109,6 +110,14 @@ impl<'a> VisitMut for DynamicImport<'a> {
 
                     self.changed = true;
 
+                    self.context
+                        .meta
+                        .script
+                        .origin_comments
+                        .write()
+                        .unwrap()
+                        .remove_leading_comments_at(source.span.lo, |c| is_magic_comment(&c.text));
+
                     let generated_module_id = resolved_info.to_replace_source.clone();
                     *expr = {
                         // let load_promise = self.make_load_promise(&chunk_ids);
@@ -0,0 +1,103 @@
+use std::sync::Arc;
+
+use swc_core::common::errors::HANDLER;
+use swc_core::ecma::ast::{CallExpr, Callee, Expr, Lit, Str};
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::compiler::Context;
+
+/**
+ * Rewrite the specifiers of dynamic imports with the `resolve_dynamic_import` hook of the
+ * plugins before the dependencies are analyzed, e.g.
+ *
+ * import('./old') => import('./new')
+ *
+ * so that the rewritten specifier is resolved as usual. Only the string literal specifiers
+ * are passed to the hook.
+ */
+pub struct DynamicImportRewriter {
+    pub path: String,
+    pub context: Arc<Context>,
+}
+
+impl VisitMut for DynamicImportRewriter {
+    fn visit_mut_call_expr(&mut self, call: &mut CallExpr) {
+        if matches!(call.callee, Callee::Import(_))
+            && let Some(arg) = call.args.first_mut()
+            && let Expr::Lit(Lit::Str(specifier)) = &mut *arg.expr
+        {
+            match self.context.plugin_driver.resolve_dynamic_import(
+                &specifier.value,
+                &self.path,
+                &self.context,
+            ) {
+                Ok(Some(rewritten)) => {
+                    *specifier = Str {
+                        span: specifier.span,
+                        value: rewritten.into(),
+                        raw: None,
+                    };
+                }
+                Ok(None) => {}
+                Err(err) => HANDLER.with(|handler| {
+                    handler
+                        .struct_span_err(
+                            specifier.span,
+                            &format!("failed to rewrite dynamic import: {}", err),
+                        )
+                        .emit();
+                }),
+            }
+        }
+        call.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use crate::compiler::Context;
+    use crate::module::ModuleId;
+    use crate::plugin::Plugin;
+    use crate::utils::test_helper::setup_compiler_with_plugins;
+
+    // rewrites `import('./old')` to `import('./new')`
+    struct DynamicImportRewritePlugin {}
+
+    impl Plugin for DynamicImportRewritePlugin {
+        fn name(&self) -> &str {
+            "dynamic_import_rewrite_test"
+        }
+
+        fn resolve_dynamic_import(
+            &self,
+            specifier: &str,
+            _importer: &str,
+            _context: &Arc<Context>,
+        ) -> Result<Option<String>> {
+            Ok((specifier == "./old").then(|| "./new".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_resolve_dynamic_import() {
+        let compiler = setup_compiler_with_plugins(
+            "test/build/dynamic-import-rewrite",
+            vec![Arc::new(DynamicImportRewritePlugin {})],
+            |_| {},
+        );
+        compiler.compile().unwrap();
+
+        let module_graph = compiler.context.module_graph.read().unwrap();
+        let root = &compiler.context.root;
+        let id = |name: &str| ModuleId::new(root.join(name).to_string_lossy().to_string());
+        assert!(module_graph.has_module(&id("new.ts")));
+        assert!(!module_graph.has_module(&id("old.ts")));
+        let deps = module_graph.get_dependencies(&id("index.ts"));
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].1.source, "./new");
+    }
+}
@@ -7,8 +7,8 @@ use serde_json::Value;
 use swc_core::common::{Mark, Span, DUMMY_SP};
 use swc_core::ecma::ast::{
     ArrayLit, Bool, ComputedPropName, Expr, ExprOrSpread, Ident, IdentName, KeyValueProp, Lit,
-    MemberExpr, MemberProp, ModuleItem, Null, Number, ObjectLit, OptChainBase, OptChainExpr, Prop,
-    PropOrSpread, Stmt, Str,
+    MemberExpr, MemberProp, MetaPropExpr, MetaPropKind, ModuleItem, Null, Number, ObjectLit,
+    OptChainBase, OptChainExpr, Prop, PropOrSpread, Stmt, Str,
 };
 use swc_core::ecma::utils::{quote_ident, ExprExt};
 use swc_core::ecma::visit::{VisitMut, VisitMutWith};
@@ -154,12 +154,22 @@ impl VisitMut for EnvReplacer {
                     current_member_obj = obj.as_mut();
                 }
 
-                if let Expr::Ident(Ident { sym, ctxt, .. }) = current_member_obj {
-                    if ctxt.outer() != self.unresolved_mark {
-                        return;
+                match current_member_obj {
+                    Expr::Ident(Ident { sym, ctxt, .. }) => {
+                        if ctxt.outer() != self.unresolved_mark {
+                            return;
+                        }
+                        member_visit_path.push('.');
+                        member_visit_path.push_str(sym.as_ref());
+                    }
+                    // e.g. import.meta.env.API_URL
+                    Expr::MetaProp(MetaPropExpr {
+                        kind: MetaPropKind::ImportMeta,
+                        ..
+                    }) => {
+                        member_visit_path.push_str(".meta.import");
                     }
-                    member_visit_path.push('.');
-                    member_visit_path.push_str(sym.as_ref());
+                    _ => {}
                 }
 
                 let member_visit_path = member_visit_path
@@ -414,6 +424,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_import_meta_env_key() {
+        assert_eq!(
+            run(
+                r#"log(import.meta.env.A);log(import.meta.env.B);"#,
+                hashmap! {
+                    "import.meta.env.A".to_string() => json!("\"foo\"")
+                }
+            ),
+            "log(\"foo\");log(import.meta.env.B);"
+        );
+    }
+
     #[test]
     fn test_computed() {
         assert_eq!(
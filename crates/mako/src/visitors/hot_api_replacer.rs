@@ -0,0 +1,139 @@
+use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::ecma::ast::{Expr, IdentName, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind};
+use swc_core::ecma::utils::member_expr;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::is_ident_undefined;
+use crate::ast::DUMMY_CTXT;
+
+/// Replace `import.meta.hot` with `module.hot` when hmr is enabled, the hot object is created
+/// for every module by the hmr runtime. Otherwise, both `import.meta.hot` and `module.hot` are
+/// replaced with `void 0`, so that the blocks guarded by them are removed by the simplifier.
+pub(crate) struct HotApiReplacer {
+    pub(crate) hmr: bool,
+    pub(crate) unresolved_mark: Mark,
+}
+
+impl HotApiReplacer {
+    pub(crate) fn new(hmr: bool, unresolved_mark: Mark) -> Self {
+        Self {
+            hmr,
+            unresolved_mark,
+        }
+    }
+
+    fn is_hot_object(&self, expr: &Expr) -> bool {
+        match expr {
+            // import.meta.hot
+            Expr::Member(MemberExpr {
+                obj:
+                    box Expr::MetaProp(MetaPropExpr {
+                        kind: MetaPropKind::ImportMeta,
+                        ..
+                    }),
+                prop: MemberProp::Ident(IdentName { sym, .. }),
+                ..
+            }) => sym == "hot",
+            // module.hot, only when module is not declared in the file
+            Expr::Member(MemberExpr {
+                obj: box Expr::Ident(ident),
+                prop: MemberProp::Ident(IdentName { sym, .. }),
+                ..
+            }) => {
+                !self.hmr
+                    && sym == "hot"
+                    && is_ident_undefined(ident, "module", &self.unresolved_mark)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for HotApiReplacer {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if self.is_hot_object(expr) {
+            *expr = if self.hmr {
+                member_expr!(
+                    DUMMY_CTXT.apply_mark(self.unresolved_mark),
+                    DUMMY_SP,
+                    module.hot
+                )
+            } else {
+                *Expr::undefined(DUMMY_SP)
+            };
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::HotApiReplacer;
+    use crate::ast::tests::TestUtils;
+    use crate::utils::test_helper::setup_compiler;
+
+    #[test]
+    fn test_import_meta_hot_with_hmr() {
+        assert_eq!(
+            run(
+                r#"if (import.meta.hot) { import.meta.hot.accept(); } module.hot.dispose(fn);"#,
+                true
+            ),
+            r#"if (module.hot) {
+    module.hot.accept();
+}
+module.hot.dispose(fn);"#
+        );
+    }
+
+    #[test]
+    fn test_import_meta_hot_without_hmr() {
+        assert_eq!(
+            run(
+                r#"if (import.meta.hot) { import.meta.hot.accept(); } module.hot && module.hot.dispose(fn);"#,
+                false
+            ),
+            r#"if (void 0) {
+    (void 0).accept();
+}
+void 0 && (void 0).dispose(fn);"#
+        );
+    }
+
+    #[test]
+    fn test_declared_module_is_kept() {
+        assert_eq!(
+            run(r#"const module = {}; module.hot.accept();"#, false),
+            r#"const module = {};
+module.hot.accept();"#
+        );
+    }
+
+    #[test]
+    fn test_hot_api_removed_in_production() {
+        let compiler = setup_compiler("test/build/hot-api-production", false);
+        compiler.compile().unwrap();
+        let content =
+            fs::read_to_string(compiler.context.config.output.path.join("index.js")).unwrap();
+        assert!(content.contains("console.log(state.count);"));
+        assert!(!content.contains("accepted"));
+        assert!(!content.contains("disposed"));
+        assert!(!content.contains(".hot"));
+    }
+
+    fn run(js_code: &str, hmr: bool) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = HotApiReplacer::new(hmr, ast.unresolved_mark);
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
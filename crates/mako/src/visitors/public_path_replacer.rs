@@ -0,0 +1,159 @@
+use swc_core::common::{Mark, DUMMY_SP};
+use swc_core::ecma::ast::{
+    AssignExpr, AssignOp, AssignTarget, Expr, Ident, MemberExpr, MemberProp, SimpleAssignTarget,
+};
+use swc_core::ecma::utils::member_expr;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::DUMMY_CTXT;
+
+/**
+ * Replace the public path variables of webpack with `__mako_require__.publicPath`, which
+ * is initialized with `output.publicPath`, e.g.
+ *
+ * - `__webpack_public_path__` and `__mako_public_path__`
+ * - `__webpack_require__.p`
+ *
+ * Both reading and assigning are supported.
+ */
+pub struct PublicPathReplacer {
+    pub unresolved_mark: Mark,
+}
+
+impl PublicPathReplacer {
+    fn is_public_path_ident(&self, ident: &Ident) -> bool {
+        (ident.sym == "__webpack_public_path__" || ident.sym == "__mako_public_path__")
+            && ident.ctxt.outer() == self.unresolved_mark
+    }
+
+    // __webpack_require__.p
+    fn is_public_path_member(&self, member: &MemberExpr) -> bool {
+        if let Expr::Ident(obj) = member.obj.as_ref()
+            && let MemberProp::Ident(prop) = &member.prop
+        {
+            obj.sym == "__webpack_require__"
+                && obj.ctxt.outer() == self.unresolved_mark
+                && prop.sym == "p"
+        } else {
+            false
+        }
+    }
+}
+
+impl VisitMut for PublicPathReplacer {
+    fn visit_mut_assign_expr(&mut self, n: &mut AssignExpr) {
+        let is_public_path = match &n.left {
+            AssignTarget::Simple(SimpleAssignTarget::Ident(binding)) => {
+                self.is_public_path_ident(&binding.id)
+            }
+            AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+                self.is_public_path_member(member)
+            }
+            _ => false,
+        };
+        if n.op == AssignOp::Assign && is_public_path {
+            n.left = member_expr!(DUMMY_CTXT, DUMMY_SP, __mako_require__.publicPath).into();
+            n.right.visit_mut_with(self);
+        } else {
+            n.visit_mut_children_with(self);
+        }
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        let is_public_path = match expr {
+            Expr::Ident(ident) => self.is_public_path_ident(ident),
+            Expr::Member(member) => self.is_public_path_member(member),
+            _ => false,
+        };
+        if is_public_path {
+            *expr = *member_expr!(DUMMY_CTXT, DUMMY_SP, __mako_require__.publicPath);
+        } else {
+            expr.visit_mut_children_with(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::PublicPathReplacer;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_normal() {
+        assert_eq!(
+            run(r#"__webpack_public_path__ = '/foo/';"#),
+            r#"__mako_require__.publicPath = '/foo/';"#.trim()
+        );
+        assert_eq!(
+            run(r#"__mako_public_path__ = '/foo/';"#),
+            r#"__mako_require__.publicPath = '/foo/';"#.trim()
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        assert_eq!(
+            run(r#"const url = __webpack_public_path__ + 'a.png';"#),
+            r#"const url = __mako_require__.publicPath + 'a.png';"#.trim()
+        );
+        assert_eq!(
+            run(r#"const url = __webpack_require__.p + 'a.png';"#),
+            r#"const url = __mako_require__.publicPath + 'a.png';"#.trim()
+        );
+    }
+
+    #[test]
+    fn test_webpack_require_p() {
+        assert_eq!(
+            run(r#"__webpack_require__.p = __webpack_public_path__ + 'foo/';"#),
+            r#"__mako_require__.publicPath = __mako_require__.publicPath + 'foo/';"#.trim()
+        );
+        assert_eq!(
+            run(r#"__webpack_require__.e = 1;"#),
+            r#"__webpack_require__.e = 1;"#.trim()
+        );
+    }
+
+    #[test]
+    fn test_ident_defined() {
+        assert_eq!(
+            run(r#"let __webpack_public_path__ = 1; __webpack_public_path__ = '/foo/';"#),
+            r#"
+let __webpack_public_path__ = 1;
+__webpack_public_path__ = '/foo/';
+"#
+            .trim()
+        );
+        assert_eq!(
+            run(r#"let __mako_public_path__ = 1; __mako_public_path__ = '/foo/';"#),
+            r#"
+let __mako_public_path__ = 1;
+__mako_public_path__ = '/foo/';
+"#
+            .trim()
+        );
+        assert_eq!(
+            run(r#"const __webpack_require__ = {}; __webpack_require__.p;"#),
+            r#"
+const __webpack_require__ = {};
+__webpack_require__.p;
+"#
+            .trim()
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            let mut visitor = PublicPathReplacer {
+                unresolved_mark: ast.unresolved_mark,
+            };
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
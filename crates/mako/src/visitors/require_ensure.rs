@@ -0,0 +1,277 @@
+use swc_core::common::{Mark, SyntaxContext, DUMMY_SP};
+use swc_core::ecma::ast::{
+    ArrayLit, CallExpr, Callee, Expr, ExprOrSpread, Id, Ident, Import, Lit, MemberExpr, MemberProp,
+    Pat,
+};
+use swc_core::ecma::utils::{quote_str, ExprFactory};
+use swc_core::ecma::visit::{Visit, VisitMut, VisitMutWith, VisitWith};
+
+use crate::ast::utils::{
+    get_first_str_arg, is_commonjs_require, is_ident_undefined, member_call, member_prop,
+    promise_all,
+};
+use crate::ast::DUMMY_CTXT;
+
+/**
+ * Convert webpack's `require.ensure` to dynamic imports, so that the dependencies are split
+ * into async chunks like `import()`, e.g.
+ *
+ * require.ensure(['./a'], function(require) { require('./a'); }, onError, 'chunk-name')
+ * => Promise.all([import('./a')]).then(function() { require('./a'); }, onError)
+ *
+ * The `require` parameter of the callback is replaced with the global `require`, and the
+ * modules required in the callback are imported too, so that they are split into the async
+ * chunks instead of the chunk of the caller, like webpack. The chunk name is ignored. Other
+ * forms, e.g. with a non-array dependencies argument, are left as is.
+ */
+pub struct RequireEnsure {
+    unresolved_mark: Mark,
+}
+
+impl RequireEnsure {
+    pub fn new(unresolved_mark: Mark) -> Self {
+        Self { unresolved_mark }
+    }
+
+    fn is_require_ensure(&self, callee: &Callee) -> bool {
+        if let Callee::Expr(box Expr::Member(MemberExpr {
+            obj: box Expr::Ident(obj),
+            prop: MemberProp::Ident(prop),
+            ..
+        })) = callee
+        {
+            is_ident_undefined(obj, "require", &self.unresolved_mark) && prop.sym == "ensure"
+        } else {
+            false
+        }
+    }
+
+    fn convert(&self, call: &mut CallExpr) -> Option<Expr> {
+        if !self.is_require_ensure(&call.callee) || call.args.len() < 2 {
+            return None;
+        }
+        let Expr::Array(ArrayLit { elems, .. }) = &*call.args[0].expr else {
+            return None;
+        };
+        if elems
+            .iter()
+            .any(|elem| elem.as_ref().map_or(true, |elem| elem.spread.is_some()))
+        {
+            return None;
+        }
+
+        let mut callback = call.args[1].expr.clone();
+        let require_param = match &mut *callback {
+            Expr::Fn(fn_expr) => {
+                let params = std::mem::take(&mut fn_expr.function.params);
+                params.into_iter().next().map(|param| param.pat)
+            }
+            Expr::Arrow(arrow) => std::mem::take(&mut arrow.params).into_iter().next(),
+            _ => return None,
+        };
+        if let Some(param) = require_param {
+            let Pat::Ident(param) = param else {
+                return None;
+            };
+            callback.visit_mut_with(&mut RebindRequire {
+                param: param.id.to_id(),
+                ctxt: SyntaxContext::empty().apply_mark(self.unresolved_mark),
+            });
+        }
+
+        // the modules required in the callback which are not in the dependencies
+        let mut required = RequiredSources {
+            unresolved_mark: self.unresolved_mark,
+            sources: vec![],
+        };
+        callback.visit_with(&mut required);
+        let mut listed = elems
+            .iter()
+            .flatten()
+            .filter_map(|elem| match &*elem.expr {
+                Expr::Lit(Lit::Str(str)) => Some(str.value.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut deps = elems.iter().flatten().cloned().collect::<Vec<_>>();
+        for source in required.sources {
+            if !listed.contains(&source) {
+                deps.push(quote_str!(source.clone()).as_arg());
+                listed.push(source);
+            }
+        }
+
+        let imports = deps
+            .into_iter()
+            .map(|elem| {
+                Some(
+                    Expr::Call(CallExpr {
+                        span: DUMMY_SP,
+                        ctxt: DUMMY_CTXT,
+                        callee: Callee::Import(Import {
+                            span: DUMMY_SP,
+                            phase: Default::default(),
+                        }),
+                        args: vec![elem],
+                        type_args: None,
+                    })
+                    .as_arg(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut then_args = vec![callback.as_arg()];
+        // `require.ensure(deps, callback, chunkName)` has no error callback
+        if let Some(on_error) = call.args.get(2)
+            && !matches!(&*on_error.expr, Expr::Lit(Lit::Str(_)))
+        {
+            then_args.push(on_error.clone());
+        }
+
+        Some(member_call(
+            promise_all(ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: imports,
+                })),
+            }),
+            member_prop("then"),
+            then_args,
+        ))
+    }
+}
+
+impl VisitMut for RequireEnsure {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Call(call) = expr
+            && let Some(converted) = self.convert(call)
+        {
+            *expr = converted;
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+// the `require` parameter of the callback, e.g. `function(r) { r('./a') }`
+struct RebindRequire {
+    param: Id,
+    ctxt: SyntaxContext,
+}
+
+impl VisitMut for RebindRequire {
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if ident.to_id() == self.param {
+            ident.sym = "require".into();
+            ident.ctxt = self.ctxt;
+        }
+    }
+}
+
+struct RequiredSources {
+    unresolved_mark: Mark,
+    sources: Vec<String>,
+}
+
+impl Visit for RequiredSources {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if is_commonjs_require(call, &self.unresolved_mark)
+            && let Some(source) = get_first_str_arg(call)
+        {
+            self.sources.push(source);
+        }
+        call.visit_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::RequireEnsure;
+    use crate::ast::tests::TestUtils;
+
+    #[test]
+    fn test_require_ensure() {
+        assert_eq!(
+            run(r#"require.ensure(['./a', './b'], function(r) { r('./a'); });"#),
+            r#"
+Promise.all([
+    import('./a'),
+    import('./b')
+]).then(function() {
+    require('./a');
+});
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_required_in_callback() {
+        assert_eq!(
+            run(r#"require.ensure(['./a'], function(r) { r('./a'); r('./b'); r('./b'); });"#),
+            r#"
+Promise.all([
+    import('./a'),
+    import('./b')
+]).then(function() {
+    require('./a');
+    require('./b');
+    require('./b');
+});
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_error_callback_and_chunk_name() {
+        assert_eq!(
+            run(r#"require.ensure([], (require) => require('./a'), onError, 'a');"#),
+            r#"
+Promise.all([
+    import('./a')
+]).then(()=>require('./a'), onError);
+            "#
+            .trim()
+        );
+        assert_eq!(
+            run(r#"require.ensure(['./a'], () => {}, 'a');"#),
+            r#"
+Promise.all([
+    import('./a')
+]).then(()=>{});
+            "#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert_eq!(
+            run(r#"require.ensure(deps, function() {});"#),
+            r#"require.ensure(deps, function() {});"#
+        );
+        assert_eq!(
+            run(r#"const require = {}; require.ensure(['./a'], function() {});"#),
+            r#"
+const require = {};
+require.ensure([
+    './a'
+], function() {});
+            "#
+            .trim()
+        );
+    }
+
+    fn run(js_code: &str) -> String {
+        let mut test_utils = TestUtils::gen_js_ast(js_code);
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast
+                .visit_mut_with(&mut RequireEnsure::new(ast.unresolved_mark));
+        });
+        test_utils.js_ast_to_code()
+    }
+}
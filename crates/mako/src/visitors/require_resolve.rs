@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use swc_core::common::Mark;
+use swc_core::ecma::ast::{Callee, Expr, MemberExpr, MemberProp};
+use swc_core::ecma::utils::quote_str;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::ast::utils::{get_first_str_arg, is_ident_undefined};
+use crate::compiler::Context;
+use crate::config::Platform;
+use crate::module::{Dependency, ResolveType};
+use crate::resolve::{self, ResolverResource};
+
+/**
+ * Replace `require.resolve('./template.html')` with the resolved absolute path, since
+ * there's no resolver at runtime in non-node builds, e.g.
+ *
+ * require.resolve('./template.html') => "/path/to/src/template.html"
+ *
+ * It's left as is for node, and so are non-literal and unresolvable specifiers.
+ */
+pub struct RequireResolve {
+    pub path: String,
+    pub context: Arc<Context>,
+    pub unresolved_mark: Mark,
+}
+
+impl RequireResolve {
+    fn is_require_resolve(&self, callee: &Callee) -> bool {
+        if let Callee::Expr(box Expr::Member(MemberExpr {
+            obj: box Expr::Ident(obj),
+            prop: MemberProp::Ident(prop),
+            ..
+        })) = callee
+        {
+            is_ident_undefined(obj, "require", &self.unresolved_mark) && prop.sym == "resolve"
+        } else {
+            false
+        }
+    }
+
+    fn resolve(&self, source: String) -> Option<String> {
+        let dep = Dependency {
+            source,
+            resolve_as: None,
+            resolve_type: ResolveType::Require,
+            order: 0,
+            span: None,
+        };
+        match resolve::resolve(&self.path, &dep, &self.context.resolvers, &self.context) {
+            Ok(resource @ ResolverResource::Resolved(_)) => Some(resource.get_resolved_path()),
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for RequireResolve {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if self.context.config.platform == Platform::Node {
+            return;
+        }
+        if let Expr::Call(call_expr) = expr
+            && self.is_require_resolve(&call_expr.callee)
+            && let Some(source) = get_first_str_arg(call_expr)
+            && let Some(path) = self.resolve(source)
+        {
+            *expr = quote_str!(path).into();
+            return;
+        }
+        expr.visit_mut_children_with(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use swc_core::common::GLOBALS;
+    use swc_core::ecma::visit::VisitMutWith;
+
+    use super::RequireResolve;
+    use crate::ast::tests::{TestUtils, TestUtilsOpts};
+    use crate::compiler::Context;
+    use crate::config::Platform;
+
+    #[test]
+    fn test_browser() {
+        let fixture = fixture();
+        assert_eq!(
+            run(
+                r#"const a = require.resolve('./source'); const b = require.resolve(x);"#,
+                Platform::Browser
+            ),
+            format!(
+                r#"
+const a = "{}/source.ts";
+const b = require.resolve(x);
+"#,
+                fixture
+            )
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_unresolvable() {
+        assert_eq!(
+            run(r#"require.resolve('./missing');"#, Platform::Browser),
+            r#"require.resolve('./missing');"#
+        );
+    }
+
+    #[test]
+    fn test_require_defined() {
+        assert_eq!(
+            run(
+                r#"const require = {}; require.resolve('./source');"#,
+                Platform::Browser
+            ),
+            r#"
+const require = {};
+require.resolve('./source');
+"#
+            .trim()
+        );
+    }
+
+    #[test]
+    fn test_node() {
+        assert_eq!(
+            run(r#"require.resolve('./source');"#, Platform::Node),
+            r#"require.resolve('./source');"#
+        );
+    }
+
+    fn fixture() -> String {
+        std::env::current_dir()
+            .unwrap()
+            .join("test/resolve/normal")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn run(js_code: &str, platform: Platform) -> String {
+        let mut context = Context::default();
+        context.config.devtool = None;
+        context.config.platform = platform;
+        let mut test_utils = TestUtils::with_context(
+            TestUtilsOpts {
+                file: Some("test.js".to_string()),
+                content: Some(js_code.to_string()),
+            },
+            Arc::new(context),
+        );
+        let ast = test_utils.ast.js_mut();
+        GLOBALS.set(&test_utils.context.meta.script.globals, || {
+            ast.ast
+                .visit_mut_with(&mut swc_core::ecma::transforms::base::resolver(
+                    ast.unresolved_mark,
+                    ast.top_level_mark,
+                    false,
+                ));
+            let mut visitor = RequireResolve {
+                path: format!("{}/index.ts", fixture()),
+                context: test_utils.context.clone(),
+                unresolved_mark: ast.unresolved_mark,
+            };
+            ast.ast.visit_mut_with(&mut visitor);
+        });
+        test_utils.js_ast_to_code()
+    }
+}
@@ -0,0 +1,3 @@
+import './style.css';
+
+console.log('banner-marker');
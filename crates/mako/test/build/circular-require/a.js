@@ -0,0 +1,3 @@
+exports.a = 'a';
+const b = require('./b');
+exports.b = b.b;
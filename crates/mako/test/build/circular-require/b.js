@@ -0,0 +1,2 @@
+const a = require('./a');
+exports.b = 'b' + a.a;
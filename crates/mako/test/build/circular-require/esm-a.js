@@ -0,0 +1,3 @@
+import { esmB } from './esm-b';
+export const esmA = 'esmA';
+export const fromB = () => esmB;
@@ -0,0 +1,2 @@
+import { esmA } from './esm-a';
+export const esmB = () => esmA;
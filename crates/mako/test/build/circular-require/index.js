@@ -0,0 +1,5 @@
+const a = require('./a');
+const cyclic = require('cyclic');
+import { esmA } from './esm-a';
+
+console.log(a, cyclic, esmA);
@@ -0,0 +1,6 @@
+import './shared.css';
+import './first-a.css';
+import './late.css';
+import './last-a.css';
+
+console.log('a');
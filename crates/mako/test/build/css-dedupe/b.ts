@@ -0,0 +1,6 @@
+import './shared.css';
+import './first-b.css';
+import './late.css';
+import './last-b.css';
+
+console.log('b');
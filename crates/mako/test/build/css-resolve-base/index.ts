@@ -0,0 +1 @@
+import './src/style.css';
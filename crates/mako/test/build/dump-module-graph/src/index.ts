@@ -0,0 +1,5 @@
+import { a } from './a';
+import { b } from './b';
+
+console.log(a, b);
+import('./c').then(({ c }) => console.log(c));
@@ -0,0 +1 @@
+import('./old').then((m) => console.log(m.default));
@@ -0,0 +1,3 @@
+console.log(process.env.REACT_APP_API_URL);
+console.log(import.meta.env.REACT_APP_API_V2_URL);
+console.log(process.env.REACT_APP_NAME);
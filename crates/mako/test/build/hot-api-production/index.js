@@ -0,0 +1,14 @@
+const state = { count: 1 };
+
+if (import.meta.hot) {
+  import.meta.hot.accept();
+  console.log('import.meta.hot accepted');
+}
+
+if (module.hot) {
+  module.hot.dispose(() => {
+    console.log('module.hot disposed');
+  });
+}
+
+console.log(state.count);
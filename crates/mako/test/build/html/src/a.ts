@@ -0,0 +1,4 @@
+import './a.css';
+
+console.log('a');
+import(/* webpackPrefetch: true */ './lazy');
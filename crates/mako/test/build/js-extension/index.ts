@@ -0,0 +1,3 @@
+import { answer } from './answer.myjs';
+
+console.log(answer);
@@ -0,0 +1,3 @@
+import { greet } from './a-dep';
+
+export default greet('a');
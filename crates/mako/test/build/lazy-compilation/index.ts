@@ -0,0 +1,4 @@
+export const routes = {
+  a: () => import('./a'),
+  b: () => import('./b'),
+};
@@ -0,0 +1,8 @@
+const greet = (name: string) => `hello ${name}`;
+
+let count = 0;
+[1, 2, 3].forEach((n) => {
+  count += n;
+});
+
+console.log(greet('mako'), count);
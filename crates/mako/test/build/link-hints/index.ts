@@ -0,0 +1,3 @@
+import(/* makoPrefetch: true */ './prefetch');
+import(/* webpackPreload: true */ './preload');
+import('./plain');
@@ -0,0 +1 @@
+export const prefetch = 1;
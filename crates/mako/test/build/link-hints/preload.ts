@@ -0,0 +1 @@
+export const preload = 1;
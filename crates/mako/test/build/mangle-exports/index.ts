@@ -0,0 +1,5 @@
+import { formatDate, parseDate as parse, DateRange } from './utils';
+import { defaultLocale } from './reexport';
+import * as ns from './ns';
+
+console.log(formatDate(parse('2024-01-02')), new DateRange(1, 3), defaultLocale, ns);
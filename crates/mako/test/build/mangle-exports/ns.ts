@@ -0,0 +1,2 @@
+export const nsValue = 1;
+export const otherValue = 2;
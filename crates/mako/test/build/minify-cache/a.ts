@@ -0,0 +1,4 @@
+export function greet(name: string) {
+  const message = `hello, ${name}`;
+  return message.toUpperCase();
+}
@@ -0,0 +1,7 @@
+export function sum(numbers: number[]) {
+  let total = 0;
+  for (const n of numbers) {
+    total += n;
+  }
+  return total;
+}
@@ -0,0 +1,2 @@
+import('./a').then(({ greet }) => console.log(greet('a')));
+import('./b').then(({ sum }) => console.log(sum([1, 2, 3])));
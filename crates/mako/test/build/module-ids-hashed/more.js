@@ -0,0 +1,4 @@
+import { extra } from './extra';
+import { a } from './a';
+
+console.log(extra, a);
@@ -0,0 +1,4 @@
+import { add } from './math';
+import { name } from './name';
+
+console.log(name, add(1, 2));
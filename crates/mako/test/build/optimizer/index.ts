@@ -0,0 +1,8 @@
+console.log('removed');
+
+function render() {
+  console.log('removed');
+  document.title = 'kept';
+}
+
+render();
@@ -0,0 +1,4 @@
+import { foo } from 'broken/foo';
+import { bar } from 'patched/bar';
+
+console.log(foo, bar);
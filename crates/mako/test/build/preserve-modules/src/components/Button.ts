@@ -0,0 +1,8 @@
+import React from 'react';
+import './Button.css';
+import icon from './icon.png';
+import smallIcon from '../icons/icon.png';
+
+export function Button() {
+  return React.createElement('button', { className: 'button' }, icon, smallIcon);
+}
@@ -0,0 +1,9 @@
+import { add } from './utils/math';
+import { Button } from './components/Button';
+
+export { add, Button };
+export * from './utils/format';
+
+export function loadLazy() {
+  return import('./lazy');
+}
@@ -0,0 +1,3 @@
+import('./locale').then(({ default: messages }) => {
+  console.log(messages.hello);
+});
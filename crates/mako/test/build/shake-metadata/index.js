@@ -0,0 +1,3 @@
+import { kept } from './utils';
+
+console.log(kept);
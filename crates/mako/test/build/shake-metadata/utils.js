@@ -0,0 +1,5 @@
+export const kept = 'kept';
+
+export function removed() {
+  return 'removed';
+}
@@ -0,0 +1,4 @@
+/* sideEffects: false */
+console.log('side-effect-of-commented');
+
+export const commented = 'commented';
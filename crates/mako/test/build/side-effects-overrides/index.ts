@@ -0,0 +1,9 @@
+import 'matched';
+import 'plain';
+import 'forced';
+import 'pure';
+import 'both';
+import { commented } from './commented';
+import { jsdoc } from './jsdoc';
+import { uncommented } from './uncommented';
+import { trailing } from './trailing';
@@ -0,0 +1,7 @@
+// license
+/**
+ * sideEffects:false
+ */
+console.log('side-effect-of-jsdoc');
+
+export const jsdoc = 'jsdoc';
@@ -0,0 +1,3 @@
+console.log('side-effect-of-trailing'); /* sideEffects: false */
+
+export const trailing = 'trailing';
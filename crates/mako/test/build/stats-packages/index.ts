@@ -0,0 +1,4 @@
+import tiny from 'tiny';
+import wrapper from 'wrapper';
+
+console.log(tiny, wrapper);
@@ -0,0 +1,4 @@
+import { instance as a } from 'shared';
+import { instance as b } from './packages/shared';
+
+console.log(a === b);
@@ -0,0 +1,3 @@
+import message from './broken';
+
+console.log(message);
@@ -0,0 +1,3 @@
+import { config, used } from './lib';
+
+console.log(used, config);
@@ -0,0 +1,26 @@
+export const config = {
+  get level() {
+    console.log('level-getter-called');
+    return 1;
+  },
+};
+const settings = {
+  nested: {
+    set theme(value) {
+      console.log('theme-setter-called', value);
+    },
+  },
+};
+export { settings };
+export const createConfig = () => ({
+  get lazy() {
+    return 'lazy-getter';
+  },
+});
+export const used = 'used';
+export const { theme } = {
+  get theme() {
+    console.log('destructured-getter-called');
+    return 'dark';
+  },
+};
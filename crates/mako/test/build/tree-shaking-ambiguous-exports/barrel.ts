@@ -0,0 +1,2 @@
+export const own = 'own';
+export * from './cjs';
@@ -0,0 +1,3 @@
+exports.foo = 'foo';
+exports.bar = 'bar';
+exports.baz = 'baz';
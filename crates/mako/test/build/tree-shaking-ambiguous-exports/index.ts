@@ -0,0 +1,3 @@
+import { foo, bar, own } from './barrel';
+
+console.log(foo, bar, own);
@@ -0,0 +1,2 @@
+export const foo = 'b';
+export { shared } from './shared';
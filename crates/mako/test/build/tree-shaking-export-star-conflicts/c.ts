@@ -0,0 +1,2 @@
+export const bar = 'c';
+export * from './mid';
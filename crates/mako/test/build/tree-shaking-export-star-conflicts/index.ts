@@ -0,0 +1,3 @@
+import { shared, bar, foo } from './mid';
+
+console.log(shared, bar, foo);
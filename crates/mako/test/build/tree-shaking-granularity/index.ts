@@ -0,0 +1,4 @@
+import { used } from './lib';
+import './dead';
+
+console.log(used);
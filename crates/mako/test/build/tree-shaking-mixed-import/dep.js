@@ -0,0 +1,3 @@
+export default 'dep-default';
+
+export const named = 'dep-named';
@@ -0,0 +1,3 @@
+import dep, { named } from './dep';
+
+console.log(dep);
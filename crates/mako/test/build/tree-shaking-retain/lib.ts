@@ -0,0 +1,6 @@
+const pinned = { name: 'pinned-by-plugin', helper };
+function helper() {
+  return 'pinned-helper';
+}
+const dead = 'dead-code';
+export const used = 'used';
@@ -0,0 +1,12 @@
+import { a } from './a';
+import { b } from './b';
+
+function make() {
+  return {};
+}
+
+const used = a + 1, unused = b;
+const pure = /*#__PURE__*/ make();
+console.log('side effect');
+export { used, unused, pure };
+export default function foo() {}
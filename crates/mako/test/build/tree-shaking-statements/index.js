@@ -0,0 +1,3 @@
+import { used } from './dep';
+
+console.log(used);
@@ -0,0 +1,5 @@
+import greet, { a, b as renamed } from './utils';
+import * as all from './all';
+import { z } from './reexport';
+
+console.log(greet(), a, renamed, all, z);
@@ -0,0 +1,7 @@
+export const a = 'a';
+export const b = 'b';
+export const c = 'c';
+
+export default function greet() {
+  return 'hello';
+}
@@ -0,0 +1,2 @@
+export const z = 'z';
+export const w = 'w';
@@ -0,0 +1 @@
+import { utils } from './utils';
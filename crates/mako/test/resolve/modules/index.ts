@@ -0,0 +1 @@
+import { from } from 'utils';
@@ -0,0 +1,26 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const names = Object.keys(files).join(",");
+const content = files["index.js"];
+
+assert.match(names, /logo\.(.*)\.svg/, "should emit logo.svg");
+
+assert.match(
+  content,
+  moduleReg("src/logo.svg", '__mako_require__.d(exports, "ReactComponent", {', true),
+  "should load svg as a component"
+);
+assert(
+  content.includes(`viewBox: "0 0 24 24"`) && content.includes(`...props`),
+  "should keep the svg attributes and spread the props onto the root element"
+);
+assert.match(
+  content,
+  moduleReg(
+    "src/logo.svg\\?url",
+    "module.exports = `\\$\\{__mako_require__.publicPath\\}logo\\.[a-zA-Z0-9]+\\.svg`;"
+  ),
+  "should export the url of the svg with ?url"
+);
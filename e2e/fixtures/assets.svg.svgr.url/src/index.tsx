@@ -0,0 +1,4 @@
+import { ReactComponent as Logo } from './logo.svg';
+import logoUrl from './logo.svg?url';
+
+console.log(<Logo width={24} aria-label="logo" />, logoUrl);
@@ -0,0 +1,30 @@
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const wasmFile = Object.keys(files).find((file) => /^add\.\w+\.wasm$/.test(file));
+assert(wasmFile, 'wasm should be emitted with content hash');
+
+const fetched = [];
+global.fetch = (url) => {
+  fetched.push(url);
+  return Promise.resolve({
+    arrayBuffer: () =>
+      Promise.resolve(fs.readFileSync(path.join(__dirname, 'dist', wasmFile))),
+  });
+};
+WebAssembly.instantiateStreaming = undefined;
+
+const { sum } = require('./dist');
+
+module.exports = () =>
+  sum().then((result) => {
+    assert.deepEqual(
+      fetched,
+      [`/static/${wasmFile}`],
+      'add.wasm?init should fetch the wasm from the publicPath',
+    );
+    assert.equal(result, 3, 'add.wasm?init should instantiate the wasm');
+  });
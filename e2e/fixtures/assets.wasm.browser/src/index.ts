@@ -0,0 +1,4 @@
+import init from './add.wasm?init';
+
+export const sum = () =>
+  init({ env: { log() {} } }).then((exports) => exports.add(1, 2));
@@ -0,0 +1,25 @@
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const wasmFile = Object.keys(files).find((file) => /^add\.\w+\.wasm$/.test(file));
+assert(wasmFile, 'wasm should be emitted with content hash');
+
+const { sum, url } = require('./dist');
+assert(url.endsWith(wasmFile), 'add.wasm?url should be the url of the wasm');
+
+const stats = JSON.parse(
+  fs.readFileSync(path.join(__dirname, 'dist/stats.json'), 'utf-8'),
+);
+assert.deepEqual(
+  stats.wasmImports['src/add.wasm'],
+  { env: ['log'] },
+  'imports of the wasm should be in stats',
+);
+
+module.exports = () =>
+  sum.then((result) => {
+    assert.equal(result, 3, 'add.wasm?init should instantiate the wasm');
+  });
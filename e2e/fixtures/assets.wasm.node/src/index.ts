@@ -0,0 +1,7 @@
+import init from './add.wasm?init';
+import url from './add.wasm?url';
+
+export const sum = init({ env: { log() {} } }).then((exports) =>
+  exports.add(1, 2),
+);
+export { url };
@@ -0,0 +1,13 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+module.exports = async () => {
+  assert.deepEqual(
+    Object.keys(files).filter((f) => f.endsWith(".js")),
+    ["index.js"],
+    `dynamic imports are inlined into the entry chunk`,
+  );
+  const result = await require("./dist").load();
+  assert.strictEqual(result, "foo_bar_foo", `inlined dynamic imports work`);
+};
@@ -0,0 +1,3 @@
+import { foo } from './foo';
+
+export const bar = `_bar_${foo}`;
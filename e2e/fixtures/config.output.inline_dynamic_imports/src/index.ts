@@ -0,0 +1,5 @@
+export const load = async () => {
+  const { foo } = await import('./foo');
+  const { bar } = await import('./bar');
+  return foo + bar;
+};
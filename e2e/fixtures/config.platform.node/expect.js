@@ -9,10 +9,9 @@ assert.match(
   /__mako_require__\("src\/constants.ts"\)/,
   "should replace require to __mako_require__ for normal module"
 );
-assert.match(
-  content,
-  /require\("fs"\)/,
-  "should keep require for standard module"
+assert(
+  content.includes(`module.exports = require("fs");`),
+  "should externalize standard module"
 );
 assert.match(
   content,
@@ -34,5 +33,6 @@ assert.match(
   /console\.log\('dirname', "src"\);/,
   "should transform __dirname"
 );
-assert(content.includes(`require('crypto');`), `should keep require for crypto`);
-assert(content.includes(`require('node:sqlite');`), `should keep require for node:sqlite`);
+assert(content.includes(`module.exports = require("crypto");`), `should keep require for crypto`);
+assert(content.includes(`module.exports = require("node:sqlite");`), `should keep require for node:sqlite`);
+assert(content.includes(`module.exports = require("http2");`), `should keep require for http2`);
@@ -0,0 +1,20 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(
+  content.includes(`__mako_require__.publicPath = window.CDN_BASE || __mako_require__.publicPath`),
+  `__webpack_public_path__ assignment works`,
+);
+assert(
+  content.includes(`return __mako_require__.publicPath + name`),
+  `__webpack_public_path__ works`,
+);
+assert(
+  content.includes(`return __mako_require__.publicPath;`),
+  `__webpack_require__.p works`,
+);
+assert(!content.includes(`__webpack_public_path__`), `no __webpack_public_path__ left`);
+assert(!content.includes(`__webpack_require__`), `no __webpack_require__ left`);
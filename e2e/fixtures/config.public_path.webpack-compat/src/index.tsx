@@ -0,0 +1,3 @@
+import { getAssetUrl, getChunkBase } from 'cdn-assets';
+
+console.log(getAssetUrl('logo.png'), getChunkBase());
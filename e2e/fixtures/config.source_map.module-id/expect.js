@@ -0,0 +1,12 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const map = JSON.parse(files["index.js.map"]);
+
+assert(map.sources.includes("mako:///./src/hello.ts"), "should use module ids as sources");
+assert(!files["index.js.map"].includes(__dirname), "should not leak the project path");
+assert(
+  map.sourcesContent.some((content) => content && content.includes("hello ${name}")),
+  "should keep sourcesContent by default",
+);
@@ -0,0 +1,4 @@
+import './index.css';
+import { hello } from './hello';
+
+console.log(hello('mako'));
@@ -0,0 +1,18 @@
+const assert = require("assert");
+const path = require("path");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+for (const file of ["index.js.map", "index.css.map"]) {
+  const map = JSON.parse(files[file]);
+  assert(map.sources.length > 0, `${file} should have sources`);
+  map.sources.forEach((source) => {
+    assert(!path.isAbsolute(source), `${file} should not have absolute source ${source}`);
+  });
+  assert(!files[file].includes(__dirname), `${file} should not leak the project path`);
+  assert.strictEqual(map.sourcesContent, undefined, `${file} should not have sourcesContent`);
+  assert.strictEqual(map.sourceRoot, "/app/", `${file} should have sourceRoot`);
+}
+
+assert(JSON.parse(files["index.js.map"]).sources.includes("src/hello.ts"));
+assert(JSON.parse(files["index.css.map"]).sources.includes("src/index.css"));
@@ -0,0 +1,64 @@
+const assert = require("assert");
+
+const { parseBuildResult, trim } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const names = Object.keys(files).join(",");
+const content = trim(files["index.css"]);
+
+assert.match(names, /logo\.(.*)\.png/, "should emit logo.png");
+assert.match(names, /font\.(.*)\.eot/, "should emit font.eot");
+
+// css
+assert.match(
+  content,
+  /src:url\(font\.(.*)\.eot\?#iefix\)format\("embedded-opentype"\)/,
+  "should keep the query and fragment of the rewritten url"
+);
+assert.match(
+  content,
+  /\.css-alias{background:url\(logo\.(.*)\.png\)}/,
+  "should resolve the alias"
+);
+assert.match(
+  content,
+  /\.css-tilde{background:url\(logo\.(.*)\.png\)}/,
+  "should resolve ~ from node_modules"
+);
+assert.match(
+  content,
+  /\.css-remote{background:url\(https:\/\/a\.com\/a\.png\)}/,
+  "should keep the remote url"
+);
+assert.match(
+  content,
+  /\.css-data{background:url\(data:image\/png;base64,AAAA\)}/,
+  "should keep the data url"
+);
+assert.match(
+  content,
+  /\.css-hash{fill:url\(#gradient\)}/,
+  "should keep the hash url"
+);
+
+// less
+assert.match(
+  content,
+  /\.less-alias{background:url\(logo\.(.*)\.png\)}/,
+  "should resolve the alias in less"
+);
+assert.match(
+  content,
+  /\.less-tilde{background:url\(logo\.(.*)\.png\)}/,
+  "should resolve ~ from node_modules in less"
+);
+assert.match(
+  content,
+  /\.less-fragment{background:url\("?font\.(.*)\.eot#iefix"?\)}/,
+  "should keep the fragment in less"
+);
+assert.match(
+  content,
+  /\.less-remote{background:url\(\/\/a\.com\/a\.png\)}/,
+  "should keep the remote url in less"
+);
@@ -0,0 +1,2 @@
+import "./index.css";
+import "./index.less";
@@ -0,0 +1,10 @@
+const assert = require("assert");
+
+module.exports = (err) => {
+  assert(
+    err.stderr.includes(`CompileLessError`) &&
+      err.stderr.includes(`index.less`) &&
+      err.stderr.includes(`timed out after 100ms`),
+    "should throw timeout error"
+  );
+};
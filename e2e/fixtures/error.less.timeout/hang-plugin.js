@@ -0,0 +1,12 @@
+// simulate less hanging, e.g. on a recursive mixin
+module.exports = {
+  install(_less, pluginManager) {
+    pluginManager.addPreProcessor({
+      process(src) {
+        const end = Date.now() + 60 * 1000;
+        while (Date.now() < end) {}
+        return src;
+      },
+    });
+  },
+};
@@ -0,0 +1,31 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert.match(
+  content,
+  /console\.log\(1,\s*2,\s*10,\s*-1\)/,
+  "should inline the number members"
+);
+assert.match(
+  content,
+  /console\.log\("RED_BLUE",\s*"bar"\)/,
+  "should inline the string members through the barrel"
+);
+assert.doesNotMatch(
+  content,
+  moduleReg("src/enums.ts", ""),
+  "should remove the fully inlined enums module"
+);
+assert.doesNotMatch(
+  content,
+  moduleReg("src/kind.ts", ""),
+  "should remove the fully inlined re-exported enum module"
+);
+assert.match(
+  content,
+  moduleReg("src/dynamic.ts", 'High"?:\\s*"high"'),
+  "should keep the enum accessed dynamically"
+);
@@ -0,0 +1,2 @@
+export * from './enums';
+export { Kind as K } from './kind';
@@ -0,0 +1,12 @@
+export const enum Direction {
+  Up = 1,
+  Down,
+  Left = Up * 10,
+  Right = -Direction.Up,
+}
+
+export const enum Color {
+  Red = 'RED',
+  Blue = `BLUE`,
+  Purple = Red + '_' + Blue,
+}
@@ -0,0 +1,8 @@
+import { Color, Direction, K } from './barrel';
+import { Level } from './dynamic';
+
+console.log(Direction.Up, Direction.Down, Direction.Left, Direction.Right);
+console.log(Color.Purple, K['Bar']);
+
+const key = 'High' as const;
+console.log(Level.Low, Level[key]);
@@ -0,0 +1,4 @@
+export const enum Kind {
+  Foo = 'foo',
+  Bar = 'bar',
+}
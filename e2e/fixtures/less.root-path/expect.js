@@ -0,0 +1,9 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+assert.match(
+  files["index.css"],
+  /url\(["']?https:\/\/cdn\.example\.com\/assets\/images\/logo\.png["']?\)/,
+  "should prepend less rootPath to relative urls"
+);
@@ -0,0 +1,15 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+assert(
+  files['index.css'].endsWith('/*# sourceMappingURL=index.css.map*/'),
+  'should link to the source map file',
+);
+assert('index.css.map' in files, 'should have file: index.css.map');
+
+const map = JSON.parse(files['index.css.map']);
+assert(
+  map.sources.some((source) => source.endsWith('index.less')),
+  'should map to the less file',
+);
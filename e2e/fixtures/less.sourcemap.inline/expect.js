@@ -0,0 +1,16 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.css'];
+const match = content.match(
+  /\/\*# sourceMappingURL=data:application\/json;charset=utf-8;base64,(.+?)\*\/$/,
+);
+assert(match, 'should inline the source map as a data uri comment');
+assert(!('index.css.map' in files), 'should not have file: index.css.map');
+
+const map = JSON.parse(Buffer.from(match[1], 'base64').toString('utf-8'));
+assert(
+  map.sources.some((source) => source.endsWith('index.less')),
+  'should map to the less file',
+);
@@ -0,0 +1,10 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+assert.doesNotMatch(
+  files['index.css'],
+  /sourceMappingURL/,
+  'should not have source map comment',
+);
+assert(!('index.css.map' in files), 'should not have file: index.css.map');
@@ -0,0 +1,26 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const index = files["index.js"];
+assert(
+  index.includes("return sdk === void 0 ? sdk = "),
+  "the pure export should be initialized by its getter",
+);
+assert(
+  index.includes("const config = {"),
+  "the export used inside its module should be kept as is",
+);
+
+const { read } = require("./dist/index.js");
+const first = read();
+assert.deepEqual(first, {
+  config: { name: "config", items: [1, 2, 3] },
+  sdk: { id: "sdk" },
+  used: "config",
+});
+assert.strictEqual(
+  read().sdk,
+  first.sdk,
+  "the deferred export should be initialized only once",
+);
@@ -0,0 +1,5 @@
+import { config, sdk, used } from './utils';
+
+export function read() {
+  return { config, sdk, used: used() };
+}
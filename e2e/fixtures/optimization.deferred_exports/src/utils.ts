@@ -0,0 +1,12 @@
+export const config = { name: 'config', items: [1, 2, 3] };
+
+export const sdk = (function () {
+  const client = { id: 'sdk' };
+  return client;
+})();
+
+export const unused = { name: 'unused' };
+
+export function used() {
+  return config.name;
+}
@@ -0,0 +1,14 @@
+const assert = require("assert");
+const path = require("path");
+const { parseBuildResult, injectSimpleJest } = require("../../../scripts/test-utils");
+const { files, distDir } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(!content.includes(`formatDate: function() {`), "should mangle formatDate");
+assert(!content.includes(`parseDate: function() {`), "should mangle parseDate");
+assert(!content.includes(`DateRange: function() {`), "should mangle DateRange");
+assert(content.includes(`nsValue: function() {`), "should not mangle namespace imported exports");
+
+injectSimpleJest();
+require(path.join(distDir, "index.js"));
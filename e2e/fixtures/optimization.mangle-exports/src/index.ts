@@ -0,0 +1,13 @@
+import { formatDate, parseDate as parse, DateRange } from './utils';
+import { defaultLocale } from './reexport';
+import * as ns from './ns';
+
+it('mangled exports should work', () => {
+  expect(formatDate(parse('2024-01-02'))).toEqual('2024-01-02');
+  expect(new DateRange(1, 3).length()).toEqual(2);
+  expect(defaultLocale).toEqual('en-US');
+});
+
+it('namespace imported exports should keep their names', () => {
+  expect(Object.keys(ns).sort()).toEqual(['nsValue', 'otherValue']);
+});
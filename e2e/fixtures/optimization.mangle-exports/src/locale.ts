@@ -0,0 +1,3 @@
+const locale = 'en-US';
+
+export { locale as defaultLocale };
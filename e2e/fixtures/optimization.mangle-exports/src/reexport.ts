@@ -0,0 +1 @@
+export { defaultLocale } from './locale';
@@ -0,0 +1,16 @@
+export function formatDate(date: Date) {
+  return date.toISOString().slice(0, 10);
+}
+
+export const parseDate = (value: string) => new Date(value);
+
+export class DateRange {
+  constructor(
+    public start: number,
+    public end: number,
+  ) {}
+
+  length() {
+    return this.end - this.start;
+  }
+}
@@ -0,0 +1,17 @@
+const assert = require("assert");
+const path = require("path");
+const { pathToFileURL } = require("url");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files, distDir } = parseBuildResult(__dirname);
+
+assert(files["index.js"], "should emit the entry");
+assert(files["utils/math.js"], "should mirror nested modules");
+assert(files["utils/format.js"], "should mirror nested modules");
+assert(!files["index.js"].includes("__mako_require__"), "should not inject runtime");
+assert(!files["utils/math.js"].includes("function sub"), "should shake unused statements");
+
+module.exports = async () => {
+  const lib = await import(pathToFileURL(path.join(distDir, "index.js")).href);
+  assert.strictEqual(lib.add(1, 2), 3);
+  assert.strictEqual(lib.format(1, 2), "1 + 2 = 3");
+};
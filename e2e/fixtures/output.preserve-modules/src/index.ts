@@ -0,0 +1,4 @@
+import { add } from './utils/math';
+
+export { add };
+export * from './utils/format';
@@ -0,0 +1,3 @@
+import { add } from './math';
+
+export const format = (a: number, b: number) => `${a} + ${b} = ${add(a, b)}`;
@@ -0,0 +1,8 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes(`"dev-build"`), `mode should be read from config`);
+assert(!content.includes(`"prod-build"`), `mode should be read from config`);
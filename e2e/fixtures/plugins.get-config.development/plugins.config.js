@@ -0,0 +1,2 @@
+// the same plugin as plugins.get-config, which builds in production mode
+module.exports = require('../plugins.get-config/plugins.config.js');
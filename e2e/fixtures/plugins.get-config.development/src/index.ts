@@ -0,0 +1,3 @@
+import mode from './mode.virtual';
+
+console.log(mode);
@@ -0,0 +1,11 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert(content.includes(`"prod-build"`), `mode should be read from config`);
+assert(!content.includes(`"dev-build"`), `mode should be read from config`);
+assert(content.includes(`"dist"`), `output.path should be read from config`);
+assert(content.includes(`"plugins.get-config"`), `root should be read from config`);
+assert(content.includes(`"filtered"`), `process.env defines should be filtered`);
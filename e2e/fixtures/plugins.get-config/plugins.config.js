@@ -0,0 +1,31 @@
+const path = require('path');
+
+module.exports = [
+  {
+    async load(filePath) {
+      if (!filePath.endsWith('.virtual')) {
+        return;
+      }
+      const config = this.getConfig();
+      let value;
+      switch (path.basename(filePath)) {
+        case 'mode.virtual':
+          value = config.mode === 'production' ? 'prod-build' : 'dev-build';
+          break;
+        case 'output-path.virtual':
+          value = path.basename(config.output.path);
+          break;
+        case 'root.virtual':
+          value = path.basename(config.root);
+          break;
+        case 'has-secret.virtual':
+          value = 'process.env.SECRET' in config.define ? 'leaked' : 'filtered';
+          break;
+      }
+      return {
+        content: `export default ${JSON.stringify(value)};`,
+        type: 'js',
+      };
+    },
+  },
+];
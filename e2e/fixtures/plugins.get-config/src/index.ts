@@ -0,0 +1,6 @@
+import mode from './mode.virtual';
+import outputPath from './output-path.virtual';
+import hasSecret from './has-secret.virtual';
+import root from './root.virtual';
+
+console.log(mode, outputPath, hasSecret, root);
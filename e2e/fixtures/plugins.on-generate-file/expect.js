@@ -0,0 +1,11 @@
+const assert = require("assert");
+const fs = require("fs");
+const path = require("path");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files, distDir } = parseBuildResult(__dirname);
+
+assert(files["index.js"].includes(`from "./icon.png"`), "should import the copied asset");
+assert(
+  fs.readFileSync(path.join(distDir, "icon.png")).equals(fs.readFileSync(path.join(__dirname, "src/icon.png"))),
+  "should write the binary asset as is",
+);
@@ -0,0 +1,23 @@
+const assert = require('assert');
+const fs = require('fs');
+const path = require('path');
+
+const icon = fs.readFileSync(path.join(__dirname, 'src/icon.png'));
+let generated = false;
+
+module.exports = [
+  {
+    name: 'on-generate-file',
+    _onGenerateFile({ path: filePath, content }) {
+      assert(Buffer.isBuffer(content), `content of ${filePath} should be a Buffer`);
+      if (filePath.endsWith('icon.png')) {
+        // the binary files are passed as is, not as lossy utf-8 strings
+        assert(content.equals(icon), 'content of icon.png should be kept as is');
+        generated = true;
+      }
+    },
+    writeBundle() {
+      assert(generated, '_onGenerateFile should be called with icon.png');
+    },
+  },
+];
@@ -0,0 +1,3 @@
+import icon from './icon.png';
+
+export { icon };
@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const pluginHooks = JSON.parse(files["plugin-hooks.json"]);
+const find = (plugin, hook) =>
+  pluginHooks.find((stats) => stats.plugin === plugin && stats.hook === hook);
+
+const active = find("active", "load");
+const passive = find("passive", "load");
+assert(active && passive, `load of both plugins should be recorded`);
+assert.strictEqual(active.calls, passive.calls, `both plugins should see every module`);
+assert.strictEqual(active.returned, 1, `active plugin should load the virtual module`);
+assert.strictEqual(passive.returned, 0, `passive plugin should pass through`);
+assert(active.ms >= 0 && passive.ms >= 0, `time should be recorded`);
+assert(!find("active", "generateEnd"), `stats are taken before generateEnd`);
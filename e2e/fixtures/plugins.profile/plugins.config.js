@@ -0,0 +1,26 @@
+const fs = require('fs');
+const path = require('path');
+
+module.exports = [
+  {
+    name: 'passive',
+    async load() {},
+  },
+  {
+    name: 'active',
+    async load(filePath) {
+      if (filePath.endsWith('.virtual')) {
+        return {
+          content: `export default "virtual";`,
+          type: 'js',
+        };
+      }
+    },
+    generateEnd(params) {
+      fs.writeFileSync(
+        path.join(__dirname, 'dist', 'plugin-hooks.json'),
+        JSON.stringify(params.stats.pluginHooks),
+      );
+    },
+  },
+];
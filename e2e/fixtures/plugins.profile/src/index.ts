@@ -0,0 +1,3 @@
+import foo from './foo.virtual';
+
+console.log(foo);
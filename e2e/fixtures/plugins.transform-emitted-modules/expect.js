@@ -0,0 +1,11 @@
+const assert = require("assert");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const js = files["index.js"];
+const css = files["index.css"];
+
+assert(js.includes(`return 'button';`), `primary module from transform works`);
+assert(!js.includes(`<style>`), `style is extracted from primary module`);
+assert(css.includes(`.button`), `emitted css module is chunked into index.css`);
+assert(css.includes(`color: red`), `emitted css module content works`);
@@ -0,0 +1,31 @@
+const fs = require('fs');
+
+module.exports = [
+  {
+    async load(path) {
+      if (path.endsWith('.sfc')) {
+        return {
+          content: fs.readFileSync(path, 'utf-8'),
+          type: 'js',
+        };
+      }
+    },
+    async transform(content, path) {
+      if (path.endsWith('.sfc')) {
+        const script = content.match(/<script>([\s\S]*?)<\/script>/)[1];
+        const style = content.match(/<style>([\s\S]*?)<\/style>/)[1];
+        return {
+          content: script,
+          type: 'js',
+          emittedModules: [
+            {
+              id: './Button.sfc.css',
+              content: style,
+              type: 'css',
+            },
+          ],
+        };
+      }
+    },
+  },
+];
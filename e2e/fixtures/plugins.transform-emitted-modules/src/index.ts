@@ -0,0 +1,3 @@
+import Button from './Button.sfc';
+
+console.log(Button());
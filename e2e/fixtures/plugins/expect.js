@@ -11,6 +11,7 @@ assert(content.includes(`children: ".hoo"`), `relative plugin works`);
 
 // resolve_id hook
 assert(content.includes(`resolve_id mocked`), `resolve_id hook works`);
+assert(content.includes(`resolve_id mocked from_resolve_id`), `meta of resolve_id is passed to transform`);
 assert(content.includes(`module.exports = resolve_id_external;`), `resolve_id hook with external works`);
 
 // transform hook
@@ -30,6 +30,7 @@ module.exports = [
         return {
           id: require("path").join(__dirname, "resolve_id_mock.js"),
           external: false,
+          meta: { flavor: "from_resolve_id" },
         };
       }
       if (source === "resolve_id_external") {
@@ -49,6 +50,17 @@ module.exports = [
       }
     },
   },
+  {
+    async transform(code, id) {
+      if (id.endsWith("resolve_id_mock.js")) {
+        const { flavor } = this.getModuleMeta(id);
+        return {
+          content: code.replace("mocked", `mocked ${flavor}`),
+          type: "js",
+        };
+      }
+    },
+  },
   {
     enforce: "pre",
     async transform(code, id) {
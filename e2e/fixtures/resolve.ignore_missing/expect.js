@@ -0,0 +1,15 @@
+const assert = require("assert");
+
+module.exports = async () => {
+  const { optional, empty, target, load } = require('./dist');
+
+  // resolve.ignoreMissing
+  assert(optional instanceof Error, `missing optional dep throws when required`);
+  assert.strictEqual(optional.message, "Cannot find module 'optional-supports-color'");
+  assert.strictEqual(optional.code, 'MODULE_NOT_FOUND');
+  await assert.rejects(load(), /Cannot find module 'optional-lazy'/, `dynamic import of missing optional dep rejects`);
+
+  // resolve.fallback
+  assert.strictEqual(typeof empty, 'object', `false fallback is an empty module`);
+  assert.strictEqual(target, 'target', `fallback resolves the target`);
+};
@@ -0,0 +1,13 @@
+import target from 'missing-target';
+
+let supportsColor;
+try {
+  supportsColor = require('optional-supports-color');
+} catch (e) {
+  supportsColor = e;
+}
+
+export const optional = supportsColor;
+export const empty = require('missing-empty');
+export { target };
+export const load = () => import('optional-lazy');
@@ -0,0 +1,95 @@
+const assert = require("assert");
+const vm = require("vm");
+const { parseBuildResult } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+// a minimal dom which fails the first `failures[ext]` loads of js and css chunks
+function createHarness() {
+  const failures = { js: 0, css: 0 };
+  const requests = [];
+  const events = [];
+  const head = {
+    appendChild(el) {
+      el.parentNode = head;
+      setTimeout(() => load(el));
+    },
+    insertBefore(el) {
+      this.appendChild(el);
+    },
+    removeChild() {},
+  };
+  const document = {
+    head,
+    createElement: (tagName) => ({ tagName }),
+    querySelector: () => null,
+    querySelectorAll: () => [],
+  };
+  class CustomEvent {
+    constructor(type, init) {
+      this.type = type;
+      this.detail = init.detail;
+    }
+  }
+  const context = vm.createContext({
+    document,
+    CustomEvent,
+    URL,
+    setTimeout,
+    clearTimeout,
+    console,
+    dispatchEvent: (event) => events.push(event),
+  });
+  context.self = context.window = context;
+
+  function load(el) {
+    const url = el.src || el.href;
+    const file = url.split("?")[0].replace(/^\//, "");
+    const ext = file.split(".").pop();
+    requests.push(url);
+    if (failures[ext] > 0 || !files[file]) {
+      failures[ext]--;
+      el.onerror({ type: "error", target: el });
+      return;
+    }
+    if (ext === "js") vm.runInContext(files[file], context);
+    el.onload({ type: "load", target: el });
+  }
+
+  vm.runInContext(files["index.js"], context);
+  return { context, failures, requests, events };
+}
+
+module.exports = async () => {
+  const { context, failures, requests, events } = createHarness();
+
+  // recovers after failed loads of both the js and css chunk
+  failures.js = 2;
+  failures.css = 1;
+  const a = await context.loadA();
+  assert.strictEqual(a.default, "a");
+  const jsRequests = requests.filter((url) => /\.js(\?|$)/.test(url));
+  const cssRequests = requests.filter((url) => /\.css(\?|$)/.test(url));
+  assert.strictEqual(jsRequests.length, 3, "js chunk should be retried twice");
+  assert.strictEqual(cssRequests.length, 2, "css chunk should be retried once");
+  assert(!jsRequests[0].includes("?"), "first load should not be cache busted");
+  assert(
+    jsRequests.slice(1).every((url) => url.includes("?mako_retry=")),
+    "retries should be cache busted",
+  );
+  assert.strictEqual(events.length, 0);
+
+  // rejects with the typed error after the retries are exhausted
+  requests.length = 0;
+  failures.js = Infinity;
+  const error = await context.loadB().then(
+    () => assert.fail("should fail to load chunk b"),
+    (e) => e,
+  );
+  assert.strictEqual(error.name, "ChunkLoadError");
+  assert.strictEqual(error.urls.length, 3);
+  assert.deepStrictEqual([...error.urls], requests);
+  assert.strictEqual(events.length, 1);
+  assert.strictEqual(events[0].type, "mako:chunk-load-error");
+  assert.strictEqual(events[0].detail, error);
+  assert(events[0].detail.chunkId, "event should expose the chunk id");
+};
@@ -0,0 +1,2 @@
+globalThis.loadA = () => import('./a');
+globalThis.loadB = () => import('./b');
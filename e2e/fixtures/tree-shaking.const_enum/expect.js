@@ -0,0 +1,26 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert.match(
+  content,
+  /console\.log\("status-active",\s*"local-a"/,
+  "should inline the const enum members"
+);
+assert.doesNotMatch(
+  content,
+  /status-inactive/,
+  "should remove the inlined const enum declaration"
+);
+assert.doesNotMatch(
+  content,
+  /local-b/,
+  "should remove the inlined local const enum declaration"
+);
+assert.match(
+  content,
+  moduleReg("src/status.ts", "regular-unused"),
+  "should keep all the members of a regular enum"
+);
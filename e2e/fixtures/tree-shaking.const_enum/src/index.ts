@@ -0,0 +1,8 @@
+import { Regular, Status } from './status';
+
+const enum Local {
+  A = 'local-a',
+  B = 'local-b',
+}
+
+console.log(Status.Active, Local.A, Regular.Used);
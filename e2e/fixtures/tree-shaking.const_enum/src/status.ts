@@ -0,0 +1,9 @@
+export const enum Status {
+  Active = 'status-active',
+  Inactive = 'status-inactive',
+}
+
+export enum Regular {
+  Used = 'regular-used',
+  Unused = 'regular-unused',
+}
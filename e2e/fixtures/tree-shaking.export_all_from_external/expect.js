@@ -0,0 +1,18 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.doesNotMatch(
+  content,
+  /unused-export/,
+  'should still shake the local exports of the barrel',
+);
+
+const { result } = require('./dist');
+assert.equal(
+  result,
+  'used:from-external',
+  'export * from an external module should be kept',
+);
@@ -0,0 +1,4 @@
+export * from 'ext-lib';
+
+export const used = 'used';
+export const unused = 'unused-export';
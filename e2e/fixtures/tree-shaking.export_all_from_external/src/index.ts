@@ -0,0 +1,3 @@
+import { fromExternal, used } from './barrel';
+
+export const result = `${used}:${fromExternal}`;
@@ -0,0 +1,21 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.match(
+  content,
+  /kept-a/,
+  'should keep the destructuring which declares a used binding',
+);
+assert.doesNotMatch(
+  content,
+  /unused-c/,
+  'should remove the pure destructuring whose bindings are all unused',
+);
+assert.match(
+  content,
+  /kept-fallback/,
+  'should keep the destructuring whose default value has side effects',
+);
@@ -0,0 +1,7 @@
+const config = { a: 'kept-a', b: 'kept-b' };
+
+export const { a, b } = config;
+
+export const { c, d } = { c: 'unused-c', d: 'unused-d' };
+
+export const { e = (window.__fallback__ = 'kept-fallback') } = {} as any;
@@ -0,0 +1,3 @@
+import { a } from './destructured';
+
+console.log(a);
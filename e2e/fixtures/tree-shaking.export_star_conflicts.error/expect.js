@@ -0,0 +1,10 @@
+const assert = require("assert");
+
+module.exports = (err) => {
+  assert(
+    err.stderr.includes(
+      `The requested module './dep' contains conflicting star exports for name 'foo'`
+    ),
+    "should throw error when importing a name conflicted between star exports"
+  );
+};
@@ -0,0 +1,3 @@
+import { foo, bar } from './dep';
+
+console.log(foo, bar);
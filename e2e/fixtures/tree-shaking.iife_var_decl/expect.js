@@ -0,0 +1,16 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.doesNotMatch(
+  content,
+  /unused-sdk/,
+  'should remove the unused binding initialized by a pure iife',
+);
+assert.match(
+  content,
+  /kept-tracker/,
+  'should keep the iife which writes to window',
+);
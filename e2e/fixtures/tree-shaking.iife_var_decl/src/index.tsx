@@ -0,0 +1,3 @@
+import { used } from './sdk';
+
+console.log(used);
@@ -0,0 +1,12 @@
+export const used = 'used';
+
+export const sdk = (function () {
+  const client = { name: 'unused-sdk', plugins: [] as string[] };
+  client.plugins.push('core');
+  return client;
+})();
+
+export const tracker = (function () {
+  window.__tracker__ = 'kept-tracker';
+  return {};
+})();
@@ -0,0 +1,22 @@
+const assert = require('assert');
+const { parseBuildResult } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.match(
+  content,
+  /kept-analytics/,
+  'should keep the statement annotated with KEEP',
+);
+assert.match(
+  content,
+  /kept-start/,
+  'should keep what the statement annotated with KEEP depends on',
+);
+assert.doesNotMatch(
+  content,
+  /dropped-track/,
+  'should remove the statement annotated with DROPPABLE',
+);
+assert.match(content, /1\.0\.0/);
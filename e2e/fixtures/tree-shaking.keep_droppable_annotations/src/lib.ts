@@ -0,0 +1,9 @@
+function start() {
+  return 'kept-start';
+}
+
+/*#__KEEP__*/ const analytics = { id: 'kept-analytics', start };
+
+/*#__DROPPABLE__*/ (window as any).__tracked__ = 'dropped-track';
+
+export const version = '1.0.0';
@@ -0,0 +1,20 @@
+const assert = require('assert');
+const path = require('path');
+const {
+  parseBuildResult,
+  injectSimpleJest,
+} = require('../../../scripts/test-utils');
+const { files, distDir } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.match(content, /let count = 0/, 'should keep the reassigned binding');
+assert.match(
+  content,
+  /count = init\(\)/,
+  'should keep the reassignment in the self-executed block of a module without side effects',
+);
+assert.match(content, /function init\(\)/);
+
+injectSimpleJest();
+require(path.join(distDir, 'index.js'));
@@ -0,0 +1,10 @@
+export let count = 0;
+
+{
+  count = init();
+}
+
+function init() {
+  console.log('init');
+  return 1;
+}
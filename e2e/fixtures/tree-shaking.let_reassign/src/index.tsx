@@ -0,0 +1,5 @@
+import { count } from './counter';
+
+it('should keep the reassignment of the let export', () => {
+  expect(count).toBe(1);
+});
@@ -0,0 +1,22 @@
+const assert = require('assert');
+const { parseBuildResult, moduleReg } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.doesNotMatch(
+  content,
+  moduleReg('src/store.ts', ''),
+  'should remove the module of the unused annotated default function',
+);
+assert.doesNotMatch(
+  content,
+  moduleReg('src/logger.ts', ''),
+  'should remove the module of the unused annotated default arrow function',
+);
+assert.doesNotMatch(
+  content,
+  /createStore|createLogger/,
+  'should remove the calls whose results are not used',
+);
+assert.match(content, /console.log\(["']index["']\)/);
@@ -0,0 +1,7 @@
+import createStore from './store';
+import createLogger from './logger';
+
+const store = createStore();
+createLogger('unused');
+
+console.log('index');
@@ -0,0 +1,4 @@
+export default /*#__NO_SIDE_EFFECTS__*/ (name: string) => {
+  console.log('createLogger');
+  return name;
+};
@@ -0,0 +1,4 @@
+export default /*#__NO_SIDE_EFFECTS__*/ function createStore() {
+  console.log('createStore');
+  return {};
+}
@@ -0,0 +1,17 @@
+const assert = require('assert');
+const { parseBuildResult, moduleReg } = require('../../../scripts/test-utils');
+const { files } = parseBuildResult(__dirname);
+
+const content = files['index.js'];
+
+assert.doesNotMatch(
+  content,
+  moduleReg('src/lib.ts', ''),
+  'should remove the module of unused annotated functions',
+);
+assert.doesNotMatch(
+  content,
+  /createStore|createLogger/,
+  'should remove the calls whose results are not used',
+);
+assert.match(content, /console.log\(["']index["']\)/);
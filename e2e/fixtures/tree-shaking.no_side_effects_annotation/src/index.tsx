@@ -0,0 +1,6 @@
+import { createLogger, createStore } from './lib';
+
+createStore();
+createLogger('unused');
+
+console.log('index');
@@ -0,0 +1,10 @@
+export const createStore = /*#__NO_SIDE_EFFECTS__*/ () => {
+  console.log('createStore');
+  return {};
+};
+
+/*#__NO_SIDE_EFFECTS__*/
+export function createLogger(name: string) {
+  console.log('createLogger');
+  return name;
+}
@@ -0,0 +1,16 @@
+const assert = require("assert");
+const { parseBuildResult, moduleReg } = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const content = files["index.js"];
+
+assert.match(
+  content,
+  moduleReg("src/greet.ts", "function greet\\("),
+  "should keep the default export re-exported twice"
+);
+assert.doesNotMatch(
+  content,
+  moduleReg("src/greet.ts", "function unusedHelper\\("),
+  "should remove the unused export"
+);
@@ -0,0 +1,7 @@
+export default function greet(name: string) {
+  return `hello ${name}`;
+}
+
+export function unusedHelper() {
+  return 'should be removed';
+}
@@ -0,0 +1,3 @@
+import greet from './reexport';
+
+console.log(greet('mako'));
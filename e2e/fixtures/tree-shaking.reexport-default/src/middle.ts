@@ -0,0 +1 @@
+export { default, unusedHelper } from './greet';
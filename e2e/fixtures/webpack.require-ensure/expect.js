@@ -0,0 +1,35 @@
+const assert = require("assert");
+const {
+  parseBuildResult,
+  moduleDefinitionOf,
+} = require("../../../scripts/test-utils");
+const { files } = parseBuildResult(__dirname);
+
+const index = files["index.js"];
+assert(
+  /Promise\.all\(\[\s*__mako_require__\.ensure\("src\/a\.ts"\)/.test(index) &&
+    !index.includes("require.ensure("),
+  "require.ensure should be converted to a dynamic import",
+);
+assert(
+  Object.keys(files).some((file) => file.startsWith("src_a_ts-async")),
+  "the dependency of require.ensure should be split into an async chunk",
+);
+assert(
+  Object.keys(files).some((file) => file.startsWith("src_b_ts-async")),
+  "the module required in the callback of require.ensure should be split into an async chunk",
+);
+assert(
+  !index.includes(moduleDefinitionOf("src/a.ts")) &&
+    !index.includes(moduleDefinitionOf("src/b.ts")),
+  "the modules required in the callback should not be in the entry chunk",
+);
+
+module.exports = async () => {
+  const { ready } = require("./dist/index.js");
+  assert.deepEqual(
+    await ready,
+    ["sync", "a", "b"],
+    "the callback should run after the dependencies are loaded",
+  );
+};
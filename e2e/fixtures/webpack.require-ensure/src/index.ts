@@ -0,0 +1,12 @@
+const loaded: string[] = [];
+
+export const ready = new Promise((resolve) => {
+  require.ensure(['./a'], function (require) {
+    loaded.push(require('./a').default);
+    require.ensure([], function (require) {
+      loaded.push(require('./b').default);
+      resolve(loaded);
+    });
+  });
+  loaded.push('sync');
+});
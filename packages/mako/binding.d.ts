@@ -8,10 +8,13 @@ export interface JsHooks {
   enforce?: string;
   load?: (
     filePath: string,
-  ) => Promise<{ content: string; type: 'css' | 'js' } | void> | void;
+  ) => Promise<
+    { content: string; type: 'css' | 'js'; resolveBase?: string } | void
+  > | void;
   loadInclude?: (filePath: string) => Promise<bool> | bool;
   generateEnd?: (data: {
     isFirstCompile: boolean;
+    buildId: number;
     time: number;
     stats: {
       hash: number;
@@ -29,6 +32,7 @@ export interface JsHooks {
         string,
         { id: string; dependents: string[]; dependencies: string[] }
       >;
+      usedExports: Record<string, string[]>;
       chunks: {
         type: string;
         id: string;
@@ -43,6 +47,15 @@ export interface JsHooks {
           loc: string;
           request: string;
         }[];
+        linkHints: { rel: 'prefetch' | 'preload'; chunkId: string }[];
+      }[];
+      packages: {
+        name: string;
+        version: string;
+        size: number;
+        modules: string[];
+        duplicated: boolean;
+        chain: string[];
       }[];
       entrypoints: Record<string, { name: string; chunks: string[] }>;
       rscClientComponents: { path; string; moduleId: string }[];
@@ -50,11 +63,57 @@ export interface JsHooks {
       startTime: number;
       endTime: number;
     };
+    rebuild:
+      | {
+          addedModules: string[];
+          modifiedModules: string[];
+          removedModules: string[];
+          chunks: {
+            chunkId: string;
+            oldFilename: string | null;
+            newFilename: string | null;
+          }[];
+          assets: {
+            path: string;
+            oldFilename: string | null;
+            newFilename: string | null;
+          }[];
+          timing: { update: number; generateHotUpdate: number; emit: number };
+        }
+      | null;
+    buildStats: {
+      resolve: { ms: number; count: number };
+      load: { ms: number; count: number };
+      transform: { ms: number; count: number };
+      treeShake: { ms: number; count: number };
+      generate: { ms: number; count: number };
+    } | null;
   }) => void;
+  generateBundle?: (
+    chunks: {
+      fileName: string;
+      code: string;
+      map: string | null;
+      type: 'js' | 'css';
+    }[],
+  ) => Promise<
+    | {
+        fileName: string;
+        code: string;
+        map?: string | null;
+        type: 'js' | 'css';
+      }[]
+    | void
+  > | void;
   writeBundle?: () => Promise<void>;
   watchChanges?: (
     id: string,
-    change: { event: 'create' | 'delete' | 'update' },
+    change: {
+      event: 'create' | 'delete' | 'update' | 'rename';
+      from: string | null;
+      to: string | null;
+      importers: string[];
+    },
   ) => Promise<void> | void;
   onGenerateFile?: (path: string, content: Buffer) => Promise<void>;
   buildStart?: () => Promise<void>;
@@ -63,13 +122,57 @@ export interface JsHooks {
     source: string,
     importer: string,
     { isEntry: bool },
-  ) => Promise<{ id: string }>;
+  ) => Promise<{ id: string; meta?: any }>;
+  filterExternals?: (
+    source: string,
+    importer: string,
+  ) => Promise<bool | void> | bool | void;
+  resolveDynamicImport?: (
+    specifier: string,
+    importer: string,
+  ) => Promise<string | void> | string | void;
+  onResolvePackageExports?: (
+    pkgDir: string,
+    subpath: string,
+    conditions: string[],
+  ) => Promise<string | void> | string | void;
+  resolveFileUrl?: (
+    source: string,
+    importer: string,
+  ) => Promise<string | void> | string | void;
   transform?: (
     content: string,
     path: string,
-  ) => Promise<{ content: string; type: 'css' | 'js' } | void> | void;
+  ) => Promise<
+    | {
+        content: string;
+        type: 'css' | 'js';
+        emittedModules?: {
+          id: string;
+          content: string;
+          type: 'css' | 'js';
+        }[];
+      }
+    | void
+  > | void;
   transformInclude?: (filePath: string) => Promise<bool> | bool;
+  transformError?: (
+    error: string,
+    path: string,
+  ) => Promise<
+    { content: string; type: 'css' | 'js'; resolveBase?: string } | void
+  > | void;
   beforeRebuild?: (paths: string[]) => Promise<string[] | void> | void;
+  banner?: (chunk: {
+    name: string;
+    fileName: string;
+    type: 'js' | 'css';
+  }) => Promise<string | void> | string | void;
+  footer?: (chunk: {
+    name: string;
+    fileName: string;
+    type: 'js' | 'css';
+  }) => Promise<string | void> | string | void;
 }
 export interface WriteFile {
   path: string;
@@ -78,13 +181,18 @@ export interface WriteFile {
 export interface LoadResult {
   content: string;
   type: string;
+  resolveBase: string | null;
 }
 export interface WatchChangesParams {
   event: string;
+  from: string | null;
+  to: string | null;
+  importers: Array<string>;
 }
 export interface ResolveIdResult {
   id: string;
   external: boolean | null;
+  meta: any | null;
 }
 export interface ResolveIdParams {
   isEntry: boolean;
@@ -92,11 +200,40 @@ export interface ResolveIdParams {
 export interface TransformResult {
   content: string;
   type: string;
+  emittedModules: Array<EmittedModule> | null;
+}
+export interface EmittedModule {
+  id: string;
+  content: string;
+  type: string;
+}
+export interface ChunkInfo {
+  name: string;
+  fileName: string;
+  type: string;
+}
+export interface BundleChunk {
+  fileName: string;
+  code: string;
+  map: string | null;
+  type: 'js' | 'css';
+}
+export interface EmitFileMeta {
+  sideEffects?: boolean;
+}
+export interface ChunkPreloadHint {
+  rel: 'prefetch' | 'preload';
+  chunkId: string;
+  parentChunkId: string;
+  href: string;
+  as: 'script' | 'style';
 }
 export interface BuildParams {
   root: string;
   config: {
-    entry?: Record<string, string>;
+    entry?:
+      | Record<string, string | { import: string; filename?: string; banner?: string; footer?: string }>
+      | { pattern: string; name?: string };
     output?: {
       path: string;
       mode: 'bundle' | 'bundless';
@@ -105,10 +242,19 @@ export interface BuildParams {
       preserveModules?: boolean;
       preserveModulesRoot?: string;
       skipWrite?: boolean;
+      inlineDynamicImports?: boolean;
+      banner?: string;
+      footer?: string;
+      legacyBrowsers?: boolean;
     };
     resolve?: {
       alias?: Array<[string, string]>;
       extensions?: string[];
+      modules?: string[];
+      browserField?: boolean;
+      fallback?: Record<string, string | false | string[]>;
+      ignoreMissing?: string[];
+      symlinks?: boolean;
     };
     manifest?:
       | false
@@ -116,9 +262,25 @@ export interface BuildParams {
           fileName: string;
           basePath: string;
         };
+    html?:
+      | boolean
+      | {
+          entry: string;
+          template?: string;
+          filename?: string;
+          inject?: 'head' | 'body';
+          publicPath?: string;
+          title?: string;
+        }[];
     mode?: 'development' | 'production';
     define?: Record<string, string>;
+    envFile?: string[];
     devtool?: false | 'source-map' | 'inline-source-map';
+    sourceMap?: {
+      sourcesContent?: boolean;
+      sourceRoot?: string;
+      pathTransform?: 'relative' | 'module-id';
+    };
     externals?: Record<
       string,
       | string
@@ -182,10 +344,13 @@ export interface BuildParams {
           selectorDoubleList?: string[];
           mediaQuery?: boolean;
         };
+    analyze?: {} | boolean;
     stats?:
       | false
       | {
           modules?: boolean;
+          duplicatePackageThreshold?: number;
+          shakeMetadata?: string;
         };
     hash?: boolean;
     autoCSSModules?: boolean;
@@ -203,7 +368,10 @@ export interface BuildParams {
     clean?: boolean;
     nodePolyfill?: boolean;
     ignores?: string[];
-    moduleIdStrategy?: 'hashed' | 'named';
+    noSideEffectModules?: string[];
+    forceSideEffects?: string[];
+    moduleIdStrategy?: 'hashed' | 'named' | 'numeric';
+    moduleWrapper?: 'factory' | 'commonjs' | 'none';
     minify?: boolean;
     _minifish?:
       | false
@@ -239,6 +407,12 @@ export interface BuildParams {
       | {
           skipModules?: boolean;
           concatenateModules?: boolean;
+          mangleExports?: boolean;
+          treeShaking?: 'modules' | 'statements' | false;
+          ambiguousExportsThreshold?: number;
+          deferredExports?: boolean;
+          minifyWorkers?: number;
+          cssDedupe?: boolean;
         };
     react?: {
       runtime?: 'automatic' | 'classic';
@@ -247,6 +421,9 @@ export interface BuildParams {
       pragmaFrag?: string;
     };
     emitAssets?: boolean;
+    profile?: boolean;
+    profilePlugins?: boolean;
+    svgr?: boolean;
     cssModulesExportOnlyLocales?: boolean;
     inlineCSS?: false | {};
     rscServer?:
@@ -260,6 +437,15 @@ export interface BuildParams {
       | {
           logServerComponent: 'error' | 'ignore';
         };
+    runtime?: {
+      chunkRetry?:
+        | false
+        | {
+            times: number;
+            delayMs: number;
+            cacheBust?: boolean;
+          };
+    };
     moduleFederation?: {
       name: string;
       filename?: string;
@@ -289,19 +475,56 @@ export interface BuildParams {
       rustPlugins?: Array<[string, any]>;
       centralEnsure?: bool;
       importsChecker?: bool;
+      lazyCompilation?: bool;
     };
     watch?: {
       ignoredPaths?: string[];
       _nodeModulesRegexes?: string[];
+      debounce?: number;
+    };
+    debug?: {
+      dumpModuleGraph?: boolean;
+      dumpStatementGraph?: string;
     };
     caseSensitiveCheck?: boolean;
+    lint?: {
+      circularRequire?: 'warn' | 'error' | 'off';
+      circularDependency?: 'warn' | 'error' | 'off';
+    };
+    performance?: {
+      maxChunkSize?: number;
+      maxEntrypointSize?: number;
+    };
   };
   plugins: Array<JsHooks>;
+  builtinPlugins?: Array<string>;
   watch: boolean;
 }
-export declare function build(buildParams: BuildParams): Promise<void>;
+export interface CompileResult {
+  outputFiles: Array<OutputFile>;
+  warnings: Array<Diagnostic>;
+  stats: any | null;
+}
+export interface OutputFile {
+  path: string;
+  size: number;
+}
+export interface Diagnostic {
+  message: string;
+}
+export declare function build(buildParams: BuildParams): Promise<CompileResult>;
 export class PluginContext {
   warn(msg: string): void;
   error(msg: string): void;
-  emitFile(originPath: string, outputPath: string): void;
+  emitFile(
+    originPath: string,
+    outputPath: string,
+    meta?: EmitFileMeta | undefined | null,
+  ): void;
+  getConfig(): any;
+  getModuleMeta(id: string): any | null;
+  logTiming(label: string, ms: number): void;
+  registerJsExtension(ext: string): void;
+  chunkPreloadHints(): Array<ChunkPreloadHint>;
+  reportError(message: string, stack?: string | undefined | null): void;
 }
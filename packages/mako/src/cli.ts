@@ -25,6 +25,7 @@ import { check } from './checker';
         root,
         config: {
           mode: argv.mode || 'development',
+          ...(argv.analyze ? { analyze: {} } : {}),
         },
         plugins: [],
         watch,
@@ -70,6 +71,7 @@ function showBuildHelp() {
   console.log(``);
   console.log(`Options:`);
   console.log(`  --help,-h`);
+  console.log(`  --analyze`);
   console.log(`  --root`);
   console.log(`  --watch,-w`);
   console.log(``);
@@ -77,4 +79,5 @@ function showBuildHelp() {
   console.log(`  mako build`);
   console.log(`  mako build --watch`);
   console.log(`  mako build --root ./src`);
+  console.log(`  mako build --analyze`);
 }
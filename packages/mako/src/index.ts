@@ -24,6 +24,7 @@ type BuildParams = {
   config: Config;
   root: binding.BuildParams['root'];
   watch: binding.BuildParams['watch'];
+  builtinPlugins?: binding.BuildParams['builtinPlugins'];
 };
 
 export { BuildParams };
@@ -249,6 +250,8 @@ export async function build(params: BuildParams) {
                 name?: string;
                 fileName?: string;
                 source?: string | Uint8Array;
+                // mako only, e.g. mark the asset as free of side effects in stats
+                sideEffects?: boolean;
               }) {
                 if (file.type !== 'asset') {
                   throw new Error('emitFile only support asset type');
@@ -266,7 +269,27 @@ export async function build(params: BuildParams) {
                   Math.random().toString(36).substring(2, 15),
                 );
                 fs.writeFileSync(tmpFile, file.source!);
-                context.emitFile(tmpFile, file.fileName!);
+                context.emitFile(
+                  tmpFile,
+                  file.fileName!,
+                  file.sideEffects === undefined
+                    ? undefined
+                    : { sideEffects: file.sideEffects },
+                );
+              },
+              getConfig() {
+                return context.getConfig();
+              },
+              getModuleMeta(id: string) {
+                return context.getModuleMeta(id);
+              },
+              logTiming(label: string, ms: number) {
+                context.logTiming(label, ms);
+              },
+              // mako only, load and parse the files with the extension as js,
+              // should be called in buildStart
+              registerJsExtension(ext: string) {
+                context.registerJsExtension(ext);
               },
               warn(
                 message:
@@ -334,6 +357,28 @@ export async function build(params: BuildParams) {
           }
           return result;
         };
+        // report the errors of the module hooks with their message and stack,
+        // which are lost across the napi boundary, a sync throw also crashes
+        if (key === 'load' || key === 'transform' || key === 'resolveId') {
+          const hook = plugin[key];
+          plugin[key] = (context: any, ...args: any[]) => {
+            const reportError = (e: any) => {
+              context.reportError(
+                e instanceof Error ? e.message : String(e),
+                e instanceof Error ? e.stack : undefined,
+              );
+              throw e;
+            };
+            try {
+              const result = hook(context, ...args);
+              return result && typeof result.then === 'function'
+                ? result.catch(reportError)
+                : result;
+            } catch (e) {
+              return Promise.reject(e).catch(reportError);
+            }
+          };
+        }
       }
     });
   });
@@ -345,7 +390,7 @@ export async function build(params: BuildParams) {
     'forkTSChecker',
     'plugins',
   ]) as BuildParams['config'];
-  await binding.build({
+  const result = await binding.build({
     ...params,
     plugins,
   });
@@ -357,6 +402,8 @@ export async function build(params: BuildParams) {
     });
     forkTypeChecker.runTypeCheckInChildProcess();
   }
+
+  return result;
 }
 
 function adapterResult(result: any) {
@@ -0,0 +1,118 @@
+import assert from 'assert';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
+import { createParallelLoader } from '../../runLoaders/parallelLoader';
+import { LessPlugin, getSourceMap } from './';
+
+(async () => {
+  const dir = fs.mkdtempSync(path.join(os.tmpdir(), 'mako-less-'));
+  const filename = path.join(dir, 'index.less');
+  const dep = path.join(dir, 'dep.less');
+  fs.writeFileSync(filename, `@import './dep.less';`);
+  fs.writeFileSync(dep, `a { color: red; }`);
+
+  const plugin = new LessPlugin({
+    root: dir,
+    config: {},
+    watch: true,
+    resolveAlias: {},
+  } as any);
+  let renders = 0;
+  plugin.render = async () => {
+    renders++;
+    return {
+      result: [`a { color: red; }`],
+      fileDependencies: [filename, dep],
+    } as any;
+  };
+  const load = () => plugin.load({} as any, filename);
+  const touch = (file: string, time: number) => {
+    const date = new Date(time);
+    fs.utimesSync(file, date, date);
+  };
+
+  const first = await load();
+  assert.deepStrictEqual(first, { content: 'a { color: red; }', type: 'css' });
+  assert.strictEqual(renders, 1);
+
+  // unchanged, neither read nor compiled again
+  assert.deepStrictEqual(await load(), first);
+  assert.strictEqual(renders, 1);
+
+  // the file changed
+  touch(filename, Date.now() + 10000);
+  await load();
+  assert.strictEqual(renders, 2);
+
+  // a dependency changed
+  touch(dep, Date.now() + 20000);
+  await load();
+  assert.strictEqual(renders, 3);
+  await load();
+  assert.strictEqual(renders, 3);
+
+  // the output may differ with another env, so it's compiled again
+  plugin.env = { MAKO_LESS_THEME: 'dark' };
+  await load();
+  assert.strictEqual(renders, 4);
+  await load();
+  assert.strictEqual(renders, 4);
+
+  // the env reaches the less plugins in the workers
+  const theme = path.join(dir, 'theme.less');
+  const worker = path.join(dir, 'render.js');
+  fs.writeFileSync(theme, `a { color: theme(); }`);
+  fs.writeFileSync(
+    worker,
+    `
+const fs = require('fs');
+const less = require(${JSON.stringify(require.resolve('less'))});
+const themePlugin = {
+  install(less, pluginManager, functions) {
+    functions.add('theme', () => new less.tree.Keyword(process.env.MAKO_LESS_THEME));
+  },
+};
+module.exports = async ({ filename }) => {
+  const output = await less.render(fs.readFileSync(filename, 'utf-8'), {
+    filename,
+    plugins: [themePlugin],
+  });
+  return { result: [output.css] };
+};
+`,
+  );
+  const renderWithEnv = async (env: Record<string, string>) => {
+    const loader = createParallelLoader(worker, { env });
+    const result = await loader.run({
+      filename: theme,
+      extOpts: { root: dir },
+    });
+    await loader.destroy();
+    return String(result.result?.[0]);
+  };
+  assert.match(
+    await renderWithEnv({ MAKO_LESS_THEME: 'red' }),
+    /color: red;/,
+  );
+  assert.match(
+    await renderWithEnv({ MAKO_LESS_THEME: 'blue' }),
+    /color: blue;/,
+  );
+
+  // the less map follows devtool, it's always inlined to be chained by mako
+  assert.deepStrictEqual(getSourceMap({ outputSourceFiles: true }, undefined), {
+    outputSourceFiles: true,
+    sourceMapFileInline: true,
+  });
+  assert.deepStrictEqual(getSourceMap(true, 'inline-source-map'), {
+    sourceMapFileInline: true,
+  });
+  assert.strictEqual(getSourceMap({ outputSourceFiles: true }, false), false);
+  assert.strictEqual(getSourceMap(undefined, 'source-map'), false);
+
+  fs.rmSync(dir, { recursive: true, force: true });
+})().catch((e) => {
+  console.error(e);
+  process.exit(1);
+});
@@ -1,3 +1,4 @@
+import fs from 'fs';
 import path from 'path';
 import url from 'url';
 import { BuildParams } from '../../';
@@ -25,8 +26,40 @@ export interface LessLoaderOpts {
    * We do this because the less loader runs in a worker pool for speed, and a less plugin instance can't be passed to worker directly.
    */
   plugins?: (string | [string, Record<string, any>])[];
+  /**
+   * Forwarded to less as `rootpath` (the `--rootpath` cli option),
+   * it's prepended to relative `url()`s so they still resolve when the output directory differs from the source.
+   */
+  rootPath?: string;
+  /**
+   * Timeout of compiling a single less file in milliseconds, the worker is terminated
+   * and the build fails when it's exceeded, e.g. less hangs on a recursive mixin.
+   */
+  timeoutMs?: number;
+  /**
+   * Environment variables of the workers compiling less, on top of the env of the
+   * current process, e.g. for less plugins which pick a theme by an env variable.
+   */
+  env?: Record<string, string>;
 }
 
+const DEFAULT_TIMEOUT_MS = 30000;
+
+export class CompileLessError extends Error {
+  constructor(filename: string, reason: string) {
+    super(`CompileLessError: compile ${filename} failed, ${reason}`);
+    this.name = 'CompileLessError';
+  }
+}
+
+type LoadResult = { content: string; type: 'css'; resolveBase: string };
+
+type LoadCacheEntry = {
+  // mtimes of the file and its dependencies when it was compiled
+  mtimes: Map<string, number | undefined>;
+  result: LoadResult;
+};
+
 type LessModule = {
   id: string;
   deps: Set<LessModule>;
@@ -40,7 +73,10 @@ export class LessPlugin implements binding.JsHooks {
   params: BuildParams & { resolveAlias: Record<string, string> };
   extOpts: RunLoadersOptions;
   lessOptions: LessLoaderOpts;
+  timeoutMs: number;
+  env: Record<string, string>;
   moduleGraph: Map<string, LessModule> = new Map();
+  loadCache: Map<string, LoadCacheEntry> = new Map();
   __isPatched = true;
 
   constructor(params: BuildParams & { resolveAlias: Record<string, string> }) {
@@ -54,16 +90,22 @@ export class LessPlugin implements binding.JsHooks {
       modifyVars: params.config.less?.modifyVars || {},
       globalVars: params.config.less?.globalVars,
       math: params.config.less?.math,
-      sourceMap: params.config.less?.sourceMap || false,
+      sourceMap: getSourceMap(
+        params.config.less?.sourceMap,
+        params.config.devtool,
+      ),
       plugins: params.config.less?.plugins || [],
+      rootPath: params.config.less?.rootPath,
     };
+    this.timeoutMs = params.config.less?.timeoutMs ?? DEFAULT_TIMEOUT_MS;
+    this.env = params.config.less?.env || {};
   }
 
   // @ts-ignore
   load: (
     _ctx: PluginContext,
     filePath: string,
-  ) => Promise<{ content: string; type: 'css' } | undefined> = async (
+  ) => Promise<LoadResult | undefined> = async (
     _ctx: PluginContext,
     filePath: string,
   ) => {
@@ -84,14 +126,16 @@ export class LessPlugin implements binding.JsHooks {
       this.moduleGraph.set(filename, module);
     }
 
-    this.parallelLoader ||= createParallelLoader(
-      path.resolve(__dirname, './render.js'),
-    );
-    const result = await this.parallelLoader.run({
-      filename,
-      opts: this.lessOptions,
-      extOpts: this.extOpts,
-    });
+    // unchanged files skip both the disk read and the compile in watch rebuilds
+    const cacheKey = getCacheKey(filename, this.env);
+    const cached = this.loadCache.get(cacheKey);
+    if (cached && isFresh(cached.mtimes)) {
+      return cached.result;
+    }
+    // taken before compiling, so edits during the compile invalidate the cache
+    const mtime = getMtime(filename);
+
+    const result = await this.render(filename);
 
     let content: string = '';
 
@@ -141,10 +185,57 @@ export class LessPlugin implements binding.JsHooks {
       }
     }
 
-    return {
+    const mtimes = new Map<string, number | undefined>([[filename, mtime]]);
+    for (const dep of [
+      ...(result.fileDependencies || []),
+      ...(result.missingDependencies || []),
+    ]) {
+      if (!mtimes.has(dep)) {
+        mtimes.set(dep, getMtime(dep));
+      }
+    }
+    const loadResult: LoadResult = {
       content,
       type: 'css',
+      // the urls and @imports left in the compiled css are relative to the entry less file
+      resolveBase: path.dirname(filename),
     };
+    this.loadCache.set(cacheKey, { mtimes, result: loadResult });
+    return loadResult;
+  };
+
+  render = async (filename: string) => {
+    this.parallelLoader ||= createParallelLoader(
+      path.resolve(__dirname, './render.js'),
+      { env: this.env },
+    );
+    for (let attempt = 0; ; attempt++) {
+      // the worker running the task is terminated when it's aborted
+      const signal = AbortSignal.timeout(this.timeoutMs);
+      try {
+        return await this.parallelLoader.run(
+          {
+            filename,
+            opts: this.lessOptions,
+            extOpts: this.extOpts,
+          },
+          { signal },
+        );
+      } catch (e: any) {
+        if (signal.aborted) {
+          throw new CompileLessError(
+            filename,
+            `timed out after ${this.timeoutMs}ms`,
+          );
+        }
+        // retry once for transient failures which come without an error message,
+        // e.g. the worker exits unexpectedly
+        if (attempt === 0 && !e?.message) {
+          continue;
+        }
+        throw e;
+      }
+    }
   };
 
   // @ts-ignore
@@ -181,6 +272,48 @@ export class LessPlugin implements binding.JsHooks {
   };
 }
 
+// the map is inlined into the compiled css, where it's chained by mako and then
+// emitted inline or as a sidecar file per `devtool`, it's dropped without `devtool`
+export function getSourceMap(
+  sourceMap: LessLoaderOpts['sourceMap'],
+  devtool: BuildParams['config']['devtool'],
+) {
+  if (!sourceMap || devtool === false) {
+    return false;
+  }
+  return {
+    ...(typeof sourceMap === 'object' ? sourceMap : {}),
+    sourceMapFileInline: true,
+  };
+}
+
+function getMtime(filename: string) {
+  try {
+    return fs.statSync(filename).mtimeMs;
+  } catch (e) {
+    return undefined;
+  }
+}
+
+function isFresh(mtimes: Map<string, number | undefined>) {
+  for (const [filename, mtime] of mtimes) {
+    if (getMtime(filename) !== mtime) {
+      return false;
+    }
+  }
+  return true;
+}
+
+// the output may depend on the env through less plugins
+function getCacheKey(filename: string, env: Record<string, string>) {
+  const entries = Object.keys(env)
+    .sort()
+    .map((key) => [key, env[key]]);
+  return entries.length
+    ? `${filename}?env=${JSON.stringify(entries)}`
+    : filename;
+}
+
 function getFilename(filePath: string) {
   let filename = '';
   try {
@@ -6,7 +6,8 @@ module.exports = async function render(param: {
   opts: LessLoaderOpts;
   extOpts: RunLoadersOptions;
 }) {
-  const { modifyVars, globalVars, math, sourceMap, plugins } = param.opts;
+  const { modifyVars, globalVars, math, sourceMap, plugins, rootPath } =
+    param.opts;
   const extOpts = param.extOpts;
 
   const pluginInstances: Less.Plugin[] | undefined = (plugins || []).map(
@@ -37,6 +38,7 @@ module.exports = async function render(param: {
             modifyVars,
             globalVars,
             rewriteUrls: 'all',
+            ...(rootPath ? { rootpath: rootPath } : {}),
             sourceMap,
           },
         },
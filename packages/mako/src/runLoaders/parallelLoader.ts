@@ -3,7 +3,13 @@ import { RunLoaderResult } from 'loader-runner';
 import { Piscina } from 'piscina';
 import { RunLoadersOptions } from '.';
 
-export function createParallelLoader<T>(renderPath: string) {
+export function createParallelLoader<T>(
+  renderPath: string,
+  options: {
+    // added to the env of the current process in the workers
+    env?: Record<string, string>;
+  } = {},
+) {
   return new Piscina<
     {
       filename: string;
@@ -17,6 +23,7 @@ export function createParallelLoader<T>(renderPath: string) {
     idleTimeout: 30000,
     recordTiming: false,
     useAtomics: false,
+    env: options.env ? { ...process.env, ...options.env } : undefined,
     maxThreads:
       // strange panic on docker linux
       os.platform() === 'linux' ? (os.cpus().length > 4 ? 2 : 1) : undefined,
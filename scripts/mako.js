@@ -43,6 +43,10 @@ function getMakoConfig() {
   makoConfig.less = {
     modifyVars: makoConfig.less?.theme || {},
     globalVars: makoConfig.less?.globalVars,
+    timeoutMs: makoConfig.less?.timeoutMs,
+    plugins: (makoConfig.less?.plugins || []).map((plugin) =>
+      path.join(cwd, plugin),
+    ),
   };
   makoConfig.plugins = getPlugins();
   makoConfig.resolve.alias.forEach((alias) => {
@@ -1905,6 +1905,117 @@ runTest('change async import magic comment chunk name', async () => {
   await cleanup({ process, browser });
 });
 
+runTest('js: import.meta.hot self accept with dispose data', async () => {
+  await commonTest(
+    {
+      '/src/index.ts': `
+const root = document.getElementById('root');
+root.innerHTML = \`<div id="counter"></div><section>\${Math.random()}</section>\`;
+require('./counter');
+      `,
+      '/src/counter.ts': `
+const count = (import.meta.hot.data ? import.meta.hot.data.count : 0) + 1;
+document.getElementById('counter').textContent = 'v1:' + count;
+import.meta.hot.dispose((data) => {
+  data.count = count;
+});
+import.meta.hot.accept();
+      `,
+    },
+    (lastResult) => {
+      assert.equal(
+        lastResult.html,
+        '<div id="counter">v1:1</div>',
+        'Initial render',
+      );
+    },
+    {
+      '/src/counter.ts': `
+const count = (import.meta.hot.data ? import.meta.hot.data.count : 0) + 1;
+document.getElementById('counter').textContent = 'v2:' + count;
+import.meta.hot.dispose((data) => {
+  data.count = count;
+});
+import.meta.hot.accept();
+      `,
+    },
+    (thisResult) => {
+      assert.equal(
+        thisResult.html,
+        '<div id="counter">v2:2</div>',
+        'Second render',
+      );
+    },
+  );
+});
+
+runTest('js: import.meta.hot accept dependency', async () => {
+  await commonTest(
+    {
+      '/src/index.ts': `
+import { message } from './message';
+const root = document.getElementById('root');
+root.innerHTML = \`<div>\${message}</div><section>\${Math.random()}</section>\`;
+import.meta.hot.accept('./message', (mod) => {
+  root.querySelector('div').textContent = mod.message;
+});
+      `,
+      '/src/message.ts': `
+export const message = 'foo';
+      `,
+    },
+    (lastResult) => {
+      assert.equal(lastResult.html, '<div>foo</div>', 'Initial render');
+    },
+    {
+      '/src/message.ts': `
+export const message = 'bar';
+      `,
+    },
+    (thisResult) => {
+      assert.equal(thisResult.html, '<div>bar</div>', 'Second render');
+    },
+  );
+});
+
+runTest('js: import.meta.hot invalidate propagates to the importer', async () => {
+  await commonTest(
+    {
+      '/src/index.ts': `
+import { label } from './label';
+const root = document.getElementById('root');
+root.innerHTML = \`<div>\${label}</div><section>\${Math.random()}</section>\`;
+import.meta.hot.accept('./label', (mod) => {
+  root.querySelector('div').textContent = mod.label;
+});
+      `,
+      '/src/label.ts': `
+export const label = 'v1';
+import.meta.hot.accept();
+      `,
+    },
+    (lastResult) => {
+      assert.equal(lastResult.html, '<div>v1</div>', 'Initial render');
+    },
+    {
+      // invalidated once, or the update stops at the self accepted module
+      '/src/label.ts': `
+export const label = 'v2';
+import.meta.hot.dispose((data) => {
+  data.invalidated = true;
+});
+if (!import.meta.hot.data || !import.meta.hot.data.invalidated) {
+  import.meta.hot.invalidate();
+}
+import.meta.hot.accept();
+      `,
+    },
+    (thisResult) => {
+      assert.equal(thisResult.html, '<div>v2</div>', 'Second render');
+    },
+  );
+});
+
 function normalizeFiles(files, makoConfig = {}) {
   return {
     '/public/index.html': `